@@ -0,0 +1,64 @@
+use super::Output;
+use crate::package::{cache, PackageManager};
+use anyhow::Result;
+use colored::Colorize;
+use std::time::Duration;
+
+pub struct CacheCommand;
+
+impl CacheCommand {
+    pub fn execute(clear: bool, out: Output) -> Result<()> {
+        if clear {
+            if cache::clear()? {
+                out.status("Package list cache cleared.".green());
+            } else {
+                out.status("The package list isn't cached.".yellow());
+            }
+            return Ok(());
+        }
+
+        let pm = PackageManager::new()?;
+        let Some(status) = cache::status(&pm.sync_db_dir()) else {
+            out.status("The package list isn't cached; it is cached the next time packages are listed.".yellow());
+            return Ok(());
+        };
+
+        let state = if status.stale {
+            "stale, the sync databases changed since".yellow()
+        } else {
+            "up to date".green()
+        };
+        out.data(format!("{} {}", "Package list cache:".cyan().bold(), status.path.display()));
+        out.data(format!(
+            "{} packages, cached {} ago ({})",
+            status.package_count,
+            format_age(status.created_at.elapsed().unwrap_or_default()),
+            state
+        ));
+
+        Ok(())
+    }
+}
+
+/// Format a duration as a rough age ("5 minutes", "3 hours", "2 days")
+fn format_age(age: Duration) -> String {
+    let (count, unit) = match age.as_secs() {
+        secs if secs < 3_600 => (secs / 60, "minute"),
+        secs if secs < 86_400 => (secs / 3_600, "hour"),
+        secs => (secs / 86_400, "day"),
+    };
+    format!("{} {}{}", count, unit, if count == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ages_are_rounded_down_to_one_unit() {
+        assert_eq!(format_age(Duration::from_secs(30)), "0 minutes");
+        assert_eq!(format_age(Duration::from_secs(60)), "1 minute");
+        assert_eq!(format_age(Duration::from_secs(3 * 3_600 + 59)), "3 hours");
+        assert_eq!(format_age(Duration::from_secs(2 * 86_400)), "2 days");
+    }
+}
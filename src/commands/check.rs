@@ -0,0 +1,84 @@
+use crate::output;
+use crate::package::verify::PackageCheck;
+use crate::package::PackageManager;
+use anyhow::Result;
+use colored::Colorize;
+
+/// Problem files listed per package before the rest are summarized
+const MAX_LISTED_PROBLEMS: usize = 5;
+
+pub struct CheckCommand;
+
+impl CheckCommand {
+    pub fn execute(deep: bool, all: bool, package: Option<String>) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        if deep {
+            println!("{}", "Checking package files in depth (this can take a while)...".cyan());
+        } else {
+            println!("{}", "Checking package files...".cyan());
+        }
+        let checks = pm.verify(deep, package.as_deref())?;
+
+        let shown: Vec<&PackageCheck> = checks.iter().filter(|c| all || c.issues() > 0).collect();
+        let name_width = shown.iter().map(|c| c.name.len()).max().unwrap_or(0);
+
+        if !shown.is_empty() {
+            println!();
+        }
+        for check in &shown {
+            Self::print_check(check, name_width);
+        }
+
+        let failed = checks.iter().filter(|c| c.issues() > 0).count();
+        println!();
+        if failed == 0 {
+            println!(
+                "{}{} package(s) checked, no problems found",
+                output::symbol("✓ ", "").green().bold(),
+                checks.len()
+            );
+            return Ok(());
+        }
+
+        // Non-zero exit so scheduled checks can alert on it
+        anyhow::bail!(
+            "{} of {} package(s) have missing or altered files",
+            failed,
+            checks.len()
+        )
+    }
+
+    fn print_check(check: &PackageCheck, name_width: usize) {
+        let name = format!("{:<name_width$}", check.name).bold();
+
+        if check.issues() == 0 {
+            println!("  {}  {} {}", name, "ok".green(), format!("({} files)", check.total_files).dimmed());
+            return;
+        }
+
+        let mut counts = Vec::new();
+        if check.missing > 0 {
+            counts.push(format!("{} missing", check.missing));
+        }
+        if check.altered > 0 {
+            counts.push(format!("{} altered", check.altered));
+        }
+        println!(
+            "  {}  {} {}",
+            name,
+            counts.join(", ").red(),
+            format!("(of {} files)", check.total_files).dimmed()
+        );
+
+        for problem in check.problems.iter().take(MAX_LISTED_PROBLEMS) {
+            println!("      {}", problem.dimmed());
+        }
+        if check.problems.len() > MAX_LISTED_PROBLEMS {
+            println!(
+                "      {}",
+                format!("... and {} more", check.problems.len() - MAX_LISTED_PROBLEMS).dimmed()
+            );
+        }
+    }
+}
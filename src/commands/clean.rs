@@ -0,0 +1,61 @@
+use super::confirm;
+use crate::output;
+use crate::package::{format_size, PackageManager};
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct CleanCommand;
+
+impl CleanCommand {
+    pub fn execute(keep: u32, yes: bool) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        let before = pm.cache_info();
+        println!(
+            "{} {} in {} files",
+            "Package cache:".cyan().bold(),
+            format_size(before.total_size).bold(),
+            before.file_count
+        );
+
+        if before.file_count == 0 {
+            println!("{}", "Nothing to clean.".yellow());
+            return Ok(());
+        }
+
+        if pm.has_paccache() {
+            println!("Keeping the {} most recent version(s) of each package.", keep);
+        } else {
+            println!(
+                "{} paccache is not installed, falling back to {}.",
+                "Warning:".yellow().bold(),
+                "pacman -Sc".bold()
+            );
+            println!(
+                "{}",
+                "Only the installed version of each package will be kept, so you won't be able to downgrade from the cache."
+                    .yellow()
+            );
+            println!("Install {} to keep older versions.", "pacman-contrib".bold());
+        }
+
+        if !yes && !confirm("Clean the package cache?")? {
+            println!("{}", "Aborted.".yellow());
+            return Ok(());
+        }
+
+        pm.clean_cache(keep)?;
+
+        let after = pm.cache_info();
+        println!(
+            "\n{} {} {} {} (freed {})",
+            "Cache cleaned:".green().bold(),
+            format_size(before.total_size),
+            output::symbol("→", "->"),
+            format_size(after.total_size).bold(),
+            format_size(before.total_size.saturating_sub(after.total_size)).green()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,32 @@
+use crate::config;
+use crate::ui;
+use anyhow::Result;
+use colored::Colorize;
+use std::time::SystemTime;
+
+pub struct DoctorCommand;
+
+impl DoctorCommand {
+    pub fn execute() -> Result<()> {
+        let settings = config::load_settings();
+
+        if let Ok(path) = config::settings_path() {
+            println!("{}  {}", "Config file:".cyan().bold(), path.display());
+        }
+
+        // The scheduler only runs while the interactive mode is open; it records each refresh
+        println!("\n{}", "Background refresh:".cyan().bold());
+        let Some(path) = ui::refresh_state_path() else {
+            println!("  {}", "No cache directory to record refreshes in".yellow());
+            return Ok(());
+        };
+
+        let status = ui::recorded_status(&path, &settings.refresh_intervals, SystemTime::now());
+        let label_width = status.iter().map(|(label, _)| label.len() + 1).max().unwrap_or(0);
+        for (label, text) in &status {
+            println!("  {}  {}", format!("{:<label_width$}", format!("{}:", label)).bold(), text);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,73 @@
+use crate::output;
+use crate::package::PackageManager;
+use crate::ui::Selector;
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct DowngradeCommand;
+
+impl DowngradeCommand {
+    pub fn execute(package: String, to: Option<String>) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        let cached = pm.list_cached_versions(&package);
+        if cached.is_empty() {
+            println!(
+                "{} No cached versions of {} found in the package cache.",
+                "Warning:".yellow().bold(),
+                package.bold()
+            );
+            return Ok(());
+        }
+
+        let installed = pm.installed_version(&package)?;
+
+        let version = match to {
+            Some(version) => version,
+            None => {
+                let versions = cached.iter().map(|c| c.version.clone()).collect();
+                match Selector::select_version(&package, versions)? {
+                    Some(version) => version,
+                    None => {
+                        println!("{}", "No version selected.".yellow());
+                        return Ok(());
+                    }
+                }
+            }
+        };
+
+        let Some(selected) = cached.iter().find(|c| c.version == version) else {
+            let available: Vec<&str> = cached.iter().map(|c| c.version.as_str()).collect();
+            anyhow::bail!(
+                "Version {} of {} is not in the package cache (cached: {})",
+                version,
+                package,
+                available.join(", ")
+            );
+        };
+
+        if installed.as_deref() == Some(selected.version.as_str()) {
+            println!(
+                "{} {} {} is already installed.",
+                "Warning:".yellow().bold(),
+                package.bold(),
+                selected.version
+            );
+            return Ok(());
+        }
+
+        println!(
+            "{} {} {} {} {}",
+            "Installing:".green().bold(),
+            package,
+            installed.as_deref().unwrap_or("(not installed)").dimmed(),
+            output::symbol("→", "->"),
+            selected.version.green()
+        );
+
+        pm.install_file(&selected.path)?;
+        println!("{}", "Downgrade complete!".green());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,33 @@
+use crate::package::PackageManager;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct ExportCommand;
+
+impl ExportCommand {
+    pub fn execute(output: Option<PathBuf>) -> Result<()> {
+        let pm = PackageManager::new()?;
+        let packages = pm.list_explicit()?;
+
+        let mut content = packages.join("\n");
+        content.push('\n');
+
+        match output {
+            Some(path) => {
+                fs::write(&path, content)
+                    .with_context(|| format!("Failed to write {}", path.display()))?;
+                eprintln!(
+                    "{} {} packages to {}",
+                    "Exported".green().bold(),
+                    packages.len(),
+                    path.display()
+                );
+            }
+            None => print!("{}", content),
+        }
+
+        Ok(())
+    }
+}
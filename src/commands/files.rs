@@ -0,0 +1,55 @@
+use crate::package::PackageManager;
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct FilesCommand;
+
+impl FilesCommand {
+    pub fn execute(package: String, tree: bool) -> Result<()> {
+        let pm = PackageManager::new()?;
+        let files = pm.list_files(&package)?;
+
+        if files.files_db_missing {
+            anyhow::bail!(
+                "{} is not installed and the files database is missing (run `sudo pacman -Fy`)",
+                package
+            );
+        }
+
+        if files.from_files_db {
+            println!(
+                "{} {} is not installed, showing files from the files database\n",
+                "Note:".cyan().bold(),
+                package.bold()
+            );
+        }
+
+        if tree {
+            Self::print_tree(&files.paths);
+        } else {
+            for path in files.paths.iter().filter(|p| !p.ends_with('/')) {
+                println!("{}", path);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Print paths as an indented directory tree
+    fn print_tree(paths: &[String]) {
+        for path in paths {
+            let is_dir = path.ends_with('/');
+            let components: Vec<&str> = path.trim_matches('/').split('/').collect();
+            let Some(name) = components.last().filter(|name| !name.is_empty()) else {
+                continue;
+            };
+            let indent = "  ".repeat(components.len() - 1);
+
+            if is_dir {
+                println!("{}{}/", indent, name.blue().bold());
+            } else {
+                println!("{}{}", indent, name);
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use crate::package::history::HistoryAction;
+use crate::package::PackageManager;
+use anyhow::Result;
+use colored::{ColoredString, Colorize};
+
+pub struct HistoryCommand;
+
+impl HistoryCommand {
+    pub fn execute(package: Option<String>, action: Option<HistoryAction>, limit: usize) -> Result<()> {
+        let pm = PackageManager::new()?;
+        let history = pm.read_history()?;
+
+        let entries: Vec<_> = history
+            .iter()
+            .rev()
+            .filter(|e| package.as_ref().is_none_or(|p| &e.package == p))
+            .filter(|e| action.is_none_or(|a| e.action == a))
+            .take(limit)
+            .collect();
+
+        if entries.is_empty() {
+            println!("{}", "No matching transactions found.".yellow());
+            return Ok(());
+        }
+
+        let name_width = entries.iter().map(|e| e.package.len()).max().unwrap_or(0);
+
+        for entry in entries {
+            let versions = match (&entry.old_version, &entry.new_version) {
+                (Some(old), Some(new)) => format!("{} {} {}", old.dimmed(), "->".dimmed(), new.green()),
+                (Some(old), None) => old.dimmed().to_string(),
+                (None, Some(new)) => new.green().to_string(),
+                (None, None) => String::new(),
+            };
+
+            println!(
+                "{}  {}  {}  {}",
+                entry.timestamp.dimmed(),
+                Self::colored_action(entry.action),
+                format!("{:<width$}", entry.package, width = name_width).bold(),
+                versions
+            );
+        }
+
+        Ok(())
+    }
+
+    fn colored_action(action: HistoryAction) -> ColoredString {
+        let label = format!("{:<11}", action);
+        match action {
+            HistoryAction::Installed => label.green(),
+            HistoryAction::Upgraded => label.cyan(),
+            HistoryAction::Downgraded => label.yellow(),
+            HistoryAction::Reinstalled => label.blue(),
+            HistoryAction::Removed => label.red(),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use super::confirm;
+use crate::package::{parse_package_list, InstallOptions, PackageManager};
+use anyhow::{Context, Result};
+use colored::Colorize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+pub struct ImportCommand;
+
+impl ImportCommand {
+    pub fn execute(file: PathBuf, dry_run: bool, yes: bool) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        let content = fs::read_to_string(&file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let wanted = parse_package_list(&content);
+
+        if wanted.is_empty() {
+            println!("{}", "No packages listed in the file.".yellow());
+            return Ok(());
+        }
+
+        let installed: HashSet<String> = pm.list_installed()?.into_iter().collect();
+        let (present, missing): (Vec<String>, Vec<String>) = wanted
+            .into_iter()
+            .partition(|name| installed.contains(name));
+
+        println!(
+            "{} {} to install, {} already present",
+            "Import:".cyan().bold(),
+            missing.len().to_string().green().bold(),
+            present.len()
+        );
+
+        if missing.is_empty() {
+            println!("{}", "Nothing to install.".green());
+            return Ok(());
+        }
+
+        for pkg in &missing {
+            println!("  {}", pkg);
+        }
+
+        if dry_run {
+            println!("{}", "Dry run, nothing was installed.".yellow());
+            return Ok(());
+        }
+
+        if !yes && !confirm("Install these packages?")? {
+            println!("{}", "Aborted.".yellow());
+            return Ok(());
+        }
+
+        pm.install(&missing, &InstallOptions::default())?;
+        println!("{}", "Import complete!".green());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use crate::output;
+use crate::package::{PackageInfo, PackageManager, PmgrError};
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct InfoCommand;
+
+impl InfoCommand {
+    pub fn execute(packages: Vec<String>, installed: bool) -> Result<()> {
+        let pm = PackageManager::new()?;
+        let mut not_found = Vec::new();
+
+        for (i, package) in packages.iter().enumerate() {
+            if i > 0 {
+                println!("{}", output::symbol("─", "-").repeat(60).dimmed());
+            }
+
+            // Look in the local database first, then fall back to the sync databases
+            // and finally the AUR RPC (needed when the backend is plain pacman)
+            let info = match pm.get_info_parsed(package, true) {
+                Ok(info) => Some(info),
+                Err(_) if !installed => pm.get_info_parsed(package, false).ok().or_else(|| {
+                    pm.aur_info(package).ok().flatten().map(|pkg| pkg.to_info())
+                }),
+                Err(_) => None,
+            };
+
+            match info {
+                Some(info) => Self::print_info(&info),
+                None => {
+                    eprintln!("{} {}", "Package not found:".red().bold(), package);
+                    not_found.push(package.clone());
+                }
+            }
+        }
+
+        if !not_found.is_empty() {
+            return Err(PmgrError::PackageNotFound(not_found.join(", ")).into());
+        }
+
+        Ok(())
+    }
+
+    /// Print package details as aligned fields with colored keys
+    fn print_info(info: &PackageInfo) {
+        let fields = info.fields();
+        let key_width = fields.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+
+        for (key, values) in fields {
+            let label = format!("{:<width$} :", key, width = key_width).cyan().bold();
+            if values.is_empty() {
+                println!("{} {}", label, "None".dimmed());
+                continue;
+            }
+
+            for (i, value) in values.iter().enumerate() {
+                let value = match key {
+                    "Name" => value.bold().to_string(),
+                    "Version" => value.green().to_string(),
+                    _ => value.clone(),
+                };
+                if i == 0 {
+                    println!("{} {}", label, value);
+                } else {
+                    // Further entries (e.g. optional dependencies) line up under the first
+                    println!("{:width$} {}", "", value, width = key_width + 2);
+                }
+            }
+        }
+    }
+}
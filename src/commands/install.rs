@@ -1,4 +1,7 @@
-use crate::package::PackageManager;
+use super::{confirm, wait_for_db_lock, Output};
+use crate::config;
+use crate::output;
+use crate::package::{InstallOptions, PackageManager};
 use crate::ui::Selector;
 use anyhow::Result;
 use colored::Colorize;
@@ -6,51 +9,93 @@ use colored::Colorize;
 pub struct InstallCommand;
 
 impl InstallCommand {
-    pub fn execute(packages: Vec<String>, interactive: bool) -> Result<()> {
-        let pm = PackageManager::new();
+    pub fn execute(packages: Vec<String>, interactive: bool, options: InstallOptions, out: Output) -> Result<()> {
+        let pm = PackageManager::new()?;
 
         if interactive || packages.is_empty() {
             // Interactive mode: show all available packages
-            println!("{}", "Loading available packages...".cyan());
-            let available = pm.list_available()?;
+            out.status("Loading available packages...".cyan());
+            let available = pm.list_available_cached(false)?;
 
-            let package_names: Vec<String> = available
-                .iter()
-                .map(|p| format!("{}/{}", p.repository, p.name))
-                .collect();
-
-            let selected = Selector::select_available(package_names)?;
+            let selected = Selector::select_available(&available.packages, &pm)?;
 
             if selected.is_empty() {
-                println!("{}", "No packages selected.".yellow());
+                out.status("No packages selected.".yellow());
                 return Ok(());
             }
 
             // Extract package names (remove repository prefix)
             let to_install: Vec<String> = selected
                 .iter()
-                .map(|s| s.split('/').last().unwrap_or(s).to_string())
+                .map(|s| s.rsplit('/').next().unwrap_or(s).to_string())
                 .collect();
 
-            println!(
+            out.status(format!(
                 "\n{} {}",
                 "Installing:".green().bold(),
                 to_install.join(", ")
-            );
+            ));
 
-            pm.install(&to_install)?;
-            println!("{}", "Installation complete!".green());
+            Self::warn_pin_conflicts(&pm, &to_install);
+            if !Self::confirm_ignored(&pm, &to_install)? {
+                out.status("Aborted.".yellow());
+                return Ok(());
+            }
+            wait_for_db_lock(&pm, out)?;
+            pm.install(&to_install, &options)?;
+            out.status("Installation complete!".green());
         } else {
             // Direct mode: install specified packages
-            println!(
+            out.status(format!(
                 "{} {}",
                 "Installing:".green().bold(),
                 packages.join(", ")
-            );
-            pm.install(&packages)?;
-            println!("{}", "Installation complete!".green());
+            ));
+            Self::warn_pin_conflicts(&pm, &packages);
+            if !Self::confirm_ignored(&pm, &packages)? {
+                out.status("Aborted.".yellow());
+                return Ok(());
+            }
+            wait_for_db_lock(&pm, out)?;
+            pm.install(&packages, &options)?;
+            out.status("Installation complete!".green());
         }
 
         Ok(())
     }
+
+    /// Ask for confirmation before installing packages on pmgr's ignore list
+    fn confirm_ignored(pm: &PackageManager, packages: &[String]) -> Result<bool> {
+        let ignored: Vec<&String> = packages.iter().filter(|p| pm.is_ignored(p)).collect();
+        if ignored.is_empty() {
+            return Ok(true);
+        }
+
+        for pkg in &ignored {
+            eprintln!("{} {} is ignored by pmgr", "Warning:".yellow().bold(), pkg.bold());
+        }
+        confirm("Install ignored packages anyway?")
+    }
+
+    /// Warn loudly when the transaction would change a pinned package's version
+    fn warn_pin_conflicts(pm: &PackageManager, packages: &[String]) {
+        let pins = config::load_settings().pins;
+        if pins.is_empty() {
+            return;
+        }
+
+        // AUR packages can't be planned by pacman, so the check is best effort
+        let Ok(plan) = pm.plan_install(packages) else {
+            return;
+        };
+
+        for conflict in config::pin_conflicts(&pins, &plan) {
+            eprintln!(
+                "{}{} {}",
+                output::symbol("⚠ ", "!! "),
+                "PINNED:".red().bold(),
+                conflict.red().bold()
+            );
+        }
+    }
 }
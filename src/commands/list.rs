@@ -1,36 +1,152 @@
-use crate::package::PackageManager;
+use super::Output;
+use crate::package::{date, format_size, InstallFilter, InstalledDetails, PackageManager};
 use crate::ui::Selector;
 use anyhow::Result;
 use colored::Colorize;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Order of the package list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ListSort {
+    #[default]
+    Name,
+    /// Largest first
+    Size,
+    /// Most recently installed first
+    Date,
+}
+
+impl FromStr for ListSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "name" => Ok(Self::Name),
+            "size" => Ok(Self::Size),
+            "date" => Ok(Self::Date),
+            _ => Err(format!("unknown sort '{}' (expected name, size or date)", s)),
+        }
+    }
+}
 
 pub struct ListCommand;
 
 impl ListCommand {
-    pub fn execute(interactive: bool) -> Result<()> {
-        let pm = PackageManager::new();
+    pub fn execute(interactive: bool, filter: InstallFilter, sort: ListSort, json: bool, out: Output) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        if json {
+            let mut installed = pm.list_installed_versions()?;
+            if filter != InstallFilter::All {
+                let names = pm.list_installed_filtered(filter)?;
+                installed.retain(|pkg| names.contains(&pkg.name));
+            }
+            out.data(serde_json::to_string_pretty(&installed)?);
+            return Ok(());
+        }
 
-        println!("{}", "Loading installed packages...".cyan());
-        let installed = pm.list_installed()?;
+        out.status("Loading installed packages...".cyan());
+        let installed = pm.list_installed_filtered(filter)?;
 
         if installed.is_empty() {
-            println!("{}", "No packages installed.".yellow());
+            out.status("No packages installed.".yellow());
             return Ok(());
         }
 
         if interactive {
             // Interactive browsing mode
-            Selector::browse_installed(installed)?;
-        } else {
-            // Simple list mode
-            println!(
-                "\n{} packages installed:\n",
-                installed.len().to_string().green().bold()
-            );
+            Selector::browse_installed(installed, filter.label(), pm.backend())?;
+            return Ok(());
+        }
+
+        if out.is_quiet() && sort == ListSort::Name {
+            // One name per line for scripts
             for pkg in installed {
-                println!("  {}", pkg);
+                out.data(pkg);
+            }
+            return Ok(());
+        }
+
+        // Versions, sizes and dates all come from a single pacman -Qi run
+        let names: HashSet<&String> = installed.iter().collect();
+        let mut details = pm.list_installed_detailed()?;
+        details.retain(|pkg| names.contains(&pkg.name));
+        sort_details(&mut details, sort);
+
+        if out.is_quiet() {
+            for pkg in &details {
+                out.data(&pkg.name);
             }
+            return Ok(());
+        }
+
+        let label = filter.label().map(|l| format!(" ({})", l)).unwrap_or_default();
+        println!(
+            "\n{} packages installed{}:\n",
+            details.len().to_string().green().bold(),
+            label
+        );
+
+        let name_width = details.iter().map(|p| p.name.len()).max().unwrap_or(0);
+        let version_width = details.iter().map(|p| p.version.len()).max().unwrap_or(0);
+        for pkg in &details {
+            let mut line = format!(
+                "  {}  {}  {}",
+                format!("{:<name_width$}", pkg.name).bold(),
+                format!("{:<version_width$}", pkg.version).green(),
+                format!("{:>10}", format_size(pkg.installed_size)).dimmed()
+            );
+            if sort == ListSort::Date {
+                let installed_on = pkg.install_date.map(date::format_date).unwrap_or_default();
+                line.push_str(&format!("  {}", installed_on.dimmed()));
+            }
+            println!("{}", line);
         }
 
         Ok(())
     }
 }
+
+/// Sort packages for display; ties are broken by name
+fn sort_details(details: &mut [InstalledDetails], sort: ListSort) {
+    match sort {
+        ListSort::Name => details.sort_by(|a, b| a.name.cmp(&b.name)),
+        ListSort::Size => details.sort_by(|a, b| b.installed_size.cmp(&a.installed_size).then_with(|| a.name.cmp(&b.name))),
+        ListSort::Date => details.sort_by(|a, b| b.install_date.cmp(&a.install_date).then_with(|| a.name.cmp(&b.name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::InstallReason;
+
+    fn pkg(name: &str, size: u64, date: Option<i64>) -> InstalledDetails {
+        InstalledDetails {
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            installed_size: size,
+            install_date: date,
+            reason: InstallReason::Explicit,
+        }
+    }
+
+    fn names(details: &[InstalledDetails]) -> Vec<&str> {
+        details.iter().map(|p| p.name.as_str()).collect()
+    }
+
+    #[test]
+    fn sorts_by_size_and_date() {
+        let mut details = vec![pkg("b", 10, Some(2)), pkg("a", 10, None), pkg("c", 99, Some(1))];
+
+        sort_details(&mut details, ListSort::Size);
+        assert_eq!(names(&details), vec!["c", "a", "b"]);
+
+        sort_details(&mut details, ListSort::Date);
+        assert_eq!(names(&details), vec!["b", "c", "a"]);
+
+        sort_details(&mut details, ListSort::Name);
+        assert_eq!(names(&details), vec!["a", "b", "c"]);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::oplog::{self, BEGIN_MARKER, END_MARKER};
+use anyhow::Result;
+use colored::Colorize;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Seek};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// How often the log file is polled in follow mode
+const FOLLOW_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct LogCommand;
+
+impl LogCommand {
+    pub fn execute(follow: bool, last: usize) -> Result<()> {
+        let path = oplog::log_path()?;
+
+        if follow {
+            return Self::follow(&path);
+        }
+
+        let content = fs::read_to_string(&path).unwrap_or_default();
+        let operations = oplog::split_operations(&content);
+
+        if operations.is_empty() {
+            println!("{}", "No operations logged yet.".yellow());
+            return Ok(());
+        }
+
+        let start = operations.len().saturating_sub(last);
+        for operation in &operations[start..] {
+            for line in operation {
+                print_line(line);
+            }
+            println!();
+        }
+
+        Ok(())
+    }
+
+    /// Open the log viewer, or print the per-operation log files with `list`
+    pub fn logs(list: bool) -> Result<()> {
+        if !list {
+            return crate::ui::LogViewer::run();
+        }
+
+        let logs = oplog::list_logs()?;
+        if logs.is_empty() {
+            println!("{}", "No operations logged yet.".yellow());
+        }
+        for path in logs {
+            println!("{}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Tail the log file, printing new lines until the running operation ends or Ctrl+C
+    fn follow(path: &Path) -> Result<()> {
+        let interrupted = Arc::new(AtomicBool::new(false));
+        signal_hook::flag::register(signal_hook::consts::SIGINT, Arc::clone(&interrupted))?;
+
+        // Wait for the log file to exist (nothing has been logged yet)
+        let file = loop {
+            if let Ok(file) = File::open(path) {
+                break file;
+            }
+            if interrupted.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+            thread::sleep(FOLLOW_POLL_INTERVAL);
+        };
+
+        let mut reader = BufReader::new(file);
+        if !replay_in_progress(&mut reader)? {
+            println!("{}", "Waiting for the next operation... (Ctrl+C to stop)".dimmed());
+        }
+
+        let mut buffer = String::new();
+        while !interrupted.load(Ordering::Relaxed) {
+            // Partial lines stay in the buffer until their newline arrives
+            if reader.read_line(&mut buffer)? == 0 || !buffer.ends_with('\n') {
+                // A new operation trimmed the log: read the new file from the start
+                if replaced(&mut reader, path)? {
+                    if let Ok(file) = File::open(path) {
+                        reader = BufReader::new(file);
+                        buffer.clear();
+                        replay_in_progress(&mut reader)?;
+                    }
+                }
+                thread::sleep(FOLLOW_POLL_INTERVAL);
+                continue;
+            }
+
+            let line = buffer.trim_end_matches('\n');
+            print_line(line);
+            if line.starts_with(END_MARKER) {
+                break;
+            }
+            buffer.clear();
+        }
+
+        Ok(())
+    }
+}
+
+/// Read the rest of the log and print the operation in progress from its begin marker
+/// Returns whether there was one
+fn replay_in_progress(reader: &mut BufReader<File>) -> Result<bool> {
+    let mut existing = String::new();
+    reader.read_to_string(&mut existing)?;
+
+    let in_progress = oplog::split_operations(&existing)
+        .pop()
+        .filter(|op| !op.iter().any(|line| line.starts_with(END_MARKER)));
+    if let Some(lines) = &in_progress {
+        for line in lines {
+            print_line(line);
+        }
+    }
+    Ok(in_progress.is_some())
+}
+
+/// Whether the log at `path` is no longer the file being read (trimmed logs are replaced) or was truncated
+fn replaced(reader: &mut BufReader<File>, path: &Path) -> Result<bool> {
+    let open = reader.get_ref().metadata()?;
+    let current = match fs::metadata(path) {
+        Ok(current) => current,
+        Err(_) => return Ok(false),
+    };
+    Ok(open.ino() != current.ino() || open.dev() != current.dev() || reader.stream_position()? > open.len())
+}
+
+/// Print a log line, colorizing markers, errors and warnings
+fn print_line(line: &str) {
+    let lower = line.to_lowercase();
+
+    if line.starts_with(BEGIN_MARKER) || line.starts_with(END_MARKER) {
+        if line.ends_with("failed ===") {
+            println!("{}", line.red().bold());
+        } else {
+            println!("{}", line.cyan().bold());
+        }
+    } else if lower.contains("error") || lower.contains("failed") {
+        println!("{}", line.red());
+    } else if lower.contains("warning") {
+        println!("{}", line.yellow());
+    } else {
+        println!("{}", line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trimming_is_noticed_while_the_log_is_still_longer() {
+        let dir = std::env::temp_dir().join(format!("pmgr-test-follow-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("operations.log");
+        let operation = |n: u32| format!("{} [{}] op ===\n$ cmd\nline {}\n{} [{}] success ===\n", BEGIN_MARKER, n, n, END_MARKER, n);
+        fs::write(&path, (1..=4).map(operation).collect::<String>()).unwrap();
+
+        // The follower has read the first operation only
+        let mut reader = BufReader::new(File::open(&path).unwrap());
+        let mut line = String::new();
+        for _ in 0..4 {
+            reader.read_line(&mut line).unwrap();
+        }
+        assert!(!replaced(&mut reader, &path).unwrap());
+
+        oplog::trim_shared_log(&path, 2).unwrap();
+        assert!(fs::metadata(&path).unwrap().len() > reader.stream_position().unwrap());
+        assert!(replaced(&mut reader, &path).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
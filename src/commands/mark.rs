@@ -0,0 +1,21 @@
+use crate::package::{InstallReason, PackageManager};
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct MarkCommand;
+
+impl MarkCommand {
+    pub fn execute(packages: Vec<String>, reason: InstallReason) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        pm.set_install_reason(&packages, reason)?;
+
+        let label = match reason {
+            InstallReason::Explicit => "explicitly installed",
+            InstallReason::Dependency => "installed as a dependency",
+        };
+        println!("{} {} as {}", "Marked".green().bold(), packages.join(", ").bold(), label);
+
+        Ok(())
+    }
+}
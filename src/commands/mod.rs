@@ -2,8 +2,95 @@ pub mod install;
 pub mod remove;
 pub mod search;
 pub mod list;
+pub mod info;
+pub mod outdated;
+pub mod log;
+pub mod cache;
+pub mod clean;
+pub mod refresh;
+pub mod files;
+pub mod pin;
+pub mod export;
+pub mod import;
+pub mod history;
+pub mod downgrade;
+pub mod stats;
+pub mod why;
+pub mod news;
+pub mod mark;
+pub mod check;
+pub mod doctor;
+pub mod output;
 
 pub use install::InstallCommand;
 pub use remove::RemoveCommand;
 pub use search::SearchCommand;
 pub use list::ListCommand;
+pub use info::InfoCommand;
+pub use outdated::OutdatedCommand;
+pub use log::LogCommand;
+pub use cache::CacheCommand;
+pub use clean::CleanCommand;
+pub use refresh::RefreshCommand;
+pub use files::FilesCommand;
+pub use pin::{PinCommand, UnpinCommand};
+pub use export::ExportCommand;
+pub use import::ImportCommand;
+pub use history::HistoryCommand;
+pub use downgrade::DowngradeCommand;
+pub use stats::StatsCommand;
+pub use why::WhyCommand;
+pub use news::NewsCommand;
+pub use mark::MarkCommand;
+pub use check::CheckCommand;
+pub use doctor::DoctorCommand;
+pub use output::Output;
+
+use crate::config;
+use crate::package::{lock, DbLock, PackageManager, PmgrError};
+use anyhow::Result;
+use colored::Colorize;
+use std::io::{self, Write};
+use std::time::Duration;
+
+/// Ask a yes/no question on stdin (defaults to no)
+pub(crate) fn confirm(question: &str) -> Result<bool> {
+    print!("{} [y/N] ", question);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Make sure no other package manager holds the database lock, waiting a while for it
+pub(crate) fn wait_for_db_lock(pm: &PackageManager, out: Output) -> Result<()> {
+    if !pm.is_db_locked() {
+        return Ok(());
+    }
+
+    let lock_file = pm.db_lock_file();
+    let mut state = pm.db_lock();
+
+    if state == DbLock::Held {
+        let wait_secs = config::load_settings().lock_wait_secs;
+        if wait_secs > 0 {
+            out.status(format!(
+                "{} waiting up to {}s for it to finish (Ctrl+C to cancel)...",
+                "Another package manager is running;".yellow().bold(),
+                wait_secs
+            ));
+            state = lock::wait_while_held(Duration::from_secs(wait_secs), lock::POLL_INTERVAL, || pm.db_lock());
+        }
+    }
+
+    match state {
+        DbLock::Free => Ok(()),
+        DbLock::Held => Err(PmgrError::DatabaseLocked { stale: false }.into()),
+        DbLock::Stale => {
+            eprintln!("{} {}", "Hint:".cyan().bold(), lock::stale_hint(&lock_file));
+            Err(PmgrError::DatabaseLocked { stale: true }.into())
+        }
+    }
+}
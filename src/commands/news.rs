@@ -0,0 +1,42 @@
+use crate::package::news;
+use crate::package::PackageManager;
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct NewsCommand;
+
+impl NewsCommand {
+    pub fn execute(limit: usize) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        println!("{}", "Fetching Arch Linux news...".cyan());
+        let items = news::fetch()?;
+        if items.is_empty() {
+            println!("{}", "No news items found.".yellow());
+            return Ok(());
+        }
+
+        // Items published since the last full upgrade may need attention before the next one
+        let last_upgrade = pm.last_upgrade_day();
+
+        println!();
+        for item in items.iter().take(limit) {
+            let is_new = match (item.date, last_upgrade) {
+                (Some(date), Some(upgrade)) => date >= upgrade,
+                _ => false,
+            };
+            let marker = if is_new {
+                format!(" {}", "(new since last upgrade)".yellow().bold())
+            } else {
+                String::new()
+            };
+
+            println!("{}  {}{}", item.date_string().dimmed(), item.title.bold(), marker);
+            if !item.link.is_empty() {
+                println!("            {}", item.link.blue());
+            }
+        }
+
+        Ok(())
+    }
+}
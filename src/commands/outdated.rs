@@ -0,0 +1,71 @@
+use crate::config;
+use crate::output;
+use crate::package::PackageManager;
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct OutdatedCommand;
+
+impl OutdatedCommand {
+    pub fn execute() -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        println!("{}", "Checking for updates...".cyan());
+        let upgradable = pm.list_upgradable()?;
+
+        if upgradable.is_empty() {
+            println!("{}", "System is up to date".green());
+            return Ok(());
+        }
+
+        let name_width = upgradable.iter().map(|p| p.name.len()).max().unwrap_or(0);
+        let version_width = upgradable
+            .iter()
+            .map(|p| p.current_version.len())
+            .max()
+            .unwrap_or(0);
+
+        let pins = config::load_settings().pins;
+
+        println!();
+        for pkg in &upgradable {
+            let line = format!(
+                "  {:<name_width$}  {:<version_width$}  ->  {}",
+                pkg.name, pkg.current_version, pkg.new_version
+            );
+
+            // Ignored and pinned packages are skipped by system updates
+            if pkg.ignored {
+                println!("{} {}", line.dimmed(), "(ignored)".dimmed());
+                continue;
+            }
+
+            let pin_marker = if pins.contains_key(&pkg.name) {
+                format!("  {}{}", output::symbol("📌 ", ""), "pinned".yellow())
+            } else {
+                String::new()
+            };
+
+            println!(
+                "  {}  {}  {}  {}{}",
+                format!("{:<width$}", pkg.name, width = name_width).bold(),
+                format!("{:<width$}", pkg.current_version, width = version_width).dimmed(),
+                "->".dimmed(),
+                pkg.new_version.green(),
+                pin_marker
+            );
+        }
+
+        let ignored = upgradable.iter().filter(|p| p.ignored).count();
+        print!(
+            "\n{} package(s) can be upgraded",
+            (upgradable.len() - ignored).to_string().green().bold()
+        );
+        if ignored > 0 {
+            print!(" ({} ignored)", ignored);
+        }
+        println!();
+
+        Ok(())
+    }
+}
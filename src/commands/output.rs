@@ -0,0 +1,31 @@
+use std::fmt::Display;
+
+/// How much a command prints
+/// Quiet output keeps only the data a script needs (e.g. package names, one per line);
+/// errors always go to stderr and the exit code still reflects success or failure
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Output {
+    quiet: bool,
+}
+
+impl Output {
+    pub fn new(quiet: bool) -> Self {
+        Self { quiet }
+    }
+
+    pub fn is_quiet(&self) -> bool {
+        self.quiet
+    }
+
+    /// Progress messages and banners, hidden in quiet mode
+    pub fn status(&self, message: impl Display) {
+        if !self.quiet {
+            println!("{}", message);
+        }
+    }
+
+    /// Essential data, always printed
+    pub fn data(&self, line: impl Display) {
+        println!("{}", line);
+    }
+}
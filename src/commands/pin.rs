@@ -0,0 +1,68 @@
+use crate::config::{self, parse_pin_spec};
+use crate::output;
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct PinCommand;
+
+impl PinCommand {
+    /// Pin a package at a version, hold it at any version, or list held packages
+    pub fn execute(spec: Option<String>, list: bool) -> Result<()> {
+        let mut settings = config::load_settings();
+
+        let Some(spec) = spec.filter(|_| !list) else {
+            if settings.pins.is_empty() && settings.ignored_packages.is_empty() {
+                println!("{}", "No pinned or ignored packages.".yellow());
+            }
+            for (name, version) in &settings.pins {
+                println!("{}{} {}", output::symbol("📌 ", ""), name.bold(), version.green());
+            }
+            for name in &settings.ignored_packages {
+                println!("{}{} {}", output::symbol("📌 ", ""), name.bold(), "(ignored)".dimmed());
+            }
+            return Ok(());
+        };
+
+        let (name, version) = parse_pin_spec(&spec);
+        match version {
+            Some(version) => {
+                println!("{}Pinned {} at {}", output::symbol("📌 ", ""), name.bold(), version.green());
+                settings.ignored_packages.retain(|p| p != &name);
+                settings.pins.insert(name, version);
+            }
+            None => {
+                if settings.ignored_packages.contains(&name) {
+                    println!("{} is already ignored", name.bold());
+                    return Ok(());
+                }
+                println!("{}Ignoring {} in updates", output::symbol("📌 ", ""), name.bold());
+                settings.pins.remove(&name);
+                settings.ignored_packages.push(name);
+            }
+        }
+        config::save_settings(&settings)?;
+
+        Ok(())
+    }
+}
+
+pub struct UnpinCommand;
+
+impl UnpinCommand {
+    pub fn execute(package: String) -> Result<()> {
+        let mut settings = config::load_settings();
+
+        let was_pinned = settings.pins.remove(&package).is_some();
+        let was_ignored = settings.ignored_packages.contains(&package);
+        settings.ignored_packages.retain(|p| p != &package);
+
+        if !was_pinned && !was_ignored {
+            anyhow::bail!("{} is not pinned or ignored", package);
+        }
+
+        config::save_settings(&settings)?;
+        println!("Unpinned {}", package.bold());
+
+        Ok(())
+    }
+}
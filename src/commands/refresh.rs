@@ -0,0 +1,24 @@
+use super::{wait_for_db_lock, Output};
+use crate::package::PackageManager;
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct RefreshCommand;
+
+impl RefreshCommand {
+    pub fn execute(files: bool, out: Output) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        out.status(if files {
+            "Refreshing package and files databases...".cyan()
+        } else {
+            "Refreshing package databases...".cyan()
+        });
+
+        wait_for_db_lock(&pm, out)?;
+        pm.refresh_databases(files)?;
+        out.status("Package databases are up to date.".green());
+
+        Ok(())
+    }
+}
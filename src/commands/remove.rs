@@ -1,4 +1,5 @@
-use crate::package::PackageManager;
+use super::{wait_for_db_lock, Output};
+use crate::package::{PackageManager, RemoveOptions};
 use crate::ui::Selector;
 use anyhow::Result;
 use colored::Colorize;
@@ -6,43 +7,47 @@ use colored::Colorize;
 pub struct RemoveCommand;
 
 impl RemoveCommand {
-    pub fn execute(packages: Vec<String>, interactive: bool) -> Result<()> {
-        let pm = PackageManager::new();
+    pub fn execute(packages: Vec<String>, interactive: bool, options: RemoveOptions, out: Output) -> Result<()> {
+        let pm = PackageManager::new()?;
 
         if interactive || packages.is_empty() {
             // Interactive mode: show installed packages
-            println!("{}", "Loading installed packages...".cyan());
+            out.status("Loading installed packages...".cyan());
             let installed = pm.list_installed()?;
 
             if installed.is_empty() {
-                println!("{}", "No packages installed.".yellow());
+                out.status("No packages installed.".yellow());
                 return Ok(());
             }
 
-            let selected = Selector::select_installed(installed)?;
+            let selected = Selector::select_installed(installed, options, pm.backend())?;
 
             if selected.is_empty() {
-                println!("{}", "No packages selected.".yellow());
+                out.status("No packages selected.".yellow());
                 return Ok(());
             }
 
-            println!(
-                "\n{} {}",
+            out.status(format!(
+                "\n{} {} {}",
                 "Removing:".red().bold(),
-                selected.join(", ")
-            );
+                selected.join(", "),
+                format!("(pacman {})", options.flag()).dimmed()
+            ));
 
-            pm.remove(&selected)?;
-            println!("{}", "Removal complete!".green());
+            wait_for_db_lock(&pm, out)?;
+            pm.remove(&selected, &options)?;
+            out.status("Removal complete!".green());
         } else {
             // Direct mode: remove specified packages
-            println!(
-                "{} {}",
+            out.status(format!(
+                "{} {} {}",
                 "Removing:".red().bold(),
-                packages.join(", ")
-            );
-            pm.remove(&packages)?;
-            println!("{}", "Removal complete!".green());
+                packages.join(", "),
+                format!("(pacman {})", options.flag()).dimmed()
+            ));
+            wait_for_db_lock(&pm, out)?;
+            pm.remove(&packages, &options)?;
+            out.status("Removal complete!".green());
         }
 
         Ok(())
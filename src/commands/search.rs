@@ -1,34 +1,218 @@
-use crate::package::PackageManager;
+use crate::package::aur::AurPackage;
+use crate::package::date::format_date;
+use crate::package::{merge_providers, Package, PackageManager, SearchScope, PROVIDES_LOOKUP_THRESHOLD};
+use super::Output;
 use anyhow::Result;
 use colored::Colorize;
+use std::collections::HashSet;
 
 pub struct SearchCommand;
 
 impl SearchCommand {
-    pub fn execute(query: String) -> Result<()> {
-        let pm = PackageManager::new();
+    pub fn execute(terms: Vec<String>, scope: SearchScope, json: bool, out: Output) -> Result<()> {
+        let pm = PackageManager::new()?;
+        let query = terms.join(" ");
 
-        println!("{} '{}'...", "Searching for".cyan(), query);
+        if !json {
+            out.status(format!("{} '{}'...", "Searching for".cyan(), query));
+        }
+
+        if scope == SearchScope::Aur {
+            match pm.search_aur(&terms) {
+                Ok(packages) => return Self::print_aur_results(&pm, &packages, json, out),
+                // The AUR helper can still search (it may have its own mirror or cache)
+                Err(e) => eprintln!(
+                    "{} AUR search failed ({}), falling back to {}",
+                    "Warning:".yellow().bold(),
+                    e,
+                    pm.backend()
+                ),
+            }
+        }
 
-        let results = pm.search(&query)?;
+        let results = pm.search(&terms, scope)?;
 
-        if results.is_empty() {
+        // Few matches: the query may be a command name rather than a package name
+        // (the files database only covers the sync repositories)
+        let mut providers = Vec::new();
+        let mut files_db_missing = false;
+        if results.len() < PROVIDES_LOOKUP_THRESHOLD && scope != SearchScope::Aur && terms.len() == 1 {
+            if let Ok(lookup) = pm.find_command_providers(&query) {
+                providers = merge_providers(&results, lookup.providers);
+                files_db_missing = lookup.files_db_missing;
+            }
+        }
+
+        if json {
+            let all: Vec<&Package> = results.iter().chain(providers.iter()).collect();
+            out.data(serde_json::to_string_pretty(&all)?);
+            return Ok(());
+        }
+
+        if out.is_quiet() {
+            // One name per line for scripts
+            for pkg in results.iter().chain(providers.iter()) {
+                out.data(&pkg.name);
+            }
+            return Ok(());
+        }
+
+        let installed: HashSet<String> = pm
+            .list_installed()?
+            .iter()
+            .map(|name| base_name(name).to_string())
+            .collect();
+
+        if results.is_empty() && providers.is_empty() {
             println!("{}", "No packages found.".yellow());
+            if files_db_missing {
+                Self::print_files_db_hint();
+            }
             return Ok(());
         }
 
-        println!("\n{} packages found:\n", results.len().to_string().green());
+        if !results.is_empty() {
+            println!("\n{} packages found:\n", results.len().to_string().green());
+
+            let repo_width = Self::repo_width(&results);
+            for pkg in &results {
+                Self::print_package(pkg, repo_width, &installed);
+            }
+        }
 
-        for pkg in results {
+        if !providers.is_empty() {
             println!(
-                "{} {} {}",
-                format!("{}/{}", pkg.repository, pkg.name).blue().bold(),
-                pkg.version.green(),
-                format!("[installed]").yellow()
+                "\n{} '{}':\n",
+                "Provides command".magenta().bold(),
+                query
             );
+
+            let repo_width = Self::repo_width(&providers);
+            for pkg in &providers {
+                Self::print_package(pkg, repo_width, &installed);
+            }
+        } else if files_db_missing {
+            Self::print_files_db_hint();
+        }
+
+        Ok(())
+    }
+
+    /// Print RPC results, with votes, popularity and maintenance status
+    fn print_aur_results(pm: &PackageManager, packages: &[AurPackage], json: bool, out: Output) -> Result<()> {
+        let results: Vec<Package> = packages.iter().map(AurPackage::to_package).collect();
+
+        if json {
+            out.data(serde_json::to_string_pretty(&results)?);
+            return Ok(());
+        }
+
+        if out.is_quiet() {
+            for pkg in &results {
+                out.data(&pkg.name);
+            }
+            return Ok(());
+        }
+
+        if results.is_empty() {
+            println!("{}", "No packages found.".yellow());
+            return Ok(());
+        }
+
+        let installed: HashSet<String> = pm.list_installed()?.into_iter().collect();
+        println!("\n{} packages found:\n", results.len().to_string().green());
+
+        for (aur, pkg) in packages.iter().zip(&results) {
+            let mut line = format_result(pkg, 3, &installed);
+            if aur.out_of_date.is_some() {
+                line.push_str(&format!(" {}", "[out of date]".red()));
+            }
+            if aur.is_orphaned() {
+                line.push_str(&format!(" {}", "[orphaned]".red()));
+            }
+            println!("{}", line);
             println!("    {}", pkg.description.dimmed());
+            println!("    {}", aur_details(aur).dimmed());
         }
 
         Ok(())
     }
+
+    fn repo_width(packages: &[Package]) -> usize {
+        packages.iter().map(|p| p.repository.len()).max().unwrap_or(0)
+    }
+
+    fn print_package(pkg: &Package, repo_width: usize, installed: &HashSet<String>) {
+        println!("{}", format_result(pkg, repo_width, installed));
+        println!("    {}", pkg.description.dimmed());
+    }
+
+    fn print_files_db_hint() {
+        println!(
+            "\n{} run {} to also search by command name",
+            "Hint:".cyan().bold(),
+            "sudo pacman -Fy".bold()
+        );
+    }
+}
+
+/// Package name without a `repo/` prefix
+fn base_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// Header line of a search result, tagged when the package is installed
+fn format_result(pkg: &Package, repo_width: usize, installed: &HashSet<String>) -> String {
+    // Repository column first so the source of each hit is obvious
+    let mut line = format!(
+        "{} {} {}",
+        format!("{:<width$}", pkg.repository, width = repo_width).magenta(),
+        pkg.name.blue().bold(),
+        pkg.version.green()
+    );
+    if installed.contains(base_name(&pkg.name)) {
+        line.push_str(&format!(" {}", "[installed]".yellow()));
+    }
+    line
+}
+
+/// Votes, popularity, maintainer and last update of an AUR package
+fn aur_details(pkg: &AurPackage) -> String {
+    format!(
+        "votes: {}, popularity: {:.2}, maintainer: {}, updated: {}",
+        pkg.votes,
+        pkg.popularity,
+        pkg.maintainer.as_deref().unwrap_or("none"),
+        format_date(pkg.last_modified_days())
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg(repository: &str, name: &str) -> Package {
+        Package {
+            repository: repository.to_string(),
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            description: String::new(),
+        }
+    }
+
+    #[test]
+    fn only_installed_packages_are_tagged() {
+        let installed: HashSet<String> = ["vim".to_string()].into();
+
+        assert!(format_result(&pkg("extra", "vim"), 5, &installed).contains("[installed]"));
+        assert!(!format_result(&pkg("extra", "gvim"), 5, &installed).contains("[installed]"));
+    }
+
+    #[test]
+    fn repository_prefix_is_ignored() {
+        let installed: HashSet<String> = ["yay".to_string()].into();
+
+        assert!(format_result(&pkg("aur", "aur/yay"), 3, &installed).contains("[installed]"));
+        assert!(format_result(&pkg("local", "yay"), 5, &installed).contains("[installed]"));
+    }
 }
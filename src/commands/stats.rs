@@ -0,0 +1,69 @@
+use crate::package::{format_size, PackageManager};
+use anyhow::Result;
+use colored::Colorize;
+
+/// Number of packages listed in the "largest packages" table
+const LARGEST_COUNT: usize = 10;
+
+pub struct StatsCommand;
+
+impl StatsCommand {
+    pub fn execute() -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        println!("{}", "Collecting package statistics...".cyan());
+        let mut packages = pm.list_installed_detailed()?;
+        let explicit = pm.count_explicit()?;
+        let deps = pm.count_deps()?;
+        let orphans = pm.count_orphans()?;
+        let cache = pm.cache_info();
+        let total_size: u64 = packages.iter().map(|p| p.installed_size).sum();
+
+        let rows = [
+            (
+                "Installed packages",
+                format!(
+                    "{} ({} explicit, {} dependencies)",
+                    packages.len().to_string().bold(),
+                    explicit,
+                    deps
+                ),
+            ),
+            ("Installed size", format_size(total_size).bold().to_string()),
+            (
+                "Package cache",
+                format!("{} in {} files", format_size(cache.total_size).bold(), cache.file_count),
+            ),
+            (
+                "Orphans",
+                if orphans > 0 {
+                    orphans.to_string().yellow().bold().to_string()
+                } else {
+                    orphans.to_string().green().to_string()
+                },
+            ),
+        ];
+
+        let label_width = rows.iter().map(|(label, _)| label.len() + 1).max().unwrap_or(0);
+        println!();
+        for (label, value) in &rows {
+            println!("{}  {}", format!("{:<label_width$}", format!("{}:", label)).cyan().bold(), value);
+        }
+
+        packages.sort_by(|a, b| b.installed_size.cmp(&a.installed_size).then_with(|| a.name.cmp(&b.name)));
+        packages.truncate(LARGEST_COUNT);
+
+        if !packages.is_empty() {
+            let sizes: Vec<String> = packages.iter().map(|p| format_size(p.installed_size)).collect();
+            let name_width = packages.iter().map(|p| p.name.len()).max().unwrap_or(0);
+            let size_width = sizes.iter().map(|s| s.len()).max().unwrap_or(0);
+
+            println!("\n{}", "Largest packages:".cyan().bold());
+            for (pkg, size) in packages.iter().zip(&sizes) {
+                println!("  {}  {:>size_width$}", format!("{:<name_width$}", pkg.name).bold(), size);
+            }
+        }
+
+        Ok(())
+    }
+}
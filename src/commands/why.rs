@@ -0,0 +1,72 @@
+use crate::output;
+use crate::package::deps::{reverse_dep_tree, ReverseDepNode};
+use crate::package::{PackageManager, PmgrError};
+use anyhow::Result;
+use colored::Colorize;
+
+pub struct WhyCommand;
+
+impl WhyCommand {
+    pub fn execute(package: String, recursive: bool, depth: usize) -> Result<()> {
+        let pm = PackageManager::new()?;
+
+        if recursive {
+            let all = pm.all_reverse_deps()?;
+            if !all.contains_key(&package) {
+                return Err(PmgrError::PackageNotFound(package).into());
+            }
+
+            let tree = reverse_dep_tree(&package, &all, depth);
+            println!("{}", tree.name.bold());
+            if tree.children.is_empty() {
+                println!("  {}", "(none)".dimmed());
+            }
+            Self::print_children(&tree, "");
+            return Ok(());
+        }
+
+        let reverse = pm.required_by(&package)?;
+        let list = |names: &[String]| {
+            if names.is_empty() {
+                "(none)".dimmed().to_string()
+            } else {
+                names.join(", ")
+            }
+        };
+
+        println!("{} {}:", "Packages that depend on".cyan().bold(), package.bold());
+        println!("  {}  {}", "Required by:".red().bold(), list(&reverse.required_by).red());
+        println!("  {} {}", "Optional for:".yellow().bold(), list(&reverse.optional_for).yellow());
+
+        Ok(())
+    }
+
+    /// Print a node's dependents as tree branches
+    fn print_children(node: &ReverseDepNode, prefix: &str) {
+        for (i, child) in node.children.iter().enumerate() {
+            let last = i + 1 == node.children.len();
+            let branch = if last {
+                output::symbol("└── ", "`-- ")
+            } else {
+                output::symbol("├── ", "|-- ")
+            };
+
+            let name = if child.optional {
+                format!("{} {}", child.name.yellow(), "(optional)".dimmed())
+            } else {
+                child.name.red().to_string()
+            };
+            let note = if child.cycle {
+                " (cycle)".dimmed().to_string()
+            } else if child.truncated {
+                " (more below depth limit)".dimmed().to_string()
+            } else {
+                String::new()
+            };
+            println!("{}{}{}{}", prefix, branch, name, note);
+
+            let indent = if last { "    " } else { output::symbol("│   ", "|   ") };
+            Self::print_children(child, &format!("{}{}", prefix, indent));
+        }
+    }
+}
@@ -1,3 +1,5 @@
+mod pins;
 mod settings;
 
-pub use settings::{load_settings, save_settings, Settings};
+pub use pins::{parse_pin_spec, pin_conflicts, Pins};
+pub use settings::{load_settings, save_settings, settings_path, RefreshIntervals, Settings};
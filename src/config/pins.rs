@@ -0,0 +1,66 @@
+use crate::package::InstalledPackage;
+use std::collections::BTreeMap;
+
+/// Pinned packages: name -> exact version that must be kept
+pub type Pins = BTreeMap<String, String>;
+
+/// Describe every planned package that would move a pinned package off its version
+pub fn pin_conflicts(pins: &Pins, plan: &[InstalledPackage]) -> Vec<String> {
+    plan.iter()
+        .filter_map(|pkg| {
+            let pinned = pins.get(&pkg.name)?;
+            if *pinned == pkg.version {
+                return None;
+            }
+            Some(format!(
+                "{} is pinned at {} but would change to {}",
+                pkg.name, pinned, pkg.version
+            ))
+        })
+        .collect()
+}
+
+/// Parse a `name[=version]` pin argument
+pub fn parse_pin_spec(spec: &str) -> (String, Option<String>) {
+    match spec.split_once('=') {
+        Some((name, version)) if !version.is_empty() => (name.to_string(), Some(version.to_string())),
+        Some((name, _)) => (name.to_string(), None),
+        None => (spec.to_string(), None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn planned(name: &str, version: &str) -> InstalledPackage {
+        InstalledPackage {
+            name: name.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    #[test]
+    fn detects_version_changes_of_pinned_packages() {
+        let pins = Pins::from([("electron25".to_string(), "25.9.8-1".to_string())]);
+        let plan = vec![planned("electron25", "25.9.9-1"), planned("vscode", "1.90-1")];
+
+        let conflicts = pin_conflicts(&pins, &plan);
+
+        assert_eq!(conflicts, vec!["electron25 is pinned at 25.9.8-1 but would change to 25.9.9-1"]);
+    }
+
+    #[test]
+    fn same_version_is_not_a_conflict() {
+        let pins = Pins::from([("electron25".to_string(), "25.9.8-1".to_string())]);
+
+        assert!(pin_conflicts(&pins, &[planned("electron25", "25.9.8-1")]).is_empty());
+    }
+
+    #[test]
+    fn parses_pin_specs() {
+        assert_eq!(parse_pin_spec("foo=1.2-1"), ("foo".to_string(), Some("1.2-1".to_string())));
+        assert_eq!(parse_pin_spec("foo"), ("foo".to_string(), None));
+        assert_eq!(parse_pin_spec("foo="), ("foo".to_string(), None));
+    }
+}
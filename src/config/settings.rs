@@ -1,13 +1,61 @@
-use crate::ui::Theme;
+use super::Pins;
+use crate::package::{Backend, RemoveOptions};
+use crate::ui::{PreviewLayout, RepoFilter, SortMode, Theme};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
 /// Application settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Settings {
     pub theme: Theme,
+    /// Where the preview pane goes in the package tabs
+    pub layout: PreviewLayout,
+    /// Percent of a package tab taken by the preview pane (20 to 80)
+    pub preview_ratio: u16,
+    /// Show the preview pane (hidden, no preview commands are run)
+    pub preview_enabled: bool,
+    /// Command for the preview pane, split into arguments without a shell (`{}` is the package); the backend's package info when unset
+    pub preview_command: Option<String>,
+    /// Seconds a preview command may run before it is killed
+    pub preview_timeout_secs: u64,
+    /// Pass --noconfirm to pacman for `pmgr install` and `pmgr remove` (the TUI never prompts)
+    pub noconfirm: bool,
+    /// Cached package data older than this is flagged as stale in the Install view
+    pub stale_cache_hours: u64,
+    /// Packages held at an exact version (excluded from updates)
+    pub pins: Pins,
+    /// Packages held at whatever version is installed (like pacman's IgnorePkg)
+    pub ignored_packages: Vec<String>,
+    /// AUR packages not updated for longer than this are flagged in the install dialog
+    pub aur_stale_days: u64,
+    /// Require an extra confirmation keystroke when a selected AUR package has warnings
+    pub confirm_aur_warnings: bool,
+    /// Packages (or `prefix*` patterns) whose removal must be confirmed by typing "yes", on top of the built-in list
+    pub protected_packages: Vec<String>,
+    /// Installing skips packages that are already up to date (`--needed`); the confirmation dialog can include them
+    pub always_needed: bool,
+    /// How often background data is refreshed
+    pub refresh_intervals: RefreshIntervals,
+    /// Default `pacman -R` modifiers (overridable per command on the CLI)
+    pub remove_behavior: RemoveOptions,
+    /// Package manager to use; detected (paru, yay, then pacman) when unset
+    pub backend: Option<Backend>,
+    /// Seconds to wait for another package manager to release the database lock (0: don't wait)
+    pub lock_wait_secs: u64,
+    /// Last repository filter used in each package list (`install` tab, `select` for the CLI picker)
+    pub repo_filters: HashMap<String, RepoFilter>,
+    /// `y` copies `repository/name` instead of just the package name
+    pub copy_with_repository: bool,
+    /// Sort order last used in each package list (`install`, `remove` and `list` tabs, `select` for the CLI picker)
+    pub sort_modes: HashMap<String, SortMode>,
+    /// Seconds a successful operation stays on screen before its window closes (null: until a key is pressed)
+    pub auto_close_delay: Option<u64>,
+    /// Operations kept in `~/.local/share/pmgr/logs` and `operations.log` (the oldest are deleted)
+    pub log_retention: usize,
     // Future: keybindings, layout preferences, etc.
 }
 
@@ -15,12 +63,82 @@ impl Default for Settings {
     fn default() -> Self {
         Self {
             theme: Theme::Default,
+            layout: PreviewLayout::Vertical,
+            preview_ratio: 50,
+            preview_enabled: true,
+            preview_command: None,
+            preview_timeout_secs: 5,
+            noconfirm: false,
+            stale_cache_hours: 6,
+            pins: Pins::new(),
+            ignored_packages: Vec::new(),
+            aur_stale_days: 730,
+            confirm_aur_warnings: false,
+            protected_packages: Vec::new(),
+            always_needed: true,
+            refresh_intervals: RefreshIntervals::default(),
+            remove_behavior: RemoveOptions::default(),
+            backend: None,
+            lock_wait_secs: 60,
+            repo_filters: HashMap::new(),
+            sort_modes: HashMap::new(),
+            copy_with_repository: false,
+            auto_close_delay: Some(3),
+            log_retention: 50,
         }
     }
 }
 
+/// Background refresh intervals, in minutes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RefreshIntervals {
+    /// Installed/available package counts on the Home view
+    pub stats: u64,
+    /// Pending update count
+    pub updates: u64,
+    /// Arch Linux news published since the last upgrade
+    pub news: u64,
+    /// Security audit results (nothing audits in the background yet)
+    pub audit: u64,
+}
+
+impl Default for RefreshIntervals {
+    fn default() -> Self {
+        Self {
+            stats: 10,
+            updates: 60,
+            news: 360,
+            audit: 1440,
+        }
+    }
+}
+
+impl Settings {
+    /// Names of all packages excluded from system updates (pinned or ignored)
+    pub fn held_packages(&self) -> Vec<String> {
+        let mut held: Vec<String> = self.pins.keys().cloned().collect();
+        for name in &self.ignored_packages {
+            if !held.contains(name) {
+                held.push(name.clone());
+            }
+        }
+        held
+    }
+
+    /// Repository filter last used in a package list
+    pub fn repo_filter(&self, view: &str) -> RepoFilter {
+        self.repo_filters.get(view).cloned().unwrap_or_default()
+    }
+
+    /// Sort order last used in a package list
+    pub fn sort_mode(&self, view: &str) -> SortMode {
+        self.sort_modes.get(view).copied().unwrap_or_default()
+    }
+}
+
 /// Get the path to the settings file
-fn settings_path() -> Result<PathBuf> {
+pub fn settings_path() -> Result<PathBuf> {
     let config_dir = dirs::config_dir()
         .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
         .join("pmgr");
@@ -34,17 +152,14 @@ fn settings_path() -> Result<PathBuf> {
 /// Load settings from disk
 /// Falls back to default settings if file doesn't exist or is invalid
 pub fn load_settings() -> Settings {
-    match settings_path() {
-        Ok(path) => {
-            if path.exists() {
-                if let Ok(content) = fs::read_to_string(&path) {
-                    if let Ok(settings) = serde_json::from_str(&content) {
-                        return settings;
-                    }
+    if let Ok(path) = settings_path() {
+        if path.exists() {
+            if let Ok(content) = fs::read_to_string(&path) {
+                if let Ok(settings) = serde_json::from_str(&content) {
+                    return settings;
                 }
             }
         }
-        Err(_) => {}
     }
 
     // Return default settings if anything fails
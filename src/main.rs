@@ -1,10 +1,13 @@
 mod commands;
 mod config;
+mod oplog;
+mod output;
 mod package;
 mod ui;
 
 use clap::{Parser, Subcommand};
 use colored::Colorize;
+use std::path::PathBuf;
 
 /// pmgr - Modern TUI package manager for Arch Linux
 #[derive(Parser)]
@@ -13,6 +16,26 @@ use colored::Colorize;
 #[command(version = "0.1.0")]
 #[command(about = "Modern TUI package manager for Arch Linux", long_about = None)]
 struct Cli {
+    /// Machine-readable JSON output (search, list)
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// Plain output without colors or unicode symbols
+    #[arg(long, global = true)]
+    plain: bool,
+
+    /// Disable colored output (also honors the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    no_color: bool,
+
+    /// Only print essential data (e.g. package names, one per line) for scripts
+    #[arg(short, long, global = true)]
+    quiet: bool,
+
+    /// Package manager to use: pacman, yay or paru (detected in the order paru, yay, pacman)
+    #[arg(long, global = true)]
+    backend: Option<package::Backend>,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -28,6 +51,14 @@ enum Commands {
         /// Skip interactive mode
         #[arg(short = 'y', long)]
         no_interactive: bool,
+
+        /// Install as dependencies (removed as orphans once nothing needs them)
+        #[arg(long)]
+        asdeps: bool,
+
+        /// Skip packages that are already up to date
+        #[arg(long)]
+        needed: bool,
     },
 
     /// Remove packages (interactive by default)
@@ -39,13 +70,34 @@ enum Commands {
         /// Skip interactive mode
         #[arg(short = 'y', long)]
         no_interactive: bool,
+
+        /// Keep configuration files (saved as .pacsave)
+        #[arg(long)]
+        keep_config: bool,
+
+        /// Keep dependencies that are no longer needed
+        #[arg(long)]
+        no_recursive: bool,
+
+        /// Also remove packages that depend on the removed ones
+        #[arg(long)]
+        cascade: bool,
     },
 
     /// Search for packages
     #[command(alias = "s")]
     Search {
-        /// Search query
-        query: String,
+        /// Search terms (packages must match all of them)
+        #[arg(required = true)]
+        query: Vec<String>,
+
+        /// Only search the AUR (through its web API, falling back to yay or paru)
+        #[arg(long, conflicts_with = "repo_only")]
+        aur_only: bool,
+
+        /// Only search the sync repositories
+        #[arg(long)]
+        repo_only: bool,
     },
 
     /// List installed packages
@@ -54,38 +106,386 @@ enum Commands {
         /// Interactive browsing mode
         #[arg(short, long)]
         interactive: bool,
+
+        /// Only explicitly installed packages
+        #[arg(long, conflicts_with_all = ["deps", "foreign"])]
+        explicit: bool,
+
+        /// Only packages installed as dependencies
+        #[arg(long, conflicts_with = "foreign")]
+        deps: bool,
+
+        /// Only foreign packages (not in the sync databases, e.g. from the AUR)
+        #[arg(long)]
+        foreign: bool,
+
+        /// Sort by name, size (largest first) or date (newest first)
+        #[arg(short, long, default_value = "name")]
+        sort: commands::list::ListSort,
+    },
+
+    /// Show detailed package information
+    Info {
+        /// Package names to show
+        #[arg(required = true)]
+        packages: Vec<String>,
+
+        /// Only look in the local (installed) database
+        #[arg(long)]
+        installed: bool,
+    },
+
+    /// List packages with pending updates
+    Outdated,
+
+    /// Mark packages as explicitly installed or as dependencies
+    Mark {
+        /// Package names
+        #[arg(required = true)]
+        packages: Vec<String>,
+
+        /// Mark as explicitly installed (never reported as an orphan)
+        #[arg(long, conflicts_with = "asdeps", required_unless_present = "asdeps")]
+        explicit: bool,
+
+        /// Mark as installed as a dependency
+        #[arg(long)]
+        asdeps: bool,
+    },
+
+    /// Verify installed package files (exits non-zero when problems are found)
+    Check {
+        /// Also compare file sizes, permissions and timestamps (`pacman -Qkk`)
+        #[arg(long)]
+        deep: bool,
+
+        /// List every checked package, not only those with problems
+        #[arg(short, long)]
+        all: bool,
+
+        /// Only check this package
+        #[arg(short, long)]
+        package: Option<String>,
+    },
+
+    /// Show the latest Arch Linux news
+    News {
+        /// Number of items to show
+        #[arg(short = 'n', long, default_value_t = 5)]
+        limit: usize,
+    },
+
+    /// Show which installed packages depend on a package
+    Why {
+        /// Package name
+        package: String,
+
+        /// Also show what depends on the dependents, as a tree
+        #[arg(short, long)]
+        recursive: bool,
+
+        /// Maximum tree depth with --recursive
+        #[arg(long, default_value_t = 5)]
+        depth: usize,
+    },
+
+    /// Show a disk usage summary of installed packages and the package cache
+    Stats,
+
+    /// Show pmgr's configuration and when its background data was last refreshed
+    Doctor,
+
+    /// Show the operations log
+    Log {
+        /// Follow the log as new output is written
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Number of previous operations to show
+        #[arg(short = 'n', long, default_value_t = 1)]
+        last: usize,
+    },
+
+    /// Browse the logs of past operations, one file per operation
+    Logs {
+        /// Print the log files, newest first, instead of opening the viewer
+        #[arg(short, long)]
+        list: bool,
+    },
+
+    /// Show the package cache size and clean it
+    Clean {
+        /// Number of versions of each package to keep
+        #[arg(short, long, default_value_t = 3)]
+        keep: u32,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show or clear pmgr's cache of the available package list
+    Cache {
+        /// Remove the cached list (it is rebuilt the next time packages are listed)
+        #[arg(long)]
+        clear: bool,
+    },
+
+    /// Download fresh package databases (pacman -Sy) without upgrading
+    Refresh {
+        /// Also refresh the files databases used by file searches (pacman -Fy)
+        #[arg(short, long)]
+        files: bool,
+    },
+
+    /// List files installed by a package
+    Files {
+        /// Package name
+        package: String,
+
+        /// Show files as a directory tree
+        #[arg(short, long)]
+        tree: bool,
+    },
+
+    /// Hold a package back from updates (lists held packages without arguments)
+    Pin {
+        /// Package to hold, as `name` (ignored at any version) or `name=version`
+        package: Option<String>,
+
+        /// List pinned and ignored packages
+        #[arg(short, long)]
+        list: bool,
+    },
+
+    /// Remove a package pin or ignore entry
+    Unpin {
+        /// Package to unpin
+        package: String,
+    },
+
+    /// Export the list of explicitly installed packages
+    Export {
+        /// Write the list to a file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Install the packages listed in a file that aren't installed yet
+    Import {
+        /// Package list file (one name per line, `#` starts a comment)
+        file: PathBuf,
+
+        /// Only show what would be installed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Skip the confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Show package transaction history from pacman.log
+    History {
+        /// Only show transactions for this package
+        package: Option<String>,
+
+        /// Only show one action (install, upgrade, downgrade, reinstall, remove)
+        #[arg(short, long)]
+        action: Option<package::history::HistoryAction>,
+
+        /// Maximum number of entries to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        limit: usize,
+    },
+
+    /// Install an older version of a package from the package cache
+    Downgrade {
+        /// Package name
+        package: String,
+
+        /// Version to install (skips the interactive picker)
+        #[arg(long)]
+        to: Option<String>,
     },
 }
 
 fn main() {
-    if let Err(e) = run() {
-        eprintln!("{} {}", "Error:".red().bold(), e);
-        std::process::exit(1);
+    // Put the terminal back before a panic message is printed, even from inside the interactive views
+    ui::install_panic_hook();
+    let cli = Cli::parse();
+    let json = cli.json;
+    output::init(cli.plain, cli.no_color);
+    package::backend::init(cli.backend);
+
+    if let Err(e) = run(cli) {
+        if json {
+            // Structured error so scripts can tell failures from empty results
+            eprintln!("{}", serde_json::json!({ "error": e.to_string() }));
+        } else {
+            eprintln!("{} {}", "Error:".red().bold(), e);
+        }
+        // Distinct codes for missing programs, a locked database, failed authentication...
+        let code = e.downcast_ref::<package::PmgrError>().map_or(1, package::PmgrError::exit_code);
+        std::process::exit(code);
     }
 }
 
-fn run() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+fn run(cli: Cli) -> anyhow::Result<()> {
+    let out = commands::Output::new(cli.quiet);
 
     match cli.command {
         Some(cmd) => match cmd {
             Commands::Install {
                 packages,
                 no_interactive,
+                asdeps,
+                needed,
             } => {
-                commands::InstallCommand::execute(packages, !no_interactive)?;
+                let options = package::InstallOptions {
+                    asdeps,
+                    needed,
+                    noconfirm: config::load_settings().noconfirm,
+                };
+                commands::InstallCommand::execute(packages, !no_interactive, options, out)?;
             }
             Commands::Remove {
                 packages,
                 no_interactive,
+                keep_config,
+                no_recursive,
+                cascade,
             } => {
-                commands::RemoveCommand::execute(packages, !no_interactive)?;
+                // Flags override the configured remove_behavior
+                let settings = config::load_settings();
+                let mut options = settings.remove_behavior;
+                options.noconfirm = settings.noconfirm;
+                if keep_config {
+                    options.nosave = false;
+                }
+                if no_recursive {
+                    options.recursive = false;
+                }
+                if cascade {
+                    options.cascade = true;
+                }
+                commands::RemoveCommand::execute(packages, !no_interactive, options, out)?;
             }
-            Commands::Search { query } => {
-                commands::SearchCommand::execute(query)?;
+            Commands::Search {
+                query,
+                aur_only,
+                repo_only,
+            } => {
+                let scope = if aur_only {
+                    package::SearchScope::Aur
+                } else if repo_only {
+                    package::SearchScope::Repo
+                } else {
+                    package::SearchScope::Combined
+                };
+                commands::SearchCommand::execute(query, scope, cli.json, out)?;
+            }
+            Commands::List {
+                interactive,
+                explicit,
+                deps,
+                foreign,
+                sort,
+            } => {
+                let filter = if explicit {
+                    package::InstallFilter::Explicit
+                } else if deps {
+                    package::InstallFilter::Deps
+                } else if foreign {
+                    package::InstallFilter::Foreign
+                } else {
+                    package::InstallFilter::All
+                };
+                commands::ListCommand::execute(interactive, filter, sort, cli.json, out)?;
+            }
+            Commands::Info {
+                packages,
+                installed,
+            } => {
+                commands::InfoCommand::execute(packages, installed)?;
+            }
+            Commands::Outdated => {
+                commands::OutdatedCommand::execute()?;
+            }
+            Commands::Mark {
+                packages,
+                explicit,
+                asdeps: _,
+            } => {
+                let reason = if explicit {
+                    package::InstallReason::Explicit
+                } else {
+                    package::InstallReason::Dependency
+                };
+                commands::MarkCommand::execute(packages, reason)?;
+            }
+            Commands::Check { deep, all, package } => {
+                commands::CheckCommand::execute(deep, all, package)?;
+            }
+            Commands::News { limit } => {
+                commands::NewsCommand::execute(limit)?;
+            }
+            Commands::Why {
+                package,
+                recursive,
+                depth,
+            } => {
+                commands::WhyCommand::execute(package, recursive, depth)?;
+            }
+            Commands::Stats => {
+                commands::StatsCommand::execute()?;
+            }
+            Commands::Doctor => {
+                commands::DoctorCommand::execute()?;
+            }
+            Commands::Log { follow, last } => {
+                commands::LogCommand::execute(follow, last)?;
+            }
+            Commands::Logs { list } => {
+                commands::LogCommand::logs(list)?;
+            }
+            Commands::Clean { keep, yes } => {
+                commands::CleanCommand::execute(keep, yes)?;
+            }
+            Commands::Cache { clear } => {
+                commands::CacheCommand::execute(clear, out)?;
+            }
+            Commands::Refresh { files } => {
+                commands::RefreshCommand::execute(files, out)?;
+            }
+            Commands::Files { package, tree } => {
+                commands::FilesCommand::execute(package, tree)?;
+            }
+            Commands::Pin { package, list } => {
+                commands::PinCommand::execute(package, list)?;
+            }
+            Commands::Unpin { package } => {
+                commands::UnpinCommand::execute(package)?;
+            }
+            Commands::Export { output } => {
+                commands::ExportCommand::execute(output)?;
+            }
+            Commands::Import {
+                file,
+                dry_run,
+                yes,
+            } => {
+                commands::ImportCommand::execute(file, dry_run, yes)?;
+            }
+            Commands::History {
+                package,
+                action,
+                limit,
+            } => {
+                commands::HistoryCommand::execute(package, action, limit)?;
             }
-            Commands::List { interactive } => {
-                commands::ListCommand::execute(interactive)?;
+            Commands::Downgrade { package, to } => {
+                commands::DowngradeCommand::execute(package, to)?;
             }
         },
         None => {
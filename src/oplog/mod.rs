@@ -0,0 +1,279 @@
+use anyhow::Result;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prefix written at the start of every operation
+pub const BEGIN_MARKER: &str = "=== pmgr: begin";
+/// Prefix written once an operation finishes
+pub const END_MARKER: &str = "=== pmgr: end";
+
+/// Get the path to the operations log file
+pub fn log_path() -> Result<PathBuf> {
+    Ok(data_dir()?.join("operations.log"))
+}
+
+/// Directory holding one log file per operation, named `<timestamp>-<operation>.log`
+pub fn logs_dir() -> Result<PathBuf> {
+    let dir = data_dir()?.join("logs");
+    fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+/// pmgr's data directory, created if it doesn't exist
+fn data_dir() -> Result<PathBuf> {
+    let data_dir = dirs::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find data directory"))?
+        .join("pmgr");
+
+    // Create data directory if it doesn't exist
+    fs::create_dir_all(&data_dir)?;
+
+    Ok(data_dir)
+}
+
+/// Per-operation log files, newest first
+pub fn list_logs() -> Result<Vec<PathBuf>> {
+    let mut logs = log_files(&logs_dir()?)?;
+    logs.reverse();
+    Ok(logs)
+}
+
+/// The `.log` files in a directory, oldest first (their names start with the time)
+fn log_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut logs: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+        .collect();
+    logs.sort();
+    Ok(logs)
+}
+
+/// Delete the oldest log files in a directory so that at most `keep` remain
+fn prune_logs(dir: &Path, keep: usize) -> Result<()> {
+    let logs = log_files(dir)?;
+    for path in &logs[..logs.len().saturating_sub(keep)] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Drop the oldest operations from the shared log so that at most `keep` remain
+pub(crate) fn trim_shared_log(path: &Path, keep: usize) -> Result<()> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e.into()),
+    };
+    let operations = split_operations(&content);
+    if operations.len() <= keep {
+        return Ok(());
+    }
+    let kept: String = operations[operations.len() - keep..]
+        .iter()
+        .flatten()
+        .map(|line| format!("{}\n", line))
+        .collect();
+    // Replace the file rather than rewriting it, so `pmgr log --follow` notices and reopens it
+    let trimmed = path.with_extension("log.tmp");
+    fs::write(&trimmed, kept)?;
+    fs::rename(&trimmed, path)?;
+    Ok(())
+}
+
+/// Line-buffered writer for a single operation, in the shared log file and in its own file
+/// Each line is flushed as it is written so `pmgr log --follow` sees it immediately
+pub struct OperationLog {
+    writer: LineWriter<File>,
+    file: LineWriter<File>,
+    path: PathBuf,
+}
+
+impl OperationLog {
+    /// Open the logs and write the begin marker for a new operation
+    /// Only the `keep` newest operations (this one included) are kept, in the shared log and as files
+    pub fn begin(title: &str, operation: &str, command: &str, keep: usize) -> Result<Self> {
+        let shared_path = log_path()?;
+        trim_shared_log(&shared_path, keep.max(1) - 1)?;
+        let shared = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&shared_path)?;
+
+        let dir = logs_dir()?;
+        let (file, path) = create_log_file(&dir, &log_file_name(now(), operation))?;
+        prune_logs(&dir, keep.max(1))?;
+
+        let mut log = Self {
+            writer: LineWriter::new(shared),
+            file: LineWriter::new(file),
+            path,
+        };
+        log.write_line(&format!("{} [{}] {} ===", BEGIN_MARKER, timestamp(), title))?;
+        log.write_line(&format!("$ {}", command))?;
+
+        Ok(log)
+    }
+
+    /// This operation's own log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append a single output line
+    pub fn write_line(&mut self, line: &str) -> Result<()> {
+        writeln!(self.writer, "{}", line)?;
+        writeln!(self.file, "{}", line)?;
+        Ok(())
+    }
+
+    /// Write the end marker, consuming the log
+    pub fn end(mut self, success: bool) -> Result<()> {
+        let status = if success { "success" } else { "failed" };
+        self.write_line(&format!("{} [{}] {} ===", END_MARKER, timestamp(), status))?;
+        self.writer.flush()?;
+        self.file.flush()?;
+        Ok(())
+    }
+}
+
+/// `<timestamp>-<operation>.log`, with the time sortable and safe in a file name
+fn log_file_name(secs: u64, operation: &str) -> String {
+    let time = format_timestamp(secs).replace(' ', "_").replace(':', "-");
+    let operation: String = operation
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '_' { c.to_ascii_lowercase() } else { '-' })
+        .collect();
+    format!("{}-{}.log", time, operation)
+}
+
+/// Create a new log file, numbering it when an operation already started in the same second
+fn create_log_file(dir: &Path, name: &str) -> Result<(File, PathBuf)> {
+    let stem = name.trim_end_matches(".log");
+    for attempt in 1.. {
+        let path = match attempt {
+            1 => dir.join(name),
+            n => dir.join(format!("{}-{}.log", stem, n)),
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(file) => return Ok((file, path)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    unreachable!("ran out of log file names")
+}
+
+/// Split the log contents into operations (each starting at a begin marker)
+pub fn split_operations(content: &str) -> Vec<Vec<&str>> {
+    let mut operations: Vec<Vec<&str>> = Vec::new();
+
+    for line in content.lines() {
+        if line.starts_with(BEGIN_MARKER) || operations.is_empty() {
+            operations.push(Vec::new());
+        }
+        if let Some(current) = operations.last_mut() {
+            current.push(line);
+        }
+    }
+
+    operations
+}
+
+/// Current UTC time formatted as `YYYY-MM-DD HH:MM:SS`
+fn timestamp() -> String {
+    format_timestamp(now())
+}
+
+/// Seconds since the Unix epoch
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format seconds since the Unix epoch as a UTC date and time
+fn format_timestamp(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Convert days since epoch to a civil date (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        rem / 3_600,
+        (rem % 3_600) / 60,
+        rem % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn log_files_are_named_after_the_time_and_operation() {
+        assert_eq!(log_file_name(1_760_620_402, "system_update"), "2025-10-16_13-13-22-system_update.log");
+        assert_eq!(log_file_name(0, "Installing AUR/Packages"), "1970-01-01_00-00-00-installing-aur-packages.log");
+    }
+
+    #[test]
+    fn only_the_newest_logs_are_kept() {
+        let dir = std::env::temp_dir().join(format!("pmgr-test-logs-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        for secs in [30, 10, 20] {
+            create_log_file(&dir, &log_file_name(secs, "install")).unwrap();
+        }
+        // Same second: numbered instead of overwritten
+        let (_, path) = create_log_file(&dir, &log_file_name(30, "install")).unwrap();
+        assert!(path.ends_with("1970-01-01_00-00-30-install-2.log"));
+        fs::write(dir.join("notes.txt"), "not a log").unwrap();
+
+        prune_logs(&dir, 2).unwrap();
+        let names: Vec<String> = log_files(&dir)
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, ["1970-01-01_00-00-30-install-2.log", "1970-01-01_00-00-30-install.log"]);
+        assert!(dir.join("notes.txt").exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn the_shared_log_keeps_the_newest_operations() {
+        let path = std::env::temp_dir().join(format!("pmgr-test-shared-{}.log", std::process::id()));
+        trim_shared_log(&path, 2).unwrap();
+        assert!(!path.exists(), "nothing to trim yet");
+
+        let operation = |n: u32| format!("{} [{}] op ===\n$ cmd\nline {}\n{} [{}] success ===\n", BEGIN_MARKER, n, n, END_MARKER, n);
+        let content: String = (1..=4).map(operation).collect();
+        fs::write(&path, &content).unwrap();
+
+        trim_shared_log(&path, 5).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        trim_shared_log(&path, 2).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), operation(3) + &operation(4));
+        trim_shared_log(&path, 0).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "");
+
+        fs::remove_file(&path).unwrap();
+    }
+}
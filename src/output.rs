@@ -0,0 +1,51 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static ASCII_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Decide how CLI output is decorated (the TUI draws its own styles and isn't affected)
+/// Colors are off with --plain, --no-color, NO_COLOR, TERM=dumb or when stdout isn't a
+/// terminal (CLICOLOR_FORCE keeps them on); unicode symbols fall back to ASCII with
+/// --plain, TERM=dumb or a non-UTF-8 locale
+pub fn init(plain: bool, no_color: bool) {
+    let dumb = std::env::var("TERM").is_ok_and(|term| term == "dumb");
+
+    if plain || no_color || env_flag("NO_COLOR") || dumb {
+        colored::control::set_override(false);
+    } else if env_flag("CLICOLOR_FORCE") {
+        colored::control::set_override(true);
+    } else if !std::io::stdout().is_terminal() {
+        colored::control::set_override(false);
+    }
+
+    ASCII_ONLY.store(plain || dumb || !locale_is_utf8(), Ordering::Relaxed);
+}
+
+/// The unicode symbol, or its ASCII fallback when unicode output is disabled
+pub fn symbol(unicode: &'static str, ascii: &'static str) -> &'static str {
+    if ASCII_ONLY.load(Ordering::Relaxed) {
+        ascii
+    } else {
+        unicode
+    }
+}
+
+/// Measure and show timings of hot paths in the TUI (PMGR_DEBUG_TIMING=1)
+pub fn debug_timing() -> bool {
+    env_flag("PMGR_DEBUG_TIMING")
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|value| !value.is_empty() && value != "0")
+}
+
+/// LC_ALL overrides LC_CTYPE, which overrides LANG
+fn locale_is_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.is_empty()))
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
@@ -0,0 +1,372 @@
+use super::date::{format_date, parse_date};
+use super::info::PackageInfo;
+use super::{Package, PmgrError};
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::process::Command;
+
+/// AUR web RPC (v5), see https://aur.archlinux.org/rpc
+const RPC_URL: &str = "https://aur.archlinux.org/rpc/v5";
+/// Give up on the RPC after this long so callers can fall back to the AUR helper
+const RPC_TIMEOUT_SECS: u32 = 5;
+/// Last line of a preview whose AUR details couldn't be fetched
+pub const INFO_UNAVAILABLE: &str = "AUR info unavailable";
+
+/// A package as reported by the AUR RPC
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AurPackage {
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(rename = "URL", default)]
+    pub url: Option<String>,
+    #[serde(rename = "NumVotes", default)]
+    pub votes: u64,
+    #[serde(default)]
+    pub popularity: f64,
+    /// None for orphaned packages
+    #[serde(default)]
+    pub maintainer: Option<String>,
+    /// When the package was flagged out of date (Unix time)
+    #[serde(default)]
+    pub out_of_date: Option<i64>,
+    /// Unix time
+    #[serde(default)]
+    pub first_submitted: Option<i64>,
+    /// Unix time
+    pub last_modified: i64,
+    /// Only filled in by the info endpoint
+    #[serde(rename = "License", default)]
+    pub licenses: Vec<String>,
+    #[serde(default)]
+    pub depends: Vec<String>,
+    #[serde(rename = "OptDepends", default)]
+    pub optdepends: Vec<String>,
+}
+
+impl AurPackage {
+    /// Last modification date, as days since the Unix epoch
+    pub fn last_modified_days(&self) -> i64 {
+        self.last_modified.div_euclid(86_400)
+    }
+
+    pub fn is_orphaned(&self) -> bool {
+        self.maintainer.is_none()
+    }
+
+    /// As a search result from the `aur` repository
+    pub fn to_package(&self) -> Package {
+        Package {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            description: self.description.clone().unwrap_or_default(),
+            repository: "aur".to_string(),
+        }
+    }
+
+    pub fn to_info(&self) -> PackageInfo {
+        PackageInfo {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            repository: Some("aur".to_string()),
+            description: self.description.clone().unwrap_or_default(),
+            url: self.url.clone(),
+            licenses: self.licenses.clone(),
+            depends: self.depends.clone(),
+            optdepends: self.optdepends.clone(),
+            packager: self.maintainer.clone(),
+            ..Default::default()
+        }
+    }
+
+    /// Community details for previews, labelled like `yay -Si` prints them
+    pub fn preview_fields(&self) -> Vec<(&'static str, String)> {
+        let date = |time: i64| format_date(time.div_euclid(86_400));
+        vec![
+            ("AUR URL", format!("https://aur.archlinux.org/packages/{}", self.name)),
+            ("Maintainer", self.maintainer.clone().unwrap_or_else(|| "None".to_string())),
+            ("Votes", self.votes.to_string()),
+            ("Popularity", format!("{:.2}", self.popularity)),
+            ("First Submitted", self.first_submitted.map(date).unwrap_or_else(|| "None".to_string())),
+            ("Last Modified", date(self.last_modified)),
+            ("Out-of-date", self.out_of_date.map(date).unwrap_or_else(|| "No".to_string())),
+        ]
+    }
+
+    /// Preview output with the AUR details it lacks added to its first record
+    /// Output without package info (e.g. pacman can't show AUR packages) is replaced by the RPC's
+    pub fn merge_into_preview(&self, output: &str) -> String {
+        let field = |key: &str, value: &str| format!("{:<16}: {}", key, value);
+
+        if PackageInfo::from_pacman_output(output).is_none() {
+            let info = self.to_info();
+            let mut lines = vec![
+                field("Repository", "aur"),
+                field("Name", &info.name),
+                field("Version", &info.version),
+                field("Description", &info.description),
+                field("URL", info.url.as_deref().unwrap_or("None")),
+                field("Licenses", &info.licenses.join("  ")),
+                field("Depends On", &info.depends.join("  ")),
+            ];
+            let mut optdepends = info.optdepends.iter();
+            lines.push(field("Optional Deps", optdepends.next().map_or("None", String::as_str)));
+            lines.extend(optdepends.map(|dep| format!("{:<18}{}", "", dep)));
+            lines.extend(self.preview_fields().iter().map(|(key, value)| field(key, value)));
+            return lines.join("\n") + "\n";
+        }
+
+        // Only the first record is shown, so the details go at its end
+        let lines: Vec<&str> = output.lines().collect();
+        let start = lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(0);
+        let end = lines[start..].iter().position(|line| line.trim().is_empty()).map_or(lines.len(), |i| start + i);
+        let known: Vec<&str> = lines[start..end]
+            .iter()
+            .filter(|line| !line.starts_with(' '))
+            .filter_map(|line| line.split_once(':').map(|(key, _)| key.trim()))
+            .collect();
+
+        let added = self
+            .preview_fields()
+            .into_iter()
+            .filter(|(key, _)| !known.contains(key))
+            .map(|(key, value)| field(key, &value));
+        let mut merged: Vec<String> = lines[..end].iter().map(|line| line.to_string()).collect();
+        merged.extend(added);
+        merged.extend(lines[end..].iter().map(|line| line.to_string()));
+        merged.join("\n") + "\n"
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    results: Vec<AurPackage>,
+}
+
+/// Packages whose name or description contains the term
+pub fn search(term: &str) -> Result<Vec<AurPackage>> {
+    rpc(&format!("{}/search/{}", RPC_URL, encode(term)))
+}
+
+/// Details of the named packages (unknown names are left out)
+pub fn info(names: &[String]) -> Result<Vec<AurPackage>> {
+    let query: Vec<String> = names.iter().map(|name| format!("arg[]={}", encode(name))).collect();
+    rpc(&format!("{}/info?{}", RPC_URL, query.join("&")))
+}
+
+fn rpc(url: &str) -> Result<Vec<AurPackage>> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", &RPC_TIMEOUT_SECS.to_string(), url])
+        .output()
+        .context("Failed to run curl")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(PmgrError::Network(format!("could not reach the AUR ({})", stderr.trim())).into());
+    }
+
+    parse_response(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Results of an RPC reply, or the error it reports (e.g. "Too many package results.")
+pub fn parse_response(json: &str) -> Result<Vec<AurPackage>> {
+    let response: RpcResponse = serde_json::from_str(json).context("Unexpected reply from the AUR")?;
+    if response.kind == "error" {
+        anyhow::bail!("AUR error: {}", response.error.unwrap_or_default());
+    }
+    Ok(response.results)
+}
+
+/// Percent-encode a path segment or query value
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Maintenance status of an AUR package, read from `yay -Si` / `paru -Si` output
+#[derive(Debug, Clone, PartialEq)]
+pub struct AurStatus {
+    pub orphaned: bool,
+    /// Last modification date, as days since the Unix epoch
+    pub last_modified_days: Option<i64>,
+}
+
+impl AurStatus {
+    /// Parse the AUR fields of `-Si` output
+    /// Returns None when the output has no maintainer field (not AUR info, or the RPC failed)
+    pub fn parse(info: &str) -> Option<Self> {
+        let mut maintainer = None;
+        let mut last_modified = None;
+
+        for line in info.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            match key.trim() {
+                "Maintainer" => maintainer = Some(value.trim().to_string()),
+                "Last Modified" => last_modified = parse_date(value.trim()),
+                _ => {}
+            }
+        }
+
+        let maintainer = maintainer?;
+        Some(Self {
+            orphaned: maintainer.is_empty()
+                || maintainer.eq_ignore_ascii_case("none")
+                || maintainer.eq_ignore_ascii_case("(none)"),
+            last_modified_days: last_modified,
+        })
+    }
+
+    /// Warning badges for this package given the staleness threshold in days
+    pub fn badges(&self, stale_days: u64, today: i64) -> Vec<String> {
+        let mut badges = Vec::new();
+
+        if self.orphaned {
+            badges.push("orphaned (no maintainer)".to_string());
+        }
+
+        if let Some(modified) = self.last_modified_days {
+            if today - modified > stale_days as i64 {
+                badges.push(format!("last updated > {} ago", format_days(stale_days)));
+            }
+        }
+
+        badges
+    }
+}
+
+/// Human-readable threshold ("2 years", "90 days")
+fn format_days(days: u64) -> String {
+    match days {
+        365 => "1 year".to_string(),
+        d if d % 365 == 0 => format!("{} years", d / 365),
+        1 => "1 day".to_string(),
+        d => format!("{} days", d),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::date::days_from_civil;
+
+    const SEARCH_REPLY: &str = r#"{"resultcount":2,"results":[
+        {"Description":"Yet another yogurt","FirstSubmitted":1475688004,"ID":1,"LastModified":1717000000,
+         "Maintainer":"jguer","Name":"yay","NumVotes":2400,"OutOfDate":null,"PackageBase":"yay",
+         "Popularity":21.5,"URL":"https://github.com/Jguer/yay","URLPath":"/cgit/aur.git/snapshot/yay.tar.gz",
+         "Version":"12.3.5-1"},
+        {"Description":null,"LastModified":1577836800,"Maintainer":null,"Name":"old-tool",
+         "NumVotes":0,"OutOfDate":1600000000,"Popularity":0,"Version":"0.1-1"}
+        ],"type":"search","version":5}"#;
+
+    #[test]
+    fn parses_orphaned_package() {
+        let info = "Repository      : aur\nName            : foo\nMaintainer      : None\nLast Modified   : 2020-01-15 10:30\n";
+        let status = AurStatus::parse(info).unwrap();
+
+        assert!(status.orphaned);
+        assert_eq!(status.last_modified_days, Some(days_from_civil(2020, 1, 15)));
+    }
+
+    #[test]
+    fn parses_long_date_format() {
+        let info = "Maintainer      : someone\nLast Modified   : Sat 01 Apr 2023 12:00:00 PM UTC\n";
+        let status = AurStatus::parse(info).unwrap();
+
+        assert!(!status.orphaned);
+        assert_eq!(status.last_modified_days, Some(days_from_civil(2023, 4, 1)));
+    }
+
+    #[test]
+    fn missing_maintainer_means_no_status() {
+        assert_eq!(AurStatus::parse("Repository : extra\nName : bash\n"), None);
+        assert_eq!(AurStatus::parse(""), None);
+    }
+
+    #[test]
+    fn badges_respect_threshold() {
+        let status = AurStatus {
+            orphaned: false,
+            last_modified_days: Some(days_from_civil(2020, 1, 1)),
+        };
+
+        assert!(status.badges(730, days_from_civil(2021, 1, 1)).is_empty());
+        assert_eq!(
+            status.badges(730, days_from_civil(2023, 1, 1)),
+            vec!["last updated > 2 years ago"]
+        );
+    }
+
+    #[test]
+    fn parses_search_reply() {
+        let packages = parse_response(SEARCH_REPLY).unwrap();
+
+        assert_eq!(packages.len(), 2);
+        assert_eq!(packages[0].name, "yay");
+        assert_eq!(packages[0].votes, 2400);
+        assert_eq!(packages[0].maintainer.as_deref(), Some("jguer"));
+        assert_eq!(packages[0].out_of_date, None);
+        assert!(packages[0].depends.is_empty());
+
+        assert!(packages[1].is_orphaned());
+        assert_eq!(packages[1].out_of_date, Some(1600000000));
+        assert_eq!(packages[1].last_modified_days(), days_from_civil(2020, 1, 1));
+        assert_eq!(packages[1].to_package().description, "");
+        assert_eq!(packages[1].to_package().repository, "aur");
+    }
+
+    #[test]
+    fn merges_missing_details_into_the_first_record() {
+        let packages = parse_response(SEARCH_REPLY).unwrap();
+        let output = "Repository      : aur\nName            : yay\nVotes           : 2399\n\nName            : other\n";
+        let merged = packages[0].merge_into_preview(output);
+
+        let info = PackageInfo::from_pacman_output(&merged).unwrap();
+        let extra = |key: &str| info.extra.iter().find(|(k, _)| k == key).map(|(_, values)| values.join(" "));
+        assert_eq!(extra("Votes").as_deref(), Some("2399"), "what the helper printed is kept");
+        assert_eq!(extra("Popularity").as_deref(), Some("21.50"));
+        assert_eq!(extra("First Submitted").as_deref(), Some("2016-10-05"));
+        assert_eq!(info.flagged_out_of_date(), None);
+        assert!(merged.ends_with("\nName            : other\n"), "later records stay after the first");
+    }
+
+    #[test]
+    fn failed_preview_is_replaced_by_the_rpc_details() {
+        let packages = parse_response(SEARCH_REPLY).unwrap();
+        let merged = packages[1].merge_into_preview("Preview failed:\nerror: package 'old-tool' was not found");
+
+        let info = PackageInfo::from_pacman_output(&merged).unwrap();
+        assert_eq!(info.name, "old-tool");
+        assert_eq!(info.repository.as_deref(), Some("aur"));
+        assert_eq!(info.flagged_out_of_date(), Some("2020-09-13"));
+        assert_eq!(AurStatus::parse(&merged).map(|status| status.orphaned), Some(true));
+    }
+
+    #[test]
+    fn error_reply_is_an_error() {
+        let reply = r#"{"error":"Too many package results.","resultcount":0,"results":[],"type":"error","version":5}"#;
+        let err = parse_response(reply).unwrap_err();
+
+        assert!(err.to_string().contains("Too many package results."));
+        assert!(parse_response("<html>502 Bad Gateway</html>").is_err());
+    }
+
+    #[test]
+    fn encodes_query_values() {
+        assert_eq!(encode("python-foo_bar.1"), "python-foo_bar.1");
+        assert_eq!(encode("c++ lib/x"), "c%2B%2B%20lib%2Fx");
+    }
+}
@@ -0,0 +1,133 @@
+use super::{PmgrError, PreviewSource};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// Program that runs package queries and transactions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Pacman,
+    Yay,
+    Paru,
+}
+
+/// Backend forced with --backend, ahead of the configured one
+static OVERRIDE: OnceLock<Backend> = OnceLock::new();
+
+/// Force a backend for this run (the global --backend flag)
+pub fn init(backend: Option<Backend>) {
+    if let Some(backend) = backend {
+        let _ = OVERRIDE.set(backend);
+    }
+}
+
+impl Backend {
+    /// Preference order when auto-detecting
+    const DETECT_ORDER: [Backend; 3] = [Backend::Paru, Backend::Yay, Backend::Pacman];
+
+    pub fn command(&self) -> &'static str {
+        match self {
+            Self::Pacman => "pacman",
+            Self::Yay => "yay",
+            Self::Paru => "paru",
+        }
+    }
+
+    /// AUR helpers can search, install and upgrade AUR packages
+    pub fn supports_aur(&self) -> bool {
+        *self != Self::Pacman
+    }
+
+    /// The first installed backend (paru, then yay, then pacman)
+    pub fn detect(installed: impl Fn(&str) -> bool) -> Backend {
+        Self::DETECT_ORDER
+            .into_iter()
+            .find(|backend| installed(backend.command()))
+            .unwrap_or(Self::Pacman)
+    }
+
+    /// The --backend override, else the configured backend, else auto-detection;
+    /// a forced backend that isn't installed is an error
+    pub fn resolve(configured: Option<Backend>, installed: impl Fn(&str) -> bool) -> anyhow::Result<Backend> {
+        match OVERRIDE.get().copied().or(configured) {
+            Some(backend) if installed(backend.command()) => Ok(backend),
+            Some(backend) => Err(anyhow::Error::new(PmgrError::BackendMissing(backend.command().to_string()))
+                .context(format!(
+                    "Backend '{}' was selected but is not installed (use --backend or the `backend` setting to pick another)",
+                    backend
+                ))),
+            None => Ok(Self::detect(installed)),
+        }
+    }
+
+    /// Package info preview from the local (installed) or sync database
+    pub fn info_preview(&self, installed: bool) -> PreviewSource {
+        PreviewSource::PackageInfo { backend: *self, installed }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.command())
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "pacman" => Ok(Self::Pacman),
+            "yay" => Ok(Self::Yay),
+            "paru" => Ok(Self::Paru),
+            _ => Err(format!("unknown backend '{}' (expected pacman, yay or paru)", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detection_prefers_paru_then_yay() {
+        assert_eq!(Backend::detect(|_| true), Backend::Paru);
+        assert_eq!(Backend::detect(|cmd| cmd != "paru"), Backend::Yay);
+        assert_eq!(Backend::detect(|cmd| cmd == "pacman"), Backend::Pacman);
+    }
+
+    #[test]
+    fn configured_backend_must_be_installed() {
+        let only_pacman = |cmd: &str| cmd == "pacman";
+        assert_eq!(Backend::resolve(Some(Backend::Pacman), only_pacman).unwrap(), Backend::Pacman);
+        assert_eq!(Backend::resolve(None, only_pacman).unwrap(), Backend::Pacman);
+
+        let err = Backend::resolve(Some(Backend::Paru), only_pacman).unwrap_err();
+        assert!(err.to_string().contains("'paru'"));
+    }
+
+    #[test]
+    fn parses_names() {
+        assert_eq!("Paru".parse::<Backend>(), Ok(Backend::Paru));
+        assert!("apt".parse::<Backend>().is_err());
+    }
+
+    #[test]
+    fn preview_uses_backend() {
+        let cmd = Backend::Paru.info_preview(true).command("vim");
+        assert_eq!(cmd.get_program(), "paru");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), ["-Qi", "vim"]);
+        assert_eq!(Backend::Pacman.info_preview(false).command("vim").get_program(), "pacman");
+    }
+
+    #[test]
+    fn missing_backend_is_a_backend_missing_error() {
+        let err = Backend::resolve(Some(Backend::Yay), |cmd| cmd == "pacman").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PmgrError>(),
+            Some(&PmgrError::BackendMissing("yay".to_string()))
+        );
+    }
+}
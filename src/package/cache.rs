@@ -0,0 +1,219 @@
+use super::Package;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Bumped when cached packages change meaning; older caches are read from pacman again
+const CACHE_VERSION: u32 = 2;
+
+/// On-disk format of the available package cache
+#[derive(Serialize, Deserialize)]
+struct CacheFile<P> {
+    /// Format version (missing in caches without descriptions)
+    #[serde(default)]
+    version: u32,
+    /// Seconds since the Unix epoch when the package list was read from pacman
+    created_at: u64,
+    /// Sync databases the list was read from; the cache is stale once they change
+    #[serde(default)]
+    sync_dbs: Vec<DbStamp>,
+    packages: P,
+}
+
+/// Name and modification time (milliseconds since the Unix epoch) of a sync database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DbStamp {
+    pub name: String,
+    pub modified: u64,
+}
+
+/// Available packages loaded from (or just written to) the cache
+#[derive(Debug, Clone)]
+pub struct CachedPackages {
+    pub packages: Vec<Package>,
+    pub created_at: SystemTime,
+}
+
+/// What is known about the cache file, for `pmgr cache`
+#[derive(Debug, Clone)]
+pub struct CacheStatus {
+    pub path: PathBuf,
+    pub package_count: usize,
+    pub created_at: SystemTime,
+    /// The sync databases changed since the list was cached
+    pub stale: bool,
+}
+
+/// Get the path to the available package cache file
+pub fn cache_path() -> Result<PathBuf> {
+    let cache_dir = dirs::cache_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not find cache directory"))?
+        .join("pmgr");
+
+    // Create cache directory if it doesn't exist
+    fs::create_dir_all(&cache_dir)?;
+
+    Ok(cache_dir.join("available.json"))
+}
+
+/// Stamps of the `.db` files in pacman's sync directory, sorted by name
+pub fn sync_db_stamps(sync_dir: &Path) -> Vec<DbStamp> {
+    let Ok(entries) = fs::read_dir(sync_dir) else {
+        return Vec::new();
+    };
+
+    let mut stamps: Vec<DbStamp> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "db"))
+        .filter_map(|entry| {
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some(DbStamp {
+                name: entry.file_name().to_string_lossy().into_owned(),
+                modified: modified.duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0),
+            })
+        })
+        .collect();
+    stamps.sort_by(|a, b| a.name.cmp(&b.name));
+    stamps
+}
+
+/// Load the cached package list, if present, readable and read from the current sync databases
+pub fn load(sync_dir: &Path) -> Option<CachedPackages> {
+    load_from(&cache_path().ok()?, sync_dir)
+}
+
+fn load_from(path: &Path, sync_dir: &Path) -> Option<CachedPackages> {
+    let cache = read(path)?;
+    if is_stale(&cache.sync_dbs, sync_dir) {
+        return None;
+    }
+
+    Some(CachedPackages {
+        packages: cache.packages,
+        created_at: UNIX_EPOCH + Duration::from_secs(cache.created_at),
+    })
+}
+
+fn read(path: &Path) -> Option<CacheFile<Vec<Package>>> {
+    let content = fs::read_to_string(path).ok()?;
+    let cache: CacheFile<Vec<Package>> = serde_json::from_str(&content).ok()?;
+    (cache.version == CACHE_VERSION).then_some(cache)
+}
+
+/// A list cached without any database stamps can't be checked, so it counts as stale
+fn is_stale(cached: &[DbStamp], sync_dir: &Path) -> bool {
+    cached.is_empty() || cached != sync_db_stamps(sync_dir)
+}
+
+/// Write the package list to the cache, stamped with the current time and sync databases
+pub fn store(packages: &[Package], sync_dir: &Path) -> Result<SystemTime> {
+    store_to(&cache_path()?, packages, sync_dir)
+}
+
+fn store_to(path: &Path, packages: &[Package], sync_dir: &Path) -> Result<SystemTime> {
+    let now = SystemTime::now();
+    let cache = CacheFile {
+        version: CACHE_VERSION,
+        created_at: now.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+        sync_dbs: sync_db_stamps(sync_dir),
+        packages,
+    };
+
+    fs::write(path, serde_json::to_string(&cache)?)?;
+
+    Ok(now)
+}
+
+/// The cache file and whether it is still usable; None when there is no (readable) cache
+pub fn status(sync_dir: &Path) -> Option<CacheStatus> {
+    let path = cache_path().ok()?;
+    let cache = read(&path)?;
+
+    Some(CacheStatus {
+        package_count: cache.packages.len(),
+        created_at: UNIX_EPOCH + Duration::from_secs(cache.created_at),
+        stale: is_stale(&cache.sync_dbs, sync_dir),
+        path,
+    })
+}
+
+/// Remove the cached package list (it is rebuilt on the next listing)
+/// Returns whether there was a cache to remove
+pub fn clear() -> Result<bool> {
+    match fs::remove_file(cache_path()?) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pmgr-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("sync")).unwrap();
+        dir
+    }
+
+    fn packages() -> Vec<Package> {
+        vec![Package {
+            name: "bash".to_string(),
+            version: "5.2.026-2".to_string(),
+            description: "The GNU Bourne Again shell".to_string(),
+            repository: "core".to_string(),
+        }]
+    }
+
+    fn touch(path: &Path, modified: SystemTime) {
+        fs::write(path, "").unwrap();
+        fs::File::options().write(true).open(path).unwrap().set_modified(modified).unwrap();
+    }
+
+    #[test]
+    fn cache_is_used_until_a_sync_database_changes() {
+        let dir = temp_dir("stale");
+        let sync = dir.join("sync");
+        let cache = dir.join("available.json");
+        let yesterday = SystemTime::now() - Duration::from_secs(86_400);
+        touch(&sync.join("core.db"), yesterday);
+        touch(&sync.join("extra.db"), yesterday);
+        // Files databases don't affect the package list
+        touch(&sync.join("core.files"), yesterday);
+
+        store_to(&cache, &packages(), &sync).unwrap();
+        let loaded = load_from(&cache, &sync).expect("fresh cache");
+        assert_eq!(loaded.packages[0].name, "bash");
+
+        touch(&sync.join("core.files"), SystemTime::now());
+        assert!(load_from(&cache, &sync).is_some());
+
+        touch(&sync.join("extra.db"), SystemTime::now());
+        assert!(load_from(&cache, &sync).is_none(), "extra.db was synced");
+
+        store_to(&cache, &packages(), &sync).unwrap();
+        touch(&sync.join("multilib.db"), yesterday);
+        assert!(load_from(&cache, &sync).is_none(), "a repository was added");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn cache_without_sync_databases_is_not_trusted() {
+        let dir = temp_dir("empty");
+        let cache = dir.join("available.json");
+
+        store_to(&cache, &packages(), &dir.join("sync")).unwrap();
+        assert!(load_from(&cache, &dir.join("sync")).is_none());
+
+        // Caches from before the stamps were recorded are read again too
+        fs::write(&cache, r#"{"version":1,"created_at":0,"packages":[]}"#).unwrap();
+        assert!(read(&cache).is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,99 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Today's date as days since the Unix epoch
+pub fn today() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| (d.as_secs() / 86_400) as i64)
+        .unwrap_or(0)
+}
+
+/// Parse the date part of a timestamp as days since the Unix epoch
+/// Handles both ISO (`2023-04-01 12:00`) and long (`Sat 01 Apr 2023 12:00:00 PM UTC`) formats
+pub fn parse_date(value: &str) -> Option<i64> {
+    let mut year = None;
+    let mut month = None;
+    let mut day = None;
+
+    for token in value.split(|c: char| c.is_whitespace() || c == ',') {
+        let parts: Vec<&str> = token.split('-').collect();
+        if let [y, m, d] = parts.as_slice() {
+            if let (Ok(y), Ok(m), Ok(d)) = (y.parse(), m.parse(), d.get(..2).unwrap_or(d).parse()) {
+                return Some(days_from_civil(y, m, d));
+            }
+        }
+
+        if let Some(m) = month_from_name(token) {
+            month = Some(m);
+        } else if let Ok(n) = token.parse::<i64>() {
+            if token.len() == 4 {
+                year = Some(n);
+            } else if token.len() <= 2 && day.is_none() {
+                day = Some(n);
+            }
+        }
+    }
+
+    Some(days_from_civil(year?, month?, day?))
+}
+
+fn month_from_name(token: &str) -> Option<i64> {
+    const MONTHS: [&str; 12] = [
+        "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+    ];
+    let prefix = token.get(..3)?.to_lowercase();
+    MONTHS
+        .iter()
+        .position(|m| *m == prefix)
+        .map(|i| i as i64 + 1)
+}
+
+/// Days since the Unix epoch for a civil date (Howard Hinnant's algorithm)
+pub fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Civil date for a day count since the Unix epoch, as `YYYY-MM-DD`
+pub fn format_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(days_from_civil(2024, 2, 29)), "2024-02-29");
+        assert_eq!(format_date(days_from_civil(1999, 12, 31)), "1999-12-31");
+    }
+
+    #[test]
+    fn parses_rss_dates() {
+        assert_eq!(parse_date("Mon, 01 Apr 2024 12:00:00 +0000"), Some(days_from_civil(2024, 4, 1)));
+        assert_eq!(parse_date("2023-05-01 10:15"), Some(days_from_civil(2023, 5, 1)));
+    }
+}
@@ -0,0 +1,236 @@
+use super::parse_info;
+use std::collections::{HashMap, HashSet};
+
+/// Installed packages that depend on a package
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReverseDeps {
+    /// Packages that can't be installed without it
+    pub required_by: Vec<String>,
+    /// Packages that list it as an optional dependency
+    pub optional_for: Vec<String>,
+}
+
+impl ReverseDeps {
+    /// Nothing depends on the package, not even optionally
+    pub fn is_leaf(&self) -> bool {
+        self.required_by.is_empty() && self.optional_for.is_empty()
+    }
+}
+
+/// Packages whose removal can leave the system unbootable or without a package manager:
+/// base, base-devel and its members, kernels, glibc, pacman and systemd
+/// A trailing `*` matches any suffix
+pub const PROTECTED_PACKAGES: [&str; 31] = [
+    "base", "base-devel", "linux*", "glibc", "pacman", "systemd",
+    "archlinux-keyring", "autoconf", "automake", "binutils", "bison", "debugedit", "fakeroot", "file",
+    "findutils", "flex", "gawk", "gcc", "gettext", "grep", "groff", "gzip", "libtool", "m4", "make",
+    "patch", "pkgconf", "sed", "sudo", "texinfo", "which",
+];
+
+/// Whether removing a package needs extra care: it's on the built-in list or the user's
+/// `protected_packages` (which take the same patterns)
+pub fn is_protected(package: &str, extra: &[String]) -> bool {
+    let matches = |pattern: &str| match pattern.strip_suffix('*') {
+        Some(prefix) => package.starts_with(prefix),
+        None => package == pattern,
+    };
+    PROTECTED_PACKAGES.iter().any(|p| matches(p)) || extra.iter().any(|p| matches(p))
+}
+
+/// Read the "Required By" and "Optional For" fields of `pacman -Qi` output
+pub fn parse_reverse_deps(info: &str) -> ReverseDeps {
+    let fields = parse_info(info);
+    let names = |key: &str| -> Vec<String> {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| {
+                value
+                    .split_whitespace()
+                    .filter(|name| *name != "None")
+                    .map(|name| name.to_string())
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+
+    ReverseDeps {
+        required_by: names("Required By"),
+        optional_for: names("Optional For"),
+    }
+}
+
+/// Reverse dependencies of every installed package, from `pacman -Qi` output for all of them
+pub fn parse_all_reverse_deps(output: &str) -> HashMap<String, ReverseDeps> {
+    output
+        .split("\n\n")
+        .filter_map(|record| {
+            let name = parse_info(record)
+                .into_iter()
+                .find(|(key, _)| key == "Name")
+                .map(|(_, value)| value)?;
+            Some((name, parse_reverse_deps(record)))
+        })
+        .collect()
+}
+
+/// A package in the reverse dependency tree
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReverseDepNode {
+    pub name: String,
+    /// Only an optional dependency of its parent
+    pub optional: bool,
+    pub children: Vec<ReverseDepNode>,
+    /// Already on the path from the root; its dependents aren't repeated
+    pub cycle: bool,
+    /// Has dependents below the depth limit that aren't shown
+    pub truncated: bool,
+}
+
+/// Walk what depends on `root`, up to `max_depth` levels
+/// Optional dependents are listed but not followed, since they don't block a removal
+pub fn reverse_dep_tree(root: &str, deps: &HashMap<String, ReverseDeps>, max_depth: usize) -> ReverseDepNode {
+    let mut path = HashSet::new();
+    build_node(root, false, deps, max_depth, &mut path)
+}
+
+fn build_node(
+    name: &str,
+    optional: bool,
+    deps: &HashMap<String, ReverseDeps>,
+    depth_left: usize,
+    path: &mut HashSet<String>,
+) -> ReverseDepNode {
+    let mut node = ReverseDepNode {
+        name: name.to_string(),
+        optional,
+        children: Vec::new(),
+        cycle: false,
+        truncated: false,
+    };
+
+    if optional {
+        return node;
+    }
+    if path.contains(name) {
+        node.cycle = true;
+        return node;
+    }
+
+    let Some(reverse) = deps.get(name).filter(|r| !r.is_leaf()) else {
+        return node;
+    };
+    if depth_left == 0 {
+        node.truncated = true;
+        return node;
+    }
+
+    path.insert(name.to_string());
+    for dependent in &reverse.required_by {
+        node.children.push(build_node(dependent, false, deps, depth_left - 1, path));
+    }
+    for dependent in &reverse.optional_for {
+        node.children.push(build_node(dependent, true, deps, depth_left - 1, path));
+    }
+    path.remove(name);
+
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deps(entries: &[(&str, &[&str], &[&str])]) -> HashMap<String, ReverseDeps> {
+        entries
+            .iter()
+            .map(|(name, required, optional)| {
+                (
+                    name.to_string(),
+                    ReverseDeps {
+                        required_by: required.iter().map(|s| s.to_string()).collect(),
+                        optional_for: optional.iter().map(|s| s.to_string()).collect(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_required_and_optional_fields() {
+        let info = "\
+Name            : glibc
+Required By     : bash  coreutils
+                  gcc-libs
+Optional For    : None
+";
+        let reverse = parse_reverse_deps(info);
+        assert_eq!(reverse.required_by, vec!["bash", "coreutils", "gcc-libs"]);
+        assert!(reverse.optional_for.is_empty());
+
+        let leaf = parse_reverse_deps("Name            : htop\nRequired By     : None\nOptional For    : None\n");
+        assert!(leaf.is_leaf());
+        assert!(parse_reverse_deps("error: package 'x' was not found").is_leaf());
+    }
+
+    #[test]
+    fn protected_packages_match_patterns() {
+        let extra = vec!["nvidia*".to_string(), "grub".to_string()];
+        for name in ["glibc", "linux", "linux-lts", "linux-firmware", "make", "nvidia-dkms", "grub"] {
+            assert!(is_protected(name, &extra), "{}", name);
+        }
+        for name in ["htop", "glib2", "grub-customizer", "makepkg-optimize"] {
+            assert!(!is_protected(name, &extra), "{}", name);
+        }
+    }
+
+    #[test]
+    fn parses_all_records() {
+        let output = "\
+Name            : bash
+Required By     : base
+Optional For    : None
+
+Name            : python
+Required By     : None
+Optional For    : vim
+";
+        let all = parse_all_reverse_deps(output);
+
+        assert_eq!(all.len(), 2);
+        assert_eq!(all["bash"].required_by, vec!["base"]);
+        assert_eq!(all["python"].optional_for, vec!["vim"]);
+    }
+
+    #[test]
+    fn tree_follows_required_dependents_only() {
+        let deps = deps(&[
+            ("glibc", &["bash"], &["python"]),
+            ("bash", &["base"], &[]),
+            ("python", &["pip"], &[]),
+            ("base", &[], &[]),
+        ]);
+        let tree = reverse_dep_tree("glibc", &deps, 5);
+
+        assert_eq!(tree.children.len(), 2);
+        assert_eq!(tree.children[0].name, "bash");
+        assert_eq!(tree.children[0].children[0].name, "base");
+        assert!(tree.children[1].optional);
+        assert!(tree.children[1].children.is_empty());
+    }
+
+    #[test]
+    fn tree_is_cycle_safe_and_bounded() {
+        let deps = deps(&[("a", &["b"], &[]), ("b", &["c"], &[]), ("c", &["a"], &[])]);
+
+        let tree = reverse_dep_tree("a", &deps, 10);
+        let c = &tree.children[0].children[0];
+        assert_eq!(c.name, "c");
+        assert!(c.children[0].cycle);
+        assert!(c.children[0].children.is_empty());
+
+        let shallow = reverse_dep_tree("a", &deps, 1);
+        assert_eq!(shallow.children[0].name, "b");
+        assert!(shallow.children[0].truncated);
+    }
+}
@@ -0,0 +1,216 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// Kind of package transaction recorded in pacman.log
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryAction {
+    Installed,
+    Upgraded,
+    Downgraded,
+    Reinstalled,
+    Removed,
+}
+
+impl HistoryAction {
+    fn from_verb(verb: &str) -> Option<Self> {
+        match verb {
+            "installed" => Some(Self::Installed),
+            "upgraded" => Some(Self::Upgraded),
+            "downgraded" => Some(Self::Downgraded),
+            "reinstalled" => Some(Self::Reinstalled),
+            "removed" => Some(Self::Removed),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for HistoryAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let verb = match self {
+            Self::Installed => "installed",
+            Self::Upgraded => "upgraded",
+            Self::Downgraded => "downgraded",
+            Self::Reinstalled => "reinstalled",
+            Self::Removed => "removed",
+        };
+        f.pad(verb)
+    }
+}
+
+impl FromStr for HistoryAction {
+    type Err = String;
+
+    /// Accepts both the verb (`installed`) and the imperative (`install`)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let verb = s.to_lowercase();
+        Self::from_verb(&verb)
+            .or_else(|| Self::from_verb(&format!("{}ed", verb.trim_end_matches('e'))))
+            .ok_or_else(|| {
+                format!(
+                    "unknown action '{}' (expected install, upgrade, downgrade, reinstall or remove)",
+                    s
+                )
+            })
+    }
+}
+
+/// A single package transaction from pacman.log
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// Local time as `YYYY-MM-DD HH:MM`
+    pub timestamp: String,
+    pub action: HistoryAction,
+    pub package: String,
+    pub old_version: Option<String>,
+    pub new_version: Option<String>,
+}
+
+/// Parse pacman.log contents into transactions (oldest first), skipping anything else
+pub fn parse_log(content: &str) -> Vec<HistoryEntry> {
+    content.lines().filter_map(parse_line).collect()
+}
+
+/// Timestamp of the last `pacman -Syu` recorded in pacman.log (`YYYY-MM-DD HH:MM`)
+pub fn last_full_upgrade(content: &str) -> Option<String> {
+    content
+        .lines()
+        .rev()
+        .filter(|line| line.ends_with("starting full system upgrade"))
+        .find_map(|line| normalize_timestamp(line.strip_prefix('[')?.split_once(']')?.0))
+}
+
+/// Parse a single log line such as
+/// `[2023-05-01T10:15:30+0200] [ALPM] upgraded bash (5.1-1 -> 5.2-1)` or
+/// `[2012-03-04 12:34] installed bash (4.2-1)`
+fn parse_line(line: &str) -> Option<HistoryEntry> {
+    let (timestamp, rest) = line.strip_prefix('[')?.split_once(']')?;
+    let timestamp = normalize_timestamp(timestamp)?;
+
+    // Newer logs tag each line with its source; only ALPM lines are transactions
+    let mut rest = rest.trim_start();
+    if let Some(tagged) = rest.strip_prefix('[') {
+        let (source, message) = tagged.split_once(']')?;
+        if source != "ALPM" {
+            return None;
+        }
+        rest = message.trim_start();
+    }
+
+    let (verb, rest) = rest.split_once(' ')?;
+    let action = HistoryAction::from_verb(verb)?;
+
+    let (package, versions) = rest.split_once(" (")?;
+    let versions = versions.trim_end().strip_suffix(')')?;
+    if package.is_empty() || package.contains(' ') {
+        return None;
+    }
+
+    let (old_version, new_version) = match action {
+        HistoryAction::Upgraded | HistoryAction::Downgraded => {
+            let (old, new) = versions.split_once(" -> ")?;
+            (Some(old.to_string()), Some(new.to_string()))
+        }
+        HistoryAction::Removed => (Some(versions.to_string()), None),
+        HistoryAction::Installed | HistoryAction::Reinstalled => (None, Some(versions.to_string())),
+    };
+
+    Some(HistoryEntry {
+        timestamp,
+        action,
+        package: package.to_string(),
+        old_version,
+        new_version,
+    })
+}
+
+/// Accepts `YYYY-MM-DD HH:MM` (pacman < 5.2) and ISO-8601 `YYYY-MM-DDTHH:MM:SS+ZZZZ`
+fn normalize_timestamp(timestamp: &str) -> Option<String> {
+    let bytes = timestamp.as_bytes();
+    if bytes.len() < 16 || !matches!(bytes[10], b' ' | b'T') {
+        return None;
+    }
+
+    let date = &timestamp[..10];
+    let time = timestamp.get(11..16)?;
+    let matches_pattern = |value: &str, pattern: &str| {
+        value
+            .chars()
+            .zip(pattern.chars())
+            .all(|(c, p)| if p == '0' { c.is_ascii_digit() } else { c == p })
+    };
+
+    if !matches_pattern(date, "0000-00-00") || !matches_pattern(time, "00:00") {
+        return None;
+    }
+
+    Some(format!("{} {}", date, time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_iso_timestamp_lines() {
+        let entry = parse_line("[2023-05-01T10:15:30+0200] [ALPM] upgraded bash (5.1.016-1 -> 5.2.015-1)").unwrap();
+
+        assert_eq!(entry.timestamp, "2023-05-01 10:15");
+        assert_eq!(entry.action, HistoryAction::Upgraded);
+        assert_eq!(entry.package, "bash");
+        assert_eq!(entry.old_version.as_deref(), Some("5.1.016-1"));
+        assert_eq!(entry.new_version.as_deref(), Some("5.2.015-1"));
+    }
+
+    #[test]
+    fn parses_old_timestamp_lines() {
+        let installed = parse_line("[2012-03-04 12:34] installed vim (7.3.446-1)").unwrap();
+        assert_eq!(installed.timestamp, "2012-03-04 12:34");
+        assert_eq!(installed.action, HistoryAction::Installed);
+        assert_eq!(installed.old_version, None);
+        assert_eq!(installed.new_version.as_deref(), Some("7.3.446-1"));
+
+        let removed = parse_line("[2012-03-05 08:00] removed vim (7.3.446-1)").unwrap();
+        assert_eq!(removed.action, HistoryAction::Removed);
+        assert_eq!(removed.old_version.as_deref(), Some("7.3.446-1"));
+        assert_eq!(removed.new_version, None);
+    }
+
+    #[test]
+    fn skips_malformed_and_non_transaction_lines() {
+        let log = "\
+[2023-05-01T10:15:28+0200] [PACMAN] Running 'pacman -Syu'
+[2023-05-01T10:15:29+0200] [ALPM] transaction started
+[2023-05-01T10:15:30+0200] [ALPM] installed linux (6.3.1.arch1-1)
+[2023-05-01T10:15:31+0200] [ALPM-SCRIPTLET] installed something (1.0)
+garbage line
+[not a date] [ALPM] installed foo (1.0-1)
+[2023-05-01T10:15:32+0200] [ALPM] upgraded broken (1.0-1)
+[2023-05-01T10:15:33+0200] [ALPM] downgraded mesa (23.1-1 -> 23.0-1)
+";
+        let entries = parse_log(log);
+
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].package, "linux");
+        assert_eq!(entries[1].action, HistoryAction::Downgraded);
+    }
+
+    #[test]
+    fn finds_last_full_upgrade() {
+        let log = "\
+[2023-05-01T10:15:28+0200] [PACMAN] starting full system upgrade
+[2023-05-01T10:15:30+0200] [ALPM] upgraded bash (5.1-1 -> 5.2-1)
+[2023-06-02T09:00:00+0200] [PACMAN] starting full system upgrade
+[2023-06-03T09:00:00+0200] [ALPM] installed vim (9.0-1)
+";
+        assert_eq!(last_full_upgrade(log).as_deref(), Some("2023-06-02 09:00"));
+        assert_eq!(last_full_upgrade("[2023-06-03 09:00] installed vim (9.0-1)"), None);
+    }
+
+    #[test]
+    fn action_from_str() {
+        assert_eq!("install".parse(), Ok(HistoryAction::Installed));
+        assert_eq!("upgrade".parse(), Ok(HistoryAction::Upgraded));
+        assert_eq!("Removed".parse(), Ok(HistoryAction::Removed));
+        assert!("frobnicate".parse::<HistoryAction>().is_err());
+    }
+}
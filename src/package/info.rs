@@ -0,0 +1,293 @@
+use super::{format_size, parse_size};
+
+/// Package details from `-Qi`/`-Si` output of pacman or an AUR helper (C locale field names)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: String,
+    /// Sync repository (`-Si` only)
+    pub repository: Option<String>,
+    pub description: String,
+    pub url: Option<String>,
+    pub licenses: Vec<String>,
+    pub depends: Vec<String>,
+    /// One entry per optional dependency, e.g. `bash-completion: for tab completion [installed]`
+    pub optdepends: Vec<String>,
+    pub required_by: Vec<String>,
+    pub installed_size: Option<u64>,
+    /// Package download size (`-Si` only)
+    pub download_size: Option<u64>,
+    pub build_date: Option<String>,
+    /// When the package was installed (`-Qi` only)
+    pub install_date: Option<String>,
+    pub packager: Option<String>,
+    /// Fields not parsed above (e.g. Architecture, Provides), as pacman printed them
+    pub extra: Vec<(String, Vec<String>)>,
+}
+
+/// Fields parsed into `PackageInfo`; anything else is kept in `extra`
+const KNOWN_FIELDS: [&str; 14] = [
+    "Name",
+    "Version",
+    "Repository",
+    "Description",
+    "URL",
+    "Licenses",
+    "Depends On",
+    "Optional Deps",
+    "Required By",
+    "Installed Size",
+    "Download Size",
+    "Build Date",
+    "Install Date",
+    "Packager",
+];
+
+impl PackageInfo {
+    /// Parse the first record of the output; None if it has no Name field
+    pub fn from_pacman_output(output: &str) -> Option<Self> {
+        let fields = parse_record(output);
+        let lines = |key: &str| -> Vec<String> {
+            fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, lines)| lines.iter().filter(|l| *l != "None").cloned().collect())
+                .unwrap_or_default()
+        };
+        let value = |key: &str| -> Option<String> {
+            let lines = lines(key);
+            (!lines.is_empty()).then(|| lines.join(" "))
+        };
+        let words = |key: &str| -> Vec<String> {
+            lines(key)
+                .iter()
+                .flat_map(|line| line.split_whitespace())
+                .map(|word| word.to_string())
+                .collect()
+        };
+
+        Some(Self {
+            name: value("Name")?,
+            version: value("Version").unwrap_or_default(),
+            repository: value("Repository"),
+            description: value("Description").unwrap_or_default(),
+            url: value("URL"),
+            licenses: words("Licenses"),
+            depends: words("Depends On"),
+            optdepends: lines("Optional Deps"),
+            required_by: words("Required By"),
+            installed_size: value("Installed Size").and_then(|s| parse_size(&s)),
+            download_size: value("Download Size").and_then(|s| parse_size(&s)),
+            build_date: value("Build Date"),
+            install_date: value("Install Date"),
+            packager: value("Packager"),
+            extra: fields
+                .iter()
+                .filter(|(key, _)| !KNOWN_FIELDS.contains(&key.as_str()))
+                .cloned()
+                .collect(),
+        })
+    }
+
+    /// When an AUR package was flagged out of date (the `Out-of-date` field of `yay -Si`), if it is
+    pub fn flagged_out_of_date(&self) -> Option<&str> {
+        self.extra
+            .iter()
+            .find(|(key, _)| key == "Out-of-date")
+            .and_then(|(_, values)| values.first())
+            .map(String::as_str)
+            .filter(|value| !matches!(*value, "No" | "None"))
+    }
+
+    /// Labelled fields for display, one value per line (empty for none)
+    /// Fields the output didn't contain (e.g. Install Date of a sync package) are left out
+    pub fn fields(&self) -> Vec<(&'static str, Vec<String>)> {
+        let optional = |value: &Option<String>| value.iter().cloned().collect::<Vec<_>>();
+        let joined = |items: &[String]| {
+            if items.is_empty() {
+                Vec::new()
+            } else {
+                vec![items.join("  ")]
+            }
+        };
+
+        let mut fields = vec![("Name", vec![self.name.clone()]), ("Version", vec![self.version.clone()])];
+        if self.repository.is_some() {
+            fields.push(("Repository", optional(&self.repository)));
+        }
+        let description = if self.description.is_empty() {
+            Vec::new()
+        } else {
+            vec![self.description.clone()]
+        };
+        fields.push(("Description", description));
+        fields.push(("URL", optional(&self.url)));
+        fields.push(("Licenses", joined(&self.licenses)));
+        fields.push(("Depends On", joined(&self.depends)));
+        fields.push(("Optional Deps", self.optdepends.clone()));
+        // Only the local database knows the dependents
+        if self.install_date.is_some() {
+            fields.push(("Required By", joined(&self.required_by)));
+        }
+        if let Some(size) = self.download_size {
+            fields.push(("Download Size", vec![format_size(size)]));
+        }
+        if let Some(size) = self.installed_size {
+            fields.push(("Installed Size", vec![format_size(size)]));
+        }
+        fields.push(("Packager", optional(&self.packager)));
+        fields.push(("Build Date", optional(&self.build_date)));
+        if self.install_date.is_some() {
+            fields.push(("Install Date", optional(&self.install_date)));
+        }
+
+        fields
+    }
+}
+
+/// Fields of the first record, keeping continuation lines (e.g. one per optional dependency)
+fn parse_record(output: &str) -> Vec<(String, Vec<String>)> {
+    let mut fields: Vec<(String, Vec<String>)> = Vec::new();
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            if fields.is_empty() {
+                continue;
+            }
+            // Records (e.g. the same package in several repositories) are separated by blank lines
+            break;
+        }
+
+        if line.starts_with(' ') {
+            if let Some((_, lines)) = fields.last_mut() {
+                lines.push(line.trim().to_string());
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            let value = value.trim();
+            let lines = if value.is_empty() { Vec::new() } else { vec![value.to_string()] };
+            fields.push((key.trim().to_string(), lines));
+        }
+    }
+
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCAL: &str = "\
+Name            : git
+Version         : 2.45.2-1
+Description     : the fast distributed version control system
+Architecture    : x86_64
+URL             : https://git-scm.com/
+Licenses        : GPL-2.0-only
+Groups          : None
+Provides        : None
+Depends On      : curl  expat  grep  openssl  pcre2  perl  perl-error
+                  perl-mailtools  shadow  zlib-ng
+Optional Deps   : tk: gitk and git gui [installed]
+                  openssh: ssh transport and crypto
+                  perl-libwww: git svn
+Required By     : base-devel-extras  hub
+Optional For    : None
+Conflicts With  : None
+Replaces        : None
+Installed Size  : 29.62 MiB
+Packager        : Christian Hesse <eworm@archlinux.org>
+Build Date      : Mon 03 Jun 2024 09:14:09 AM UTC
+Install Date    : Wed 05 Jun 2024 08:00:12 PM UTC
+Install Reason  : Explicitly installed
+Install Script  : Yes
+Validated By    : Signature
+
+";
+
+    const SYNC: &str = "\
+Repository      : extra
+Name            : ripgrep
+Version         : 14.1.0-1
+Description     : A search tool that combines the usability of ag with the raw speed of grep
+Architecture    : x86_64
+URL             : https://github.com/BurntSushi/ripgrep
+Licenses        : MIT  Unlicense
+Groups          : None
+Provides        : None
+Depends On      : gcc-libs  glibc  pcre2
+Optional Deps   : None
+Conflicts With  : None
+Replaces        : None
+Download Size   : 1.60 MiB
+Installed Size  : 4.55 MiB
+Packager        : Orhun Parmaksız <orhun@archlinux.org>
+Build Date      : Tue 09 Jan 2024 10:43:57 AM UTC
+Validated By    : MD5 Sum  SHA-256 Sum  Signature
+
+Repository      : testing
+Name            : ripgrep
+Version         : 14.2.0-1
+";
+
+    #[test]
+    fn parses_installed_package() {
+        let info = PackageInfo::from_pacman_output(LOCAL).unwrap();
+
+        assert_eq!(info.name, "git");
+        assert_eq!(info.version, "2.45.2-1");
+        assert_eq!(info.url.as_deref(), Some("https://git-scm.com/"));
+        assert_eq!(info.licenses, vec!["GPL-2.0-only"]);
+        assert_eq!(info.depends.len(), 10);
+        assert_eq!(info.depends.last().map(String::as_str), Some("zlib-ng"));
+        assert_eq!(
+            info.optdepends,
+            vec![
+                "tk: gitk and git gui [installed]",
+                "openssh: ssh transport and crypto",
+                "perl-libwww: git svn",
+            ]
+        );
+        assert_eq!(info.required_by, vec!["base-devel-extras", "hub"]);
+        assert_eq!(info.installed_size, parse_size("29.62 MiB"));
+        assert_eq!(info.download_size, None);
+        assert_eq!(info.install_date.as_deref(), Some("Wed 05 Jun 2024 08:00:12 PM UTC"));
+        assert_eq!(info.repository, None);
+        let extra: Vec<&str> = info.extra.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(extra.first(), Some(&"Architecture"));
+        assert!(extra.contains(&"Install Reason"));
+        assert!(!extra.contains(&"Depends On"));
+    }
+
+    #[test]
+    fn parses_first_sync_record() {
+        let info = PackageInfo::from_pacman_output(SYNC).unwrap();
+
+        assert_eq!(info.repository.as_deref(), Some("extra"));
+        assert_eq!(info.version, "14.1.0-1");
+        assert_eq!(info.licenses, vec!["MIT", "Unlicense"]);
+        assert!(info.optdepends.is_empty());
+        assert_eq!(info.download_size, Some(1677721));
+        assert_eq!(info.install_date, None);
+        assert_eq!(info.packager.as_deref(), Some("Orhun Parmaksız <orhun@archlinux.org>"));
+    }
+
+    #[test]
+    fn fields_skip_what_the_output_lacks() {
+        let sync = PackageInfo::from_pacman_output(SYNC).unwrap();
+        let labels: Vec<&str> = sync.fields().iter().map(|(label, _)| *label).collect();
+        assert!(labels.contains(&"Download Size"));
+        assert!(!labels.contains(&"Required By"));
+        assert!(!labels.contains(&"Install Date"));
+
+        let local = PackageInfo::from_pacman_output(LOCAL).unwrap();
+        let fields = local.fields();
+        let optional = fields.iter().find(|(label, _)| *label == "Optional Deps").unwrap();
+        assert_eq!(optional.1.len(), 3);
+    }
+
+    #[test]
+    fn output_without_name_is_rejected() {
+        assert_eq!(PackageInfo::from_pacman_output("error: package 'nope' was not found\n"), None);
+        assert_eq!(PackageInfo::from_pacman_output(""), None);
+    }
+}
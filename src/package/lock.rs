@@ -0,0 +1,128 @@
+use std::path::{Path, PathBuf};
+use super::runner;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Lock file pacman creates in its database directory while a transaction runs
+const LOCK_FILE: &str = "db.lck";
+/// How often a waiting operation checks the lock again
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// State of pacman's database lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbLock {
+    Free,
+    /// Another pacman (or AUR helper running it) is working on the database
+    Held,
+    /// The lock file exists but no pacman process is running, e.g. after a crash
+    Stale,
+}
+
+/// Path of the lock file for a database directory (`DBPath` in pacman.conf)
+pub fn lock_path(db_path: &Path) -> PathBuf {
+    db_path.join(LOCK_FILE)
+}
+
+/// Current lock state; `pacman_running` is only asked when the lock file exists
+pub fn check(lock_file: &Path, pacman_running: impl FnOnce() -> bool) -> DbLock {
+    if !lock_file.exists() {
+        DbLock::Free
+    } else if pacman_running() {
+        DbLock::Held
+    } else {
+        DbLock::Stale
+    }
+}
+
+/// Whether a pacman process is running (AUR helpers run pacman for the transaction)
+pub fn pacman_running() -> bool {
+    runner::query("pgrep")
+        .args(["-x", "pacman"])
+        .output()
+        .map(|output| output.status.success())
+        // Without pgrep, assume the lock is in use rather than calling it stale
+        .unwrap_or(true)
+}
+
+/// Poll the lock until it is no longer held or the timeout runs out; returns the last state
+pub fn wait_while_held(timeout: Duration, poll_interval: Duration, mut check: impl FnMut() -> DbLock) -> DbLock {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let state = check();
+        if state != DbLock::Held || Instant::now() >= deadline {
+            return state;
+        }
+        thread::sleep(poll_interval);
+    }
+}
+
+/// What to tell the user about a stale lock
+pub fn stale_hint(lock_file: &Path) -> String {
+    format!(
+        "No pacman process is running, so the lock is probably left over from an interrupted operation. \
+         If nothing else is updating the system, remove it with: sudo rm {}",
+        lock_file.display()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_db(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("pmgr-lock-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn detects_held_and_stale_locks() {
+        let dir = temp_db("detect");
+        let lock = lock_path(&dir);
+
+        assert_eq!(check(&lock, || panic!("no lock file, no process lookup")), DbLock::Free);
+
+        fs::write(&lock, "").unwrap();
+        assert_eq!(check(&lock, || true), DbLock::Held);
+        assert_eq!(check(&lock, || false), DbLock::Stale);
+        assert!(stale_hint(&lock).contains(&lock.display().to_string()));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn waiting_stops_once_the_lock_is_released() {
+        let dir = temp_db("release");
+        let lock = lock_path(&dir);
+        fs::write(&lock, "").unwrap();
+
+        let remover = {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                thread::sleep(Duration::from_millis(50));
+                fs::remove_file(lock).unwrap();
+            })
+        };
+        let state = wait_while_held(Duration::from_secs(5), Duration::from_millis(10), || check(&lock, || true));
+        remover.join().unwrap();
+
+        assert_eq!(state, DbLock::Free);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn waiting_is_bounded() {
+        let dir = temp_db("timeout");
+        let lock = lock_path(&dir);
+        fs::write(&lock, "").unwrap();
+
+        let started = Instant::now();
+        let state = wait_while_held(Duration::from_millis(50), Duration::from_millis(10), || check(&lock, || true));
+
+        assert_eq!(state, DbLock::Held);
+        assert!(started.elapsed() < Duration::from_secs(2));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
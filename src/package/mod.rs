@@ -1,6 +1,33 @@
+pub mod aur;
+pub mod backend;
+pub mod cache;
+pub mod date;
+pub mod deps;
+pub mod history;
+pub mod info;
+pub mod lock;
+pub mod news;
+mod pacman_config;
+pub mod preview;
+pub mod progress;
+pub mod runner;
+pub mod verify;
+pub mod version;
+
+pub use backend::Backend;
+pub use cache::CachedPackages;
+pub use info::PackageInfo;
+pub use lock::DbLock;
+pub use pacman_config::PacmanConfig;
+pub use preview::PreviewSource;
+
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::process::{Command, Stdio};
+use std::cell::OnceCell;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Package {
@@ -10,38 +37,434 @@ pub struct Package {
     pub repository: String,
 }
 
+/// An installed package with its version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstalledPackage {
+    pub name: String,
+    pub version: String,
+}
+
+/// An installed package with the details `pacman -Qi` reports
+#[derive(Debug, Clone, PartialEq)]
+pub struct InstalledDetails {
+    pub name: String,
+    pub version: String,
+    pub installed_size: u64,
+    /// Install date, as days since the Unix epoch
+    pub install_date: Option<i64>,
+    pub reason: InstallReason,
+}
+
+/// A package with a pending upgrade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpgradablePackage {
+    pub name: String,
+    pub current_version: String,
+    pub new_version: String,
+    /// Held back from updates by pmgr's ignore list
+    #[serde(default)]
+    pub ignored: bool,
+}
+
+/// Packages that provide a command, found through the files database
+#[derive(Debug, Clone, Default)]
+pub struct ProvidesLookup {
+    pub providers: Vec<Package>,
+    /// True when no files database has been downloaded yet (`pacman -Fy`)
+    pub files_db_missing: bool,
+}
+
+/// Files owned by a package
+#[derive(Debug, Clone, Default)]
+pub struct PackageFiles {
+    pub paths: Vec<String>,
+    /// True when the list came from the files database (package not installed)
+    pub from_files_db: bool,
+    /// True when the package isn't installed and no files database is available
+    pub files_db_missing: bool,
+}
+
+/// Which sources a search covers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchScope {
+    /// Whatever the backend returns (repositories, plus the AUR with yay or paru)
+    #[default]
+    Combined,
+    /// Sync repositories only (`pacman -Ss`)
+    Repo,
+    /// AUR only (`yay -Ssa` / `paru -Ssa`)
+    Aur,
+}
+
+/// A version of a package available in the local package cache
+#[derive(Debug, Clone)]
+pub struct CachedVersion {
+    pub version: String,
+    pub path: PathBuf,
+}
+
+/// Flags for installing packages from the sync repositories or the AUR
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct InstallOptions {
+    /// Record the packages as installed as dependencies (`--asdeps`)
+    pub asdeps: bool,
+    /// Skip packages that are already up to date (`--needed`)
+    pub needed: bool,
+    /// Don't ask for confirmation (`--noconfirm`)
+    pub noconfirm: bool,
+}
+
+impl InstallOptions {
+    /// Extra arguments for `-S` of pacman or an AUR helper
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if self.asdeps {
+            args.push("--asdeps".to_string());
+        }
+        if self.needed {
+            args.push("--needed".to_string());
+        }
+        if self.noconfirm {
+            args.push("--noconfirm".to_string());
+        }
+        args
+    }
+}
+
+/// How packages are removed (modifiers for `pacman -R`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RemoveOptions {
+    /// Also remove dependencies no other package needs (`-s`)
+    pub recursive: bool,
+    /// Delete configuration files instead of keeping them as `.pacsave` (`-n`)
+    pub nosave: bool,
+    /// Also remove every package that depends on the removed ones (`-c`)
+    pub cascade: bool,
+    /// Don't ask for confirmation (`--noconfirm`); set from the `noconfirm` setting, not stored here
+    #[serde(skip)]
+    pub noconfirm: bool,
+}
+
+impl Default for RemoveOptions {
+    fn default() -> Self {
+        // pmgr has always removed with -Rns
+        Self {
+            recursive: true,
+            nosave: true,
+            cascade: false,
+            noconfirm: false,
+        }
+    }
+}
+
+impl RemoveOptions {
+    /// The pacman operation flag, e.g. `-Rns`
+    pub fn flag(&self) -> String {
+        let mut flag = "-R".to_string();
+        if self.cascade {
+            flag.push('c');
+        }
+        if self.nosave {
+            flag.push('n');
+        }
+        if self.recursive {
+            flag.push('s');
+        }
+        flag
+    }
+}
+
+/// Why a package is installed, as recorded in the local database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstallReason {
+    Explicit,
+    Dependency,
+}
+
+impl InstallReason {
+    fn flag(&self) -> &'static str {
+        match self {
+            InstallReason::Explicit => "--asexplicit",
+            InstallReason::Dependency => "--asdeps",
+        }
+    }
+}
+
+/// Subset of installed packages to list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InstallFilter {
+    #[default]
+    All,
+    /// Explicitly installed (`-Qqe`)
+    Explicit,
+    /// Installed as dependencies (`-Qqd`)
+    Deps,
+    /// Not found in the sync databases, e.g. AUR packages (`-Qqm`)
+    Foreign,
+}
+
+impl InstallFilter {
+    fn flag(&self) -> &'static str {
+        match self {
+            InstallFilter::All => "-Qq",
+            InstallFilter::Explicit => "-Qqe",
+            InstallFilter::Deps => "-Qqd",
+            InstallFilter::Foreign => "-Qqm",
+        }
+    }
+
+    /// Short name shown next to filtered lists (None when unfiltered)
+    pub fn label(&self) -> Option<&'static str> {
+        match self {
+            InstallFilter::All => None,
+            InstallFilter::Explicit => Some("explicit"),
+            InstallFilter::Deps => Some("deps"),
+            InstallFilter::Foreign => Some("foreign"),
+        }
+    }
+}
+
+/// Below this many regular search results, a command lookup is also performed
+pub const PROVIDES_LOOKUP_THRESHOLD: usize = 3;
+
+/// Size summary of the package cache
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheInfo {
+    pub total_size: u64,
+    pub file_count: usize,
+}
+
+/// Why a package operation failed, so callers can tailor messages and exit codes
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum PmgrError {
+    /// `pacman` isn't installed (not an Arch-based system)
+    #[error("pmgr requires pacman — this does not appear to be an Arch-based system")]
+    PacmanNotFound,
+    #[error("Package not found: {0}")]
+    PackageNotFound(String),
+    /// A program pmgr needs (a backend, an AUR helper) isn't installed
+    #[error("{0} is not installed")]
+    BackendMissing(String),
+    /// Another package manager holds the database lock (`stale`: none is running)
+    #[error("{}", if *stale {
+        "The pacman database is locked, but no package manager is running"
+    } else {
+        "Another package manager is running (the pacman database is locked); try again once it has finished"
+    })]
+    DatabaseLocked { stale: bool },
+    /// sudo/pkexec authentication failed or was dismissed
+    #[error("Authentication failed or was cancelled")]
+    PermissionDenied,
+    /// Mirrors or the AUR couldn't be reached
+    #[error("Network error: {0}")]
+    Network(String),
+    #[error("{}", match code {
+        _ if !stderr.is_empty() => format!("{} failed: {}", cmd, stderr),
+        Some(code) => format!("{} failed with exit code {}", cmd, code),
+        None => format!("{} was interrupted", cmd),
+    })]
+    CommandFailed {
+        cmd: String,
+        code: Option<i32>,
+        /// pacman's `error:` lines, or the last lines of stderr when it printed none
+        stderr: String,
+    },
+}
+
+impl PmgrError {
+    /// Classify a failed command from its exit code and captured error output
+    pub fn from_failure(cmd: &str, code: Option<i32>, stderr: &str) -> Self {
+        let lower = stderr.to_lowercase();
+        let errors = pacman_errors(stderr);
+
+        let missing: Vec<&str> = stderr
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("error: target not found: "))
+            .collect();
+        if !missing.is_empty() {
+            return Self::PackageNotFound(missing.join(", "));
+        }
+
+        if lower.contains("unable to lock database") {
+            Self::DatabaseLocked { stale: false }
+        } else if lower.contains("you cannot perform this operation unless you are root")
+            || lower.contains("not authorized")
+            || lower.contains("request dismissed")
+            || lower.contains("incorrect password attempt")
+            // pkexec exits with 126/127 when authentication is dismissed or fails
+            || (cmd.starts_with("pkexec") && matches!(code, Some(126 | 127)))
+        {
+            Self::PermissionDenied
+        } else if ["failed retrieving file", "failed to synchronize", "could not resolve host", "connection timed out"]
+            .iter()
+            .any(|pattern| lower.contains(pattern))
+        {
+            Self::Network(errors.unwrap_or_else(|| stderr_tail(stderr)))
+        } else {
+            Self::CommandFailed {
+                cmd: cmd.to_string(),
+                code,
+                stderr: errors.unwrap_or_else(|| stderr_tail(stderr)),
+            }
+        }
+    }
+
+    /// Process exit code for the CLI (sysexits-style where one fits)
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::PacmanNotFound | Self::BackendMissing(_) => 127,
+            Self::Network(_) => 69,
+            Self::DatabaseLocked { .. } => 75,
+            Self::PermissionDenied => 77,
+            Self::PackageNotFound(_) | Self::CommandFailed { .. } => 1,
+        }
+    }
+}
+
+/// Lines of stderr kept in an error when pacman printed no `error:` lines
+const STDERR_TAIL_LINES: usize = 3;
+
+/// The last few non-empty lines of stderr (where the reason for a failure usually is)
+fn stderr_tail(stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+    lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+}
+
+/// The error for a command that exited unsuccessfully
+fn command_failed(cmd: &str, output: &std::process::Output) -> PmgrError {
+    PmgrError::from_failure(cmd, output.status.code(), &String::from_utf8_lossy(&output.stderr))
+}
+
+/// What a transaction would change, dependencies included
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TransactionPreview {
+    pub download_size: u64,
+    pub installed_size: u64,
+    /// Packages the transaction adds or removes besides the requested ones
+    pub dependencies: Vec<String>,
+}
+
+impl TransactionPreview {
+    /// Totals over every package in the transaction (`names`); the ones not `requested` are dependencies
+    fn new(
+        requested: &[String],
+        names: &[String],
+        infos: &HashMap<String, PackageInfo>,
+        download_size: fn(&PackageInfo) -> Option<u64>,
+    ) -> Self {
+        let requested: HashSet<&str> = requested.iter().map(|p| p.rsplit('/').next().unwrap_or(p)).collect();
+        let known = || names.iter().filter_map(|name| infos.get(name));
+        Self {
+            download_size: known().filter_map(download_size).sum(),
+            installed_size: known().filter_map(|info| info.installed_size).sum(),
+            dependencies: names.iter().filter(|name| !requested.contains(name.as_str())).cloned().collect(),
+        }
+    }
+}
+
 pub struct PackageManager {
-    use_yay: bool,
+    backend: Backend,
+    pacman_config: OnceCell<PacmanConfig>,
+    official_names: OnceCell<HashSet<String>>,
+    ignored_packages: Vec<String>,
+    held_packages: Vec<String>,
 }
 
 impl PackageManager {
-    pub fn new() -> Self {
-        let use_yay = Command::new("which")
-            .arg("yay")
-            .output()
-            .map(|output| output.status.success())
-            .unwrap_or(false);
+    pub fn new() -> Result<Self> {
+        if !Self::command_exists("pacman") {
+            return Err(PmgrError::PacmanNotFound.into());
+        }
 
-        Self { use_yay }
+        let settings = crate::config::load_settings();
+        let backend = Backend::resolve(settings.backend, Self::command_exists)?;
+
+        Ok(Self {
+            backend,
+            pacman_config: OnceCell::new(),
+            official_names: OnceCell::new(),
+            held_packages: settings.held_packages(),
+            ignored_packages: settings.ignored_packages,
+        })
     }
 
-    fn get_cmd(&self) -> &str {
-        if self.use_yay {
-            "yay"
-        } else {
-            "pacman"
+    /// A package manager that doesn't require pacman (its commands fail when run)
+    #[cfg(test)]
+    pub(crate) fn offline() -> Self {
+        Self {
+            backend: Backend::Pacman,
+            pacman_config: OnceCell::new(),
+            official_names: OnceCell::new(),
+            ignored_packages: Vec::new(),
+            held_packages: Vec::new(),
+        }
+    }
+
+    /// Whether a package is on pmgr's ignore list
+    pub fn is_ignored(&self, package: &str) -> bool {
+        let name = package.rsplit('/').next().unwrap_or(package);
+        self.ignored_packages.iter().any(|p| p == name)
+    }
+
+    /// Get pacman's configured paths (loaded once, on first use)
+    pub fn pacman_config(&self) -> &PacmanConfig {
+        self.pacman_config.get_or_init(PacmanConfig::load)
+    }
+
+    /// Repositories packages are listed from: pacman.conf's, then `aur` when the backend has it
+    pub fn repositories(&self) -> Vec<String> {
+        let mut repositories = self.pacman_config().repositories.clone();
+        if self.backend.supports_aur() {
+            repositories.push("aur".to_string());
         }
+        repositories
+    }
+
+    /// pacman's database lock file (`db.lck` in the configured DBPath)
+    pub fn db_lock_file(&self) -> PathBuf {
+        lock::lock_path(&self.pacman_config().db_path)
+    }
+
+    /// Whether the pacman database is locked (by a running pacman or a stale lock)
+    pub fn is_db_locked(&self) -> bool {
+        self.db_lock_file().exists()
+    }
+
+    /// Whether the database lock is free, held by a running pacman, or stale
+    pub fn db_lock(&self) -> DbLock {
+        lock::check(&self.db_lock_file(), lock::pacman_running)
+    }
+
+    /// Check whether a command is available in PATH
+    pub fn command_exists(cmd: &str) -> bool {
+        runner::query("which")
+            .arg(cmd)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    /// Program used for queries and transactions
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    fn get_cmd(&self) -> &str {
+        self.backend.command()
     }
 
     /// List all available packages
     pub fn list_available(&self) -> Result<Vec<Package>> {
-        let output = Command::new(self.get_cmd())
+        let output = runner::query(self.get_cmd())
             .args(["-Sl"])
             .output()
             .context("Failed to list available packages")?;
 
         if !output.status.success() {
-            anyhow::bail!("Package manager command failed");
+            return Err(command_failed(&format!("{} -Sl", self.get_cmd()), &output).into());
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -54,7 +477,8 @@ impl PackageManager {
                         repository: parts[0].to_string(),
                         name: parts[1].to_string(),
                         version: parts[2].to_string(),
-                        description: parts.get(3..).map(|s| s.join(" ")).unwrap_or_default(),
+                        // -Sl has no descriptions, the rest of the line is the [installed] marker
+                        description: String::new(),
                     })
                 } else {
                     None
@@ -65,15 +489,59 @@ impl PackageManager {
         Ok(packages)
     }
 
+    /// Fill in descriptions of sync packages from `pacman -Ss` (all packages, one run)
+    /// Best effort: packages keep an empty description when pacman fails or doesn't know them (AUR)
+    fn add_descriptions(&self, packages: &mut [Package]) {
+        let Ok(output) = runner::query("pacman").arg("-Ss").stderr(Stdio::null()).output() else {
+            return;
+        };
+
+        let descriptions: HashMap<(String, String), String> = parse_search_output(&String::from_utf8_lossy(&output.stdout))
+            .into_iter()
+            .map(|pkg| ((pkg.repository, pkg.name), pkg.description))
+            .collect();
+        for pkg in packages {
+            if let Some(description) = descriptions.get(&(pkg.repository.clone(), pkg.name.clone())) {
+                pkg.description = description.clone();
+            }
+        }
+    }
+
+    /// Directory of the sync databases (`pacman -Sy` downloads them there)
+    pub fn sync_db_dir(&self) -> PathBuf {
+        self.pacman_config().db_path.join("sync")
+    }
+
+    /// List available packages, reading from the disk cache unless `refresh` is set
+    /// The cache is only used while the sync databases are the ones it was read from
+    pub fn list_available_cached(&self, refresh: bool) -> Result<CachedPackages> {
+        let sync_dir = self.sync_db_dir();
+        if !refresh {
+            if let Some(cached) = cache::load(&sync_dir) {
+                return Ok(cached);
+            }
+        }
+
+        let mut packages = self.list_available()?;
+        self.add_descriptions(&mut packages);
+        // Caching is best effort, fresh data is still usable
+        let created_at = cache::store(&packages, &sync_dir).unwrap_or_else(|_| std::time::SystemTime::now());
+
+        Ok(CachedPackages {
+            packages,
+            created_at,
+        })
+    }
+
     /// List installed packages
     pub fn list_installed(&self) -> Result<Vec<String>> {
-        let output = Command::new(self.get_cmd())
+        let output = runner::query(self.get_cmd())
             .args(["-Qq"])
             .output()
             .context("Failed to list installed packages")?;
 
         if !output.status.success() {
-            anyhow::bail!("Package manager command failed");
+            return Err(command_failed(&format!("{} -Qq", self.get_cmd()), &output).into());
         }
 
         let stdout = String::from_utf8_lossy(&output.stdout);
@@ -82,152 +550,1359 @@ impl PackageManager {
         Ok(packages)
     }
 
-    /// Get package info
-    pub fn get_info(&self, package: &str, installed: bool) -> Result<String> {
-        let flag = if installed { "-Qi" } else { "-Si" };
-
-        let output = Command::new(self.get_cmd())
-            .args([flag, package])
+    /// List explicitly installed packages (`pacman -Qqe`)
+    pub fn list_explicit(&self) -> Result<Vec<String>> {
+        let output = runner::query("pacman")
+            .args(["-Qqe"])
             .output()
-            .context("Failed to get package info")?;
+            .context("Failed to list explicitly installed packages")?;
 
         if !output.status.success() {
-            anyhow::bail!("Package not found: {}", package);
+            return Err(command_failed("pacman -Qqe", &output).into());
         }
 
-        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout.lines().map(|s| s.to_string()).collect())
     }
 
-    /// Install packages
-    pub fn install(&self, packages: &[String]) -> Result<()> {
-        if packages.is_empty() {
-            return Ok(());
+    /// List installed packages with their sizes, from a single `pacman -Qi` run
+    pub fn list_installed_detailed(&self) -> Result<Vec<InstalledDetails>> {
+        let output = runner::query("pacman")
+            .args(["-Qi"])
+            .output()
+            .context("Failed to read installed package details")?;
+
+        if !output.status.success() {
+            return Err(command_failed("pacman -Qi", &output).into());
         }
 
-        let mut cmd = Command::new(self.get_cmd());
-        cmd.arg("-S");
+        Ok(parse_installed_details(&String::from_utf8_lossy(&output.stdout)))
+    }
 
-        for pkg in packages {
-            cmd.arg(pkg);
+    /// Number of explicitly installed packages (`pacman -Qqe`)
+    pub fn count_explicit(&self) -> Result<usize> {
+        Ok(Self::query_names(&["-Qqe"])?.len())
+    }
+
+    /// Number of packages installed as dependencies (`pacman -Qqd`)
+    pub fn count_deps(&self) -> Result<usize> {
+        Ok(Self::query_names(&["-Qqd"])?.len())
+    }
+
+    /// Number of dependencies no installed package requires anymore (`pacman -Qqdt`)
+    pub fn count_orphans(&self) -> Result<usize> {
+        Ok(Self::query_names(&["-Qqdt"])?.len())
+    }
+
+    /// List installed packages matching a filter
+    pub fn list_installed_filtered(&self, filter: InstallFilter) -> Result<Vec<String>> {
+        Self::query_names(&[filter.flag()])
+    }
+
+    /// Package names a query lists, one per line
+    fn query_names(args: &[&str]) -> Result<Vec<String>> {
+        let output = runner::query("pacman")
+            .args(args)
+            .output()
+            .context("Failed to query installed packages")?;
+
+        // pacman exits with 1 and prints nothing when no package matches
+        let no_matches = output.stdout.is_empty() && output.stderr.is_empty();
+        if !output.status.success() && !no_matches {
+            return Err(command_failed(&format!("pacman {}", args.join(" ")), &output).into());
         }
 
-        cmd.stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
+        Ok(String::from_utf8_lossy(&output.stdout).lines().map(|s| s.to_string()).collect())
+    }
 
-        let status = cmd.status().context("Failed to install packages")?;
+    /// List installed packages with their versions
+    pub fn list_installed_versions(&self) -> Result<Vec<InstalledPackage>> {
+        let output = runner::query("pacman")
+            .args(["-Q"])
+            .output()
+            .context("Failed to list installed packages")?;
 
-        if !status.success() {
-            anyhow::bail!("Installation failed");
+        if !output.status.success() {
+            return Err(command_failed("pacman -Q", &output).into());
         }
 
-        Ok(())
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let packages = stdout
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(name, version)| InstalledPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+            })
+            .collect();
+
+        Ok(packages)
     }
 
-    /// Check if a package is from AUR (not in official repos)
-    pub fn is_aur_package(&self, package: &str) -> bool {
-        // Extract package name from "repository/package" format
-        let pkg_name = if let Some(idx) = package.rfind('/') {
-            &package[idx + 1..]
-        } else {
-            package
-        };
+    /// Installed versions of some packages (`name` or `repository/name`) from one `pacman -Q` run,
+    /// keyed by name; packages that aren't installed are left out
+    pub fn installed_versions(&self, packages: &[String]) -> Result<HashMap<String, String>> {
+        if packages.is_empty() {
+            return Ok(HashMap::new());
+        }
 
-        // Try to get info from official repos using pacman
-        // If it succeeds, it's an official package. If it fails, it's AUR.
-        Command::new("pacman")
-            .args(["-Si", pkg_name])
-            .stdout(Stdio::null())
+        // pacman fails when any of them isn't installed, but still prints the others
+        let output = runner::query("pacman")
+            .arg("-Q")
+            .args(packages.iter().map(|p| p.rsplit('/').next().unwrap_or(p)))
             .stderr(Stdio::null())
-            .status()
-            .map(|status| !status.success()) // If pacman -Si fails, it's AUR
-            .unwrap_or(true) // On error, assume AUR
+            .output()
+            .context("Failed to query installed packages")?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect())
     }
 
-    /// Separate packages into AUR and official repos
-    pub fn separate_packages(&self, packages: &[String]) -> (Vec<String>, Vec<String>) {
-        let mut aur_packages = Vec::new();
-        let mut official_packages = Vec::new();
+    /// Get the installed version of a package, if it is installed
+    pub fn installed_version(&self, package: &str) -> Result<Option<String>> {
+        let output = runner::query("pacman")
+            .args(["-Q", package])
+            .output()
+            .context("Failed to query installed package")?;
 
-        for pkg in packages {
-            if self.is_aur_package(pkg) {
-                aur_packages.push(pkg.clone());
-            } else {
-                official_packages.push(pkg.clone());
-            }
+        if !output.status.success() {
+            return Ok(None);
         }
 
-        (aur_packages, official_packages)
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .split_whitespace()
+            .nth(1)
+            .map(|version| version.to_string()))
     }
 
-    /// Remove packages
-    pub fn remove(&self, packages: &[String]) -> Result<()> {
+    /// Get the packages (with target versions) a sync transaction would install,
+    /// including dependencies, without changing anything
+    pub fn plan_install(&self, packages: &[String]) -> Result<Vec<InstalledPackage>> {
         if packages.is_empty() {
-            return Ok(());
+            return Ok(Vec::new());
         }
 
-        let mut cmd = Command::new(self.get_cmd());
-        cmd.arg("-Rns");
+        let output = runner::query("pacman")
+            .args(["-Sp", "--print-format", "%n %v"])
+            .args(packages)
+            .output()
+            .context("Failed to plan transaction")?;
 
-        for pkg in packages {
-            cmd.arg(pkg);
+        if !output.status.success() {
+            return Err(command_failed("pacman -Sp", &output).into());
         }
 
-        cmd.stdin(Stdio::inherit())
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit());
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        Ok(stdout
+            .lines()
+            .filter_map(|line| line.split_once(' '))
+            .map(|(name, version)| InstalledPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+            })
+            .collect())
+    }
 
-        let status = cmd.status().context("Failed to remove packages")?;
+    /// Sizes and dependencies of installing packages, from a dry run (`pacman -Sp`)
+    /// AUR packages (`aur/` items) are built locally, so only the repository ones are counted
+    pub fn preview_install(&self, packages: &[String]) -> Result<TransactionPreview> {
+        let requested: Vec<String> = packages.iter().filter(|pkg| !pkg.starts_with("aur/")).cloned().collect();
+        if requested.is_empty() {
+            anyhow::bail!("AUR packages are built locally");
+        }
 
-        if !status.success() {
-            anyhow::bail!("Removal failed");
+        let names: Vec<String> = self.plan_install(&requested)?.into_iter().map(|pkg| pkg.name).collect();
+        let infos = self.get_info_batch(&names, false)?;
+        Ok(TransactionPreview::new(&requested, &names, &infos, |info| info.download_size))
+    }
+
+    /// Size freed by removing packages and the dependencies removed with them, from a dry run (`pacman -Rp`)
+    pub fn preview_remove(&self, packages: &[String], options: &RemoveOptions) -> Result<TransactionPreview> {
+        let output = runner::query("pacman")
+            .arg(format!("{}p", options.flag()))
+            .args(["--print-format", "%n"])
+            .args(packages.iter().map(|p| p.rsplit('/').next().unwrap_or(p)))
+            .output()
+            .context("Failed to plan transaction")?;
+
+        if !output.status.success() {
+            return Err(command_failed("pacman -Rp", &output).into());
         }
 
-        Ok(())
+        let names: Vec<String> = String::from_utf8_lossy(&output.stdout).lines().map(str::to_string).collect();
+        let infos = self.get_info_batch(&names, true)?;
+        Ok(TransactionPreview::new(packages, &names, &infos, |_| None))
     }
 
-    /// Search packages
-    pub fn search(&self, query: &str) -> Result<Vec<Package>> {
-        let output = Command::new(self.get_cmd())
-            .args(["-Ss", query])
+    /// Get package info
+    pub fn get_info(&self, package: &str, installed: bool) -> Result<String> {
+        let flag = if installed { "-Qi" } else { "-Si" };
+
+        let output = runner::query(self.get_cmd())
+            .args([flag, package])
             .output()
-            .context("Failed to search packages")?;
+            .context("Failed to get package info")?;
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let mut packages = Vec::new();
-        let mut current_pkg: Option<Package> = None;
+        if !output.status.success() {
+            return Err(PmgrError::PackageNotFound(package.to_string()).into());
+        }
 
-        for line in stdout.lines() {
-            if line.starts_with(' ') {
-                // Description line
-                if let Some(ref mut pkg) = current_pkg {
-                    pkg.description = line.trim().to_string();
-                    packages.push(pkg.clone());
-                    current_pkg = None;
-                }
-            } else {
-                // Package name line
-                let parts: Vec<&str> = line.split_whitespace().collect();
-                if !parts.is_empty() {
-                    let name_parts: Vec<&str> = parts[0].split('/').collect();
-                    if name_parts.len() >= 2 {
-                        current_pkg = Some(Package {
-                            repository: name_parts[0].to_string(),
-                            name: name_parts[1].to_string(),
-                            version: parts.get(1).unwrap_or(&"").to_string(),
-                            description: String::new(),
-                        });
-                    }
-                }
-            }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// `-Si`/`-Qi` records of several packages from a single pacman run, keyed by package name
+    /// Unknown names (e.g. AUR packages) are left out: pacman reports them on stderr but
+    /// still prints the others
+    pub fn get_info_records(&self, packages: &[String], installed: bool) -> Result<HashMap<String, String>> {
+        if packages.is_empty() {
+            return Ok(HashMap::new());
         }
 
-        Ok(packages)
+        let flag = if installed { "-Qi" } else { "-Si" };
+        // The local database doesn't take repository prefixes
+        let names = packages
+            .iter()
+            .map(|p| if installed { p.rsplit('/').next().unwrap_or(p) } else { p.as_str() });
+        let output = runner::query("pacman")
+            .arg(flag)
+            .args(names)
+            .output()
+            .context("Failed to get package info")?;
+
+        let records = parse_info_records(&String::from_utf8_lossy(&output.stdout));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let only_unknown = stderr
+            .lines()
+            .filter(|line| line.starts_with("error: "))
+            .all(|line| line.ends_with("was not found"));
+        if records.is_empty() && !output.status.success() && !only_unknown {
+            return Err(command_failed(&format!("pacman {}", flag), &output).into());
+        }
+
+        Ok(records)
     }
-}
 
-impl Default for PackageManager {
-    fn default() -> Self {
-        Self::new()
+    /// Details of several packages from a single pacman run, keyed by package name
+    pub fn get_info_batch(&self, packages: &[String], installed: bool) -> Result<HashMap<String, PackageInfo>> {
+        Ok(self
+            .get_info_records(packages, installed)?
+            .into_iter()
+            .filter_map(|(name, record)| Some((name, PackageInfo::from_pacman_output(&record)?)))
+            .collect())
+    }
+
+    /// Package details as a structured `PackageInfo`
+    pub fn get_info_parsed(&self, package: &str, installed: bool) -> Result<PackageInfo> {
+        let output = self.get_info(package, installed)?;
+        PackageInfo::from_pacman_output(&output)
+            .ok_or_else(|| anyhow::anyhow!("Could not read package info for {}", package))
+    }
+
+    /// Installed packages that depend on a package
+    pub fn required_by(&self, package: &str) -> Result<deps::ReverseDeps> {
+        let output = runner::query("pacman")
+            .args(["-Qi", package])
+            .output()
+            .context("Failed to get package info")?;
+
+        if !output.status.success() {
+            return Err(PmgrError::PackageNotFound(package.to_string()).into());
+        }
+
+        Ok(deps::parse_reverse_deps(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Reverse dependencies of every installed package, from a single `pacman -Qi` run
+    pub fn all_reverse_deps(&self) -> Result<HashMap<String, deps::ReverseDeps>> {
+        let output = runner::query("pacman")
+            .args(["-Qi"])
+            .output()
+            .context("Failed to read installed package details")?;
+
+        if !output.status.success() {
+            return Err(command_failed("pacman -Qi", &output).into());
+        }
+
+        Ok(deps::parse_all_reverse_deps(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Check installed files against the package databases (`pacman -Qk`, or `-Qkk` when deep),
+    /// for every installed package or just one
+    pub fn verify(&self, deep: bool, package: Option<&str>) -> Result<Vec<verify::PackageCheck>> {
+        let mut cmd = runner::query("pacman");
+        cmd.arg(if deep { "-Qkk" } else { "-Qk" });
+        if let Some(package) = package {
+            cmd.arg(package);
+        }
+
+        // pacman exits non-zero when files are missing, so only treat it as a failure
+        // when there is nothing to report
+        let output = cmd
+            .output()
+            .context("Failed to check package files")?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let checks = verify::parse_check_output(&String::from_utf8_lossy(&output.stdout), &stderr);
+
+        if checks.is_empty() && !output.status.success() {
+            return Err(command_failed("pacman -Qk", &output).into());
+        }
+
+        Ok(checks)
+    }
+
+    /// Read package transactions from pacman's log file (oldest first)
+    pub fn read_history(&self) -> Result<Vec<history::HistoryEntry>> {
+        let path = &self.pacman_config().log_file;
+        let content = std::fs::read(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+
+        Ok(history::parse_log(&String::from_utf8_lossy(&content)))
+    }
+
+    /// Day (since the Unix epoch) of the last full system upgrade recorded in pacman's log
+    pub fn last_upgrade_day(&self) -> Option<i64> {
+        let content = std::fs::read(&self.pacman_config().log_file).ok()?;
+        let timestamp = history::last_full_upgrade(&String::from_utf8_lossy(&content))?;
+        date::parse_date(&timestamp)
+    }
+
+    /// Install packages
+    pub fn install(&self, packages: &[String], options: &InstallOptions) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        let mut cmd = runner::interactive(self.get_cmd());
+        cmd.arg("-S").args(options.args());
+
+        for pkg in packages {
+            cmd.arg(pkg);
+        }
+
+        cmd.stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        let status = cmd.status().context("Failed to install packages")?;
+
+        if !status.success() {
+            return Err(PmgrError::CommandFailed {
+                cmd: format!("{} -S", self.get_cmd()),
+                code: status.code(),
+                stderr: String::new(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// Program that runs pacman as root: pkexec when available (works from the TUI), otherwise sudo
+    fn escalation_command() -> &'static str {
+        if Self::command_exists("pkexec") {
+            "pkexec"
+        } else {
+            "sudo"
+        }
+    }
+
+    /// Run a command as root on the terminal (for its prompts and output); `what` names it in errors
+    fn run_privileged(&self, args: &[&OsStr], what: &str) -> Result<()> {
+        let escalate = Self::escalation_command();
+        let status = runner::interactive(escalate)
+            .args(args)
+            .stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit())
+            .status()
+            .with_context(|| format!("Failed to run {}", what))?;
+
+        if !status.success() {
+            // stderr went to the terminal; the exit code still tells a dismissed pkexec prompt apart
+            return Err(PmgrError::from_failure(&format!("{} {}", escalate, what), status.code(), "").into());
+        }
+
+        Ok(())
+    }
+
+    /// Download fresh sync databases (`pacman -Sy`), and the files databases (`pacman -Fy`) with `files_db`
+    /// The available package cache is dropped afterwards, so the next listing reads the new databases
+    pub fn refresh_databases(&self, files_db: bool) -> Result<()> {
+        let mut flags = vec!["-Sy"];
+        if files_db {
+            flags.push("-Fy");
+        }
+
+        for flag in flags {
+            self.run_privileged(&["pacman".as_ref(), flag.as_ref()], &format!("pacman {}", flag))?;
+        }
+
+        // The changed databases invalidate the cache too; this just frees the space right away
+        let _ = cache::clear();
+        Ok(())
+    }
+
+    /// Change why packages are installed (`pacman -D`)
+    pub fn set_install_reason(&self, packages: &[String], reason: InstallReason) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        let escalate = Self::escalation_command();
+        let output = runner::query(escalate)
+            .args(["pacman", "-D", reason.flag()])
+            .args(packages)
+            .stdin(Stdio::inherit())
+            .output()
+            .context("Failed to change the install reason")?;
+
+        if !output.status.success() {
+            return Err(command_failed(&format!("{} pacman -D", escalate), &output).into());
+        }
+
+        Ok(())
+    }
+
+    /// Names of all packages in the sync repositories (`pacman -Slq`, read once)
+    /// Empty when pacman fails, so everything is then treated as AUR
+    fn official_names(&self) -> &HashSet<String> {
+        self.official_names.get_or_init(|| {
+            runner::query("pacman")
+                .args(["-Slq"])
+                .stderr(Stdio::null())
+                .output()
+                .map(|output| {
+                    String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .map(|name| name.to_string())
+                        .collect()
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// Separate packages into AUR and official repos, classifying all of them with one pacman call
+    pub fn separate_packages(&self, packages: &[String]) -> (Vec<String>, Vec<String>) {
+        classify_packages(packages, self.official_names())
+    }
+
+    /// Remove packages
+    pub fn remove(&self, packages: &[String], options: &RemoveOptions) -> Result<()> {
+        if packages.is_empty() {
+            return Ok(());
+        }
+
+        let mut cmd = runner::interactive(self.get_cmd());
+        cmd.arg(options.flag());
+        if options.noconfirm {
+            cmd.arg("--noconfirm");
+        }
+
+        for pkg in packages {
+            cmd.arg(pkg);
+        }
+
+        cmd.stdin(Stdio::inherit())
+            .stdout(Stdio::inherit())
+            .stderr(Stdio::inherit());
+
+        let status = cmd.status().context("Failed to remove packages")?;
+
+        if !status.success() {
+            return Err(PmgrError::CommandFailed {
+                cmd: format!("{} {}", self.get_cmd(), options.flag()),
+                code: status.code(),
+                stderr: String::new(),
+            }
+            .into());
+        }
+
+        Ok(())
+    }
+
+    /// List packages with pending upgrades
+    /// Uses `checkupdates` when available (falls back to `pacman -Qu`),
+    /// plus `-Qua` of the AUR helper for AUR packages
+    pub fn list_upgradable(&self) -> Result<Vec<UpgradablePackage>> {
+        let output = if Self::command_exists("checkupdates") {
+            runner::query("checkupdates")
+                .output()
+                .context("Failed to check for updates")?
+        } else {
+            runner::query("pacman")
+                .args(["-Qu"])
+                .output()
+                .context("Failed to check for updates")?
+        };
+
+        // checkupdates exits with 2 and pacman -Qu with 1 when nothing is upgradable (silently)
+        let silent = output.stdout.is_empty() && output.stderr.trim_ascii().is_empty();
+        if !output.status.success() && !silent {
+            return Err(command_failed("checking for updates", &output).into());
+        }
+
+        let mut packages = Self::parse_upgradable(&String::from_utf8_lossy(&output.stdout));
+
+        if self.backend.supports_aur() {
+            let output = runner::query(self.get_cmd())
+                .args(["-Qua"])
+                .output()
+                .context("Failed to check for AUR updates")?;
+
+            packages.extend(Self::parse_upgradable(&String::from_utf8_lossy(&output.stdout)));
+        }
+
+        // System updates skip pinned packages as well as ignored ones
+        for pkg in &mut packages {
+            pkg.ignored = self.held_packages.contains(&pkg.name);
+        }
+
+        Ok(packages)
+    }
+
+    /// Number of packages a system update would upgrade (held packages excluded)
+    /// `checkupdates` syncs a temporary copy of the databases, so the count is current without
+    /// root; without it this falls back to `pacman -Qu`, which only knows about the last sync
+    /// (running `-Sy` ourselves would need root and risk partial upgrades)
+    pub fn count_upgradable(&self) -> Result<usize> {
+        Ok(self.list_upgradable()?.iter().filter(|p| !p.ignored).count())
+    }
+
+    /// Parse `name old-version -> new-version` lines
+    fn parse_upgradable(output: &str) -> Vec<UpgradablePackage> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() >= 4 && parts[2] == "->" {
+                    Some(UpgradablePackage {
+                        name: parts[0].to_string(),
+                        current_version: parts[1].to_string(),
+                        new_version: parts[3].to_string(),
+                        ignored: false,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+
+    /// Search packages matching all of the terms
+    pub fn search(&self, terms: &[String], scope: SearchScope) -> Result<Vec<Package>> {
+        let (program, flag) = match scope {
+            SearchScope::Combined => (self.get_cmd(), "-Ss"),
+            SearchScope::Repo => ("pacman", "-Ss"),
+            SearchScope::Aur => {
+                if !self.backend.supports_aur() {
+                    return Err(anyhow::Error::new(PmgrError::BackendMissing("yay".to_string()))
+                        .context("Searching the AUR requires an AUR helper (yay or paru), but the backend is pacman"));
+                }
+                (self.get_cmd(), "-Ssa")
+            }
+        };
+
+        let output = runner::query(program)
+            .arg(flag)
+            .args(terms)
+            .output()
+            .context("Failed to search packages")?;
+
+        // No matches is a silent non-zero exit; an error says why on stderr
+        if !output.status.success() && output.stdout.is_empty() && !output.stderr.trim_ascii().is_empty() {
+            return Err(command_failed(&format!("{} {}", program, flag), &output).into());
+        }
+
+        Ok(parse_search_output(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Search the AUR through its RPC API, matching all of the terms (most popular first)
+    /// Errors when the AUR can't be reached, so callers can fall back to `search`
+    pub fn search_aur(&self, terms: &[String]) -> Result<Vec<aur::AurPackage>> {
+        // The RPC takes a single term; the longest one narrows the results the most
+        let Some(term) = terms.iter().max_by_key(|term| term.len()) else {
+            return Ok(Vec::new());
+        };
+
+        let mut packages: Vec<aur::AurPackage> = aur::search(term)?
+            .into_iter()
+            .filter(|pkg| matches_all_terms(pkg, terms))
+            .collect();
+        packages.sort_by(|a, b| b.popularity.total_cmp(&a.popularity));
+        Ok(packages)
+    }
+
+    /// Details of an AUR package from the RPC API, None if the AUR doesn't know it
+    pub fn aur_info(&self, package: &str) -> Result<Option<aur::AurPackage>> {
+        Ok(aur::info(&[package.to_string()])?.into_iter().next())
+    }
+
+    /// Find packages that install `usr/bin/<command>` using the files database
+    pub fn find_command_providers(&self, command: &str) -> Result<ProvidesLookup> {
+        if !is_command_name(command) {
+            return Ok(ProvidesLookup::default());
+        }
+
+        let files_db_missing = !self.has_files_database();
+        if files_db_missing {
+            return Ok(ProvidesLookup {
+                providers: Vec::new(),
+                files_db_missing,
+            });
+        }
+
+        let output = runner::query("pacman")
+            .args(["-F", &format!("usr/bin/{}", command)])
+            .output()
+            .context("Failed to query the files database")?;
+
+        // pacman -F exits with 1 when nothing owns the file
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let providers = stdout
+            .lines()
+            .filter_map(|line| {
+                // Format: "usr/bin/ifconfig is owned by core/net-tools 2.10-2"
+                let owner = line.split_once(" is owned by ")?.1;
+                let (full_name, version) = owner.split_once(' ').unwrap_or((owner, ""));
+                let (repository, name) = full_name.split_once('/')?;
+                Some(Package {
+                    repository: repository.to_string(),
+                    name: name.to_string(),
+                    version: version.trim().to_string(),
+                    description: format!("provides /usr/bin/{}", command),
+                })
+            })
+            .collect();
+
+        Ok(ProvidesLookup {
+            providers,
+            files_db_missing,
+        })
+    }
+
+    /// Get the total size and number of files in the package cache directories
+    pub fn cache_info(&self) -> CacheInfo {
+        let mut info = CacheInfo::default();
+
+        for dir in &self.pacman_config().cache_dirs {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+
+            for metadata in entries.filter_map(|e| e.ok()).filter_map(|e| e.metadata().ok()) {
+                if metadata.is_file() {
+                    info.total_size += metadata.len();
+                    info.file_count += 1;
+                }
+            }
+        }
+
+        info
+    }
+
+    /// Check whether `paccache` (from pacman-contrib) is installed
+    pub fn has_paccache(&self) -> bool {
+        Self::command_exists("paccache")
+    }
+
+    /// Clean the package cache, keeping the `keep` most recent versions of each package
+    /// Falls back to `pacman -Sc` (keeps only installed versions) without paccache
+    pub fn clean_cache(&self, keep: u32) -> Result<()> {
+        if !self.has_paccache() {
+            return self.run_privileged(&["pacman".as_ref(), "-Sc".as_ref(), "--noconfirm".as_ref()], "pacman -Sc");
+        }
+
+        let keep = format!("-rk{}", keep);
+        let mut args: Vec<&OsStr> = vec!["paccache".as_ref(), keep.as_ref()];
+        for dir in &self.pacman_config().cache_dirs {
+            args.push("-c".as_ref());
+            args.push(dir.as_os_str());
+        }
+        self.run_privileged(&args, "paccache")
+    }
+
+    /// Versions of a package in the local package cache, newest first
+    pub fn list_cached_versions(&self, package: &str) -> Vec<CachedVersion> {
+        let mut versions: Vec<CachedVersion> = Vec::new();
+
+        for dir in &self.pacman_config().cache_dirs {
+            let Ok(entries) = std::fs::read_dir(dir) else {
+                continue;
+            };
+
+            for entry in entries.filter_map(|e| e.ok()) {
+                let filename = entry.file_name().to_string_lossy().into_owned();
+                let Some((name, version)) = version::parse_package_filename(&filename) else {
+                    continue;
+                };
+
+                // The same version may be cached in several directories
+                if name == package && !versions.iter().any(|v| v.version == version) {
+                    versions.push(CachedVersion {
+                        version,
+                        path: entry.path(),
+                    });
+                }
+            }
+        }
+
+        versions.sort_by(|a, b| version::vercmp(&b.version, &a.version));
+        versions
+    }
+
+    /// Install a package file (`pacman -U`)
+    pub fn install_file(&self, path: &Path) -> Result<()> {
+        self.run_privileged(&["pacman".as_ref(), "-U".as_ref(), path.as_os_str()], "pacman -U")
+    }
+
+    /// List the files installed by a package (`pacman -Ql`)
+    /// Falls back to the files database (`pacman -Fl`) for packages that aren't installed
+    pub fn list_files(&self, package: &str) -> Result<PackageFiles> {
+        let output = runner::query("pacman")
+            .args(["-Ql", package])
+            .output()
+            .context("Failed to list package files")?;
+
+        if output.status.success() {
+            return Ok(PackageFiles {
+                paths: parse_file_list(&String::from_utf8_lossy(&output.stdout)),
+                ..Default::default()
+            });
+        }
+
+        if !self.has_files_database() {
+            return Ok(PackageFiles {
+                files_db_missing: true,
+                ..Default::default()
+            });
+        }
+
+        let output = runner::query("pacman")
+            .args(["-Fl", package])
+            .output()
+            .context("Failed to query the files database")?;
+
+        if !output.status.success() {
+            return Err(PmgrError::PackageNotFound(package.to_string()).into());
+        }
+
+        Ok(PackageFiles {
+            paths: parse_file_list(&String::from_utf8_lossy(&output.stdout)),
+            from_files_db: true,
+            files_db_missing: false,
+        })
+    }
+
+    /// Check whether any `.files` database has been downloaded
+    fn has_files_database(&self) -> bool {
+        std::fs::read_dir(self.sync_db_dir())
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .any(|entry| entry.path().extension().is_some_and(|ext| ext == "files"))
+            })
+            .unwrap_or(false)
+    }
+}
+
+/// Format a byte count as a human readable size (e.g. "1.5 GiB")
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Parse `Key : Value` pairs from pacman info output, joining continuation lines
+pub fn parse_info(info: &str) -> Vec<(String, String)> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+
+    for line in info.lines() {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if line.starts_with(' ') {
+            // Continuation of the previous value (e.g. long dependency lists)
+            if let Some((_, value)) = fields.last_mut() {
+                value.push(' ');
+                value.push_str(line.trim());
+            }
+        } else if let Some((key, value)) = line.split_once(':') {
+            fields.push((key.trim().to_string(), value.trim().to_string()));
+        }
+    }
+
+    fields
+}
+
+/// Parse `-Ss` output: a `repo/name version [extra]` line, then an indented description
+pub fn parse_search_output(output: &str) -> Vec<Package> {
+    let mut packages: Vec<Package> = Vec::new();
+    let mut awaiting_description = false;
+
+    for line in output.lines() {
+        if line.starts_with(' ') {
+            // Description line (only the first one belongs to the package)
+            if awaiting_description {
+                if let Some(pkg) = packages.last_mut() {
+                    pkg.description = line.trim().to_string();
+                }
+                awaiting_description = false;
+            }
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let Some((repository, name)) = parts.first().and_then(|full| full.split_once('/')) else {
+            awaiting_description = false;
+            continue;
+        };
+
+        // Packages without a description are kept too
+        packages.push(Package {
+            repository: repository.to_string(),
+            name: name.to_string(),
+            version: parts.get(1).unwrap_or(&"").to_string(),
+            description: String::new(),
+        });
+        awaiting_description = true;
+    }
+
+    packages
+}
+
+/// Parse `pacman -Qi` output for all installed packages (records separated by blank lines)
+pub fn parse_installed_details(output: &str) -> Vec<InstalledDetails> {
+    split_records(output)
+        .iter()
+        .filter_map(|record| {
+            let fields = parse_info(record);
+            let field = |key: &str| fields.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+
+            let reason = match field("Install Reason") {
+                Some(reason) if reason.starts_with("Installed as a dependency") => InstallReason::Dependency,
+                _ => InstallReason::Explicit,
+            };
+
+            Some(InstalledDetails {
+                name: field("Name")?.to_string(),
+                version: field("Version").unwrap_or_default().to_string(),
+                installed_size: field("Installed Size").and_then(parse_size).unwrap_or(0),
+                install_date: field("Install Date").and_then(date::parse_date),
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// `-Si`/`-Qi` records keyed by their Name field
+/// A package found in several repositories keeps its first record (the one pacman would use)
+pub fn parse_info_records(output: &str) -> HashMap<String, String> {
+    let mut records = HashMap::new();
+    for record in split_records(output) {
+        if let Some(info) = PackageInfo::from_pacman_output(&record) {
+            records.entry(info.name).or_insert(record);
+        }
+    }
+    records
+}
+
+/// Split `pacman -Qi/-Si` output for several packages into one record per package
+/// Records are separated by blank lines (which may contain stray whitespace)
+fn split_records(output: &str) -> Vec<String> {
+    let mut records = Vec::new();
+    let mut current = String::new();
+
+    for line in output.lines() {
+        if line.trim().is_empty() {
+            if !current.is_empty() {
+                records.push(std::mem::take(&mut current));
+            }
+        } else {
+            current.push_str(line);
+            current.push('\n');
+        }
+    }
+    if !current.is_empty() {
+        records.push(current);
+    }
+
+    records
+}
+
+/// Parse a size as printed by pacman (e.g. "1.50 MiB", or "1 536,00 KiB" in some locales) into bytes
+pub fn parse_size(size: &str) -> Option<u64> {
+    let (value, unit) = size.trim().rsplit_once(|c: char| c.is_whitespace())?;
+
+    // Drop digit grouping, then treat whichever separator comes last as the decimal point
+    let value: String = value.chars().filter(|c| !c.is_whitespace() && *c != '\'').collect();
+    let value = match (value.rfind('.'), value.rfind(',')) {
+        (Some(dot), Some(comma)) if comma > dot => value.replace('.', "").replace(',', "."),
+        (Some(_), Some(_)) => value.replace(',', ""),
+        _ => value.replace(',', "."),
+    };
+    let value: f64 = value.parse().ok()?;
+
+    let multiplier = match unit.trim() {
+        "B" => 1u64,
+        "KiB" => 1 << 10,
+        "MiB" => 1 << 20,
+        "GiB" => 1 << 30,
+        "TiB" => 1 << 40,
+        _ => return None,
+    };
+
+    Some((value * multiplier as f64) as u64)
+}
+
+/// Parse `pacman -Ql` / `pacman -Fl` output (`<pkg> <path>` per line) into absolute paths
+pub fn parse_file_list(output: &str) -> Vec<String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .map(|(_, path)| {
+            // The files database lists paths without the leading slash
+            if path.starts_with('/') {
+                path.to_string()
+            } else {
+                format!("/{}", path)
+            }
+        })
+        .collect()
+}
+
+/// The `error:` messages pacman printed, joined into one readable line
+pub fn pacman_errors(stderr: &str) -> Option<String> {
+    let errors: Vec<&str> = stderr
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("error: "))
+        .collect();
+
+    if errors.is_empty() {
+        None
+    } else {
+        Some(errors.join("; "))
+    }
+}
+
+/// Whether a package (`name` or `repository/name`) comes from the AUR
+/// A repository prefix decides on its own; bare names are looked up in the official set
+fn is_aur(package: &str, official: &HashSet<String>) -> bool {
+    match package.split_once('/') {
+        Some((repository, _)) => repository == "aur",
+        None => !official.contains(package),
+    }
+}
+
+/// Split packages into (AUR, official) in a single pass
+pub fn classify_packages(packages: &[String], official: &HashSet<String>) -> (Vec<String>, Vec<String>) {
+    packages.iter().cloned().partition(|pkg| is_aur(pkg, official))
+}
+
+/// Parse a package list file (one name per line), skipping blank lines and `#` comments
+pub fn parse_package_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Only plain command names are looked up (no paths, spaces or globs)
+fn is_command_name(query: &str) -> bool {
+    !query.is_empty()
+        && query
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'))
+}
+
+/// Keep only the providers that aren't already part of the search results
+pub fn merge_providers(results: &[Package], providers: Vec<Package>) -> Vec<Package> {
+    let mut merged: Vec<Package> = Vec::new();
+
+    for provider in providers {
+        let already_listed = results
+            .iter()
+            .chain(merged.iter())
+            .any(|pkg| pkg.name == provider.name && pkg.repository == provider.repository);
+        if !already_listed {
+            merged.push(provider);
+        }
+    }
+
+    merged
+}
+
+/// Whether every term appears in the name or description (case-insensitive, like `-Ss`)
+fn matches_all_terms(pkg: &aur::AurPackage, terms: &[String]) -> bool {
+    let name = pkg.name.to_lowercase();
+    let description = pkg.description.as_deref().unwrap_or_default().to_lowercase();
+    terms.iter().all(|term| {
+        let term = term.to_lowercase();
+        name.contains(&term) || description.contains(&term)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pkg(repository: &str, name: &str) -> Package {
+        Package {
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            description: String::new(),
+            repository: repository.to_string(),
+        }
+    }
+
+    #[test]
+    fn merge_providers_skips_packages_already_in_results() {
+        let results = vec![pkg("core", "net-tools"), pkg("extra", "inetutils")];
+        let providers = vec![pkg("core", "net-tools"), pkg("extra", "busybox")];
+
+        let merged = merge_providers(&results, providers);
+
+        assert_eq!(merged.len(), 1);
+        assert_eq!(merged[0].name, "busybox");
+    }
+
+    #[test]
+    fn merge_providers_deduplicates_providers() {
+        let providers = vec![pkg("core", "net-tools"), pkg("core", "net-tools")];
+
+        let merged = merge_providers(&[], providers);
+
+        assert_eq!(merged.len(), 1);
+    }
+
+    #[test]
+    fn merge_providers_keeps_same_name_from_other_repository() {
+        let results = vec![pkg("core", "net-tools")];
+        let providers = vec![pkg("custom", "net-tools")];
+
+        assert_eq!(merge_providers(&results, providers).len(), 1);
+    }
+
+    #[test]
+    fn parse_file_list_strips_package_name() {
+        let local = "net-tools /usr/\nnet-tools /usr/bin/ifconfig\n";
+        let files_db = "net-tools usr/\nnet-tools usr/bin/ifconfig\n";
+
+        assert_eq!(parse_file_list(local), vec!["/usr/", "/usr/bin/ifconfig"]);
+        assert_eq!(parse_file_list(files_db), vec!["/usr/", "/usr/bin/ifconfig"]);
+    }
+
+    #[test]
+    fn parse_package_list_skips_comments_and_blanks() {
+        let content = "# base system\nbase\n\n  linux  \nfirefox # browser\n   \n";
+
+        assert_eq!(parse_package_list(content), vec!["base", "linux", "firefox"]);
+    }
+
+    #[test]
+    fn classify_packages_against_official_set() {
+        let official: HashSet<String> = ["bash".to_string(), "git".to_string()].into();
+        let packages: Vec<String> = ["bash", "yay", "extra/git", "aur/paru", "core/not-in-set"]
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+
+        let (aur, official_packages) = classify_packages(&packages, &official);
+        assert_eq!(aur, vec!["yay", "aur/paru"]);
+        assert_eq!(official_packages, vec!["bash", "extra/git", "core/not-in-set"]);
+    }
+
+    #[test]
+    fn remove_options_flag() {
+        assert_eq!(RemoveOptions::default().flag(), "-Rns");
+        let keep_config = RemoveOptions {
+            nosave: false,
+            ..Default::default()
+        };
+        assert_eq!(keep_config.flag(), "-Rs");
+        let all = RemoveOptions {
+            recursive: true,
+            nosave: true,
+            cascade: true,
+            ..Default::default()
+        };
+        assert_eq!(all.flag(), "-Rcns");
+        let none = RemoveOptions {
+            recursive: false,
+            nosave: false,
+            cascade: false,
+            ..Default::default()
+        };
+        assert_eq!(none.flag(), "-R");
+    }
+
+    #[test]
+    fn transaction_totals_include_dependencies() {
+        let info = |name: &str, size: u64| PackageInfo {
+            name: name.to_string(),
+            installed_size: Some(size),
+            download_size: Some(size / 4),
+            ..Default::default()
+        };
+        let infos: HashMap<String, PackageInfo> =
+            [info("git", 4000), info("perl", 2000)].into_iter().map(|info| (info.name.clone(), info)).collect();
+        let names = ["git".to_string(), "perl".to_string(), "unknown-dep".to_string()];
+
+        let preview = TransactionPreview::new(&["extra/git".to_string()], &names, &infos, |info| info.download_size);
+        assert_eq!(preview.download_size, 1500);
+        assert_eq!(preview.installed_size, 6000);
+        assert_eq!(preview.dependencies, vec!["perl", "unknown-dep"]);
+    }
+
+    #[test]
+    fn install_options_args() {
+        assert!(InstallOptions::default().args().is_empty());
+        assert_eq!(
+            InstallOptions { asdeps: true, needed: true, noconfirm: true }.args(),
+            vec!["--asdeps", "--needed", "--noconfirm"]
+        );
+    }
+
+    #[test]
+    fn pacman_errors_are_joined() {
+        let stderr = "error: could not set install reason for package foo (could not find or read package)\nerror: could not find database entry\n";
+        assert_eq!(
+            pacman_errors(stderr).as_deref(),
+            Some("could not set install reason for package foo (could not find or read package); could not find database entry")
+        );
+        assert_eq!(pacman_errors("warning: something\n"), None);
+    }
+
+    #[test]
+    fn parse_size_units() {
+        assert_eq!(parse_size("512.00 B"), Some(512));
+        assert_eq!(parse_size("1.50 KiB"), Some(1536));
+        assert_eq!(parse_size("2,00 MiB"), Some(2 * 1024 * 1024));
+        assert_eq!(parse_size("4.52 GiB"), Some((4.52 * (1u64 << 30) as f64) as u64));
+        assert_eq!(parse_size("0.00 B"), Some(0));
+        assert_eq!(parse_size("None"), None);
+        assert_eq!(parse_size("12 parsecs"), None);
+    }
+
+    #[test]
+    fn parse_size_digit_grouping() {
+        assert_eq!(parse_size("1 536,00 KiB"), Some(1536 * 1024));
+        assert_eq!(parse_size("1\u{a0}536,00 KiB"), Some(1536 * 1024));
+        assert_eq!(parse_size("1,536.00 KiB"), Some(1536 * 1024));
+        assert_eq!(parse_size("1.536,50 KiB"), Some(1536 * 1024 + 512));
+        assert_eq!(parse_size("1'536.00 KiB"), Some(1536 * 1024));
+    }
+
+    #[test]
+    fn parse_search_output_attaches_descriptions() {
+        let output = "\
+extra/firefox 124.0.1-1 [installed]
+    Fast, Private & Safe Web Browser
+extra/firefox-developer-edition 125.0b3-1
+    Developer Edition of the popular Firefox web browser
+aur/firefox-nightly 126.0a1-1 (+380 2.15)
+aur/no-description 1.0-1 (+0 0.00)
+core/filesystem 2024.01-1
+    Base Arch Linux files
+";
+        let packages = parse_search_output(output);
+
+        assert_eq!(packages.len(), 5);
+        assert_eq!(packages[0].name, "firefox");
+        assert_eq!(packages[0].description, "Fast, Private & Safe Web Browser");
+        assert_eq!(packages[1].version, "125.0b3-1");
+        assert_eq!(packages[2].repository, "aur");
+        assert_eq!(packages[2].description, "");
+        assert_eq!(packages[3].description, "");
+        assert_eq!(packages[4].description, "Base Arch Linux files");
+    }
+
+    #[test]
+    fn parse_installed_details_fixture() {
+        let details = parse_installed_details(include_str!("fixtures/pacman_qi.txt"));
+
+        assert_eq!(details.len(), 3);
+        assert_eq!(details[0].name, "bash");
+        assert_eq!(details[0].reason, InstallReason::Dependency);
+        assert_eq!(details[0].install_date, Some(date::days_from_civil(2024, 4, 13)));
+        assert_eq!(details[1].name, "firefox");
+        assert_eq!(details[1].version, "127.0-1");
+        assert_eq!(details[1].reason, InstallReason::Explicit);
+        assert_eq!(details[1].installed_size, parse_size("243.56 MiB").unwrap());
+        assert_eq!(details[2].version, "1:1.3.1-1");
+        assert_eq!(details[2].installed_size, 368 * 1024);
+    }
+
+    #[test]
+    fn parse_installed_details_decimal_comma_fixture() {
+        // Sizes formatted for a locale with decimal commas and digit grouping,
+        // records separated by a whitespace-only line
+        let details = parse_installed_details(include_str!("fixtures/pacman_qi_decimal_comma.txt"));
+
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].installed_size, parse_size("1.12 MiB").unwrap());
+        assert_eq!(details[0].install_date, Some(date::days_from_civil(2024, 5, 14)));
+        assert_eq!(details[1].name, "linux-firmware");
+        assert_eq!(details[1].installed_size, parse_size("1234.50 MiB").unwrap());
+        assert_eq!(details[1].reason, InstallReason::Dependency);
+    }
+
+    #[test]
+    fn parse_installed_details_reads_each_record() {
+        let output = "\
+Name            : bash
+Version         : 5.2.026-2
+Installed Size  : 9.09 MiB
+Required By     : base  gcc
+
+Name            : zlib
+Version         : 1:1.3.1-1
+Installed Size  : 368.00 KiB
+
+";
+        let details = parse_installed_details(output);
+
+        assert_eq!(details.len(), 2);
+        assert_eq!(details[0].name, "bash");
+        assert_eq!(details[0].installed_size, (9.09 * (1u64 << 20) as f64) as u64);
+        assert_eq!(details[1].version, "1:1.3.1-1");
+        assert_eq!(details[1].installed_size, 368 * 1024);
+    }
+
+    #[test]
+    fn format_size_units() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    #[test]
+    fn command_name_validation() {
+        assert!(is_command_name("ifconfig"));
+        assert!(is_command_name("g++"));
+        assert!(!is_command_name(""));
+        assert!(!is_command_name("usr/bin/ls"));
+        assert!(!is_command_name("two words"));
+        assert!(!is_command_name("*"));
+    }
+
+    #[test]
+    fn aur_results_must_match_every_term() {
+        let reply = r#"{"type":"search","resultcount":2,"results":[
+            {"Name":"neovim-git","Version":"0.11-1","Description":"Vim-fork focused on extensibility","LastModified":0},
+            {"Name":"neovim-qt-git","Version":"0.2-1","Description":"Qt GUI for Neovim","LastModified":0}]}"#;
+        let packages = aur::parse_response(reply).unwrap();
+        let terms = |words: &[&str]| words.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+
+        assert!(packages.iter().all(|p| matches_all_terms(p, &terms(&["neovim"]))));
+        assert!(matches_all_terms(&packages[0], &terms(&["NEOVIM", "extensibility"])));
+        assert!(!matches_all_terms(&packages[1], &terms(&["neovim", "extensibility"])));
+    }
+
+    #[test]
+    fn failures_are_classified_from_stderr() {
+        let classify = |stderr: &str| PmgrError::from_failure("pacman -S", Some(1), stderr);
+
+        assert_eq!(
+            classify("error: target not found: nope\nerror: target not found: nada\n"),
+            PmgrError::PackageNotFound("nope, nada".to_string())
+        );
+        assert_eq!(
+            classify("error: failed to init transaction (unable to lock database)\nerror: could not lock database: File exists\n"),
+            PmgrError::DatabaseLocked { stale: false }
+        );
+        assert_eq!(
+            classify("error: you cannot perform this operation unless you are root.\n"),
+            PmgrError::PermissionDenied
+        );
+        assert_eq!(
+            PmgrError::from_failure("pkexec pacman -Syu", Some(126), "Error executing command as another user: Request dismissed\n"),
+            PmgrError::PermissionDenied
+        );
+        assert_eq!(
+            classify("error: failed retrieving file 'core.db' from mirror : Could not resolve host: mirror\n"),
+            PmgrError::Network("failed retrieving file 'core.db' from mirror : Could not resolve host: mirror".to_string())
+        );
+        assert_eq!(
+            classify("warning: x\nerror: failed to commit transaction (conflicting files)\n"),
+            PmgrError::CommandFailed {
+                cmd: "pacman -S".to_string(),
+                code: Some(1),
+                stderr: "failed to commit transaction (conflicting files)".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn error_messages_and_exit_codes() {
+        let failed = |stderr: &str, code| PmgrError::CommandFailed {
+            cmd: "yay -S".to_string(),
+            code,
+            stderr: stderr.to_string(),
+        };
+        assert_eq!(failed("", Some(1)).to_string(), "yay -S failed with exit code 1");
+        assert_eq!(failed("oops", Some(1)).to_string(), "yay -S failed: oops");
+        assert_eq!(failed("", Some(1)).exit_code(), 1);
+
+        assert_eq!(PmgrError::DatabaseLocked { stale: false }.exit_code(), 75);
+        assert_eq!(PmgrError::BackendMissing("paru".to_string()).exit_code(), 127);
+
+        // The variant survives the conversion to anyhow (with or without context)
+        let err = anyhow::Error::new(PmgrError::PermissionDenied).context("Installing");
+        assert_eq!(err.downcast_ref::<PmgrError>(), Some(&PmgrError::PermissionDenied));
+    }
+
+    #[test]
+    fn failures_keep_the_end_of_stderr() {
+        let stderr = "checking keyring...\n\nstep 1\nstep 2\nmirror unreachable\n";
+        match PmgrError::from_failure("checkupdates", Some(1), stderr) {
+            PmgrError::CommandFailed { stderr, .. } => assert_eq!(stderr, "step 1\nstep 2\nmirror unreachable"),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn info_records_are_keyed_by_name() {
+        let output = "\
+Repository      : extra
+Name            : git
+Version         : 2.45.2-1
+Download Size   : 6.71 MiB
+
+Repository      : core
+Name            : bash
+Version         : 5.2.026-2
+
+Repository      : testing
+Name            : git
+Version         : 2.46.0-1
+";
+
+        let records = parse_info_records(output);
+
+        assert_eq!(records.len(), 2);
+        assert!(records["git"].contains("2.45.2-1"), "first record wins");
+        assert!(records["bash"].starts_with("Repository      : core"));
+        let info = PackageInfo::from_pacman_output(&records["git"]).unwrap();
+        assert_eq!(info.repository.as_deref(), Some("extra"));
+        assert!(info.download_size.is_some());
     }
 }
@@ -0,0 +1,151 @@
+use super::date::{self, format_date, parse_date};
+use super::{PackageManager, PmgrError};
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Arch Linux news RSS feed
+const FEED_URL: &str = "https://archlinux.org/feeds/news/";
+
+/// Give up on the feed after this many seconds so updates are never held up
+const FETCH_TIMEOUT_SECS: u32 = 3;
+
+/// Without a recorded upgrade, news from this many days back is shown before updating
+pub const DEFAULT_LOOKBACK_DAYS: i64 = 14;
+
+/// An item of the Arch Linux news feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewsItem {
+    pub title: String,
+    pub link: String,
+    /// Publication date as days since the Unix epoch
+    pub date: Option<i64>,
+}
+
+impl NewsItem {
+    /// Publication date as `YYYY-MM-DD` (or "unknown")
+    pub fn date_string(&self) -> String {
+        self.date.map(format_date).unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
+/// Download and parse the news feed (newest first)
+pub fn fetch() -> Result<Vec<NewsItem>> {
+    let output = Command::new("curl")
+        .args(["-fsSL", "--max-time", &FETCH_TIMEOUT_SECS.to_string(), FEED_URL])
+        .output()
+        .context("Failed to run curl")?;
+
+    if !output.status.success() {
+        return Err(PmgrError::Network("could not download the Arch Linux news feed".to_string()).into());
+    }
+
+    Ok(parse_feed(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// News published since the last system upgrade (or in the last `DEFAULT_LOOKBACK_DAYS` without one)
+pub fn fetch_unread() -> Result<Vec<NewsItem>> {
+    let items = fetch()?;
+    let day = PackageManager::new()
+        .ok()
+        .and_then(|pm| pm.last_upgrade_day())
+        .unwrap_or_else(|| date::today() - DEFAULT_LOOKBACK_DAYS);
+    Ok(since(items, day))
+}
+
+/// Items published on or after the given day (dateless items are kept, to be safe)
+pub fn since(items: Vec<NewsItem>, day: i64) -> Vec<NewsItem> {
+    items
+        .into_iter()
+        .filter(|item| item.date.is_none_or(|date| date >= day))
+        .collect()
+}
+
+/// Parse the `<item>` entries of an RSS feed
+pub fn parse_feed(xml: &str) -> Vec<NewsItem> {
+    let mut items = Vec::new();
+    let mut rest = xml;
+
+    while let Some(start) = rest.find("<item>") {
+        let body = &rest[start + "<item>".len()..];
+        let end = body.find("</item>").unwrap_or(body.len());
+        let item = &body[..end];
+        rest = &body[end..];
+
+        let Some(title) = tag_text(item, "title") else {
+            continue;
+        };
+        items.push(NewsItem {
+            title,
+            link: tag_text(item, "link").unwrap_or_default(),
+            date: tag_text(item, "pubDate").and_then(|d| parse_date(&d)),
+        });
+    }
+
+    items
+}
+
+/// Text content of the first `<tag>` element, with CDATA and entities decoded
+fn tag_text(xml: &str, tag: &str) -> Option<String> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = xml.find(&open)? + open.len();
+    let end = start + xml[start..].find(&close)?;
+    let text = xml[start..end].trim();
+
+    let text = match text.strip_prefix("<![CDATA[").and_then(|t| t.strip_suffix("]]>")) {
+        Some(cdata) => cdata.to_string(),
+        None => decode_entities(text),
+    };
+    Some(text.trim().to_string())
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::date::days_from_civil;
+
+    const FEED: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<rss version="2.0"><channel><title>Arch Linux: Recent news updates</title>
+<link>https://archlinux.org/news/</link>
+<item><title>Manual intervention for pacman 7.0.0 &amp; local repos</title>
+<link>https://archlinux.org/news/manual-intervention-for-pacman-700/</link>
+<description>&lt;p&gt;With the release...&lt;/p&gt;</description>
+<pubDate>Sat, 14 Sep 2024 06:56:03 +0000</pubDate></item>
+<item><title><![CDATA[The sshd service needs to be restarted]]></title>
+<link>https://archlinux.org/news/the-sshd-service-needs-to-be-restarted/</link>
+<pubDate>Mon, 01 Jul 2024 10:00:00 +0000</pubDate></item>
+<item><link>https://archlinux.org/news/untitled/</link></item>
+</channel></rss>"#;
+
+    #[test]
+    fn parses_feed_items() {
+        let items = parse_feed(FEED);
+
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].title, "Manual intervention for pacman 7.0.0 & local repos");
+        assert_eq!(items[0].link, "https://archlinux.org/news/manual-intervention-for-pacman-700/");
+        assert_eq!(items[0].date, Some(days_from_civil(2024, 9, 14)));
+        assert_eq!(items[0].date_string(), "2024-09-14");
+        assert_eq!(items[1].title, "The sshd service needs to be restarted");
+    }
+
+    #[test]
+    fn filters_items_since_a_day() {
+        let items = parse_feed(FEED);
+
+        let recent = since(items.clone(), days_from_civil(2024, 9, 1));
+        assert_eq!(recent.len(), 1);
+        assert_eq!(since(items.clone(), days_from_civil(2024, 7, 1)).len(), 2);
+        assert!(since(items, days_from_civil(2024, 9, 15)).is_empty());
+    }
+}
@@ -0,0 +1,132 @@
+use std::fs;
+use std::path::PathBuf;
+use super::runner;
+
+const DEFAULT_CACHE_DIR: &str = "/var/cache/pacman/pkg/";
+const DEFAULT_DB_PATH: &str = "/var/lib/pacman/";
+const DEFAULT_LOG_FILE: &str = "/var/log/pacman.log";
+const PACMAN_CONF: &str = "/etc/pacman.conf";
+
+/// Paths and repositories configured in pacman.conf
+#[derive(Debug, Clone)]
+pub struct PacmanConfig {
+    pub cache_dirs: Vec<PathBuf>,
+    pub db_path: PathBuf,
+    pub log_file: PathBuf,
+    pub repositories: Vec<String>, // Enabled repositories, in pacman.conf order
+}
+
+impl Default for PacmanConfig {
+    fn default() -> Self {
+        Self {
+            cache_dirs: vec![PathBuf::from(DEFAULT_CACHE_DIR)],
+            db_path: PathBuf::from(DEFAULT_DB_PATH),
+            log_file: PathBuf::from(DEFAULT_LOG_FILE),
+            repositories: Vec::new(),
+        }
+    }
+}
+
+impl PacmanConfig {
+    /// Load the effective pacman configuration
+    /// Uses `pacman-conf` when available, otherwise parses /etc/pacman.conf directly
+    pub fn load() -> Self {
+        // pacman-conf without arguments dumps the resolved config in pacman.conf format
+        if let Ok(output) = runner::query("pacman-conf").output() {
+            if output.status.success() {
+                return Self::parse(&String::from_utf8_lossy(&output.stdout));
+            }
+        }
+
+        fs::read_to_string(PACMAN_CONF)
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Parse pacman.conf-style content, falling back to defaults for missing options
+    pub fn parse(content: &str) -> Self {
+        let mut cache_dirs = Vec::new();
+        let mut db_path = None;
+        let mut log_file = None;
+        let mut repositories = Vec::new();
+        let mut in_options = false;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(section) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                in_options = section == "options";
+                if !in_options {
+                    repositories.push(section.to_string());
+                }
+                continue;
+            }
+
+            if !in_options {
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once('=') {
+                let value = value.trim();
+                match key.trim() {
+                    "CacheDir" => cache_dirs.push(PathBuf::from(value)),
+                    "DBPath" => db_path = Some(PathBuf::from(value)),
+                    "LogFile" => log_file = Some(PathBuf::from(value)),
+                    _ => {}
+                }
+            }
+        }
+
+        let defaults = Self::default();
+        Self {
+            cache_dirs: if cache_dirs.is_empty() { defaults.cache_dirs } else { cache_dirs },
+            db_path: db_path.unwrap_or(defaults.db_path),
+            log_file: log_file.unwrap_or(defaults.log_file),
+            repositories,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("fixtures/pacman.conf");
+
+    #[test]
+    fn parses_multiple_cache_dirs() {
+        let config = PacmanConfig::parse(FIXTURE);
+        assert_eq!(
+            config.cache_dirs,
+            vec![
+                PathBuf::from("/srv/chroot/var/cache/pacman/pkg/"),
+                PathBuf::from("/mnt/shared/pacman-cache/"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_paths() {
+        let config = PacmanConfig::parse(FIXTURE);
+        assert_eq!(config.db_path, PathBuf::from("/srv/chroot/var/lib/pacman/"));
+        assert_eq!(config.log_file, PathBuf::from("/srv/chroot/var/log/pacman.log"));
+    }
+
+    #[test]
+    fn parses_repositories() {
+        let config = PacmanConfig::parse(FIXTURE);
+        assert_eq!(config.repositories, vec!["core", "extra", "custom"]);
+    }
+
+    #[test]
+    fn falls_back_to_defaults() {
+        let config = PacmanConfig::parse("[options]\nColor\n");
+        assert_eq!(config.cache_dirs, vec![PathBuf::from(DEFAULT_CACHE_DIR)]);
+        assert_eq!(config.db_path, PathBuf::from(DEFAULT_DB_PATH));
+        assert_eq!(config.log_file, PathBuf::from(DEFAULT_LOG_FILE));
+        assert!(config.repositories.is_empty());
+    }
+}
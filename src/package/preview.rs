@@ -0,0 +1,211 @@
+use super::{runner, Backend};
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Shown instead of the output of a preview command that ran past its timeout
+pub const TIMED_OUT: &str = "Preview timed out (press r to retry)";
+
+/// How often a running preview command is checked for exit, timeout and cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What the preview pane shows for an item
+/// Items are passed to the program as a single argument, never through a shell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PreviewSource {
+    /// The backend's package info (`-Qi` for installed packages, else `-Si`)
+    /// Only the first word of the item is used, so Updates rows (`name  old → new`) work too
+    PackageInfo { backend: Backend, installed: bool },
+    /// A configured command line; `{}` in an argument is replaced by the item
+    Custom(Vec<String>),
+}
+
+impl PreviewSource {
+    /// A configured preview command, split into arguments like a shell would (quotes and backslashes)
+    /// None for an empty command or unbalanced quotes
+    pub fn custom(command: &str) -> Option<Self> {
+        split_args(command).filter(|args| !args.is_empty()).map(Self::Custom)
+    }
+
+    /// The command that previews an item
+    pub fn command(&self, item: &str) -> Command {
+        match self {
+            Self::PackageInfo { backend, installed } => {
+                let mut cmd = runner::query(backend.command());
+                let name = item.split_whitespace().next().unwrap_or(item);
+                cmd.arg(if *installed { "-Qi" } else { "-Si" }).arg(name);
+                cmd
+            }
+            Self::Custom(args) => {
+                let mut cmd = runner::query(&args[0]);
+                cmd.args(args[1..].iter().map(|arg| arg.replace("{}", item)));
+                cmd
+            }
+        }
+    }
+
+    /// Run the preview for an item and return its output
+    /// A failed command shows what it printed on stderr instead of an empty pane
+    pub fn load(&self, item: &str, timeout: Duration) -> String {
+        self.load_cancellable(item, timeout, || false).unwrap_or_default()
+    }
+
+    /// Like `load`, but the command is killed once `cancelled` returns true (giving None)
+    /// A command still running after `timeout` is killed and shows TIMED_OUT
+    pub fn load_cancellable(&self, item: &str, timeout: Duration, cancelled: impl Fn() -> bool) -> Option<String> {
+        let spawned = self
+            .command(item)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
+        let mut child = match spawned {
+            Ok(child) => child,
+            Err(e) => return Some(format!("Failed to load preview: {}", e)),
+        };
+
+        // Read both pipes while waiting, so a chatty command can't fill one and stall
+        let stdout = read_in_background(child.stdout.take());
+        let stderr = read_in_background(child.stderr.take());
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if cancelled() => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return None;
+                }
+                Ok(None) if started.elapsed() >= timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Some(TIMED_OUT.to_string());
+                }
+                Ok(None) => thread::sleep(POLL_INTERVAL),
+                Err(e) => {
+                    let _ = child.kill();
+                    return Some(format!("Failed to load preview: {}", e));
+                }
+            }
+        };
+
+        let stdout = stdout.join().unwrap_or_default();
+        if status.success() || !stdout.is_empty() {
+            return Some(String::from_utf8_lossy(&stdout).to_string());
+        }
+
+        let stderr = stderr.join().unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr);
+        Some(if stderr.trim().is_empty() {
+            format!("Preview failed (exit code {})", status.code().unwrap_or(-1))
+        } else {
+            format!("Preview failed:\n{}", stderr.trim())
+        })
+    }
+}
+
+/// Read a child's pipe to the end on another thread
+fn read_in_background(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut output = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut output);
+        }
+        output
+    })
+}
+
+/// Split a command line into arguments, honouring single and double quotes and backslash escapes
+fn split_args(line: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current: Option<String> = None;
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '"' => {
+                let arg = current.get_or_insert_with(String::new);
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => arg.push(chars.next()?),
+                        c => arg.push(c),
+                    }
+                }
+            }
+            '\\' => current.get_or_insert_with(String::new).push(chars.next()?),
+            c if c.is_whitespace() => args.extend(current.take()),
+            c => current.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(current);
+
+    Some(args)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(cmd: &Command) -> Vec<String> {
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| arg.to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn package_info_passes_the_name_as_an_argument() {
+        let source = PreviewSource::PackageInfo { backend: Backend::Paru, installed: true };
+        assert_eq!(args(&source.command("extra/vim; rm -rf ~")), vec!["paru", "-Qi", "extra/vim;"]);
+        assert_eq!(args(&source.command("linux  6.9-1 → 6.10-1")), vec!["paru", "-Qi", "linux"]);
+
+        let source = PreviewSource::PackageInfo { backend: Backend::Pacman, installed: false };
+        assert_eq!(args(&source.command("core/bash")), vec!["pacman", "-Si", "core/bash"]);
+    }
+
+    #[test]
+    fn custom_commands_split_like_a_shell() {
+        let source = PreviewSource::custom("pacman -Qil {}").unwrap();
+        assert_eq!(args(&source.command("$(reboot)")), vec!["pacman", "-Qil", "$(reboot)"]);
+
+        let source = PreviewSource::custom(r#"sh -c 'echo "$1" | wc -c' sh pkg={} "a b" c\ d"#).unwrap();
+        assert_eq!(args(&source.command("vim")), vec!["sh", "-c", r#"echo "$1" | wc -c"#, "sh", "pkg=vim", "a b", "c d"]);
+
+        assert_eq!(PreviewSource::custom("  "), None);
+        assert_eq!(PreviewSource::custom("echo 'unclosed"), None);
+        assert_eq!(split_args(r#"'' "\"" x"#), Some(vec![String::new(), "\"".to_string(), "x".to_string()]));
+    }
+
+    #[test]
+    fn failed_preview_shows_stderr() {
+        let custom = |cmd: &str| PreviewSource::custom(cmd).unwrap();
+        let timeout = Duration::from_secs(5);
+        assert_eq!(custom("echo {}").load("vim", timeout), "vim\n");
+        assert_eq!(
+            custom(r#"sh -c 'echo "error: package $1 not found" >&2; exit 1' sh {}"#).load("nope", timeout),
+            "Preview failed:\nerror: package nope not found"
+        );
+        assert_eq!(custom("sh -c 'exit 3'").load("x", timeout), "Preview failed (exit code 3)");
+        assert!(custom("pmgr-no-such-program {}").load("x", timeout).starts_with("Failed to load preview"));
+    }
+
+    #[test]
+    fn hung_previews_are_killed() {
+        let hung = PreviewSource::custom("sleep 10").unwrap();
+        let started = Instant::now();
+        assert_eq!(hung.load("x", Duration::from_millis(50)), TIMED_OUT);
+        assert_eq!(hung.load_cancellable("x", Duration::from_secs(10), || started.elapsed() > Duration::from_millis(100)), None);
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+}
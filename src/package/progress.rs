@@ -0,0 +1,209 @@
+/// What pacman is doing when it reports progress
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// Fetching databases or packages
+    Download,
+    /// Running the transaction: checking, installing, upgrading, removing...
+    Install,
+}
+
+/// A progress report read from one line of pacman output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Progress {
+    pub phase: Phase,
+    /// Package number and how many there are, e.g. (3, 12); unknown for single downloads
+    pub position: Option<(usize, usize)>,
+    /// Percentage shown on the line's progress bar (absent with `--noprogressbar` or without a terminal)
+    pub percent: Option<u8>,
+    /// What is being done, e.g. "upgrading linux"
+    pub action: String,
+}
+
+impl Progress {
+    /// Read a progress line, already stripped of escape sequences
+    /// - `(3/12) upgrading foo   [#####-----]  47%` (the bar is missing when output isn't a terminal)
+    /// - ` foo-1.0-1-x86_64   1.2 MiB  3.4 MiB/s 00:01 [####------]  40%` for a download
+    /// - `Total (3/12)   120.4 MiB  5.0 MiB/s 00:10 [####------]  30%` for all of them
+    /// - ` foo-1.0-1-x86_64 downloading...` for a download without a bar
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        let (text, percent) = match split_bar(line) {
+            Some((text, percent)) => (text, Some(percent)),
+            None => (line, None),
+        };
+
+        if let Some((position, action)) = split_position(text) {
+            return Some(Self {
+                phase: Phase::Install,
+                position: Some(position),
+                percent,
+                action: action.to_string(),
+            });
+        }
+
+        if let Some(rest) = text.strip_prefix("Total ") {
+            let (position, _) = split_position(rest.trim_start())?;
+            return Some(Self {
+                phase: Phase::Download,
+                position: Some(position),
+                percent,
+                action: "downloading".to_string(),
+            });
+        }
+
+        // A single download: the name first, then sizes and speed (or "downloading...")
+        let name = match percent {
+            Some(_) => text.split_whitespace().next()?,
+            None => text.strip_suffix(" downloading...")?.trim(),
+        };
+        if name.is_empty() || name.contains(char::is_whitespace) {
+            return None;
+        }
+        Some(Self {
+            phase: Phase::Download,
+            position: None,
+            percent,
+            action: format!("downloading {}", name),
+        })
+    }
+
+    /// How far along the operation is, from 0 to 1
+    /// During the transaction the package's percentage fills its share of the total
+    pub fn ratio(&self) -> f64 {
+        let percent = self.percent.map(|p| p.min(100) as f64 / 100.0);
+        match (self.phase, self.position, percent) {
+            (Phase::Install, Some((current, total)), percent) => {
+                (current.saturating_sub(1) as f64 + percent.unwrap_or(0.0)) / total.max(1) as f64
+            }
+            (_, _, Some(percent)) => percent,
+            (_, Some((current, total)), None) => current.saturating_sub(1) as f64 / total.max(1) as f64,
+            (_, None, None) => 0.0,
+        }
+        .clamp(0.0, 1.0)
+    }
+
+    /// The status line, e.g. "(3/12) upgrading foo"
+    pub fn label(&self) -> String {
+        match self.position {
+            Some((current, total)) => format!("({}/{}) {}", current, total, self.action),
+            None => self.action.clone(),
+        }
+    }
+}
+
+/// Split `text [####----]  47%` into the text and the percentage
+fn split_bar(line: &str) -> Option<(&str, u8)> {
+    let percent = line.strip_suffix('%')?;
+    let digits_start = percent.rfind(|c: char| !c.is_ascii_digit())? + 1;
+    let value = percent[digits_start..].parse::<u8>().ok()?;
+
+    let bar = percent[..digits_start].trim_end().strip_suffix(']')?;
+    let open = bar.rfind('[')?;
+    // Pacman's bar, or its ILoveCandy Pac-Man variant
+    if !bar[open + 1..].chars().all(|c| matches!(c, '#' | '-' | ' ' | 'C' | 'c' | 'o')) {
+        return None;
+    }
+    Some((bar[..open].trim_end(), value))
+}
+
+/// Split `(3/12) rest` into the position and the rest
+fn split_position(text: &str) -> Option<((usize, usize), &str)> {
+    let rest = text.strip_prefix('(')?;
+    let (counts, action) = rest.split_once(')')?;
+    let (current, total) = counts.split_once('/')?;
+    let position = (current.trim().parse().ok()?, total.trim().parse().ok()?);
+    let action = action.trim();
+    (!action.is_empty()).then_some((position, action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `pacman -Syu` output with a terminal, each bar at its last state
+    const WITH_BARS: &str = "\
+:: Synchronizing package databases...
+ core                  130.4 KiB   652 KiB/s 00:00 [######################] 100%
+ extra                   8.3 MiB  4.10 MiB/s 00:02 [##########------------]  47%
+:: Starting full system upgrade...
+resolving dependencies...
+Total Download Size:   152.34 MiB
+:: Proceed with installation? [Y/n]
+:: Retrieving packages...
+ linux-6.10.1.arch1-1-x86_64   140.2 MiB  10.2 MiB/s 00:14 [######################] 100%
+Total (2/3)            152.3 MiB  11.0 MiB/s 00:13 [###############-------]  70%
+(3/3) checking keys in keyring                     [######################] 100%
+:: Processing package changes...
+(1/3) upgrading linux                              [#########-------------]  40%
+(2/3) upgrading vim                                [c o o o o o o C-------]  62%";
+
+    /// The same upgrade through a pipe, where pacman prints no bars
+    const WITHOUT_BARS: &str = "\
+:: Retrieving packages...
+ linux-6.10.1.arch1-1-x86_64 downloading...
+checking keyring...
+(1/3) checking keys in keyring
+(1/3) upgrading linux
+(3/3) installing vim-runtime
+warning: /etc/vimrc installed as /etc/vimrc.pacnew";
+
+    fn parse_all(output: &str) -> Vec<Progress> {
+        output.lines().filter_map(Progress::parse).collect()
+    }
+
+    #[test]
+    fn progress_bars_are_parsed() {
+        let progress = parse_all(WITH_BARS);
+        let labels = progress.iter().map(|p| (p.label(), p.percent)).collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                ("downloading core".to_string(), Some(100)),
+                ("downloading extra".to_string(), Some(47)),
+                ("downloading linux-6.10.1.arch1-1-x86_64".to_string(), Some(100)),
+                ("(2/3) downloading".to_string(), Some(70)),
+                ("(3/3) checking keys in keyring".to_string(), Some(100)),
+                ("(1/3) upgrading linux".to_string(), Some(40)),
+                ("(2/3) upgrading vim".to_string(), Some(62)),
+            ]
+        );
+        assert_eq!(progress[3].phase, Phase::Download);
+        assert_eq!(progress[5].phase, Phase::Install);
+        assert_eq!(progress[5].position, Some((1, 3)));
+
+        // The package's share of the whole transaction
+        assert!((progress[6].ratio() - 0.54).abs() < 1e-9);
+        assert_eq!(progress[3].ratio(), 0.7);
+    }
+
+    #[test]
+    fn lines_without_bars_still_report_the_position() {
+        let progress = parse_all(WITHOUT_BARS);
+        let labels = progress.iter().map(|p| (p.label(), p.percent)).collect::<Vec<_>>();
+        assert_eq!(
+            labels,
+            [
+                ("downloading linux-6.10.1.arch1-1-x86_64".to_string(), None),
+                ("(1/3) checking keys in keyring".to_string(), None),
+                ("(1/3) upgrading linux".to_string(), None),
+                ("(3/3) installing vim-runtime".to_string(), None),
+            ]
+        );
+        assert_eq!(progress[3].ratio(), 2.0 / 3.0);
+    }
+
+    #[test]
+    fn other_output_is_not_progress() {
+        for line in [
+            "",
+            ":: Proceed with installation? [Y/n]",
+            "Total Installed Size:  310.52 MiB",
+            "(some text in parentheses)",
+            "disk usage: [/home] 93%",
+            "Packages (3) linux-6.10.1.arch1-1  vim-9.1-1",
+            "error: failed to commit transaction (conflicting files)",
+        ] {
+            assert_eq!(Progress::parse(line), None, "{:?}", line);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+use std::process::Command;
+
+/// A command whose output pmgr parses
+/// Runs in the C locale: pacman translates field names ("Installed Size"), messages and
+/// dates, and the parsers only understand the untranslated output
+pub fn query(program: &str) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.env("LC_ALL", "C");
+    cmd
+}
+
+/// A command passed through to the user (transactions, prompts), in their own locale
+pub fn interactive(program: &str) -> Command {
+    Command::new(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+
+    fn env<'a>(cmd: &'a Command, key: &str) -> Option<Option<&'a OsStr>> {
+        cmd.get_envs().find(|(k, _)| *k == key).map(|(_, v)| v)
+    }
+
+    #[test]
+    fn queries_run_in_the_c_locale() {
+        let cmd = query("pacman");
+        assert_eq!(cmd.get_program(), "pacman");
+        assert_eq!(env(&cmd, "LC_ALL"), Some(Some(OsStr::new("C"))));
+    }
+
+    #[test]
+    fn interactive_commands_keep_the_user_locale() {
+        assert_eq!(env(&interactive("pacman"), "LC_ALL"), None);
+    }
+}
@@ -0,0 +1,98 @@
+/// File check results for one installed package, from `pacman -Qk` or `-Qkk`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PackageCheck {
+    pub name: String,
+    pub total_files: u64,
+    /// Files that no longer exist (`-Qk`)
+    pub missing: u64,
+    /// Files that differ from the package metadata, missing ones included (`-Qkk`)
+    pub altered: u64,
+    /// Reported problems as `path (reason)`
+    pub problems: Vec<String>,
+}
+
+impl PackageCheck {
+    /// Number of missing or altered files
+    pub fn issues(&self) -> u64 {
+        self.missing + self.altered
+    }
+}
+
+/// Parse the summary lines (stdout) and file warnings (stderr) of `pacman -Qk`/`-Qkk`
+pub fn parse_check_output(stdout: &str, stderr: &str) -> Vec<PackageCheck> {
+    let mut checks: Vec<PackageCheck> = stdout.lines().filter_map(parse_summary).collect();
+
+    for line in stderr.lines() {
+        let Some((name, problem)) = line
+            .trim()
+            .strip_prefix("warning: ")
+            .and_then(|rest| rest.split_once(": "))
+        else {
+            continue;
+        };
+        if let Some(check) = checks.iter_mut().find(|c| c.name == name) {
+            check.problems.push(problem.to_string());
+        }
+    }
+
+    checks
+}
+
+/// `name: 123 total files, 4 missing files` (or `altered files` in deep mode)
+fn parse_summary(line: &str) -> Option<PackageCheck> {
+    let (name, counts) = line.split_once(": ")?;
+    let (total, issues) = counts.split_once(", ")?;
+    let total_files = total.strip_suffix(" total files")?.trim().parse().ok()?;
+
+    let mut check = PackageCheck {
+        name: name.to_string(),
+        total_files,
+        ..Default::default()
+    };
+    if let Some(missing) = issues.strip_suffix(" missing files") {
+        check.missing = missing.trim().parse().ok()?;
+    } else {
+        check.altered = issues.strip_suffix(" altered files")?.trim().parse().ok()?;
+    }
+
+    Some(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_missing_files() {
+        let stdout = "bash: 150 total files, 0 missing files\nvim: 2042 total files, 2 missing files\n";
+        let stderr = "warning: vim: /usr/bin/vim (No such file or directory)\n\
+                      warning: vim: /usr/bin/vimdiff (No such file or directory)\n";
+        let checks = parse_check_output(stdout, stderr);
+
+        assert_eq!(checks.len(), 2);
+        assert_eq!(checks[0].issues(), 0);
+        assert!(checks[0].problems.is_empty());
+        assert_eq!(checks[1].name, "vim");
+        assert_eq!(checks[1].total_files, 2042);
+        assert_eq!(checks[1].missing, 2);
+        assert_eq!(checks[1].problems[0], "/usr/bin/vim (No such file or directory)");
+    }
+
+    #[test]
+    fn parses_altered_files() {
+        let stdout = "linux: 7000 total files, 1 altered files\n";
+        let stderr = "warning: linux: /usr/lib/modules/x (Size mismatch)\n\
+                      backup file: linux: /etc/mkinitcpio.d/linux.preset (Modification time mismatch)\n";
+        let checks = parse_check_output(stdout, stderr);
+
+        assert_eq!(checks[0].altered, 1);
+        assert_eq!(checks[0].missing, 0);
+        // Modified backup files are expected and not counted as problems
+        assert_eq!(checks[0].problems, vec!["/usr/lib/modules/x (Size mismatch)"]);
+    }
+
+    #[test]
+    fn ignores_unrelated_lines() {
+        assert!(parse_check_output("error: package 'nope' was not found\n", "").is_empty());
+    }
+}
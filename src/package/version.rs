@@ -0,0 +1,179 @@
+use std::cmp::Ordering;
+
+/// Compare two package versions (`[epoch:]pkgver[-pkgrel]`) like pacman's `vercmp`
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let (epoch_a, ver_a, rel_a) = split_version(a);
+    let (epoch_b, ver_b, rel_b) = split_version(b);
+
+    rpmvercmp(epoch_a, epoch_b)
+        .then_with(|| rpmvercmp(ver_a, ver_b))
+        .then_with(|| match (rel_a, rel_b) {
+            (Some(rel_a), Some(rel_b)) => rpmvercmp(rel_a, rel_b),
+            // A missing pkgrel matches any pkgrel
+            _ => Ordering::Equal,
+        })
+}
+
+/// Split a full version into (epoch, pkgver, pkgrel)
+fn split_version(version: &str) -> (&str, &str, Option<&str>) {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((epoch, rest)) if epoch.chars().all(|c| c.is_ascii_digit()) => (epoch, rest),
+        _ => ("0", version),
+    };
+
+    match rest.rsplit_once('-') {
+        Some((pkgver, pkgrel)) => (epoch, pkgver, Some(pkgrel)),
+        None => (epoch, rest, None),
+    }
+}
+
+/// Segment-wise comparison used by rpm and pacman
+fn rpmvercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let a = a.as_bytes();
+    let b = b.as_bytes();
+    let (mut one, mut two) = (0, 0);
+
+    while one < a.len() && two < b.len() {
+        let (sep_start_one, sep_start_two) = (one, two);
+        while one < a.len() && !a[one].is_ascii_alphanumeric() {
+            one += 1;
+        }
+        while two < b.len() && !b[two].is_ascii_alphanumeric() {
+            two += 1;
+        }
+
+        if one >= a.len() || two >= b.len() {
+            break;
+        }
+
+        // Different separator lengths decide the comparison
+        if one - sep_start_one != two - sep_start_two {
+            return (one - sep_start_one).cmp(&(two - sep_start_two));
+        }
+
+        let is_num = a[one].is_ascii_digit();
+        let segment_end = |s: &[u8], start: usize| {
+            let mut end = start;
+            while end < s.len()
+                && (if is_num { s[end].is_ascii_digit() } else { s[end].is_ascii_alphabetic() })
+            {
+                end += 1;
+            }
+            end
+        };
+
+        let end_one = segment_end(a, one);
+        let end_two = segment_end(b, two);
+
+        // Segments of different types: numeric is newer than alpha
+        if end_two == two {
+            return if is_num { Ordering::Greater } else { Ordering::Less };
+        }
+
+        let mut seg_one = &a[one..end_one];
+        let mut seg_two = &b[two..end_two];
+
+        let ordering = if is_num {
+            while seg_one.len() > 1 && seg_one[0] == b'0' {
+                seg_one = &seg_one[1..];
+            }
+            while seg_two.len() > 1 && seg_two[0] == b'0' {
+                seg_two = &seg_two[1..];
+            }
+            seg_one.len().cmp(&seg_two.len()).then_with(|| seg_one.cmp(seg_two))
+        } else {
+            seg_one.cmp(seg_two)
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        one = end_one;
+        two = end_two;
+    }
+
+    let one_done = one >= a.len();
+    let two_done = two >= b.len();
+    if one_done && two_done {
+        return Ordering::Equal;
+    }
+
+    // A remaining alpha segment never beats an empty string
+    if (one_done && !b[two].is_ascii_alphabetic()) || (!one_done && a[one].is_ascii_alphabetic()) {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+/// Split a cached package file name (`name-pkgver-pkgrel-arch.pkg.tar.*`) into name and version
+pub fn parse_package_filename(filename: &str) -> Option<(&str, String)> {
+    if filename.ends_with(".sig") {
+        return None;
+    }
+
+    let (base, _) = filename.split_once(".pkg.tar")?;
+    let mut parts = base.rsplitn(4, '-');
+    let _arch = parts.next()?;
+    let pkgrel = parts.next()?;
+    let pkgver = parts.next()?;
+    let name = parts.next()?;
+
+    Some((name, format!("{}-{}", pkgver, pkgrel)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_like_pacman() {
+        let cases = [
+            ("1.0", "1.0", Ordering::Equal),
+            ("1.0", "1.0.1", Ordering::Less),
+            ("1.10", "1.9", Ordering::Greater),
+            ("1.001", "1.1", Ordering::Equal),
+            ("1.0a", "1.0", Ordering::Less),
+            ("1.0rc1", "1.0", Ordering::Less),
+            ("1.0", "1.0.a", Ordering::Less),
+            ("1.0a", "1.0b", Ordering::Less),
+            ("1.0.1", "1.0a", Ordering::Greater),
+            ("1.0-2", "1.0-1", Ordering::Greater),
+            ("1.0", "1.0-5", Ordering::Equal),
+            ("1:1.0", "2.0", Ordering::Greater),
+            ("2.0-1", "1:1.0-1", Ordering::Less),
+        ];
+
+        for (a, b, expected) in cases {
+            assert_eq!(vercmp(a, b), expected, "vercmp({}, {})", a, b);
+            assert_eq!(vercmp(b, a), expected.reverse(), "vercmp({}, {})", b, a);
+        }
+    }
+
+    #[test]
+    fn parses_cache_filenames() {
+        assert_eq!(
+            parse_package_filename("linux-firmware-20240115.9b6d0b08-2-any.pkg.tar.zst"),
+            Some(("linux-firmware", "20240115.9b6d0b08-2".to_string()))
+        );
+        assert_eq!(
+            parse_package_filename("python-3.11.6-1-x86_64.pkg.tar.xz"),
+            Some(("python", "3.11.6-1".to_string()))
+        );
+        assert_eq!(
+            parse_package_filename("gnupg-2:2.4.3-2-x86_64.pkg.tar.zst"),
+            Some(("gnupg", "2:2.4.3-2".to_string()))
+        );
+        assert_eq!(parse_package_filename("python-3.11.6-1-x86_64.pkg.tar.zst.sig"), None);
+        assert_eq!(parse_package_filename("download-abc123.part"), None);
+    }
+}
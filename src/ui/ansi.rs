@@ -0,0 +1,157 @@
+/// The text a terminal would show for some output: escape sequences are dropped and a
+/// carriage return goes back to the start of the line, so a progress bar redrawn in place
+/// leaves only its last state
+pub fn strip(text: &str) -> String {
+    text.split('\n').map(strip_line).collect::<Vec<_>>().join("\n")
+}
+
+/// Where the parser is within an escape sequence
+enum State {
+    Text,
+    /// After ESC
+    Escape,
+    /// After ESC followed by intermediate bytes (e.g. the `(` of `ESC ( B`)
+    EscapeIntermediate,
+    /// Control sequence (`ESC [` or the 8-bit CSI) up to its final byte
+    Csi,
+    /// String sequence (OSC, DCS, PM, APC, SOS) up to BEL or `ESC \`
+    String,
+    /// ESC inside a string sequence, the start of its terminator
+    StringEscape,
+}
+
+fn strip_line(line: &str) -> String {
+    let mut screen: Vec<char> = Vec::new();
+    let mut cursor: usize = 0;
+    let mut state = State::Text;
+    let mut params = String::new();
+
+    for c in line.chars() {
+        state = match state {
+            State::Text => match c {
+                '\x1b' => State::Escape,
+                '\u{9b}' => {
+                    params.clear();
+                    State::Csi
+                }
+                '\u{90}' | '\u{98}' | '\u{9d}' | '\u{9e}' | '\u{9f}' => State::String,
+                '\r' => {
+                    cursor = 0;
+                    State::Text
+                }
+                '\x08' => {
+                    cursor = cursor.saturating_sub(1);
+                    State::Text
+                }
+                c if c.is_control() && c != '\t' => State::Text,
+                c => {
+                    if cursor < screen.len() {
+                        screen[cursor] = c;
+                    } else {
+                        screen.push(c);
+                    }
+                    cursor += 1;
+                    State::Text
+                }
+            },
+            State::Escape => match c {
+                '[' => {
+                    params.clear();
+                    State::Csi
+                }
+                ']' | 'P' | 'X' | '^' | '_' => State::String,
+                '\x20'..='\x2f' => State::EscapeIntermediate,
+                _ => State::Text,
+            },
+            State::EscapeIntermediate => match c {
+                '\x20'..='\x2f' => State::EscapeIntermediate,
+                _ => State::Text,
+            },
+            State::Csi => match c {
+                '\x40'..='\x7e' => {
+                    apply_csi(c, &params, &mut screen, &mut cursor);
+                    State::Text
+                }
+                _ => {
+                    params.push(c);
+                    State::Csi
+                }
+            },
+            State::String => match c {
+                '\x07' | '\u{9c}' => State::Text,
+                '\x1b' => State::StringEscape,
+                _ => State::String,
+            },
+            State::StringEscape => match c {
+                '\\' => State::Text,
+                _ => State::String,
+            },
+        };
+    }
+
+    screen.into_iter().collect::<String>().trim_end().to_string()
+}
+
+/// Apply the cursor movement and erasing within the line; colors and the rest are dropped
+fn apply_csi(command: char, params: &str, screen: &mut Vec<char>, cursor: &mut usize) {
+    let first = params.split(';').next().and_then(|n| n.parse::<usize>().ok());
+    match command {
+        // Cursor forward/back
+        'C' => *cursor += first.unwrap_or(1).max(1),
+        'D' => *cursor = cursor.saturating_sub(first.unwrap_or(1).max(1)),
+        // Cursor to a column (1-based)
+        'G' => *cursor = first.unwrap_or(1).saturating_sub(1),
+        // Erase to the end of the line, or all of it
+        'K' => match first.unwrap_or(0) {
+            0 => screen.truncate(*cursor),
+            2 => screen.clear(),
+            _ => {}
+        },
+        _ => {}
+    }
+
+    // Moving past the end pads with spaces, like the terminal's blank cells
+    if screen.len() < *cursor && matches!(command, 'C' | 'G') {
+        screen.resize(*cursor, ' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `pacman -Syu` with colors, as captured from its output
+    const PACMAN: &str = "\x1b[0;1m:: Synchronizing package databases...\x1b[0m\n core downloading...\r core     130.4 KiB   652 KiB/s 00:00 [###########-----------]  50%\r core     130.4 KiB  1000 KiB/s 00:00 [######################] 100%\n\x1b[1;34m::\x1b[0;1m Starting full system upgrade...\x1b[0m\n\x1b]0;pacman: upgrading\x07(1/2) upgrading linux                      [####------]  40%\r\x1b[K(1/2) upgrading linux                      [##########] 100%";
+
+    #[test]
+    fn progress_bars_keep_their_last_state() {
+        assert_eq!(
+            strip(PACMAN).lines().collect::<Vec<_>>(),
+            [
+                ":: Synchronizing package databases...",
+                " core     130.4 KiB  1000 KiB/s 00:00 [######################] 100%",
+                ":: Starting full system upgrade...",
+                "(1/2) upgrading linux                      [##########] 100%",
+            ]
+        );
+    }
+
+    #[test]
+    fn escape_sequences_are_removed() {
+        // Colors, window titles (BEL and ST terminated), charset switches and 8-bit CSI
+        assert_eq!(strip("\x1b[1;31merror:\x1b[0m target not found"), "error: target not found");
+        assert_eq!(strip("\x1b]0;yay\x1b\\\x1b(Bdone\u{9b}0m"), "done");
+        assert_eq!(strip("\x1b]8;;https://archlinux.org\x07link\x1b]8;;\x07"), "link");
+
+        // Cursor movement and erasing within the line
+        assert_eq!(strip("downloading 10%\x1b[3Ddone\x1b[K"), "downloading done");
+        assert_eq!(strip("abc\x1b[2K\rxyz"), "xyz");
+        assert_eq!(strip("a\x1b[5Gb"), "a   b");
+        assert_eq!(strip("50%\x08\x08\x08100%"), "100%");
+
+        // A line shorter than the one it overwrites leaves the rest, like a terminal
+        assert_eq!(strip("12345\rab"), "ab345");
+        assert_eq!(strip("unterminated \x1b[1"), "unterminated");
+        assert_eq!(strip("tab\tkept, bell\x07 dropped"), "tab\tkept, bell dropped");
+    }
+}
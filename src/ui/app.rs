@@ -1,25 +1,124 @@
-use super::types::{ActionType, Alert, ConfirmDialog, PreviewLayout, SystemUpdateWindow};
+use super::links::{self, LinkOutcome};
+use super::spinner::LoadingState;
+use super::types::{
+    ActionType, Alert, AlertType, ConfirmButton, ConfirmDialog, PreviewLayout, RepoFilter, SearchMode, SortMode, SystemUpdateWindow,
+    ViewType,
+};
+use crate::package::aur::{self, AurStatus};
+use crate::output;
+use crate::package::date;
+use crate::package::deps::parse_reverse_deps;
+use crate::package::news::{self, NewsItem};
+use crate::package::{preview, InstallOptions, InstalledDetails, Package, PackageInfo, PackageManager, PreviewSource, RemoveOptions, PROVIDES_LOOKUP_THRESHOLD};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use fuzzy_matcher::skim::SkimMatcherV2;
 use fuzzy_matcher::FuzzyMatcher;
+use ratatui::layout::{Position, Rect};
 use ratatui::widgets::ListState;
-use std::collections::HashMap;
-use std::process::Command;
+use rayon::prelude::*;
+use regex::{Regex, RegexBuilder};
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
 use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Minimum time between two filters of the list while the search is being typed
+const FILTER_DEBOUNCE: Duration = Duration::from_millis(90);
+
+/// Candidate count above which the search is scored on all cores (smaller lists aren't worth the threads)
+const PARALLEL_FILTER_THRESHOLD: usize = 10_000;
+
+/// Share of the package view the preview pane can take, in percent, and how much one resize changes it
+const PREVIEW_RATIO_MIN: u16 = 20;
+const PREVIEW_RATIO_MAX: u16 = 80;
+const PREVIEW_RATIO_STEP: u16 = 5;
+
+/// Items on each side of the cursor whose previews are loaded ahead of time
+const PREFETCH_DISTANCE: usize = 2;
+
+/// Preview commands running at once for the cursor and prefetching
+const MAX_PREVIEW_LOADS: usize = 4;
+
+/// Shown while the news is fetched before a system update
+const CHECKING_NEWS: &str = "Checking Arch Linux news before updating...";
+
+/// Fuzzy matches of the last search, narrowed further while the query only grows
+/// (a longer query can't match an item the shorter one didn't)
+struct MatchCache {
+    query: String,
+    repo_filter: RepoFilter,
+    search_descriptions: bool,
+    selected_only: bool,
+    indices: Vec<usize>, // Matching items, in list order
+}
+
+/// The search query, prepared once per filter for the search mode
+enum Query {
+    Fuzzy(String),
+    Exact(String), // Lowercased
+    Regex(Regex),
+}
+
+/// Search and scroll position to go back to when leaving the selected-only view
+struct SavedView {
+    query: String,
+    selected: Option<usize>,
+    offset: usize,
+}
+
+/// What a key asks of the loop running the app, once `handle_key` has done what the app can do itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppAction {
+    None,
+    /// Enter: confirm the selected items (the loop fills in the confirm dialog)
+    Confirm(Vec<String>),
+    /// The confirm dialog was confirmed
+    Confirmed,
+    /// `r` in the confirm dialog switched whether already installed packages are reinstalled
+    Replan,
+    /// Esc with no search or selected-only view left to clear
+    Cancel,
+    /// Ctrl+U: run a system update
+    StartUpdate,
+    /// Alt+1-6
+    SwitchTab(ViewType),
+    /// Ctrl+R: load the list again
+    Refresh,
+    /// `a`: queue the selection
+    Queue,
+    /// `y`: copy the selected names
+    Copy,
+    /// Settings to remember, changed by the key
+    RepoFilterChanged(RepoFilter),
+    SortChanged(SortMode),
+    PreviewResized(u16),
+    PreviewToggled(bool),
+}
 
 pub struct App {
     pub items: Vec<String>,
-    pub filtered_items: Vec<(String, i64)>, // (item, score)
+    pub filtered_items: Vec<(usize, i64)>, // (index into items, score)
     pub list_state: ListState,
     pub search_query: String,
-    pub selected_indices: Vec<usize>, // For multi-select
+    pub selected: HashSet<String>, // Selected items (multi-select), kept while the search changes
+    pub selected_only: bool, // Only the selected items are listed (the search narrows them further)
+    pub locked: HashSet<String>, // Items that can't be selected (upgrades held back by a pin or IgnorePkg)
+    saved_view: Option<SavedView>, // Search and position from before the selected-only view
     pub multi: bool,
-    pub preview_cmd: Option<String>,
+    pub preview_source: Option<PreviewSource>,
     pub preview_content: String,
     pub preview_cache: HashMap<String, String>, // Cache for loaded previews
+    preview_pending: HashMap<String, Arc<AtomicBool>>, // Items whose preview command is running (cursor item or prefetch), with their cancel flags
+    preview_shutdown: Arc<AtomicBool>, // Set when the app is dropped, killing every running preview command
+    pub preview_timeout: Duration, // Preview commands running longer are killed
     pub preview_tx: Option<Sender<(String, String)>>, // Send preview requests
     pub preview_rx: Option<Receiver<(String, String)>>, // Receive preview results
     pub layout: PreviewLayout,
+    pub preview_ratio: u16, // Percent of the view taken by the preview pane (in either layout)
+    pub preview_enabled: bool, // The preview pane is shown (Alt+P); while hidden no previews are loaded
     pub matcher: SkimMatcherV2,
     pub current_preview_item: Option<String>, // Track current item being previewed
     pub update_window: SystemUpdateWindow,
@@ -28,14 +127,53 @@ pub struct App {
     pub confirm_dialog: ConfirmDialog, // Confirmation dialog for install/remove
     pub action_type: ActionType, // Type of action (install/remove)
     pub alert: Alert, // Alert/notification system
+    pub provides_matches: Vec<String>, // Items shown because they provide the searched command
+    pub provides_cache: HashMap<String, Vec<String>>, // Command lookups by query
+    pub provides_pending: Option<String>, // Query currently being looked up
+    pub provides_tx: Sender<(String, Vec<String>)>, // Send command lookup results
+    pub provides_rx: Receiver<(String, Vec<String>)>, // Receive command lookup results
+    pub data_created_at: Option<SystemTime>, // When the listed data was read from pacman (if cached)
+    pub stale_after: Duration, // Age after which the data is shown as stale
+    pub pinned: HashSet<String>, // Names of pinned packages (shown with a pin icon)
+    pub installed: HashSet<String>, // Names of installed packages (tagged in the Install view)
+    pub aur_items: HashSet<String>, // Items without an `aur/` prefix that are AUR packages (their previews add AUR details)
+    pub selection_preview: bool, // Preview pane shows a summary of all selected items
+    pub selection_scroll: u16, // Vertical scroll position for the selection summary
+    pub selection_pending: HashSet<String>, // Selected items whose previews are loading
+    pub links: Vec<String>, // Links in the preview, numbered from 1 while hints are shown
+    pub link_hints_active: bool, // Link numbers are overlaid on the preview
+    pub link_hint_input: String, // Digits typed so far in link hint mode
+    pub required_by: HashMap<String, Vec<String>>, // Installed packages depending on each previewed item (Remove view)
+    pub news_rx: Option<Receiver<Result<Vec<NewsItem>, String>>>, // Arch news checked before a system update
+    pub update_held: Vec<String>, // Packages held back from the pending system update
+    pub filter_label: Option<String>, // Filter the items were listed with, shown in the list title
+    pub remove_options: RemoveOptions, // pacman -R modifiers used when removing the selection
+    pub install_options: InstallOptions, // -S flags used when installing the selection
+    pub repo_filter: Option<RepoFilter>, // Repository the list is narrowed to (None: items have no repository)
+    pub repositories: Vec<String>, // Repositories Ctrl+F cycles through, in pacman.conf order
+    pub package_details: HashMap<String, Package>, // Version and description of each item, shown in the list
+    pub search_descriptions: bool, // Fuzzy search matches descriptions as well as names
+    pub search_mode: SearchMode, // How the query matches items
+    pub search_focused: bool, // Keys edit the search (after '/') instead of driving the list
+    search_cursor: usize, // Editing position in the search, in characters
+    pub search_error: Option<String>, // Why the query can't be used (invalid regex), shown on the search bar
+    pub sort_mode: SortMode, // Order of the filtered list
+    pub installed_details: HashMap<String, InstalledDetails>, // Size and install date of installed items (for sorting)
+    pub loading: LoadingState, // Items are being loaded in the background
+    pub debug_timing: bool, // Measure filtering (PMGR_DEBUG_TIMING), shown in the list title
+    pub filter_time: Option<Duration>, // How long the last filter_items() took
+    match_cache: Option<MatchCache>, // Matches of the last query, reused while typing
+    last_filtered: Option<Instant>, // When filter_items() last ran
+    pub list_area: Rect, // Where the list was last drawn (for mouse clicks)
+    pub preview_area: Rect, // Where the preview was last drawn (empty without a preview)
+    pub preview_scroll: u16, // Vertical scroll position for the preview of the current item
+    filter_due: Option<Instant>, // Search changed; filter_items() runs at this time
+    pub cursor_target: Option<(String, usize)>, // Item (and its row) to highlight again once the list is refiltered
 }
 
 impl App {
-    pub fn new(items: Vec<String>, multi: bool, preview_cmd: Option<String>, action_type: ActionType) -> Self {
-        let filtered_items: Vec<(String, i64)> = items
-            .iter()
-            .map(|item| (item.clone(), 0))
-            .collect();
+    pub fn new(items: Vec<String>, multi: bool, preview_source: Option<PreviewSource>, action_type: ActionType) -> Self {
+        let filtered_items: Vec<(usize, i64)> = (0..items.len()).map(|index| (index, 0)).collect();
 
         let mut list_state = ListState::default();
         if !filtered_items.is_empty() {
@@ -43,26 +181,36 @@ impl App {
         }
 
         // Create channels for async preview loading
-        let (preview_tx, preview_rx) = if preview_cmd.is_some() {
+        let (preview_tx, preview_rx) = if preview_source.is_some() {
             let (tx, rx) = mpsc::channel();
             (Some(tx), Some(rx))
         } else {
             (None, None)
         };
 
-        let mut app = Self {
+        let (provides_tx, provides_rx) = mpsc::channel();
+
+        Self {
             items,
             filtered_items,
             list_state,
             search_query: String::new(),
-            selected_indices: Vec::new(),
+            selected: HashSet::new(),
+            selected_only: false,
+            locked: HashSet::new(),
+            saved_view: None,
             multi,
-            preview_cmd,
+            preview_source,
             preview_content: String::new(),
             preview_cache: HashMap::new(),
+            preview_pending: HashMap::new(),
+            preview_shutdown: Arc::new(AtomicBool::new(false)),
+            preview_timeout: Duration::from_secs(5),
             preview_tx,
             preview_rx,
             layout: PreviewLayout::Vertical,
+            preview_ratio: 50,
+            preview_enabled: true,
             matcher: SkimMatcherV2::default(),
             current_preview_item: None,
             update_window: SystemUpdateWindow::new(),
@@ -71,37 +219,127 @@ impl App {
             confirm_dialog: ConfirmDialog::new(),
             action_type,
             alert: Alert::new(),
-        };
-
-        app.request_preview();
-        app
+            provides_matches: Vec::new(),
+            provides_cache: HashMap::new(),
+            provides_pending: None,
+            provides_tx,
+            provides_rx,
+            data_created_at: None,
+            stale_after: Duration::MAX,
+            pinned: HashSet::new(),
+            installed: HashSet::new(),
+            aur_items: HashSet::new(),
+            selection_preview: false,
+            selection_scroll: 0,
+            selection_pending: HashSet::new(),
+            links: Vec::new(),
+            link_hints_active: false,
+            link_hint_input: String::new(),
+            required_by: HashMap::new(),
+            news_rx: None,
+            update_held: Vec::new(),
+            filter_label: None,
+            remove_options: RemoveOptions::default(),
+            install_options: InstallOptions::default(),
+            repo_filter: None,
+            repositories: Vec::new(),
+            package_details: HashMap::new(),
+            search_descriptions: false,
+            search_mode: SearchMode::Fuzzy,
+            search_focused: false,
+            search_cursor: 0,
+            search_error: None,
+            sort_mode: SortMode::Score,
+            installed_details: HashMap::new(),
+            loading: LoadingState::new(),
+            debug_timing: output::debug_timing(),
+            filter_time: None,
+            match_cache: None,
+            last_filtered: None,
+            list_area: Rect::default(),
+            preview_area: Rect::default(),
+            preview_scroll: 0,
+            filter_due: None,
+            cursor_target: None,
+        }
     }
 
     pub fn filter_items(&mut self) {
+        let started = Instant::now();
+        let repo_filter = self.repo_filter.clone().unwrap_or_default();
+        // The selected-only view ignores the repository filter
+        let (items, selected, selected_only) = (&self.items, &self.selected, self.selected_only);
+        let listed = |item: &String| if selected_only { selected.contains(item) } else { repo_filter.matches(item) };
+
         if self.search_query.is_empty() {
-            self.filtered_items = self
-                .items
+            self.match_cache = None;
+            self.search_error = None;
+            self.filtered_items = items
                 .iter()
-                .map(|item| (item.clone(), 0))
+                .enumerate()
+                .filter(|(_, item)| listed(item))
+                .map(|(index, _)| (index, 0))
                 .collect();
         } else {
-            let mut scored_items: Vec<(String, i64)> = self
-                .items
-                .iter()
-                .filter_map(|item| {
-                    self.matcher
-                        .fuzzy_match(item, &self.search_query)
-                        .map(|score| (item.clone(), score))
-                })
-                .collect();
+            let query = match self.query() {
+                Ok(query) => query,
+                Err(e) => {
+                    // Keep the last matches on screen until the query is fixed
+                    self.search_error = Some(e);
+                    self.match_cache = None;
+                    self.last_filtered = Some(started);
+                    self.filter_due = None;
+                    return;
+                }
+            };
+            self.search_error = None;
+            let fuzzy = matches!(query, Query::Fuzzy(_));
 
-            scored_items.sort_by(|a, b| b.1.cmp(&a.1));
+            // While a fuzzy query only grows, only the previous matches can still match
+            let candidates: Vec<usize> = match self.match_cache.take().filter(|_| fuzzy) {
+                Some(cache)
+                    if self.search_query.starts_with(&cache.query)
+                        && cache.repo_filter == repo_filter
+                        && cache.search_descriptions == self.search_descriptions
+                        && cache.selected_only == selected_only =>
+                {
+                    cache.indices
+                }
+                _ => (0..items.len()).filter(|&index| listed(&items[index])).collect(),
+            };
+
+            let parallel = candidates.len() > PARALLEL_FILTER_THRESHOLD;
+            let mut scored_items = self.score_candidates(&query, candidates, parallel);
+
+            self.match_cache = fuzzy.then(|| MatchCache {
+                query: self.search_query.clone(),
+                repo_filter,
+                search_descriptions: self.search_descriptions,
+                selected_only: self.selected_only,
+                indices: scored_items.iter().map(|(index, _)| *index).collect(),
+            });
+            scored_items.sort_by_key(|item| std::cmp::Reverse(item.1));
             self.filtered_items = scored_items;
         }
+        self.sort_filtered();
+        if self.debug_timing {
+            self.filter_time = Some(started.elapsed());
+        }
+        self.last_filtered = Some(started);
+        self.filter_due = None;
 
-        // Reset selection to first item
+        self.provides_matches.clear();
+        if !self.search_query.is_empty() && self.filtered_items.len() < PROVIDES_LOOKUP_THRESHOLD {
+            self.request_provides_lookup();
+        }
+
+        // Reset selection to first item (or to the item highlighted before a refresh)
         if !self.filtered_items.is_empty() {
-            self.list_state.select(Some(0));
+            let position = match self.cursor_target.take() {
+                Some((item, position)) => self.position_or_nearest(&item, position),
+                None => 0,
+            };
+            self.list_state.select(Some(position));
         } else {
             self.list_state.select(None);
         }
@@ -109,7 +347,178 @@ impl App {
         self.request_preview();
     }
 
+    /// Reorder the filtered items by the sort mode; the sorts are stable, so ties keep their score order
+    fn sort_filtered(&mut self) {
+        let items = &self.items;
+        let name = |index: usize| {
+            let item = &items[index];
+            item.split_once('/').map_or(item.as_str(), |(_, name)| name)
+        };
+        let details = |index: usize| self.installed_details.get(name(index));
+
+        match self.sort_mode {
+            SortMode::Score => {}
+            SortMode::Name => self.filtered_items.sort_by(|a, b| name(a.0).cmp(name(b.0))),
+            SortMode::Repository => self
+                .filtered_items
+                .sort_by_key(|&(index, _)| items[index].split_once('/').map(|(repository, _)| repository)),
+            SortMode::Size => self
+                .filtered_items
+                .sort_by_key(|&(index, _)| std::cmp::Reverse(details(index).map_or(0, |d| d.installed_size))),
+            SortMode::Date => self
+                .filtered_items
+                .sort_by_key(|&(index, _)| std::cmp::Reverse(details(index).and_then(|d| d.install_date))),
+        }
+    }
+
+    /// Use the next sort mode this list has data for
+    pub fn cycle_sort_mode(&mut self) -> SortMode {
+        self.flush_filter();
+        self.sort_mode = self.sort_mode.next(self.repo_filter.is_some(), !self.installed_details.is_empty());
+        self.filter_items();
+        self.sort_mode
+    }
+
+    /// Keep the size and install date of installed items, re-sorting if the list is sorted by them
+    pub fn set_installed_details(&mut self, details: &[InstalledDetails]) {
+        self.installed_details = details.iter().map(|d| (d.name.clone(), d.clone())).collect();
+        if matches!(self.sort_mode, SortMode::Size | SortMode::Date) {
+            self.cursor_target = self.cursor();
+            self.filter_items();
+        }
+    }
+
+    /// The search query for the search mode; a leading `'` forces an exact search (as in fzf)
+    fn query(&self) -> Result<Query, String> {
+        if let Some(exact) = self.search_query.strip_prefix('\'') {
+            return Ok(Query::Exact(exact.to_lowercase()));
+        }
+
+        match self.search_mode {
+            SearchMode::Fuzzy => Ok(Query::Fuzzy(self.search_query.clone())),
+            SearchMode::Exact => Ok(Query::Exact(self.search_query.to_lowercase())),
+            SearchMode::Regex => RegexBuilder::new(&self.search_query)
+                .case_insensitive(true)
+                .build()
+                .map(Query::Regex)
+                // Syntax errors span several lines; the last one says what is wrong
+                .map_err(|e| e.to_string().lines().last().unwrap_or_default().trim().to_string()),
+        }
+    }
+
+    /// Use the next search mode (fuzzy → exact → regex)
+    pub fn cycle_search_mode(&mut self) -> SearchMode {
+        self.flush_filter();
+        self.search_mode = self.search_mode.next();
+        self.filter_items();
+        self.search_mode
+    }
+
+    /// Score the candidate items against the search, keeping the matches in list order
+    /// Exact and regex matches all score 0, so they stay in list order
+    /// In parallel each thread builds its own matcher
+    fn score_candidates(&self, query: &Query, candidates: Vec<usize>, parallel: bool) -> Vec<(usize, i64)> {
+        // Only the fields scoring reads are shared with the threads (App itself isn't Sync)
+        let (items, details) = (&self.items, &self.package_details);
+        let search_descriptions = self.search_descriptions;
+        let score = |matcher: &SkimMatcherV2, index: usize| {
+            let item = &items[index];
+            let description = details.get(item).filter(|_| search_descriptions).map(|pkg| pkg.description.as_str());
+            let text = match description {
+                Some(description) => std::borrow::Cow::Owned(format!("{} {}", item, description)),
+                None => std::borrow::Cow::Borrowed(item.as_str()),
+            };
+            let score = match query {
+                Query::Fuzzy(query) => fuzzy_score(matcher, &text, query),
+                Query::Exact(query) => exact_match(item, &text, query).then_some(0),
+                Query::Regex(regex) => (regex.is_match(package_name(item)) || regex.is_match(&text)).then_some(0),
+            };
+            score.map(|score| (index, score))
+        };
+
+        if parallel {
+            candidates
+                .into_par_iter()
+                .map_init(SkimMatcherV2::default, |matcher, index| score(matcher, index))
+                .flatten()
+                .collect()
+        } else {
+            candidates.into_iter().filter_map(|index| score(&self.matcher, index)).collect()
+        }
+    }
+
+    /// The search query was edited: filter now, or once FILTER_DEBOUNCE has passed since the last
+    /// filter so fast typing doesn't filter the whole list on every key
+    pub fn search_changed(&mut self) {
+        match self.last_filtered {
+            // Clearing the search always restores the full list right away
+            Some(last) if !self.search_query.is_empty() && last.elapsed() < FILTER_DEBOUNCE => {
+                self.filter_due = Some(last + FILTER_DEBOUNCE);
+            }
+            _ => self.filter_items(),
+        }
+    }
+
+    /// Run a deferred filter once it is due
+    pub fn apply_pending_filter(&mut self) {
+        if self.filter_due.is_some_and(|due| Instant::now() >= due) {
+            self.filter_items();
+        }
+    }
+
+    /// Run a deferred filter now (before acting on the filtered list)
+    pub fn flush_filter(&mut self) {
+        if self.filter_due.is_some() {
+            self.filter_items();
+        }
+    }
+
+    /// How long the event loop may wait for input without delaying a deferred filter
+    pub fn poll_timeout(&self, idle: Duration) -> Duration {
+        self.filter_due
+            .map(|due| due.saturating_duration_since(Instant::now()).min(idle))
+            .unwrap_or(idle)
+    }
+
+    /// Replace the items (e.g. once loaded in the background), keeping the search typed so far
+    /// and the selected items that are still listed
+    pub fn set_items(&mut self, items: Vec<String>) {
+        self.items = items;
+        self.match_cache = None;
+        let listed: HashSet<&String> = self.items.iter().collect();
+        self.selected.retain(|item| listed.contains(item));
+        self.filter_items();
+    }
+
+    /// List packages as `repository/name` items, keeping their versions and descriptions for display
+    pub fn set_package_details(&mut self, packages: &[Package]) {
+        self.package_details = packages
+            .iter()
+            .map(|pkg| (format!("{}/{}", pkg.repository, pkg.name), pkg.clone()))
+            .collect();
+        self.match_cache = None;
+    }
+
+    /// Match the search against descriptions too (or names only again)
+    pub fn toggle_search_descriptions(&mut self) {
+        if self.package_details.is_empty() {
+            return;
+        }
+
+        self.search_descriptions = !self.search_descriptions;
+        self.filter_items();
+    }
+
+    /// Show the next repository (All, then each of `repositories`); None if the list has no repositories
+    pub fn cycle_repo_filter(&mut self) -> Option<RepoFilter> {
+        let filter = self.repo_filter.as_ref()?.next(&self.repositories);
+        self.repo_filter = Some(filter.clone());
+        self.filter_items();
+        Some(filter)
+    }
+
     pub fn next(&mut self) {
+        self.flush_filter();
         if self.filtered_items.is_empty() {
             return;
         }
@@ -128,7 +537,341 @@ impl App {
         self.request_preview();
     }
 
+    /// The highlighted item and its row in the filtered list
+    pub fn cursor(&self) -> Option<(String, usize)> {
+        let position = self.list_state.selected()?;
+        self.filtered_item(position).map(|item| (item.clone(), position))
+    }
+
+    /// Row of an item in the filtered list, or the row closest to where it was if it is no longer shown
+    fn position_or_nearest(&self, item: &str, position: usize) -> usize {
+        self.filtered_items
+            .iter()
+            .position(|&(index, _)| self.items[index] == item)
+            .unwrap_or_else(|| position.min(self.filtered_items.len().saturating_sub(1)))
+    }
+
+    /// Jump to the first shown item
+    pub fn select_first(&mut self) {
+        self.flush_filter();
+        if !self.filtered_items.is_empty() {
+            self.list_state.select(Some(0));
+            self.request_preview();
+        }
+    }
+
+    /// Jump to the last shown item
+    pub fn select_last(&mut self) {
+        self.flush_filter();
+        if !self.filtered_items.is_empty() {
+            self.list_state.select(Some(self.filtered_items.len() - 1));
+            self.request_preview();
+        }
+    }
+
+    /// Give the search bar focus, editing at the end of the query
+    pub fn focus_search(&mut self) {
+        self.search_focused = true;
+        self.search_cursor = self.search_query.chars().count();
+    }
+
+    /// Editing position in the search, in characters (the query may have been changed elsewhere)
+    pub fn search_cursor(&self) -> usize {
+        self.search_cursor.min(self.search_query.chars().count())
+    }
+
+    /// Byte offset of a character position in the search
+    fn search_byte(&self, position: usize) -> usize {
+        self.search_query
+            .char_indices()
+            .nth(position)
+            .map_or(self.search_query.len(), |(index, _)| index)
+    }
+
+    /// Handle a key in a package list, the same in the main menu and the selector: the windows drawn
+    /// over the list take it first, then the search bar when focused, then the list's shortcuts
+    pub fn handle_key(&mut self, key: KeyEvent) -> AppAction {
+        if let Some(action) = self.handle_overlay_key(key) {
+            return action;
+        }
+
+        // Typing into the search bar (focused with '/')
+        if self.search_focused && self.handle_search_key(key) {
+            return AppAction::None;
+        }
+
+        match (key.code, key.modifiers) {
+            // Switch tabs (plain digits are typed into the search, e.g. "python311")
+            (KeyCode::Char(c @ '1'..='6'), KeyModifiers::ALT) => {
+                let view = [ViewType::Home, ViewType::Install, ViewType::Remove, ViewType::List, ViewType::Updates, ViewType::Settings];
+                return AppAction::SwitchTab(view[c as usize - '1' as usize]);
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE) if c.is_ascii_digit() => {
+                self.focus_search();
+                self.handle_search_key(key);
+            }
+            (KeyCode::Char('?'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                self.help_visible = true;
+                self.help_scroll = 0;
+            }
+            // Esc leaves the selected-only view, then clears the search, then leaves the list
+            (KeyCode::Esc, _) => {
+                if self.selected_only && self.search_query.is_empty() {
+                    self.toggle_selected_only();
+                } else if self.search_query.is_empty() {
+                    return AppAction::Cancel;
+                } else {
+                    self.search_query.clear();
+                    self.search_changed();
+                }
+            }
+            (KeyCode::Enter, _) => {
+                self.flush_filter();
+                return AppAction::Confirm(self.get_selected_items());
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => return AppAction::StartUpdate,
+            (KeyCode::Char('r'), KeyModifiers::CONTROL) => return AppAction::Refresh,
+            (KeyCode::Char('f'), KeyModifiers::CONTROL) => {
+                if let Some(filter) = self.cycle_repo_filter() {
+                    return AppAction::RepoFilterChanged(filter);
+                }
+            }
+            (KeyCode::Char('n'), KeyModifiers::ALT) => return AppAction::SortChanged(self.cycle_sort_mode()),
+            // Preview pane size and visibility (no previews are loaded while hidden)
+            (KeyCode::Char('<' | '>'), KeyModifiers::NONE | KeyModifiers::SHIFT) | (KeyCode::Left | KeyCode::Right, KeyModifiers::ALT) => {
+                return AppAction::PreviewResized(self.resize_preview(matches!(key.code, KeyCode::Char('>') | KeyCode::Right)));
+            }
+            (KeyCode::Char('p'), KeyModifiers::ALT) => return AppAction::PreviewToggled(self.toggle_preview()),
+            (KeyCode::Char('a'), KeyModifiers::NONE) => return AppAction::Queue,
+            (KeyCode::Char('y'), KeyModifiers::NONE) => return AppAction::Copy,
+            // Navigation
+            (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => self.next(),
+            (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => self.previous(),
+            (KeyCode::Char('g'), KeyModifiers::NONE) => self.select_first(),
+            (KeyCode::Char('G'), KeyModifiers::NONE | KeyModifiers::SHIFT) => self.select_last(),
+            (KeyCode::PageDown, _) => self.scroll_selection_down(),
+            (KeyCode::PageUp, _) => self.scroll_selection_up(),
+            // Multi-select with Tab, and bulk selection of the filtered items
+            (KeyCode::Tab, _) => self.toggle_select(),
+            (KeyCode::Char('a'), KeyModifiers::CONTROL) => self.select_all_filtered(),
+            (KeyCode::Char('d'), KeyModifiers::CONTROL) => self.deselect_all(),
+            // Most terminals send Ctrl+I as Tab, so Alt+I works too
+            (KeyCode::Char('i'), KeyModifiers::CONTROL | KeyModifiers::ALT) => self.invert_selection(),
+            (KeyCode::Char('s'), KeyModifiers::CONTROL) => self.toggle_selected_only(),
+            // Search
+            (KeyCode::Char('/'), KeyModifiers::NONE) => self.focus_search(),
+            (KeyCode::Backspace, _) => {
+                self.search_query.pop();
+                self.search_changed();
+            }
+            (KeyCode::Char('d'), KeyModifiers::ALT) => self.toggle_search_descriptions(),
+            (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
+                self.cycle_search_mode();
+            }
+            // Layout and preview
+            (KeyCode::Char('o'), KeyModifiers::ALT) => self.layout.toggle_to_horizontal(),
+            (KeyCode::Char('v'), KeyModifiers::ALT) => self.layout.toggle_to_vertical(),
+            (KeyCode::Char('s'), KeyModifiers::ALT) => self.toggle_selection_preview(),
+            (KeyCode::Char('l'), KeyModifiers::NONE) => self.start_link_hints(),
+            // Open the package's upstream URL
+            (KeyCode::Char('o'), KeyModifiers::NONE | KeyModifiers::CONTROL) => self.open_package_url(),
+            // Load the preview again (e.g. after it timed out)
+            (KeyCode::Char('r'), KeyModifiers::NONE) => self.retry_preview(),
+            _ => {}
+        }
+        AppAction::None
+    }
+
+    /// Handle a key for the windows drawn over the list, topmost first: the operation window,
+    /// the confirm dialog, help, an alert and link hints; None when none of them is open
+    fn handle_overlay_key(&mut self, key: KeyEvent) -> Option<AppAction> {
+        if self.update_window.active {
+            // Any other key stops the auto-close countdown, so the output can be read
+            if !matches!((key.code, key.modifiers), (KeyCode::Enter, _) | (KeyCode::Char('x'), KeyModifiers::ALT)) {
+                self.update_window.keep_open();
+            }
+            match (key.code, key.modifiers) {
+                // Close once it's over (the alert then reports how it ended)
+                (KeyCode::Char('x'), KeyModifiers::ALT) | (KeyCode::Enter, _)
+                    if self.update_window.has_error || self.update_window.completed =>
+                {
+                    self.update_window.close(false);
+                }
+                // Stop waiting for another package manager to release the lock
+                (KeyCode::Esc, _) if self.update_window.is_waiting_for_lock() => {
+                    self.update_window.close(true); // Cancelled by user
+                }
+                // Interrupt the running command
+                (KeyCode::Char('c'), KeyModifiers::CONTROL) => {
+                    self.update_window.cancel();
+                }
+                // Scroll through the output (End follows new output again)
+                (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => self.update_window.scroll_up(1),
+                (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => self.update_window.scroll_down(1),
+                (KeyCode::PageUp, _) => self.update_window.scroll_up(self.update_window.page()),
+                (KeyCode::PageDown, _) => self.update_window.scroll_down(self.update_window.page()),
+                (KeyCode::Home, _) => self.update_window.scroll_to_top(),
+                (KeyCode::End, _) => self.update_window.follow(),
+                // Retry the failed step, or check the database lock again
+                (KeyCode::Char('r'), KeyModifiers::NONE) => {
+                    self.update_window.retry_failed();
+                }
+                _ => {} // Ignore other keys while update window is active
+            }
+            return Some(AppAction::None);
+        }
+
+        if self.confirm_dialog.active {
+            match (key.code, key.modifiers) {
+                // Risky removals take "yes" typed out, so letters go to the input
+                (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) if self.confirm_dialog.require_typed => {
+                    self.confirm_dialog.type_char(c);
+                }
+                (KeyCode::Backspace, _) if self.confirm_dialog.require_typed => {
+                    self.confirm_dialog.delete_char();
+                }
+                // Include or skip the packages that are already up to date (--needed)
+                (KeyCode::Char('r'), KeyModifiers::NONE) if !self.confirm_dialog.reinstalls.is_empty() => {
+                    self.install_options.needed = !self.install_options.needed;
+                    return Some(AppAction::Replan);
+                }
+                // Confirm with Y; Enter presses the focused button
+                (KeyCode::Char('y'), KeyModifiers::NONE | KeyModifiers::SHIFT) | (KeyCode::Enter, _) => {
+                    if key.code == KeyCode::Enter {
+                        self.confirm_dialog.activate();
+                    } else {
+                        self.confirm_dialog.confirm_or_ack();
+                    }
+                    if self.confirm_dialog.is_confirmed() {
+                        return Some(AppAction::Confirmed);
+                    }
+                }
+                // Move the focus between the buttons
+                (KeyCode::Left, _) => self.confirm_dialog.focus = ConfirmButton::Yes,
+                (KeyCode::Right, _) => self.confirm_dialog.focus = ConfirmButton::No,
+                (KeyCode::Tab | KeyCode::BackTab, _) => self.confirm_dialog.toggle_focus(),
+                // Cancel with N or ESC
+                (KeyCode::Char('n'), KeyModifiers::NONE | KeyModifiers::SHIFT) | (KeyCode::Esc, _) => {
+                    self.confirm_dialog.cancel();
+                }
+                (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => self.confirm_dialog.scroll_down(),
+                (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => self.confirm_dialog.scroll_up(),
+                _ => {} // Ignore other keys while dialog is active
+            }
+            return Some(AppAction::None);
+        }
+
+        if self.help_visible {
+            match (key.code, key.modifiers) {
+                (KeyCode::Char('?'), KeyModifiers::NONE | KeyModifiers::SHIFT) | (KeyCode::Esc, _) => {
+                    self.help_visible = false;
+                    self.help_scroll = 0; // Reset scroll when closing
+                }
+                (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
+                    self.help_scroll = self.help_scroll.saturating_add(1);
+                }
+                (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
+                    self.help_scroll = self.help_scroll.saturating_sub(1);
+                }
+                _ => {} // Ignore other keys while help is visible
+            }
+            return Some(AppAction::None);
+        }
+
+        // Any key closes an alert
+        if self.alert.active {
+            self.alert.close();
+            return Some(AppAction::None);
+        }
+
+        if self.link_hints_active {
+            match (key.code, key.modifiers) {
+                (KeyCode::Char(c), KeyModifiers::NONE) if c.is_ascii_digit() => self.link_hint_digit(c),
+                (KeyCode::Enter, _) => self.open_hinted_link(),
+                (KeyCode::Backspace, _) => {
+                    self.link_hint_input.pop();
+                }
+                (KeyCode::Esc, _) => self.cancel_link_hints(),
+                _ => {} // Ignore other keys while link hints are shown
+            }
+            return Some(AppAction::None);
+        }
+
+        None
+    }
+
+    /// Handle a key while the search bar has focus: typing edits the query at the cursor,
+    /// Enter or Esc give focus back to the list (keeping the filter)
+    /// Ctrl+A, Ctrl+E and Ctrl+U edit the line here instead of running their list shortcuts
+    /// Returns false for keys that aren't for the search bar (Up/Down, Tab, other shortcuts)
+    pub fn handle_search_key(&mut self, key: KeyEvent) -> bool {
+        let cursor = self.search_cursor();
+        let edited = match (key.code, key.modifiers) {
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                let at = self.search_byte(cursor);
+                self.search_query.insert(at, c);
+                self.search_cursor = cursor + 1;
+                true
+            }
+            (KeyCode::Backspace, _) if cursor > 0 => {
+                let at = self.search_byte(cursor - 1);
+                self.search_query.remove(at);
+                self.search_cursor = cursor - 1;
+                true
+            }
+            (KeyCode::Delete, _) if cursor < self.search_query.chars().count() => {
+                let at = self.search_byte(cursor);
+                self.search_query.remove(at);
+                true
+            }
+            // Delete the word before the cursor (and the spaces after it)
+            (KeyCode::Char('w'), KeyModifiers::CONTROL) => {
+                let before: Vec<char> = self.search_query.chars().take(cursor).collect();
+                let spaces = before.iter().rev().take_while(|c| c.is_whitespace()).count();
+                let word = before.iter().rev().skip(spaces).take_while(|c| !c.is_whitespace()).count();
+                let start = cursor - spaces - word;
+                let range = self.search_byte(start)..self.search_byte(cursor);
+                self.search_query.replace_range(range, "");
+                self.search_cursor = start;
+                true
+            }
+            (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                self.search_query.clear();
+                self.search_cursor = 0;
+                true
+            }
+            (KeyCode::Left, _) => {
+                self.search_cursor = cursor.saturating_sub(1);
+                false
+            }
+            (KeyCode::Right, _) => {
+                self.search_cursor = (cursor + 1).min(self.search_query.chars().count());
+                false
+            }
+            (KeyCode::Home, _) | (KeyCode::Char('a'), KeyModifiers::CONTROL) => {
+                self.search_cursor = 0;
+                false
+            }
+            (KeyCode::End, _) | (KeyCode::Char('e'), KeyModifiers::CONTROL) => {
+                self.search_cursor = self.search_query.chars().count();
+                false
+            }
+            (KeyCode::Backspace | KeyCode::Delete, _) => false,
+            (KeyCode::Enter | KeyCode::Esc, _) => {
+                self.flush_filter();
+                self.search_focused = false;
+                false
+            }
+            _ => return false,
+        };
+        if edited {
+            self.search_changed();
+        }
+        true
+    }
+
     pub fn previous(&mut self) {
+        self.flush_filter();
         if self.filtered_items.is_empty() {
             return;
         }
@@ -148,92 +891,1438 @@ impl App {
     }
 
     pub fn toggle_select(&mut self) {
+        self.flush_filter();
         if !self.multi {
             return;
         }
 
-        if let Some(selected) = self.list_state.selected() {
-            if self.selected_indices.contains(&selected) {
-                self.selected_indices.retain(|&i| i != selected);
-            } else {
-                self.selected_indices.push(selected);
+        let position = self.list_state.selected();
+        if let Some((position, item)) = position.and_then(|p| Some((p, self.filtered_item(p)?.clone()))) {
+            if !self.selected.remove(&item) && !self.locked.contains(&item) {
+                self.selected.insert(item);
+                self.explain_required_by(position);
             }
+            self.selection_changed();
             self.next(); // Move to next item after toggling
         }
     }
 
+    /// Select every item the search currently shows
+    pub fn select_all_filtered(&mut self) {
+        self.flush_filter();
+        if !self.multi {
+            return;
+        }
+
+        for (index, _) in &self.filtered_items {
+            let item = &self.items[*index];
+            if !self.locked.contains(item) {
+                self.selected.insert(item.clone());
+            }
+        }
+        self.selection_changed();
+    }
+
+    /// Deselect every item the search currently shows (selections hidden by the search stay)
+    pub fn deselect_all(&mut self) {
+        self.flush_filter();
+        for (index, _) in &self.filtered_items {
+            self.selected.remove(&self.items[*index]);
+        }
+        self.selection_changed();
+    }
+
+    /// Deselect everything, including selections hidden by the search (e.g. once they are queued)
+    pub fn clear_selection(&mut self) {
+        self.selected.clear();
+        self.selection_changed();
+    }
+
+    /// Select the shown items that aren't selected and deselect the ones that are
+    pub fn invert_selection(&mut self) {
+        self.flush_filter();
+        if !self.multi {
+            return;
+        }
+
+        for (index, _) in &self.filtered_items {
+            let item = &self.items[*index];
+            if !self.selected.remove(item) && !self.locked.contains(item) {
+                self.selected.insert(item.clone());
+            }
+        }
+        self.selection_changed();
+    }
+
+    /// List only the selected items (Tab still unselects them), or go back to the search and
+    /// position from before
+    pub fn toggle_selected_only(&mut self) {
+        self.flush_filter();
+        if !self.multi {
+            return;
+        }
+
+        if let Some(saved) = self.saved_view.take() {
+            self.selected_only = false;
+            self.search_query = saved.query;
+            self.filter_items();
+            let last = self.filtered_items.len().checked_sub(1);
+            if let (Some(selected), Some(last)) = (saved.selected, last) {
+                self.list_state.select(Some(selected.min(last)));
+                *self.list_state.offset_mut() = saved.offset.min(last);
+                self.request_preview();
+            }
+        } else {
+            self.saved_view = Some(SavedView {
+                query: std::mem::take(&mut self.search_query),
+                selected: self.list_state.selected(),
+                offset: self.list_state.offset(),
+            });
+            self.selected_only = true;
+            self.filter_items();
+        }
+    }
+
+    fn selection_changed(&mut self) {
+        // The cached matches of the selected-only view are a snapshot of the selection
+        self.match_cache = None;
+        if self.selection_preview {
+            self.request_selection_previews();
+        }
+    }
+
+    /// Tell the user how to remove a package other installed packages depend on
+    fn explain_required_by(&mut self, index: usize) {
+        let Some(item) = self.filtered_item(index) else {
+            return;
+        };
+        let Some(dependents) = self.required_by.get(item).filter(|d| !d.is_empty()) else {
+            return;
+        };
+
+        let message = format!(
+            "{} is required by {} package(s): {}. Remove them first, or remove everything that depends on it with `pacman -Rc {}`",
+            item,
+            dependents.len(),
+            dependents.join(", "),
+            item
+        );
+        self.alert.show(AlertType::Info, message);
+    }
+
+    /// Positions of the filtered list that fit in a list `height` rows tall
+    /// Scrolls (the list state's offset) only as far as needed to keep the selection visible
+    pub fn visible_range(&mut self, height: usize) -> Range<usize> {
+        let len = self.filtered_items.len();
+        let mut offset = self.list_state.offset().min(len.saturating_sub(1));
+        if let Some(selected) = self.list_state.selected() {
+            if selected < offset {
+                offset = selected;
+            } else if height > 0 && selected >= offset + height {
+                offset = selected + 1 - height;
+            }
+        }
+        *self.list_state.offset_mut() = offset;
+
+        offset..(offset + height).min(len)
+    }
+
+    /// Item at a position of the filtered list
+    pub fn filtered_item(&self, position: usize) -> Option<&String> {
+        self.filtered_items.get(position).and_then(|(index, _)| self.items.get(*index))
+    }
+
+    /// Selected items in list order, whether or not the search currently shows them
     pub fn get_selected_items(&self) -> Vec<String> {
         if self.multi {
-            self.selected_indices
-                .iter()
-                .filter_map(|&i| self.filtered_items.get(i).map(|(item, _)| item.clone()))
-                .collect()
+            if self.selected.is_empty() {
+                return Vec::new();
+            }
+            self.items.iter().filter(|item| self.selected.contains(*item)).cloned().collect()
         } else {
             self.list_state
                 .selected()
-                .and_then(|i| self.filtered_items.get(i).map(|(item, _)| vec![item.clone()]))
+                .and_then(|i| self.filtered_item(i).map(|item| vec![item.clone()]))
                 .unwrap_or_default()
         }
     }
 
+    /// Use another preview source, dropping previews made by the old one
+    pub fn set_preview_source(&mut self, source: PreviewSource) {
+        self.preview_source = Some(source);
+        self.preview_cache.clear();
+        for (_, cancel) in self.preview_pending.drain() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+        self.current_preview_item = None;
+        self.request_preview();
+    }
+
     pub fn request_preview(&mut self) {
-        if let Some(ref cmd) = self.preview_cmd {
-            if let Some(selected) = self.list_state.selected() {
-                if let Some((item, _)) = self.filtered_items.get(selected) {
-                    // Check if already in cache
-                    if let Some(cached) = self.preview_cache.get(item) {
-                        self.preview_content = cached.clone();
-                        self.current_preview_item = Some(item.clone());
-                        return;
-                    }
+        if !self.preview_enabled || self.preview_source.is_none() {
+            return;
+        }
+        let Some(item) = self.list_state.selected().and_then(|selected| self.filtered_item(selected)).cloned() else {
+            return;
+        };
 
-                    // Check if already loading this item
-                    if self.current_preview_item.as_ref() == Some(item) {
-                        return;
-                    }
+        if self.current_preview_item.as_ref() != Some(&item) {
+            self.preview_scroll = 0;
+            self.cancel_stale_previews();
+        }
 
-                    self.current_preview_item = Some(item.clone());
-                    self.preview_content = "Loading preview...".to_string();
-
-                    // Spawn thread to load preview
-                    if let Some(ref tx) = self.preview_tx {
-                        let item_clone = item.clone();
-                        let cmd_clone = cmd.clone();
-                        let tx_clone = tx.clone();
-
-                        thread::spawn(move || {
-                            let preview_cmd = cmd_clone.replace("{}", &item_clone);
-
-                            let content = if let Ok(output) = Command::new("sh")
-                                .arg("-c")
-                                .arg(&preview_cmd)
-                                .output()
-                            {
-                                String::from_utf8_lossy(&output.stdout).to_string()
-                            } else {
-                                "Failed to load preview".to_string()
-                            };
-
-                            let _ = tx_clone.send((item_clone, content));
-                        });
-                    }
+        // Check if already in cache
+        if let Some(cached) = self.preview_cache.get(&item) {
+            self.preview_content = cached.clone();
+            self.current_preview_item = Some(item);
+            self.prefetch_neighbors();
+            return;
+        }
+
+        // Check if already loading this item
+        if self.current_preview_item.as_ref() == Some(&item) {
+            return;
+        }
+
+        self.current_preview_item = Some(item.clone());
+        self.preview_content = "Loading preview...".to_string();
+
+        // A prefetch may already be loading it
+        if !self.preview_pending.contains_key(&item) {
+            self.spawn_preview(item);
+        }
+    }
+
+    /// Load the previews of the items around the cursor, so moving through the list shows them right away
+    /// At most MAX_PREVIEW_LOADS previews load at once
+    fn prefetch_neighbors(&mut self) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+
+        let nearby = (1..=PREFETCH_DISTANCE).flat_map(|distance| [selected.checked_add(distance), selected.checked_sub(distance)]);
+        for position in nearby.flatten() {
+            if self.preview_pending.len() >= MAX_PREVIEW_LOADS {
+                break;
+            }
+            if let Some(item) = self.filtered_item(position) {
+                if !self.preview_cache.contains_key(item) && !self.preview_pending.contains_key(item) && !self.selection_pending.contains(item) {
+                    self.spawn_preview(item.clone());
                 }
             }
         }
     }
 
-    pub fn check_preview_updates(&mut self) {
-        if let Some(ref rx) = self.preview_rx {
-            // Try to receive without blocking
-            while let Ok((item, content)) = rx.try_recv() {
-                // Cache the result
-                self.preview_cache.insert(item.clone(), content.clone());
+    /// Kill the preview commands of items that are no longer at or around the cursor
+    fn cancel_stale_previews(&mut self) {
+        let Some(selected) = self.list_state.selected() else {
+            return;
+        };
+
+        let start = selected.saturating_sub(PREFETCH_DISTANCE);
+        let nearby: HashSet<String> = (start..=selected + PREFETCH_DISTANCE).filter_map(|position| self.filtered_item(position)).cloned().collect();
+        self.preview_pending.retain(|item, cancel| {
+            let keep = nearby.contains(item);
+            if !keep {
+                cancel.store(true, Ordering::Relaxed);
+            }
+            keep
+        });
+    }
+
+    /// Run the preview command for an item on a background thread (the result arrives in check_preview_updates)
+    /// A cancelled command sends nothing
+    fn spawn_preview(&mut self, item: String) {
+        let (Some(source), Some(tx)) = (self.preview_source.clone(), self.preview_tx.clone()) else {
+            return;
+        };
+
+        let aur = item.starts_with("aur/") || self.aur_items.contains(&item);
+        let cancel = Arc::new(AtomicBool::new(false));
+        self.preview_pending.insert(item.clone(), cancel.clone());
+        let shutdown = self.preview_shutdown.clone();
+        let timeout = self.preview_timeout;
+        thread::spawn(move || {
+            let cancelled = || cancel.load(Ordering::Relaxed) || shutdown.load(Ordering::Relaxed);
+            let Some(mut content) = source.load_cancellable(&item, timeout, cancelled) else {
+                return;
+            };
+            if aur && content != preview::TIMED_OUT {
+                content = with_aur_info(content, &item);
+            }
+            let _ = tx.send((item, content));
+        });
+    }
+
+    /// Load the highlighted item's preview again (after it timed out or failed)
+    pub fn retry_preview(&mut self) {
+        if let Some(item) = self.current_preview_item.take() {
+            self.preview_cache.remove(&item);
+        }
+        self.request_preview();
+    }
+
+    /// Look up packages providing the searched command (results arrive asynchronously)
+    fn request_provides_lookup(&mut self) {
+        let query = self.search_query.clone();
+
+        if let Some(providers) = self.provides_cache.get(&query).cloned() {
+            self.append_providers(&providers);
+            return;
+        }
+
+        if self.provides_pending.as_ref() == Some(&query) {
+            return;
+        }
+        self.provides_pending = Some(query.clone());
+
+        let tx = self.provides_tx.clone();
+        thread::spawn(move || {
+            let providers = PackageManager::new()
+                .and_then(|pm| pm.find_command_providers(&query))
+                .map(|lookup| {
+                    lookup
+                        .providers
+                        .iter()
+                        .map(|p| format!("{}/{}", p.repository, p.name))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let _ = tx.send((query, providers));
+        });
+    }
+
+    /// Append providers that exist in this list to the filtered items
+    fn append_providers(&mut self, providers: &[String]) {
+        for provider in providers {
+            let name = provider.rsplit('/').next().unwrap_or(provider);
+            let index = self
+                .items
+                .iter()
+                .position(|item| item == provider || item.as_str() == name);
+
+            if let Some(index) = index {
+                if !self.filtered_items.iter().any(|(existing, _)| *existing == index) {
+                    self.filtered_items.push((index, 0));
+                    self.provides_matches.push(self.items[index].clone());
+                }
+            }
+        }
+
+        if self.list_state.selected().is_none() && !self.filtered_items.is_empty() {
+            self.list_state.select(Some(0));
+            self.request_preview();
+        }
+    }
+
+    pub fn check_provides_updates(&mut self) {
+        while let Ok((query, providers)) = self.provides_rx.try_recv() {
+            if self.provides_pending.as_ref() == Some(&query) {
+                self.provides_pending = None;
+            }
+
+            // Only apply results for the query that is still being shown
+            if query == self.search_query {
+                self.append_providers(&providers);
+            }
+            self.provides_cache.insert(query, providers);
+        }
+    }
+
+    pub fn check_preview_updates(&mut self) {
+        if let Some(ref rx) = self.preview_rx {
+            // Try to receive without blocking
+            let mut current_loaded = false;
+            while let Ok((item, content)) = rx.try_recv() {
+                self.selection_pending.remove(&item);
+                self.preview_pending.remove(&item);
+
+                if self.action_type == ActionType::Remove {
+                    self.required_by.insert(item.clone(), parse_reverse_deps(&content).required_by);
+                }
+
+                // Cache the result
+                self.preview_cache.insert(item.clone(), content.clone());
 
                 // Update display if this is still the current item
                 if self.current_preview_item.as_ref() == Some(&item) {
                     self.preview_content = content;
+                    current_loaded = true;
+                }
+            }
+
+            // With the current preview shown, the freed slots go to the neighbors
+            if current_loaded && self.preview_enabled {
+                self.prefetch_neighbors();
+            }
+        }
+    }
+
+    /// Whether the preview pane is drawn (there is a preview command and it isn't hidden)
+    pub fn shows_preview(&self) -> bool {
+        self.preview_enabled && self.preview_source.is_some()
+    }
+
+    /// Show or hide the preview pane; shown again, it catches up with the cursor and selection
+    pub fn set_preview_enabled(&mut self, enabled: bool) {
+        self.preview_enabled = enabled;
+        if enabled {
+            self.current_preview_item = None;
+            self.request_preview();
+            if self.selection_preview {
+                self.request_selection_previews();
+            }
+        } else {
+            self.cancel_link_hints();
+        }
+    }
+
+    /// Hide the preview pane or show it again (Alt+P), returning whether it is shown
+    pub fn toggle_preview(&mut self) -> bool {
+        self.set_preview_enabled(!self.preview_enabled);
+        self.preview_enabled
+    }
+
+    /// Use a preview pane size (e.g. from the settings), kept within PREVIEW_RATIO_MIN..=PREVIEW_RATIO_MAX
+    pub fn set_preview_ratio(&mut self, ratio: u16) {
+        self.preview_ratio = ratio.clamp(PREVIEW_RATIO_MIN, PREVIEW_RATIO_MAX);
+    }
+
+    /// Make the preview pane one step bigger or smaller, returning its new size
+    pub fn resize_preview(&mut self, grow: bool) -> u16 {
+        let ratio = if grow {
+            self.preview_ratio.saturating_add(PREVIEW_RATIO_STEP)
+        } else {
+            self.preview_ratio.saturating_sub(PREVIEW_RATIO_STEP)
+        };
+        self.set_preview_ratio(ratio);
+        self.preview_ratio
+    }
+
+    /// Switch the preview pane between the cursor item and a summary of the selection
+    pub fn toggle_selection_preview(&mut self) {
+        if !self.multi || !self.shows_preview() {
+            return;
+        }
+
+        self.selection_preview = !self.selection_preview;
+        self.selection_scroll = 0;
+        if self.selection_preview {
+            self.request_selection_previews();
+        }
+    }
+
+    /// Load previews for selected items that aren't cached yet (results arrive asynchronously)
+    /// One pacman run covers all of them; the rest (e.g. AUR packages) run the preview command
+    fn request_selection_previews(&mut self) {
+        if !self.preview_enabled {
+            return;
+        }
+        let (Some(source), Some(tx)) = (self.preview_source.clone(), self.preview_tx.clone()) else {
+            return;
+        };
+
+        let items: Vec<String> = self
+            .get_selected_items()
+            .into_iter()
+            .filter(|item| !self.preview_cache.contains_key(item) && self.selection_pending.insert(item.clone()))
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+
+        // Same database as the package info preview
+        let installed = matches!(source, PreviewSource::PackageInfo { installed: true, .. });
+        let aur: HashSet<String> = items
+            .iter()
+            .filter(|item| item.starts_with("aur/") || self.aur_items.contains(*item))
+            .cloned()
+            .collect();
+        let shutdown = self.preview_shutdown.clone();
+        let timeout = self.preview_timeout;
+        thread::spawn(move || {
+            let records = PackageManager::new()
+                .and_then(|pm| pm.get_info_records(&items, installed))
+                .unwrap_or_default();
+
+            for item in items {
+                if let Some(record) = records.get(item.rsplit('/').next().unwrap_or(&item)) {
+                    let _ = tx.send((item, record.clone()));
+                    continue;
+                }
+
+                let source = source.clone();
+                let tx = tx.clone();
+                let shutdown = shutdown.clone();
+                let aur = aur.contains(&item);
+                thread::spawn(move || {
+                    let Some(mut content) = source.load_cancellable(&item, timeout, || shutdown.load(Ordering::Relaxed)) else {
+                        return;
+                    };
+                    if aur && content != preview::TIMED_OUT {
+                        content = with_aur_info(content, &item);
+                    }
+                    let _ = tx.send((item, content));
+                });
+            }
+        });
+    }
+
+    pub fn scroll_selection_down(&mut self) {
+        self.selection_scroll = self.selection_scroll.saturating_add(5);
+    }
+
+    pub fn scroll_selection_up(&mut self) {
+        self.selection_scroll = self.selection_scroll.saturating_sub(5);
+    }
+
+    /// Wheel scrolling and clicks: the wheel scrolls whatever is on top (or the pane under the cursor),
+    /// a click selects a list row and a click on the selected row toggles it like Tab
+    pub fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let down = match mouse.kind {
+            MouseEventKind::ScrollDown => true,
+            MouseEventKind::ScrollUp => false,
+            MouseEventKind::Down(MouseButton::Left) => {
+                if !self.has_overlay() {
+                    self.click_list(mouse.row);
+                }
+                return;
+            }
+            _ => return,
+        };
+
+        if self.update_window.active {
+            if down {
+                self.update_window.scroll_down(3);
+            } else {
+                self.update_window.scroll_up(3);
+            }
+        } else if self.help_visible {
+            self.help_scroll = if down { self.help_scroll.saturating_add(3) } else { self.help_scroll.saturating_sub(3) };
+        } else if self.confirm_dialog.active {
+            if down {
+                self.confirm_dialog.scroll_down();
+            } else {
+                self.confirm_dialog.scroll_up();
+            }
+        } else if self.alert.active || self.link_hints_active {
+            // Nothing to scroll
+        } else if self.preview_area.contains(Position::new(mouse.column, mouse.row)) {
+            if self.selection_preview {
+                if down {
+                    self.scroll_selection_down();
+                } else {
+                    self.scroll_selection_up();
+                }
+            } else {
+                self.preview_scroll = if down { self.preview_scroll.saturating_add(3) } else { self.preview_scroll.saturating_sub(3) };
+            }
+        } else if down {
+            self.next();
+        } else {
+            self.previous();
+        }
+    }
+
+    /// Whether a window is drawn over the list
+    fn has_overlay(&self) -> bool {
+        self.update_window.active || self.help_visible || self.confirm_dialog.active || self.alert.active
+    }
+
+    /// Select the clicked list row; clicking the selected row (or double-clicking) toggles it
+    fn click_list(&mut self, row: u16) {
+        let area = self.list_area;
+        // Rows start inside the top border and end above the bottom one
+        if row <= area.y || row + 1 >= area.bottom() {
+            return;
+        }
+
+        self.flush_filter();
+        let position = self.list_state.offset() + usize::from(row - area.y - 1);
+        if position >= self.filtered_items.len() {
+            return;
+        }
+
+        if self.list_state.selected() == Some(position) {
+            self.toggle_select();
+        } else {
+            self.list_state.select(Some(position));
+            self.request_preview();
+        }
+    }
+
+    /// Work out the size of the install or removal being confirmed, in the background
+    pub fn resolve_transaction(&mut self) {
+        let packages = self.confirm_dialog.packages.clone();
+        match self.action_type {
+            ActionType::Install => {
+                self.confirm_dialog
+                    .resolve_transaction(move || PackageManager::new()?.preview_install(&packages));
+            }
+            ActionType::Remove => {
+                let options = self.remove_options;
+                self.confirm_dialog
+                    .resolve_transaction(move || PackageManager::new()?.preview_remove(&packages, &options));
+            }
+            ActionType::Update | ActionType::Upgrade => {}
+        }
+    }
+
+    /// Check Arch news in the background, then start the system update (or ask first if there is news)
+    pub fn request_system_update(&mut self, held: Vec<String>) {
+        if self.news_rx.is_some() {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        self.news_rx = Some(rx);
+        self.update_held = held;
+        self.alert.show(AlertType::Info, CHECKING_NEWS.to_string());
+
+        thread::spawn(move || {
+            let _ = tx.send(news::fetch_unread().map_err(|e| e.to_string()));
+        });
+    }
+
+    /// Handle the news check; network failures only warn and the update proceeds
+    pub fn check_news_updates(&mut self) {
+        let Some(result) = self.news_rx.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return;
+        };
+        self.news_rx = None;
+        self.alert.dismiss(CHECKING_NEWS);
+
+        match result {
+            Ok(items) if !items.is_empty() => {
+                let lines = items
+                    .iter()
+                    .map(|item| format!("{}  {}", item.date_string(), item.title))
+                    .collect();
+                self.confirm_dialog.show(ActionType::Update, lines);
+            }
+            Ok(_) => self.start_system_update(),
+            Err(e) => {
+                self.start_system_update();
+                self.update_window.output.push(format!("⚠ Could not check Arch Linux news: {}", e));
+            }
+        }
+    }
+
+    /// Start the system update requested with `request_system_update`
+    pub fn start_system_update(&mut self) {
+        let held = std::mem::take(&mut self.update_held);
+        self.update_window.start_update(&held);
+    }
+
+    /// Number the links in the preview so one can be opened by typing its number
+    pub fn start_link_hints(&mut self) {
+        if self.selection_preview || !self.shows_preview() {
+            return;
+        }
+
+        self.links = links::collect_links(&self.preview_content);
+        if self.links.is_empty() {
+            self.alert.show(AlertType::Info, "No links in the preview".to_string());
+            return;
+        }
+
+        self.link_hints_active = true;
+        self.link_hint_input.clear();
+    }
+
+    pub fn cancel_link_hints(&mut self) {
+        self.link_hints_active = false;
+        self.link_hint_input.clear();
+    }
+
+    /// Add a digit to the link number; opens the link as soon as no longer number can match
+    pub fn link_hint_digit(&mut self, digit: char) {
+        self.link_hint_input.push(digit);
+
+        let number: usize = self.link_hint_input.parse().unwrap_or(0);
+        if number * 10 > self.links.len() {
+            self.open_hinted_link();
+        }
+    }
+
+    /// Open the link whose number has been typed
+    pub fn open_hinted_link(&mut self) {
+        let number: usize = self.link_hint_input.parse().unwrap_or(0);
+        self.cancel_link_hints();
+
+        let Some(url) = number.checked_sub(1).and_then(|i| self.links.get(i)).cloned() else {
+            return;
+        };
+        self.open_url(&url);
+    }
+
+    /// Open the highlighted package's upstream URL (the AUR page for AUR packages without one)
+    /// Its info is loaded first if the preview hasn't been
+    pub fn open_package_url(&mut self) {
+        self.flush_filter();
+        let Some((item, _)) = self.cursor() else {
+            return;
+        };
+
+        self.check_preview_updates();
+        if !self.preview_cache.contains_key(&item) {
+            if let Some(ref source) = self.preview_source {
+                let content = source.load(&item, self.preview_timeout);
+                self.preview_cache.insert(item.clone(), content);
+            }
+        }
+
+        let preview = self.preview_cache.get(&item).map_or("", String::as_str);
+        let aur = item.starts_with("aur/") || self.aur_items.contains(&item);
+        match package_url(&item, preview, aur) {
+            Some(url) => self.open_url(&url),
+            None => self.alert.show(AlertType::Info, format!("No URL known for {}", package_name(&item))),
+        }
+    }
+
+    /// Text `y` copies: the selected packages (space-separated, in list order) or else the highlighted one,
+    /// with or without their repository; also returns how many packages it holds
+    pub fn yank_text(&self, with_repository: bool) -> Option<(String, usize)> {
+        let items = if self.selected.is_empty() {
+            vec![self.cursor()?.0]
+        } else {
+            self.get_selected_items()
+        };
+        let names: Vec<&str> = items
+            .iter()
+            .map(|item| {
+                let item = item.split_whitespace().next().unwrap_or(item);
+                if with_repository { item } else { package_name(item) }
+            })
+            .collect();
+        Some((names.join(" "), names.len()))
+    }
+
+    /// Copy the selected (or highlighted) package names to the clipboard
+    pub fn yank(&mut self, with_repository: bool) {
+        let Some((text, count)) = self.yank_text(with_repository) else {
+            return;
+        };
+
+        match links::copy_to_clipboard(&text) {
+            Ok(()) if count == 1 => self.alert.show(AlertType::Info, format!("Copied {}", text)),
+            Ok(()) => self.alert.show(AlertType::Info, format!("Copied {} package names", count)),
+            Err(e) => self.alert.show(AlertType::Error, format!("Failed to copy: {}", e)),
+        }
+    }
+
+    /// Open a URL in the browser, or copy it when there is no xdg-open
+    fn open_url(&mut self, url: &str) {
+        match links::open_link(url) {
+            Ok(LinkOutcome::Opened) => {}
+            Ok(LinkOutcome::Copied) => {
+                self.alert.show(AlertType::Success, format!("Copied to clipboard: {}", url));
+            }
+            Err(e) => self.alert.show(AlertType::Error, format!("{}: {}", e, url)),
+        }
+    }
+
+    /// Warning badges for selected AUR packages that are orphaned or stale
+    /// Reuses cached previews and loads missing ones; unreadable info yields no badges
+    pub fn aur_badges(&mut self, packages: &[String], stale_days: u64) -> HashMap<String, Vec<String>> {
+        self.check_preview_updates();
+
+        let today = date::today();
+        let mut badges = HashMap::new();
+
+        for pkg in packages.iter().filter(|p| p.starts_with("aur/")) {
+            if !self.preview_cache.contains_key(pkg) {
+                if let Some(ref source) = self.preview_source {
+                    let content = source.load(pkg, self.preview_timeout);
+                    self.preview_cache.insert(pkg.clone(), content);
+                }
+            }
+
+            let pkg_badges = self
+                .preview_cache
+                .get(pkg)
+                .and_then(|info| AurStatus::parse(info))
+                .map(|status| status.badges(stale_days, today))
+                .unwrap_or_default();
+
+            if !pkg_badges.is_empty() {
+                badges.insert(pkg.clone(), pkg_badges);
+            }
+        }
+
+        badges
+    }
+}
+
+impl Drop for App {
+    /// Kill the preview commands still running, so closing a tab or quitting leaves none behind
+    fn drop(&mut self) {
+        self.preview_shutdown.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Score an item against every whitespace-separated term of the query (all must match, in any order)
+fn fuzzy_score(matcher: &SkimMatcherV2, item: &str, query: &str) -> Option<i64> {
+    query
+        .split_whitespace()
+        .map(|term| {
+            // Scoring is expensive; most items don't even contain the term's characters
+            if !has_chars_in_order(item, term) {
+                return None;
+            }
+            matcher.fuzzy_match(item, term)
+        })
+        .sum()
+}
+
+/// Whether every whitespace-separated term of the (lowercased) query is in the text, ignoring case
+/// `^term` and `term$` anchor to the start and end of the item's package name instead
+fn exact_match(item: &str, text: &str, query: &str) -> bool {
+    let name = package_name(item).to_lowercase();
+    let text = text.to_lowercase();
+    query.split_whitespace().all(|term| {
+        let (start, term) = term.strip_prefix('^').map_or((false, term), |term| (true, term));
+        let (end, term) = term.strip_suffix('$').map_or((false, term), |term| (true, term));
+        match (start, end) {
+            (true, true) => name == term,
+            (true, false) => name.starts_with(term),
+            (false, true) => name.ends_with(term),
+            (false, false) => text.contains(term),
+        }
+    })
+}
+
+/// The package name of an item (`repository/name`, or the first word of rows like `name  old → new`)
+fn package_name(item: &str) -> &str {
+    let name = item.split_whitespace().next().unwrap_or(item);
+    name.split_once('/').map_or(name, |(_, name)| name)
+}
+
+/// Whether the term's characters appear in the item in order, ignoring ASCII case
+/// (a quick rejection; non-ASCII terms are left to the matcher)
+fn has_chars_in_order(item: &str, term: &str) -> bool {
+    if !term.is_ascii() {
+        return true;
+    }
+
+    let mut haystack = item.bytes();
+    term.bytes().all(|wanted| haystack.any(|byte| byte.eq_ignore_ascii_case(&wanted)))
+}
+
+/// Upstream URL from a package's info, falling back to the AUR page for AUR packages
+fn package_url(item: &str, preview: &str, aur: bool) -> Option<String> {
+    PackageInfo::from_pacman_output(preview)
+        .and_then(|info| info.url)
+        .or_else(|| aur.then(|| format!("https://aur.archlinux.org/packages/{}", package_name(item))))
+}
+
+/// Preview output with the AUR's votes, popularity and flags merged in
+/// Unreachable AUR leaves the output as it was, with a note at the end
+fn with_aur_info(content: String, item: &str) -> String {
+    let name = item.rsplit('/').next().unwrap_or(item);
+    match aur::info(&[name.to_string()]) {
+        Ok(packages) => match packages.first() {
+            Some(package) => package.merge_into_preview(&content),
+            None => content,
+        },
+        Err(_) => format!("{}\n\n{}", content.trim_end(), aur::INFO_UNAVAILABLE),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn list_app() -> App {
+        let items = ["core/bash", "extra/git", "extra/vim"];
+        App::new(items.iter().map(|s| s.to_string()).collect(), true, None, ActionType::Install)
+    }
+
+    #[test]
+    fn keys_move_select_and_confirm() {
+        let mut app = list_app();
+        assert_eq!(app.handle_key(key(KeyCode::Down)), AppAction::None);
+        app.handle_key(key(KeyCode::Char('k')));
+        // Tab selects and moves on
+        app.handle_key(key(KeyCode::Tab));
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.list_state.selected(), Some(2));
+        app.handle_key(key(KeyCode::Char('k')));
+        app.handle_key(key(KeyCode::Tab));
+        assert_eq!(app.handle_key(key(KeyCode::Enter)), AppAction::Confirm(vec!["core/bash".to_string()]));
+        assert_eq!(app.handle_key(KeyEvent::new(KeyCode::Char('3'), KeyModifiers::ALT)), AppAction::SwitchTab(ViewType::Remove));
+    }
+
+    #[test]
+    fn l_numbers_the_preview_links() {
+        let mut app = App::new(vec!["extra/vim".to_string()], false, PreviewSource::custom("echo {}"), ActionType::Install);
+        app.preview_content = "URL            : https://www.vim.org".to_string();
+        app.handle_key(key(KeyCode::Char('l')));
+        assert!(app.link_hints_active);
+        assert_eq!(app.links, vec!["https://www.vim.org"]);
+
+        // While searching, l is typed
+        app.cancel_link_hints();
+        app.handle_key(key(KeyCode::Char('/')));
+        app.handle_key(key(KeyCode::Char('l')));
+        assert!(!app.link_hints_active);
+        assert_eq!(app.search_query, "l");
+    }
+
+    #[test]
+    fn search_keys_edit_the_query_and_esc_clears_it_first() {
+        let mut app = list_app();
+        app.handle_key(key(KeyCode::Char('/')));
+        for c in "vimx".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        app.handle_key(key(KeyCode::Backspace));
+        assert_eq!(app.search_query, "vim");
+        assert_eq!(app.handle_key(key(KeyCode::Char('j'))), AppAction::None, "typed, not a shortcut");
+        assert_eq!(app.search_query, "vimj");
+        app.handle_key(key(KeyCode::Backspace));
+
+        // Esc gives focus back to the list, then clears the search, then leaves
+        app.handle_key(key(KeyCode::Esc));
+        assert!(!app.search_focused);
+        app.flush_filter();
+        assert_eq!(app.filtered_items.len(), 1);
+        assert_eq!(app.handle_key(key(KeyCode::Esc)), AppAction::None);
+        assert!(app.search_query.is_empty());
+        assert_eq!(app.handle_key(key(KeyCode::Esc)), AppAction::Cancel);
+    }
+
+    #[test]
+    fn the_topmost_window_takes_the_key() {
+        let mut app = list_app();
+        app.alert.show(AlertType::Info, "Copied".to_string());
+        app.help_visible = true;
+        app.confirm_dialog.show(ActionType::Install, vec!["extra/git".to_string()]);
+        app.update_window.active = true;
+
+        // The operation window keeps Esc while it runs; then the dialog, help and alert close in turn
+        app.handle_key(key(KeyCode::Esc));
+        assert!(app.update_window.active && app.confirm_dialog.active);
+        app.update_window.active = false;
+        app.handle_key(key(KeyCode::Esc));
+        assert!(!app.confirm_dialog.active && app.help_visible);
+        app.handle_key(key(KeyCode::Esc));
+        assert!(!app.help_visible && app.alert.active);
+        assert_eq!(app.handle_key(key(KeyCode::Down)), AppAction::None);
+        assert!(!app.alert.active);
+        assert_eq!(app.list_state.selected(), Some(0), "the key closing the alert doesn't move the list");
+    }
+
+    #[test]
+    fn fuzzy_score_matches_all_terms_in_any_order() {
+        let matcher = SkimMatcherV2::default();
+
+        assert!(fuzzy_score(&matcher, "extra/firefox", "fire fox").is_some());
+        assert!(fuzzy_score(&matcher, "extra/firefox", "fox fire").is_some());
+        assert!(fuzzy_score(&matcher, "extra/firefox", "fire chrome").is_none());
+        assert_eq!(
+            fuzzy_score(&matcher, "extra/firefox", "fire"),
+            matcher.fuzzy_match("extra/firefox", "fire")
+        );
+    }
+
+    #[test]
+    fn repo_filter_applies_before_fuzzy_matching() {
+        let items = ["core/bash", "extra/bash-completion", "aur/bashtop", "multilib/lib32-bash"];
+        let mut app = App::new(items.iter().map(|s| s.to_string()).collect(), true, None, ActionType::Install);
+        assert_eq!(app.cycle_repo_filter(), None, "lists without repositories have no filter");
+
+        app.repo_filter = Some(RepoFilter::All);
+        app.repositories = ["core", "extra", "multilib", "aur"].map(String::from).to_vec();
+        app.search_query = "bash".to_string();
+        app.filter_items();
+        assert_eq!(app.filtered_items.len(), 4);
+
+        let repository = |name: &str| Some(RepoFilter::Repository(name.to_string()));
+        assert_eq!(app.cycle_repo_filter(), repository("core"));
+        assert_eq!(app.cycle_repo_filter(), repository("extra"));
+        let names: Vec<&str> = (0..app.filtered_items.len()).filter_map(|i| app.filtered_item(i)).map(String::as_str).collect();
+        assert_eq!(names, vec!["extra/bash-completion"]);
+
+        app.cycle_repo_filter();
+        assert_eq!(app.cycle_repo_filter(), repository("aur"));
+        assert_eq!(app.filtered_item(0).unwrap(), "aur/bashtop");
+        assert_eq!(app.cycle_repo_filter(), Some(RepoFilter::All));
+        assert!(!RepoFilter::from("extra".to_string()).matches("extra-testing/bash"));
+
+        // A remembered repository that is no longer configured moves on to the first one
+        app.repo_filter = repository("testing");
+        assert_eq!(app.cycle_repo_filter(), repository("core"));
+    }
+
+    #[test]
+    fn preview_resizes_in_steps_within_bounds() {
+        let mut app = App::new(vec![], false, None, ActionType::Install);
+        assert_eq!(app.resize_preview(true), 55);
+        app.set_preview_ratio(95);
+        assert_eq!(app.preview_ratio, 80, "saved sizes are clamped too");
+        assert_eq!(app.resize_preview(true), 80);
+        app.set_preview_ratio(22);
+        assert_eq!(app.resize_preview(false), 20);
+        app.layout.toggle_to_horizontal();
+        assert_eq!(app.preview_ratio, 20, "switching orientation keeps the size");
+    }
+
+    #[test]
+    fn hidden_preview_loads_nothing_until_shown_again() {
+        let mut app = App::new(vec![], true, PreviewSource::custom("echo {}"), ActionType::Install);
+        app.preview_enabled = false;
+        app.set_items(vec!["core/bash".to_string(), "extra/git".to_string()]);
+        app.next();
+        assert!(!app.shows_preview());
+        assert_eq!(app.current_preview_item, None, "no preview is requested while hidden");
+        app.toggle_selection_preview();
+        assert!(!app.selection_preview);
+
+        assert!(app.toggle_preview());
+        assert_eq!(app.current_preview_item.as_deref(), Some("extra/git"));
+    }
+
+    #[test]
+    fn neighbors_prefetch_without_replacing_the_current_preview() {
+        let items: Vec<String> = (0..8).map(|i| format!("extra/pkg{}", i)).collect();
+        let mut app = App::new(items.clone(), false, PreviewSource::custom("sleep 5"), ActionType::Install);
+        let tx = app.preview_tx.clone().unwrap();
+        let wait_for = |app: &mut App, item: &str| {
+            for _ in 0..100 {
+                app.check_preview_updates();
+                if app.preview_cache.contains_key(item) {
+                    return;
                 }
+                thread::sleep(Duration::from_millis(10));
             }
+            panic!("no preview for {}", item);
+        };
+        let pending = |app: &App| -> HashSet<String> { app.preview_pending.keys().cloned().collect() };
+
+        app.request_preview();
+        assert_eq!(pending(&app), HashSet::from([items[0].clone()]), "nothing is prefetched before the current preview");
+
+        // A result for another item is only cached
+        tx.send((items[3].clone(), "stale".to_string())).unwrap();
+        wait_for(&mut app, &items[3]);
+        assert_eq!(app.preview_content, "Loading preview...");
+
+        tx.send((items[0].clone(), "pkg0 info".to_string())).unwrap();
+        wait_for(&mut app, &items[0]);
+        assert_eq!(app.preview_content, "pkg0 info");
+        assert_eq!(pending(&app), HashSet::from([items[1].clone(), items[2].clone()]));
+
+        // Moving onto a prefetching item waits for it instead of starting another load
+        app.next();
+        assert_eq!(app.current_preview_item.as_ref(), Some(&items[1]));
+        assert_eq!(app.preview_pending.len(), 2);
+
+        // Jumping away kills the loads that are no longer near the cursor
+        let far = app.preview_pending[&items[1]].clone();
+        app.list_state.select(Some(5));
+        app.request_preview();
+        assert!(far.load(Ordering::Relaxed));
+        assert_eq!(pending(&app), HashSet::from([items[5].clone()]));
+
+        tx.send((items[5].clone(), "pkg5 info".to_string())).unwrap();
+        wait_for(&mut app, &items[5]);
+        assert_eq!(app.preview_content, "pkg5 info");
+        assert_eq!(pending(&app), HashSet::from([items[4].clone(), items[6].clone(), items[7].clone()]));
+        assert!(app.preview_pending.len() <= MAX_PREVIEW_LOADS);
+        assert_eq!(app.preview_cache[&items[3]], "stale", "cached neighbors aren't loaded again");
+
+        let shutdown = app.preview_shutdown.clone();
+        drop(app);
+        assert!(shutdown.load(Ordering::Relaxed), "dropping the app kills its preview commands");
+    }
+
+    #[test]
+    fn timed_out_preview_can_be_retried() {
+        let mut app = App::new(vec!["extra/vim".to_string()], false, PreviewSource::custom("sleep 10"), ActionType::Install);
+        app.preview_timeout = Duration::from_millis(20);
+        app.request_preview();
+        for _ in 0..200 {
+            app.check_preview_updates();
+            if app.preview_content != "Loading preview..." {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(app.preview_content, preview::TIMED_OUT);
+
+        app.retry_preview();
+        assert_eq!(app.preview_content, "Loading preview...");
+        assert!(app.preview_pending.contains_key("extra/vim"));
+    }
+
+    #[test]
+    fn package_url_falls_back_to_the_aur_page() {
+        let info = "Name            : yay\nVersion         : 12.3.5-1\nURL             : https://github.com/Jguer/yay\n";
+        assert_eq!(package_url("aur/yay", info, true).as_deref(), Some("https://github.com/Jguer/yay"));
+
+        let no_url = "Name            : foo\nURL             : None\n";
+        assert_eq!(package_url("aur/foo", no_url, true).as_deref(), Some("https://aur.archlinux.org/packages/foo"));
+        assert_eq!(package_url("foo", "Preview failed", true).as_deref(), Some("https://aur.archlinux.org/packages/foo"));
+        assert_eq!(package_url("extra/foo", no_url, false), None);
+    }
+
+    #[test]
+    fn yank_copies_the_selection_or_the_highlighted_package() {
+        let items = ["core/bash", "extra/git", "extra/vim"].iter().map(|s| s.to_string()).collect();
+        let mut app = App::new(items, true, None, ActionType::Install);
+        app.set_items(app.items.clone());
+        app.next();
+        assert_eq!(app.yank_text(false), Some(("git".to_string(), 1)));
+        assert_eq!(app.yank_text(true), Some(("extra/git".to_string(), 1)));
+
+        app.selected = ["extra/vim", "core/bash"].iter().map(|s| s.to_string()).collect();
+        assert_eq!(app.yank_text(false), Some(("bash vim".to_string(), 2)), "in list order");
+
+        let mut updates = App::new(vec!["linux  6.9-1 → 6.10-1".to_string()], true, None, ActionType::Upgrade);
+        updates.set_items(updates.items.clone());
+        assert_eq!(updates.yank_text(true), Some(("linux".to_string(), 1)));
+    }
+
+    #[test]
+    fn refreshed_list_highlights_the_same_item_or_the_nearest_row() {
+        let items = |names: &[&str]| names.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut app = App::new(items(&["bash", "git", "htop", "vim", "zsh"]), true, None, ActionType::Remove);
+        app.set_items(app.items.clone());
+        app.list_state.select(Some(2));
+        assert_eq!(app.cursor(), Some(("htop".to_string(), 2)));
+
+        // htop moved up a row
+        app.cursor_target = app.cursor();
+        app.set_items(items(&["git", "htop", "vim", "zsh"]));
+        assert_eq!(app.list_state.selected(), Some(1));
+
+        // htop was removed, so the row that took its place is highlighted
+        app.cursor_target = Some(("htop".to_string(), 1));
+        app.set_items(items(&["git", "vim", "zsh"]));
+        assert_eq!(app.filtered_item(app.list_state.selected().unwrap()).unwrap(), "vim");
+
+        // The list got shorter than the old row
+        app.cursor_target = Some(("zsh".to_string(), 2));
+        app.set_items(items(&["git"]));
+        assert_eq!(app.list_state.selected(), Some(0));
+        assert!(app.cursor_target.is_none());
+    }
+
+    #[test]
+    fn selections_are_kept_by_item_while_the_search_changes() {
+        let items = ["firefox", "python-xml", "vim", "python-toml"].map(String::from).to_vec();
+        let mut app = App::new(items, true, None, ActionType::Remove);
+        app.toggle_select(); // firefox
+
+        app.search_query = "python".to_string();
+        app.filter_items();
+        assert!(app.filtered_items.iter().all(|(index, _)| app.items[*index] != "firefox"));
+        app.list_state.select(Some(1));
+        let checked = app.filtered_item(1).unwrap().clone();
+        app.toggle_select();
+
+        app.search_query.clear();
+        app.filter_items();
+        assert_eq!(app.get_selected_items(), vec!["firefox".to_string(), checked.clone()]);
+
+        // A reload keeps the selected items that are still listed
+        app.set_items(vec!["vim".to_string(), checked.clone()]);
+        assert_eq!(app.get_selected_items(), vec![checked]);
+    }
+
+    #[test]
+    fn selected_only_view_restores_the_search() {
+        let mut app = App::new(package_list(200), true, None, ActionType::Install);
+        app.search_query = "python".to_string();
+        app.filter_items();
+        app.select_all_filtered();
+        let python_count = app.filtered_items.len();
+        app.search_query = "lib32".to_string();
+        app.filter_items();
+        app.list_state.select(Some(2));
+        app.toggle_select(); // Moves on to the next row
+
+        app.toggle_selected_only();
+        assert!(app.search_query.is_empty());
+        assert_eq!(app.filtered_items.len(), app.selected.len());
+        assert!(app.filtered_items.len() >= python_count);
+
+        // Unselecting keeps the row listed until the view is left
+        app.list_state.select(Some(0));
+        app.toggle_select();
+        assert_eq!(app.filtered_items.len(), app.selected.len() + 1);
+
+        app.toggle_selected_only();
+        assert_eq!(app.search_query, "lib32");
+        assert_eq!(app.list_state.selected(), Some(3));
+    }
+
+    #[test]
+    fn narrowing_the_selected_only_view_follows_the_selection() {
+        let items = ["core/bash", "core/base", "extra/git"].map(String::from).to_vec();
+        let mut app = App::new(items, true, None, ActionType::Install);
+        app.select_all_filtered();
+        app.toggle_selected_only();
+        app.search_query = "ba".to_string();
+        app.filter_items();
+        assert_eq!(app.filtered_items.len(), 2);
+
+        let bash = app.filtered_items.iter().position(|(index, _)| app.items[*index] == "core/bash").unwrap();
+        app.list_state.select(Some(bash));
+        app.toggle_select();
+        app.search_query = "bas".to_string();
+        app.filter_items();
+        let listed: Vec<&str> = (0..app.filtered_items.len()).filter_map(|i| app.filtered_item(i)).map(String::as_str).collect();
+        assert_eq!(listed, vec!["core/base"], "the cached matches don't bring back unselected items");
+    }
+
+    #[test]
+    fn sorting_is_stable_and_skips_modes_without_data() {
+        let items = ["extra/vim-gitgutter", "core/vim", "extra/vim", "core/gvim"].map(String::from).to_vec();
+        let mut app = App::new(items, true, None, ActionType::Install);
+        app.repo_filter = Some(RepoFilter::All);
+        app.search_query = "vim".to_string();
+        app.filter_items();
+        let by_score: Vec<String> = (0..4).map(|i| app.filtered_item(i).unwrap().clone()).collect();
+
+        assert_eq!(app.cycle_sort_mode(), SortMode::Name);
+        let names: Vec<&str> = (0..4).map(|i| app.filtered_item(i).unwrap().as_str()).collect();
+        assert_eq!(names[0], "core/gvim");
+        // Same name: the better match stays first
+        let first_vim = by_score.iter().find(|item| item.ends_with("/vim")).unwrap();
+        assert_eq!(names[1], first_vim);
+
+        assert_eq!(app.cycle_sort_mode(), SortMode::Repository);
+        let core: Vec<&String> = by_score.iter().filter(|item| item.starts_with("core/")).collect();
+        assert_eq!(app.filtered_item(0), Some(core[0]));
+        assert_eq!(app.filtered_item(1), Some(core[1]));
+
+        assert_eq!(app.cycle_sort_mode(), SortMode::Score, "no sizes or dates for available packages");
+
+        let details = |name: &str, installed_size: u64| InstalledDetails {
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            installed_size,
+            install_date: None,
+            reason: crate::package::InstallReason::Explicit,
+        };
+        let mut installed = App::new(vec!["bash".to_string(), "linux".to_string(), "vim".to_string()], true, None, ActionType::Remove);
+        installed.set_installed_details(&[details("bash", 9), details("linux", 140), details("vim", 4)]);
+        assert_eq!(installed.cycle_sort_mode(), SortMode::Name);
+        assert_eq!(installed.cycle_sort_mode(), SortMode::Size, "installed items have no repository");
+        assert_eq!(installed.filtered_item(0).unwrap(), "linux");
+        assert_eq!(installed.filtered_item(2).unwrap(), "vim");
+    }
+
+    #[test]
+    fn bulk_selection_covers_the_shown_items() {
+        let items = ["python-xml", "python-yaml", "vim", "python-toml"].map(String::from).to_vec();
+        let mut app = App::new(items, true, None, ActionType::Remove);
+        app.search_query = "python-".to_string();
+        app.filter_items();
+
+        app.select_all_filtered();
+        let mut selected = app.get_selected_items();
+        selected.sort();
+        assert_eq!(selected, vec!["python-toml", "python-xml", "python-yaml"]);
+
+        app.list_state.select(Some(0));
+        app.toggle_select();
+        app.invert_selection();
+        assert_eq!(app.get_selected_items(), vec![app.filtered_item(0).unwrap().clone()]);
+
+        app.deselect_all();
+        assert!(app.get_selected_items().is_empty());
+    }
+
+    #[test]
+    fn description_search_is_a_toggle() {
+        let pkg = |repository: &str, name: &str, description: &str| Package {
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            description: description.to_string(),
+            repository: repository.to_string(),
+        };
+        let packages = vec![pkg("extra", "ripgrep", "A search tool that combines ag with grep"), pkg("extra", "fd", "Simple alternative to find")];
+        let mut app = App::new(vec!["extra/ripgrep".to_string(), "extra/fd".to_string()], true, None, ActionType::Install);
+        app.set_package_details(&packages);
+        assert_eq!(app.package_details["extra/fd"].version, "1.0-1");
+
+        app.search_query = "find".to_string();
+        app.filter_items();
+        assert!(app.filtered_items.is_empty(), "names only by default");
+
+        app.toggle_search_descriptions();
+        assert_eq!(app.filtered_items.len(), 1);
+        assert_eq!(app.filtered_item(0).unwrap(), "extra/fd");
+    }
+
+    /// Items shaped like the Arch package list, e.g. `extra/python-xml-git`
+    fn package_list(count: usize) -> Vec<String> {
+        let repositories = ["core", "extra", "multilib", "aur"];
+        let words = [
+            "python", "lib32", "firefox", "gnome", "kde", "rust", "qt6", "haskell", "perl", "fonts", "xorg", "git",
+            "node", "ruby", "gtk", "vim", "emacs", "docker", "linux", "mesa", "vulkan", "audio", "pulse", "wayland",
+            "theme", "icons", "plugin", "bin", "cli", "tools", "xml", "json", "http", "sql", "zsh", "bash", "go", "java",
+            "lua", "tex",
+        ];
+        // Deterministic pseudo-random word choices (linear congruential generator)
+        let mut state: u64 = 42;
+        let mut next = |n: usize| {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as usize % n
+        };
+        (0..count)
+            .map(|i| {
+                let name: Vec<&str> = (0..1 + next(3)).map(|_| words[next(words.len())]).collect();
+                format!("{}/{}{}", repositories[i % 4], name.join("-"), i % 97)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn exact_and_regex_modes_anchor_to_the_package_name() {
+        let items = ["extra/python-requests", "extra/python", "aur/mypython-tools", "core/bash"];
+        let mut app = App::new(items.iter().map(|s| s.to_string()).collect(), true, None, ActionType::Install);
+        let shown = |app: &App| -> Vec<String> { (0..app.filtered_items.len()).filter_map(|i| app.filtered_item(i).cloned()).collect() };
+
+        assert_eq!(app.cycle_search_mode(), SearchMode::Exact);
+        app.search_query = "^python-".to_string();
+        app.filter_items();
+        assert_eq!(shown(&app), vec!["extra/python-requests"]);
+        app.search_query = "python$".to_string();
+        app.filter_items();
+        assert_eq!(shown(&app), vec!["extra/python"]);
+
+        assert_eq!(app.cycle_search_mode(), SearchMode::Regex);
+        app.search_query = "^python(-|$)".to_string();
+        app.filter_items();
+        assert_eq!(shown(&app), vec!["extra/python-requests", "extra/python"]);
+
+        app.search_query.push('(');
+        app.filter_items();
+        assert!(app.search_error.as_deref().unwrap().contains("unclosed group"));
+        assert_eq!(shown(&app).len(), 2, "the last matches stay until the regex is fixed");
+
+        assert_eq!(app.cycle_search_mode(), SearchMode::Fuzzy);
+        assert_eq!(app.search_error, None);
+        app.search_query = "'ython-t".to_string();
+        app.filter_items();
+        assert_eq!(shown(&app), vec!["aur/mypython-tools"], "a leading ' searches exactly");
+    }
+
+    #[test]
+    fn search_bar_edits_by_character_at_the_cursor() {
+        let mut app = App::new(package_list(10), true, None, ActionType::Install);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let ctrl = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL);
+        app.focus_search();
+        for c in "café au".chars() {
+            app.handle_search_key(key(KeyCode::Char(c)));
+        }
+
+        app.handle_search_key(ctrl('w'));
+        assert_eq!(app.search_query, "café ");
+        app.handle_search_key(key(KeyCode::Backspace));
+        app.handle_search_key(key(KeyCode::Backspace));
+        assert_eq!(app.search_query, "caf", "é is deleted whole");
+
+        app.handle_search_key(ctrl('a'));
+        app.handle_search_key(key(KeyCode::Right));
+        app.handle_search_key(key(KeyCode::Char('ö')));
+        app.handle_search_key(key(KeyCode::Delete));
+        assert_eq!(app.search_query, "cöf");
+        assert_eq!(app.search_cursor(), 2);
+
+        app.handle_search_key(ctrl('e'));
+        app.handle_search_key(key(KeyCode::Char('e')));
+        assert_eq!(app.search_query, "cöfe");
+        assert!(!app.handle_search_key(key(KeyCode::Down)), "list keys still reach the list");
+
+        app.handle_search_key(ctrl('u'));
+        assert_eq!(app.search_query, "");
+        assert!(app.search_focused);
+    }
+
+    #[test]
+    fn parallel_and_serial_scoring_agree() {
+        let mut app = App::new(package_list(PARALLEL_FILTER_THRESHOLD * 2), true, None, ActionType::Install);
+        for query in ["f", "python xml", "lib32-mesa", "gti"] {
+            app.search_query = query.to_string();
+            app.filter_items();
+            let filtered = app.filtered_items.clone();
+
+            let mut serial = app.score_candidates(&Query::Fuzzy(query.to_string()), (0..app.items.len()).collect(), false);
+            serial.sort_by_key(|item| std::cmp::Reverse(item.1));
+            assert!(!serial.is_empty(), "{} matches something", query);
+            assert_eq!(filtered, serial, "same order for {}", query);
+        }
+    }
+
+    #[test]
+    fn fast_typing_filters_once_the_debounce_passes() {
+        let mut app = App::new(package_list(1000), true, None, ActionType::Install);
+        app.search_query.push('f');
+        app.search_changed();
+        let after_first_key = app.filtered_items.len();
+        assert!(after_first_key < 1000, "the first key filters right away");
+
+        for c in "irefox".chars() {
+            app.search_query.push(c);
+            app.search_changed();
+        }
+        assert_eq!(app.filtered_items.len(), after_first_key, "keys typed quickly are batched");
+        assert!(app.poll_timeout(Duration::from_secs(1)) <= FILTER_DEBOUNCE);
+
+        app.next();
+        assert!(app.filtered_items.len() < after_first_key, "moving in the list applies the search first");
+        assert!(app.filtered_items.iter().all(|&(index, _)| app.items[index].contains("firefox")));
+
+        app.search_query.clear();
+        app.search_changed();
+        assert_eq!(app.filtered_items.len(), 1000, "clearing the search restores the list at once");
+        assert_eq!(app.list_state.selected(), Some(0));
+    }
+
+    #[test]
+    fn visible_range_follows_the_selection() {
+        let mut app = App::new(package_list(100), true, None, ActionType::Install);
+        assert_eq!(app.visible_range(10), 0..10);
+
+        app.list_state.select(Some(25));
+        assert_eq!(app.visible_range(10), 16..26);
+        // Moving within the window doesn't scroll
+        app.list_state.select(Some(20));
+        assert_eq!(app.visible_range(10), 16..26);
+        app.list_state.select(Some(3));
+        assert_eq!(app.visible_range(10), 3..13);
+
+        app.search_query = "firefox-rust".to_string();
+        app.filter_items();
+        assert!(app.visible_range(10).len() <= app.filtered_items.len());
+    }
+
+    /// Typing a query into the full package list; run with
+    /// `cargo test --release -- --ignored --nocapture filter_benchmark`
+    #[test]
+    #[ignore]
+    fn filter_benchmark() {
+        let mut app = App::new(package_list(90_000), true, None, ActionType::Install);
+        let mut slowest = Duration::ZERO;
+        for c in "firefox rust".chars() {
+            app.search_query.push(c);
+            let started = Instant::now();
+            app.filter_items();
+            let elapsed = started.elapsed();
+            println!("{:>14} {:>6} items {:?}", format!("{:?}", app.search_query), app.filtered_items.len(), elapsed);
+            slowest = slowest.max(elapsed);
         }
+        println!("slowest keystroke: {:?}", slowest);
     }
 }
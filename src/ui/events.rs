@@ -0,0 +1,43 @@
+use anyhow::Result;
+use crossterm::event::{self, Event};
+use std::time::Duration;
+
+/// Where the event loop gets its input from (the real terminal, or a script in tests)
+pub trait EventSource {
+    /// Wait up to `timeout` for the next event; `None` if nothing happened
+    fn next_event(&mut self, timeout: Duration) -> Result<Option<Event>>;
+}
+
+/// Events read from the terminal with crossterm
+pub struct CrosstermEvents;
+
+impl EventSource for CrosstermEvents {
+    fn next_event(&mut self, timeout: Duration) -> Result<Option<Event>> {
+        if event::poll(timeout)? {
+            Ok(Some(event::read()?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Events played back from a script in tests; running out of them ends the loop with an error
+#[cfg(test)]
+pub struct ScriptedEvents(pub std::collections::VecDeque<Event>);
+
+#[cfg(test)]
+impl ScriptedEvents {
+    pub fn new(keys: &[crossterm::event::KeyEvent]) -> Self {
+        Self(keys.iter().map(|key| Event::Key(*key)).collect())
+    }
+}
+
+#[cfg(test)]
+impl EventSource for ScriptedEvents {
+    fn next_event(&mut self, _timeout: Duration) -> Result<Option<Event>> {
+        match self.0.pop_front() {
+            Some(event) => Ok(Some(event)),
+            None => anyhow::bail!("event script exhausted"),
+        }
+    }
+}
@@ -1,41 +1,142 @@
-pub fn get_help_text() -> &'static str {
-    r#"
-╔════════════════════════════════════════════════════════════════╗
-║                      PMGR - Package Manager                    ║
-║                         Keyboard Shortcuts                     ║
-╚════════════════════════════════════════════════════════════════╝
-
-NAVIGATION
-  ↑ / k              Move up in the list
-  ↓ / j              Move down in the list
-
-SELECTION & ACTIONS
-  TAB                Toggle selection (multi-select mode)
-  ENTER              Confirm selection and proceed
-  ESC                Cancel and exit
-
-SEARCH
-  Type characters    Filter packages by name (fuzzy search)
-  Backspace          Delete last character from search
-
-LAYOUT
-  Alt+O              Switch to horizontal layout
-  Alt+V              Switch to vertical layout
-  Ctrl+T             Change theme
-
-SYSTEM
-  Ctrl+U             Update entire system (sudo pacman -Syu)
-
-HELP
-  ?                  Show/hide this help screen
-
-───────────────────────────────────────────────────────────────────
-
-TIPS
-  • Use fuzzy search to quickly find packages
-  • TAB to select multiple packages before confirming
-  • System updates run in a floating window
-  • Updates close automatically on success, Alt+X if error
-
-"#
+/// Groups of shortcuts in the help overlay, in the order they are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Navigation,
+    Selection,
+    Search,
+    Layout,
+    System,
+    Help,
+    Tips,
+}
+
+impl Section {
+    pub fn title(self) -> &'static str {
+        match self {
+            Section::Navigation => "NAVIGATION",
+            Section::Selection => "SELECTION & ACTIONS",
+            Section::Search => "SEARCH",
+            Section::Layout => "LAYOUT",
+            Section::System => "SYSTEM",
+            Section::Help => "HELP",
+            Section::Tips => "TIPS",
+        }
+    }
+}
+
+/// The package views' shortcuts as (section, keys, what they do), the only list the help is drawn from
+pub const BINDINGS: &[(Section, &str, &str)] = &[
+    (Section::Navigation, "↑ / k", "Move up in list"),
+    (Section::Navigation, "↓ / j", "Move down in list"),
+    (Section::Navigation, "g / G", "First / last package"),
+    (Section::Navigation, "Wheel", "Scroll list or preview"),
+    (Section::Navigation, "Alt+1-6", "Switch tabs"),
+    (Section::Selection, "TAB", "Toggle selection"),
+    (Section::Selection, "Click", "Select (again: toggle)"),
+    (Section::Selection, "Ctrl+A", "Select all shown"),
+    (Section::Selection, "Ctrl+D", "Deselect all shown"),
+    (Section::Selection, "Alt+I", "Invert selection"),
+    (Section::Selection, "Ctrl+S", "Show selected only"),
+    (Section::Selection, "ENTER", "Confirm selection"),
+    (Section::Selection, "a", "Queue selection"),
+    (Section::Selection, "Alt+Q", "Review the queue"),
+    (Section::Selection, "ESC", "Cancel and exit"),
+    (Section::Search, "/", "Type a search"),
+    (Section::Search, "ENTER/ESC", "Back to the list"),
+    (Section::Search, "Backspace", "Delete character"),
+    (Section::Search, "←/→ Ctrl+A/E", "Move cursor (typing)"),
+    (Section::Search, "Ctrl+W/U", "Delete word/line (typing)"),
+    (Section::Search, "Ctrl+F", "Filter by repository"),
+    (Section::Search, "Alt+D", "Search descriptions"),
+    (Section::Search, "Ctrl+E", "Fuzzy/exact/regex search"),
+    (Section::Search, "Alt+N", "Change sort order"),
+    (Section::Layout, "Alt+O", "Horizontal layout"),
+    (Section::Layout, "Alt+V", "Vertical layout"),
+    (Section::Layout, "< / >", "Shrink/grow preview"),
+    (Section::Layout, "Alt+P", "Hide/show preview"),
+    (Section::Layout, "Alt+S", "Selection preview"),
+    (Section::Layout, "PgUp/PgDn", "Scroll selection"),
+    (Section::Layout, "o / Ctrl+O", "Open package homepage"),
+    (Section::Layout, "y", "Copy package names"),
+    (Section::Layout, "r", "Reload preview"),
+    (Section::Layout, "l", "Open a link in the preview"),
+    (Section::System, "Ctrl+U", "Update system"),
+    (Section::System, "Ctrl+Y", "Refresh databases"),
+    (Section::System, "Ctrl+R", "Reload the view"),
+    (Section::System, "Ctrl+L", "Operation logs"),
+    (Section::System, "Ctrl+T", "Change theme"),
+    (Section::Help, "?", "Show/hide help"),
+    (Section::Tips, "•", "Updates close after a countdown"),
+    (Section::Tips, "•", "Alt+X closes errors"),
+    (Section::Tips, "•", "R retries a failed step"),
+];
+
+/// A line of the help overlay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HelpLine {
+    Heading(Section),
+    /// Keys, padded to line up with the rest, and what they do
+    Binding(String, &'static str),
+    Blank,
+}
+
+/// The help split into `columns` columns of about the same height, never splitting a section
+pub fn help_columns(columns: usize) -> Vec<Vec<HelpLine>> {
+    let key_width = BINDINGS.iter().map(|(_, keys, _)| keys.chars().count()).max().unwrap_or(0) + 1;
+
+    let mut sections: Vec<Vec<HelpLine>> = Vec::new();
+    let mut current = None;
+    for (section, keys, description) in BINDINGS {
+        if current != Some(*section) {
+            current = Some(*section);
+            sections.push(vec![HelpLine::Heading(*section)]);
+        }
+        let keys = format!("  {:<width$}", keys, width = key_width);
+        sections.last_mut().unwrap().push(HelpLine::Binding(keys, description));
+    }
+    for section in &mut sections {
+        section.push(HelpLine::Blank);
+    }
+
+    // Start the next column once this one holds its share of the lines
+    let columns = columns.max(1);
+    let total: usize = sections.iter().map(Vec::len).sum();
+    let share = total.div_ceil(columns);
+    let mut result = vec![Vec::new(); columns];
+    let mut column = 0;
+    for section in sections {
+        if column + 1 < columns && !result[column].is_empty() && result[column].len() + section.len() / 2 > share {
+            column += 1;
+        }
+        result[column].extend(section);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_keep_sections_whole() {
+        let single = help_columns(1);
+        assert_eq!(single.len(), 1);
+        assert_eq!(single[0][0], HelpLine::Heading(Section::Navigation));
+        assert_eq!(single[0].iter().filter(|line| matches!(line, HelpLine::Binding(..))).count(), BINDINGS.len());
+
+        let double = help_columns(2);
+        assert_eq!(double[0].len() + double[1].len(), single[0].len());
+        assert!(matches!(double[1][0], HelpLine::Heading(_)), "a column starts with a section");
+        assert!(double[0].len().abs_diff(double[1].len()) < 12);
+
+        // The descriptions line up
+        let starts: Vec<usize> = single[0]
+            .iter()
+            .filter_map(|line| match line {
+                HelpLine::Binding(keys, _) => Some(keys.chars().count()),
+                _ => None,
+            })
+            .collect();
+        assert!(starts.windows(2).all(|pair| pair[0] == pair[1]));
+    }
 }
@@ -1,33 +1,28 @@
 #[derive(Debug, Clone)]
 pub struct HomeState {
-    pub scroll_position: u16,
-    pub stats: Option<SystemStats>,
+    pub stats: SystemStats,
+    pub refresh_status: Vec<String>, // When each background dataset was/will be refreshed
 }
 
-#[derive(Debug, Clone)]
+/// Home view counts, filled in by background refreshes (None until loaded)
+#[derive(Debug, Clone, Default)]
 pub struct SystemStats {
-    pub installed_count: usize,
-    pub available_count: usize,
-    pub updates_available: usize,
+    pub installed_count: Option<usize>,
+    pub available_count: Option<usize>,
+    pub updates_available: Option<usize>,
+    pub checking_updates: bool, // An update check is running in the background
+    pub unread_news: Option<usize>, // Arch Linux news since the last upgrade
 }
 
 impl HomeState {
     pub fn new() -> Self {
         Self {
-            scroll_position: 0,
-            stats: None,
+            stats: SystemStats::default(),
+            refresh_status: Vec::new(),
         }
     }
 
     pub fn set_stats(&mut self, stats: SystemStats) {
-        self.stats = Some(stats);
-    }
-
-    pub fn scroll_down(&mut self) {
-        self.scroll_position = self.scroll_position.saturating_add(1);
-    }
-
-    pub fn scroll_up(&mut self) {
-        self.scroll_position = self.scroll_position.saturating_sub(1);
+        self.stats = stats;
     }
 }
@@ -0,0 +1,186 @@
+use crate::package::PackageManager;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Clipboard tools tried (in order) when xdg-open isn't available, and alongside OSC 52 when copying
+const CLIPBOARD_TOOLS: [(&str, &[&str]); 3] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// What happened to a link that was acted on
+pub enum LinkOutcome {
+    Opened,
+    Copied,
+}
+
+/// A URL found in text, as a byte range
+#[derive(Debug, Clone, PartialEq)]
+pub struct UrlMatch {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Find http(s) URLs in a line of text
+pub fn find_urls(text: &str) -> Vec<UrlMatch> {
+    let mut matches = Vec::new();
+    let mut offset = 0;
+
+    while let Some(pos) = next_url_start(&text[offset..]) {
+        let start = offset + pos;
+        let len = text[start..]
+            .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\''))
+            .unwrap_or(text.len() - start);
+
+        // Trailing punctuation usually belongs to the surrounding sentence
+        let url = text[start..start + len].trim_end_matches(['.', ',', ';', ':', ')', ']', '!', '?']);
+        let end = start + url.len();
+
+        if url.len() > "https://".len() {
+            matches.push(UrlMatch { start, end });
+        }
+        offset = start + len.max(1);
+    }
+
+    matches
+}
+
+fn next_url_start(text: &str) -> Option<usize> {
+    match (text.find("https://"), text.find("http://")) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Unique URLs in order of first appearance (hint numbers are index + 1)
+pub fn collect_links(text: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+
+    for line in text.lines() {
+        for m in find_urls(line) {
+            let url = &line[m.start..m.end];
+            if !links.iter().any(|l| l == url) {
+                links.push(url.to_string());
+            }
+        }
+    }
+
+    links
+}
+
+/// Open a URL with xdg-open, or copy it to the clipboard when xdg-open is missing
+pub fn open_link(url: &str) -> Result<LinkOutcome> {
+    if PackageManager::command_exists("xdg-open") {
+        Command::new("xdg-open")
+            .arg(url)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to run xdg-open")?;
+        return Ok(LinkOutcome::Opened);
+    }
+
+    if copy_with_tool(url)? {
+        return Ok(LinkOutcome::Copied);
+    }
+
+    anyhow::bail!("xdg-open is not installed and no clipboard tool was found")
+}
+
+/// Copy text to the clipboard: through the terminal with OSC 52 (which works over SSH),
+/// and with a clipboard tool as well when one is installed
+pub fn copy_to_clipboard(text: &str) -> Result<()> {
+    let mut stdout = std::io::stdout();
+    stdout.write_all(osc52(text).as_bytes())?;
+    stdout.flush()?;
+    copy_with_tool(text)?;
+    Ok(())
+}
+
+/// Copy text with the first clipboard tool that is installed; false if there is none
+fn copy_with_tool(text: &str) -> Result<bool> {
+    for (tool, args) in CLIPBOARD_TOOLS {
+        if !PackageManager::command_exists(tool) {
+            continue;
+        }
+
+        let mut child = Command::new(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .with_context(|| format!("Failed to run {}", tool))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        child.wait()?;
+        return Ok(true);
+    }
+
+    Ok(false)
+}
+
+/// Escape sequence asking the terminal to put text on the clipboard
+fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", base64(text.as_bytes()))
+}
+
+/// Standard base64 with padding
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn urls(text: &str) -> Vec<&str> {
+        find_urls(text).into_iter().map(|m| &text[m.start..m.end]).collect()
+    }
+
+    #[test]
+    fn finds_urls_in_preview_lines() {
+        assert_eq!(urls("URL             : https://www.gnu.org/software/bash/"), vec!["https://www.gnu.org/software/bash/"]);
+        assert_eq!(
+            urls("see http://a.org/x, and (https://b.org/y)."),
+            vec!["http://a.org/x", "https://b.org/y"]
+        );
+        assert!(urls("no links here, just https:// alone").is_empty());
+    }
+
+    #[test]
+    fn osc52_carries_base64_text() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(osc52("git vim"), "\x1b]52;c;Z2l0IHZpbQ==\x07");
+    }
+
+    #[test]
+    fn collects_unique_links() {
+        let text = "URL : https://a.org\nAUR URL : https://aur.archlinux.org/packages/foo\nmirror https://a.org\n";
+        assert_eq!(
+            collect_links(text),
+            vec!["https://a.org", "https://aur.archlinux.org/packages/foo"]
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use super::ansi;
+use super::render::render_log_viewer;
+use super::terminal::{take_interrupt, TerminalGuard};
+use crate::config;
+use crate::oplog;
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+};
+use ratatui::{backend::CrosstermBackend, Terminal};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Full-screen browser for the per-operation logs (Ctrl+L, or `pmgr logs`)
+pub struct LogViewer {
+    /// Log files, newest first
+    pub logs: Vec<PathBuf>,
+    pub selected: usize,
+    /// Why the logs couldn't be listed
+    pub error: Option<String>,
+    /// The log being read, if one was opened
+    pub open: Option<OpenLog>,
+}
+
+/// A log file opened in the viewer
+pub struct OpenLog {
+    pub path: PathBuf,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+    pub page_height: usize, // Lines that fit on screen (set when drawn)
+}
+
+impl OpenLog {
+    /// Record how many lines fit on screen, keeping the scroll within the log
+    pub fn set_page_height(&mut self, height: usize) {
+        self.page_height = height;
+        self.scroll = self.scroll.min(self.last_page());
+    }
+
+    fn last_page(&self) -> usize {
+        self.lines.len().saturating_sub(self.page_height.max(1))
+    }
+
+    fn scroll_by(&mut self, lines: isize) {
+        self.scroll = self.scroll.saturating_add_signed(lines).min(self.last_page());
+    }
+}
+
+impl LogViewer {
+    /// List the logs on disk
+    pub fn load() -> Self {
+        let (logs, error) = match oplog::list_logs() {
+            Ok(logs) => (logs, None),
+            Err(e) => (Vec::new(), Some(format!("Couldn't read the logs: {}", e))),
+        };
+        Self {
+            logs,
+            selected: 0,
+            error,
+            open: None,
+        }
+    }
+
+    /// Read the selected log, starting at its end (where a failure shows up)
+    fn open_selected(&mut self) {
+        let Some(path) = self.logs.get(self.selected) else {
+            return;
+        };
+        let lines = match fs::read_to_string(path) {
+            Ok(content) => content.lines().map(ansi::strip).collect(),
+            Err(e) => vec![format!("Couldn't read {}: {}", path.display(), e)],
+        };
+        self.open = Some(OpenLog {
+            path: path.clone(),
+            lines,
+            scroll: usize::MAX,
+            page_height: 0,
+        });
+    }
+
+    /// Handle a key; returns false once the viewer is closed
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        if let Some(open) = &mut self.open {
+            let page = open.page_height.max(1) as isize;
+            match (key.code, key.modifiers) {
+                (KeyCode::Esc, _) | (KeyCode::Char('q'), KeyModifiers::NONE) => self.open = None,
+                (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => open.scroll_by(-1),
+                (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => open.scroll_by(1),
+                (KeyCode::PageUp, _) => open.scroll_by(-page),
+                (KeyCode::PageDown, _) => open.scroll_by(page),
+                (KeyCode::Home, _) => open.scroll = 0,
+                (KeyCode::End, _) => open.scroll = open.last_page(),
+                _ => {}
+            }
+            return true;
+        }
+
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('q'), KeyModifiers::NONE) | (KeyCode::Char('l'), KeyModifiers::CONTROL) => return false,
+            (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => self.selected = self.selected.saturating_sub(1),
+            (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
+                self.selected = (self.selected + 1).min(self.logs.len().saturating_sub(1));
+            }
+            (KeyCode::Home, _) => self.selected = 0,
+            (KeyCode::End, _) => self.selected = self.logs.len().saturating_sub(1),
+            (KeyCode::Enter, _) => self.open_selected(),
+            _ => {}
+        }
+        true
+    }
+
+    /// Run the viewer on its own (`pmgr logs`)
+    pub fn run() -> Result<()> {
+        let mut viewer = Self::load();
+        let palette = config::load_settings().theme.palette();
+
+        let _guard = TerminalGuard::enter(false)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+        loop {
+            terminal.draw(|f| render_log_viewer(f, &mut viewer, &palette))?;
+            if take_interrupt() {
+                return Ok(());
+            }
+            if !event::poll(Duration::from_millis(250))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && !viewer.handle_key(key) {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn logs_open_at_their_end_and_scroll() {
+        let dir = std::env::temp_dir().join(format!("pmgr-test-viewer-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("1970-01-01_00-00-00-install.log");
+        let content: Vec<String> = (0..30).map(|i| format!("\x1b[1mline {}\x1b[0m", i)).collect();
+        fs::write(&path, content.join("\n")).unwrap();
+
+        let mut viewer = LogViewer { logs: vec![path], selected: 0, error: None, open: None };
+        assert!(viewer.handle_key(key(KeyCode::Enter)));
+        let open = viewer.open.as_mut().unwrap();
+        assert_eq!(open.lines[0], "line 0", "escape codes are stripped");
+        open.set_page_height(10);
+        assert_eq!(open.scroll, 20);
+
+        viewer.handle_key(key(KeyCode::PageUp));
+        viewer.handle_key(key(KeyCode::Up));
+        assert_eq!(viewer.open.as_ref().unwrap().scroll, 9);
+        viewer.handle_key(key(KeyCode::PageDown));
+        viewer.handle_key(key(KeyCode::PageDown));
+        assert_eq!(viewer.open.as_ref().unwrap().scroll, 20, "stops at the last page");
+
+        // Esc goes back to the list, then closes the viewer
+        assert!(viewer.handle_key(key(KeyCode::Esc)));
+        assert!(viewer.open.is_none());
+        assert!(!viewer.handle_key(key(KeyCode::Esc)));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
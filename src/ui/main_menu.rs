@@ -1,20 +1,48 @@
 use super::app::App;
+use super::events::{CrosstermEvents, EventSource};
+use super::app::AppAction;
 use super::home_state::{HomeState, SystemStats};
-use super::render::{render_home_view, render_loading_spinner, render_tab_bar, render_theme_selector, ui_in_area};
+use super::log_viewer::LogViewer;
+use super::queue::{OperationQueue, QueueCommand};
+use super::refresh::{self, Dataset, RefreshScheduler};
+use super::render::{
+    render_home_view, render_loading_spinner, render_log_viewer, render_queue_review, render_settings_view, render_status_bar, render_tab_bar, render_theme_selector, render_too_small, ui_in_area, StatusBar,
+};
+use super::settings_state::{SettingsRow, SettingsState};
 use super::spinner::LoadingState;
+use super::terminal::{take_interrupt, TerminalGuard};
 use super::theme::Theme;
-use super::types::{ActionType, ViewType};
+use super::types::{ActionType, LastOperation, SystemUpdateWindow, ViewType};
 use crate::config;
-use crate::package::PackageManager;
+use crate::oplog::OperationLog;
+use crate::package::deps::is_protected;
+use crate::package::news;
+use crate::package::{
+    runner, Backend, CachedPackages, InstallOptions, InstalledDetails, PackageManager, PmgrError, PreviewSource, UpgradablePackage,
+};
 use anyhow::Result;
 use crossterm::{
-    event::{self, poll, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, layout::{Constraint, Direction, Layout}, Terminal};
+use std::collections::{HashMap, HashSet};
 use std::io;
-use std::time::Duration;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Scheduled refreshes keep running this long after leaving the view that shows the data
+const RECENTLY_VISIBLE: Duration = Duration::from_secs(5 * 60);
+
+/// Result of a background data refresh
+enum RefreshResult {
+    Stats { installed: usize, available: usize },
+    Updates(usize),
+    News(usize),
+    Failed(Dataset),
+}
 
 /// Actions that can be requested during event handling
 enum Action {
@@ -23,6 +51,7 @@ enum Action {
     SwitchView(ViewType),
     RefreshView,
     RefreshHomeStats,
+    SettingChanged(SettingsRow),
 }
 
 /// Pending data load state
@@ -32,6 +61,15 @@ enum PendingLoad {
     Install,
     Remove,
     List,
+    Updates,
+}
+
+/// Package list read on a background thread for a package view
+enum PackageLoad {
+    Available(Result<CachedPackages, String>),
+    Installed(Result<Vec<String>, String>),
+    InstalledDetails(Result<Vec<InstalledDetails>, String>),
+    Upgradable(Result<Vec<UpgradablePackage>, String>),
 }
 
 /// Enum to represent different view states in the main menu
@@ -40,89 +78,154 @@ pub enum ViewState {
     Install(App),
     Remove(App),
     List(App),
+    Updates(App),
+    Settings(SettingsState),
 }
 
 /// Main menu coordinator that manages navigation between views
 pub struct MainMenu {
     current_view: ViewState,
     selected_tab: usize,
+    // Package views left for another tab, restored with their search, scroll, selections and previews
+    parked_views: HashMap<ViewType, App>,
     package_manager: PackageManager,
     // Cache to avoid reloading
     cached_installed: Option<Vec<String>>,
+    // Sizes and install dates of the installed packages (for sorting the Remove/List views)
+    cached_installed_details: Option<Vec<InstalledDetails>>,
+    // Pending upgrades for the Updates view (dropped after any install or update)
+    cached_upgradable: Option<Vec<UpgradablePackage>>,
+    // Available packages read so far (from the disk cache or pacman)
+    cached_available: Option<CachedPackages>,
+    // Bypass the available package disk cache on the next Install load
+    refresh_available: bool,
+    settings: config::Settings,
     // Theme system
     theme: Theme,
     theme_selector_active: bool,
     theme_selector_selected: usize,
+    // Operation log browser (Ctrl+L), drawn over everything
+    log_viewer: Option<LogViewer>,
+    // Installs and removals marked with `a` in the Install/Remove tabs, applied from the review (Alt+Q)
+    queue: OperationQueue,
+    // How the last operation ended, for the status bar
+    last_operation: Option<LastOperation>,
     // Loading state
     loading_state: LoadingState,
     pending_load: PendingLoad,
+    // Background refreshes of the Home view data
+    stats: SystemStats,
+    scheduler: RefreshScheduler,
+    refresh_tx: Sender<RefreshResult>,
+    refresh_rx: Receiver<RefreshResult>,
+    home_last_visible: Instant,
+    // Package lists loading in the background (at most one thread per list)
+    load_tx: Sender<PackageLoad>,
+    load_rx: Receiver<PackageLoad>,
+    loading_available: bool,
+    loading_installed: bool,
+    loading_details: bool,
+    loading_upgradable: bool,
 }
 
 impl MainMenu {
     pub fn new() -> Result<Self> {
-        let package_manager = PackageManager::new();
+        let mut menu = Self::with_settings(PackageManager::new()?, config::load_settings());
+        if let Some(path) = refresh::state_path() {
+            menu.scheduler.record_to(path);
+        }
+        Ok(menu)
+    }
+
+    fn with_settings(package_manager: PackageManager, settings: config::Settings) -> Self {
         let home_state = HomeState::new();
-        let settings = config::load_settings();
+        let (refresh_tx, refresh_rx) = mpsc::channel();
+        let (load_tx, load_rx) = mpsc::channel();
 
-        Ok(Self {
+        Self {
             current_view: ViewState::Home(home_state),
             selected_tab: ViewType::Home as usize,
+            parked_views: HashMap::new(),
             package_manager,
             cached_installed: None,
+            cached_installed_details: None,
+            cached_upgradable: None,
+            cached_available: None,
+            refresh_available: false,
             theme: settings.theme,
             theme_selector_active: false,
             theme_selector_selected: settings.theme as usize,
+            log_viewer: None,
+            queue: OperationQueue::default(),
+            last_operation: None,
             loading_state: LoadingState::new(),
             pending_load: PendingLoad::Home, // Load home stats on start
-        })
+            stats: SystemStats::default(),
+            scheduler: RefreshScheduler::new(settings.refresh_intervals.clone()),
+            refresh_tx,
+            refresh_rx,
+            home_last_visible: Instant::now(),
+            load_tx,
+            load_rx,
+            loading_available: false,
+            loading_installed: false,
+            loading_details: false,
+            loading_upgradable: false,
+            settings,
+        }
     }
 
     /// Main entry point - runs the interactive menu
     pub fn run() -> Result<()> {
-        // Setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-
-        // Create main menu and run
+        // Create main menu before touching the terminal so startup errors print cleanly
         let mut menu = MainMenu::new()?;
-        let result = menu.run_loop(&mut terminal);
 
-        // Restore terminal
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
-        terminal.show_cursor()?;
+        // The terminal is restored when the guard drops, also on errors and panics
+        let _guard = TerminalGuard::enter(true)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
 
-        result
+        menu.run_loop(&mut terminal, &mut CrosstermEvents)
     }
 
     /// Main event loop
-    fn run_loop<B: ratatui::backend::Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+    fn run_loop<B: ratatui::backend::Backend>(
+        &mut self,
+        terminal: &mut Terminal<B>,
+        events: &mut impl EventSource,
+    ) -> Result<()> {
         loop {
             // Update spinner animation
             self.loading_state.tick();
 
+            // Apply finished background refreshes and start the ones that are due
+            self.check_refresh_results();
+            self.run_scheduled_refreshes();
+            self.check_package_loads();
+
             // Render current view FIRST (so spinner is visible)
             terminal.draw(|f| {
+                // Get theme palette (the highlighted one while choosing, as a live preview)
+                let palette = if self.theme_selector_active {
+                    Theme::all()[self.theme_selector_selected].palette()
+                } else {
+                    self.theme.palette()
+                };
+
+                if render_too_small(f, &palette) {
+                    return;
+                }
+
                 let chunks = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints([
                         Constraint::Length(3), // Tab bar
                         Constraint::Min(0),    // Content
+                        Constraint::Length(1), // Status bar
                     ])
                     .split(f.area());
 
-                // Get theme palette
-                let palette = self.theme.palette();
-
                 // Render tab bar
-                render_tab_bar(f, chunks[0], self.selected_tab, &palette);
+                render_tab_bar(f, chunks[0], self.selected_tab, self.queue.indicator().as_deref(), &palette);
 
                 // Render current view content
                 match &mut self.current_view {
@@ -138,13 +241,33 @@ impl MainMenu {
                     ViewState::List(app) => {
                         ui_in_area(f, app, "Browse installed packages (ESC to go back): ", chunks[1], &palette);
                     }
+                    ViewState::Updates(app) => {
+                        ui_in_area(f, app, "Select upgrades (TAB: multi-select, ENTER: confirm): ", chunks[1], &palette);
+                    }
+                    ViewState::Settings(state) => {
+                        render_settings_view(f, chunks[1], state, &self.settings, &palette);
+                    }
                 }
+                let app = match &self.current_view {
+                    ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) => Some(app),
+                    _ => None,
+                };
+                let status = StatusBar { backend: Some(self.package_manager.backend()), app, last_operation: self.last_operation.as_ref() };
+                render_status_bar(f, chunks[2], &status, &palette);
 
                 // Render theme selector on top if active
                 if self.theme_selector_active {
                     render_theme_selector(f, &palette, self.theme_selector_selected);
                 }
 
+                if self.queue.reviewing {
+                    render_queue_review(f, &self.queue, &palette);
+                }
+
+                if let Some(viewer) = &mut self.log_viewer {
+                    render_log_viewer(f, viewer, &palette);
+                }
+
                 // Render loading spinner overlay if active
                 if self.loading_state.is_active() {
                     render_loading_spinner(f, &self.loading_state, &palette);
@@ -168,298 +291,426 @@ impl MainMenu {
                     PendingLoad::List => {
                         self.perform_list_load()?;
                     }
+                    PendingLoad::Updates => {
+                        self.perform_updates_load()?;
+                    }
                     PendingLoad::None => {}
                 }
                 // After load completes, continue to next iteration to render the data
                 continue;
             }
 
-            // Handle events with polling
-            if poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    // Handle global shortcuts first (work in any view)
-                    let handled_globally = match (key.code, key.modifiers) {
-                        // Show theme selector with Ctrl+T
-                        (KeyCode::Char('t'), KeyModifiers::CONTROL) => {
-                            self.theme_selector_active = !self.theme_selector_active;
-                            if self.theme_selector_active {
-                                // Reset selection to current theme when opening
-                                self.theme_selector_selected = self.theme as usize;
-                            }
-                            true
-                        }
-                        // Show help with '?'
-                        (KeyCode::Char('?'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
-                            if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
-                                app.help_visible = !app.help_visible;
-                                if !app.help_visible {
-                                    app.help_scroll = 0;
-                                }
-                            }
-                            true
+            // Handle events with polling (waking up in time for a deferred search filter)
+            let timeout = match &self.current_view {
+                ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) => {
+                    app.poll_timeout(Duration::from_millis(100))
+                }
+                ViewState::Home(_) | ViewState::Settings(_) => Duration::from_millis(100),
+            };
+            // A SIGINT from outside (raw mode reads Ctrl+C as a key) closes pmgr
+            if take_interrupt() {
+                return Ok(());
+            }
+            let event = events.next_event(timeout)?;
+            // Mouse wheel and clicks in the package views (not behind the theme selector)
+            if let Some(Event::Mouse(mouse)) = event {
+                match &mut self.current_view {
+                    ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app)
+                        if !self.theme_selector_active && self.log_viewer.is_none() =>
+                    {
+                        app.handle_mouse(mouse);
+                    }
+                    _ => {}
+                }
+            }
+            if let Some(Event::Key(key)) = event {
+                // The log browser takes every key until it is closed
+                if let Some(viewer) = &mut self.log_viewer {
+                    if !viewer.handle_key(key) {
+                        self.log_viewer = None;
+                    }
+                    continue;
+                }
+
+                // The queue review takes every key until it is closed
+                if self.queue.reviewing {
+                    if self.queue.handle_key(key) == QueueCommand::Execute {
+                        self.execute_queue();
+                    }
+                    continue;
+                }
+
+                // Handle global shortcuts first (work in any view)
+                let handled_globally = match (key.code, key.modifiers) {
+                    // Show theme selector with Ctrl+T
+                    (KeyCode::Char('t'), KeyModifiers::CONTROL) => {
+                        self.theme_selector_active = !self.theme_selector_active;
+                        if self.theme_selector_active {
+                            // Reset selection to current theme when opening
+                            self.theme_selector_selected = self.theme as usize;
                         }
-                        // System update with Ctrl+U
-                        (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
-                            // Start system update with pkexec (polkit will handle authentication)
-                            if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
-                                app.update_window.start_update();
+                        true
+                    }
+                    // Show help with '?' (unless it is being typed into the search)
+                    (KeyCode::Char('?'), KeyModifiers::NONE | KeyModifiers::SHIFT) if !self.search_focused() => {
+                        if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
+                            app.help_visible = !app.help_visible;
+                            if !app.help_visible {
+                                app.help_scroll = 0;
                             }
-                            true
                         }
-                        _ => false,
-                    };
-
-                    // If handled globally, skip view-specific handling
-                    if handled_globally {
-                        // Check for preview updates in package views
-                        if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
-                            app.check_preview_updates();
-                            app.update_window.check_updates();
-
-                            // Auto-close update window if completed successfully
-                            if app.update_window.should_auto_close() {
-                                app.update_window.close(false); // Not cancelled by user
+                        true
+                    }
+                    // System update with Ctrl+U (which clears the search while typing one)
+                    (KeyCode::Char('u'), KeyModifiers::CONTROL) if !self.search_focused() => {
+                        // Start system update with pkexec (polkit will handle authentication)
+                        if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
+                            app.request_system_update(self.settings.held_packages());
+                        }
+                        true
+                    }
+                    // Browse the operation logs with Ctrl+L
+                    (KeyCode::Char('l'), KeyModifiers::CONTROL) => {
+                        self.log_viewer = Some(LogViewer::load());
+                        true
+                    }
+                    // Review the queued installs and removals with Alt+Q
+                    (KeyCode::Char('q'), KeyModifiers::ALT) => {
+                        if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &self.current_view {
+                            if !app.update_window.active && !app.confirm_dialog.active {
+                                self.queue.open();
                             }
+                        }
+                        true
+                    }
+                    // Refresh the sync databases with Ctrl+Y
+                    (KeyCode::Char('y'), KeyModifiers::CONTROL) => {
+                        if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
+                            app.update_window.start_refresh();
+                        }
+                        true
+                    }
+                    _ => false,
+                };
 
-                            // Clear terminal if window was just closed
-                            if app.update_window.just_closed {
-                                terminal.clear()?;
+                // If handled globally, skip view-specific handling
+                if handled_globally {
+                    let mut databases_refreshed = false;
+                    // Check for preview updates in package views
+                    if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
+                        app.check_preview_updates();
+                        app.check_provides_updates();
+                        app.check_news_updates();
+                        app.alert.expire(Instant::now());
+                        app.update_window.check_updates();
+
+                        // Auto-close update window if completed successfully
+                        if app.update_window.should_auto_close() {
+                            app.update_window.close(false); // Not cancelled by user
+                        }
 
-                                // Show appropriate alert based on operation result
-                                if app.update_window.cancelled_by_user {
-                                    app.alert.show(super::types::AlertType::Info, "⚠ Operation cancelled by user".to_string());
-                                } else if app.update_window.was_successful {
-                                    // Show success message based on operation type
-                                    let message = if let Some(ref op_type) = app.update_window.operation_type {
-                                        if op_type == "system_update" {
-                                            "✓ System updated successfully".to_string()
-                                        } else {
-                                            "✓ Operation completed successfully".to_string()
-                                        }
+                        // Clear terminal if window was just closed
+                        if app.update_window.just_closed {
+                            terminal.clear()?;
+                            self.last_operation = Some(app.update_window.outcome());
+
+                            // Show appropriate alert based on operation result
+                            if app.update_window.cancelled_by_user {
+                                app.alert.show(super::types::AlertType::Info, "⚠ Operation cancelled by user".to_string());
+                            } else if app.update_window.summary.success {
+                                // Show success message based on operation type
+                                let message = if let Some(ref op_type) = app.update_window.operation_type {
+                                    if op_type == "system_update" {
+                                        databases_refreshed = true;
+                                        "✓ System updated successfully".to_string()
+                                    } else if op_type == "refresh_databases" {
+                                        databases_refreshed = true;
+                                        "✓ Package databases refreshed".to_string()
                                     } else {
                                         "✓ Operation completed successfully".to_string()
-                                    };
-                                    app.alert.show(super::types::AlertType::Success, message);
-                                } else if let Some(_) = app.update_window.operation_type {
-                                    app.alert.show(super::types::AlertType::Error, "✗ Operation failed".to_string());
-                                }
-
-                                app.update_window.clear_just_closed_flag();
+                                    }
+                                } else {
+                                    "✓ Operation completed successfully".to_string()
+                                };
+                                let message = format!("{}\n{}", message, app.update_window.summary.describe());
+                                app.alert.show(super::types::AlertType::Success, message);
+                            } else if app.update_window.operation_type.is_some() {
+                                app.alert.show(super::types::AlertType::Error, app.update_window.failure_message());
                             }
+
+                            app.update_window.clear_just_closed_flag();
                         }
-                        continue;
                     }
+                    if databases_refreshed {
+                        self.invalidate_available();
+                    }
+                    continue;
+                }
 
-                    // Handle modal windows (update, help, confirm) in package views
-                    if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
-                        // Update window is active
-                        if app.update_window.active {
-                            match (key.code, key.modifiers) {
-                                (KeyCode::Char('x'), KeyModifiers::ALT) => {
-                                    if app.update_window.has_error || app.update_window.completed {
-                                        app.update_window.close(true); // Cancelled by user
-                                    }
-                                }
-                                _ => {} // Ignore other keys while update window is active
-                            }
-                            continue;
+                // Theme selector is active
+                if self.theme_selector_active {
+                    match (key.code, key.modifiers) {
+                        (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
+                            let num_themes = Theme::all().len();
+                            self.theme_selector_selected = (self.theme_selector_selected + 1) % num_themes;
                         }
-
-                        // Confirmation dialog is active
-                        if app.confirm_dialog.active {
-                            match (key.code, key.modifiers) {
-                                // Confirm with Y or Enter
-                                (KeyCode::Char('y'), KeyModifiers::NONE | KeyModifiers::SHIFT)
-                                | (KeyCode::Enter, _) => {
-                                    app.confirm_dialog.confirm();
-                                }
-                                // Cancel with N or ESC
-                                (KeyCode::Char('n'), KeyModifiers::NONE | KeyModifiers::SHIFT)
-                                | (KeyCode::Esc, _) => {
-                                    app.confirm_dialog.cancel();
-                                }
-                                // Scroll down
-                                (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                                    app.confirm_dialog.scroll_down();
-                                }
-                                // Scroll up
-                                (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                                    app.confirm_dialog.scroll_up();
-                                }
-                                _ => {} // Ignore other keys while dialog is active
-                            }
-                            continue;
+                        (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
+                            let num_themes = Theme::all().len();
+                            self.theme_selector_selected = if self.theme_selector_selected == 0 {
+                                num_themes - 1
+                            } else {
+                                self.theme_selector_selected - 1
+                            };
                         }
-
-                        // Help screen is active
-                        if app.help_visible {
-                            match (key.code, key.modifiers) {
-                                (KeyCode::Char('?'), KeyModifiers::NONE | KeyModifiers::SHIFT)
-                                | (KeyCode::Esc, _) => {
-                                    app.help_visible = false;
-                                    app.help_scroll = 0; // Reset scroll when closing
-                                }
-                                // Scroll down
-                                (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                                    app.help_scroll = app.help_scroll.saturating_add(1);
+                        (KeyCode::Enter, _) => {
+                            // Apply theme
+                            self.theme = Theme::all()[self.theme_selector_selected];
+
+                            // Save to config (the theme still applies for this session if that fails)
+                            self.settings.theme = self.theme;
+                            if let Err(e) = config::save_settings(&self.settings) {
+                                if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
+                                    app.alert.show(super::types::AlertType::Error, format!("Failed to save the theme: {}", e));
                                 }
-                                // Scroll up
-                                (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                                    app.help_scroll = app.help_scroll.saturating_sub(1);
-                                }
-                                _ => {} // Ignore other keys while help is visible
                             }
-                            continue;
-                        }
 
-                        // Alert is active
-                        if app.alert.active {
-                            // Any key closes the alert
-                            app.alert.close();
-                            continue;
+                            self.theme_selector_active = false;
                         }
+                        // Closing without Enter goes back to the current theme
+                        (KeyCode::Esc, _) => {
+                            self.theme_selector_active = false;
+                        }
+                        _ => {}
                     }
+                    continue; // Don't process other keys when modal is active
+                }
 
-                    // Theme selector is active
-                    if self.theme_selector_active {
+                // Handle view-specific events
+                let action = match &mut self.current_view {
+                    ViewState::Home(_) => {
+                        // Home view key handling
                         match (key.code, key.modifiers) {
-                            (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                                let num_themes = Theme::all().len();
-                                self.theme_selector_selected = (self.theme_selector_selected + 1) % num_themes;
+                            // Switch tabs
+                            (KeyCode::Char('1'), _) => Action::SwitchView(ViewType::Home),
+                            (KeyCode::Char('2'), _) => Action::SwitchView(ViewType::Install),
+                            (KeyCode::Char('3'), _) => Action::SwitchView(ViewType::Remove),
+                            (KeyCode::Char('4'), _) => Action::SwitchView(ViewType::List),
+                            (KeyCode::Char('5'), _) => Action::SwitchView(ViewType::Updates),
+                            (KeyCode::Char('6'), _) => Action::SwitchView(ViewType::Settings),
+                            // Exit on ESC
+                            (KeyCode::Esc, _) => Action::Exit,
+                            // Refresh stats
+                            (KeyCode::Char('r'), KeyModifiers::CONTROL) => Action::RefreshHomeStats,
+                            _ => Action::None,
+                        }
+                    }
+                    // Typing a text setting
+                    ViewState::Settings(state) if state.input.is_some() => {
+                        let mut return_action = Action::None;
+                        let input = state.input.get_or_insert_with(String::new);
+                        match key.code {
+                            KeyCode::Char(c) => input.push(c),
+                            KeyCode::Backspace => {
+                                input.pop();
                             }
-                            (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                                let num_themes = Theme::all().len();
-                                self.theme_selector_selected = if self.theme_selector_selected == 0 {
-                                    num_themes - 1
+                            KeyCode::Esc => state.input = None,
+                            KeyCode::Enter => {
+                                if let Some(row) = state.commit_input(&mut self.settings) {
+                                    return_action = Action::SettingChanged(row);
+                                }
+                            }
+                            _ => {}
+                        }
+                        return_action
+                    }
+                    ViewState::Settings(state) => match (key.code, key.modifiers) {
+                        (KeyCode::Char('1'), _) => Action::SwitchView(ViewType::Home),
+                        (KeyCode::Char('2'), _) => Action::SwitchView(ViewType::Install),
+                        (KeyCode::Char('3'), _) => Action::SwitchView(ViewType::Remove),
+                        (KeyCode::Char('4'), _) => Action::SwitchView(ViewType::List),
+                        (KeyCode::Char('5'), _) => Action::SwitchView(ViewType::Updates),
+                        (KeyCode::Char('6'), _) => Action::None,
+                        (KeyCode::Esc, _) => Action::SwitchView(ViewType::Home),
+                        (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
+                            state.next();
+                            Action::None
+                        }
+                        (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
+                            state.previous();
+                            Action::None
+                        }
+                        (KeyCode::Enter, _) | (KeyCode::Char(' '), _) => {
+                            let backends = Self::installed_backends();
+                            match state.activate(&mut self.settings, &backends) {
+                                Some(row) => Action::SettingChanged(row),
+                                None => Action::None,
+                            }
+                        }
+                        _ => Action::None,
+                    },
+                    // Package views handle their keys themselves and say what is left to do
+                    ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) => match app.handle_key(key) {
+                        AppAction::None | AppAction::Confirmed => Action::None,
+                        AppAction::Cancel => Action::SwitchView(ViewType::Home),
+                        AppAction::SwitchTab(view_type) => Action::SwitchView(view_type),
+                        AppAction::Refresh => Action::RefreshView,
+                        AppAction::StartUpdate => {
+                            app.request_system_update(self.settings.held_packages());
+                            Action::None
+                        }
+                        AppAction::Replan => {
+                            Self::plan_install(&self.package_manager, app);
+                            Action::None
+                        }
+                        AppAction::Confirm(selected) => {
+                            if app.action_type == ActionType::Upgrade {
+                                Self::confirm_upgrade(app, selected);
+                            } else if !selected.is_empty() {
+                                let (warnings, badges) = if app.action_type == ActionType::Install {
+                                    (
+                                        Self::pin_warnings(&self.package_manager, &self.settings.pins, &selected),
+                                        app.aur_badges(&selected, self.settings.aur_stale_days),
+                                    )
                                 } else {
-                                    self.theme_selector_selected - 1
+                                    Default::default()
                                 };
-                            }
-                            (KeyCode::Enter, _) => {
-                                // Apply theme
-                                self.theme = Theme::all()[self.theme_selector_selected];
-
-                                // Save to config
-                                let settings = config::Settings {
-                                    theme: self.theme,
+                                // Repositories of the packages to install, dependents of the ones to remove
+                                // (sizes are resolved in the background)
+                                let infos = match app.action_type {
+                                    ActionType::Install => self.package_manager.get_info_batch(&selected, false).unwrap_or_default(),
+                                    ActionType::Remove => self.package_manager.get_info_batch(&selected, true).unwrap_or_default(),
+                                    ActionType::Update | ActionType::Upgrade => HashMap::new(),
                                 };
-                                if let Err(e) = config::save_settings(&settings) {
-                                    // Could show error alert, but for now just ignore
-                                    eprintln!("Failed to save theme: {}", e);
+                                app.confirm_dialog.show(app.action_type, selected);
+                                app.confirm_dialog.summarize(&infos);
+                                app.resolve_transaction();
+                                app.confirm_dialog.warnings = warnings;
+                                app.confirm_dialog.badges = badges;
+                                app.confirm_dialog.require_ack = self.settings.confirm_aur_warnings;
+                                if app.action_type == ActionType::Install {
+                                    app.confirm_dialog.flag_ignored(&self.settings.ignored_packages);
+                                    let installed = self
+                                        .package_manager
+                                        .installed_versions(&app.confirm_dialog.packages)
+                                        .unwrap_or_default();
+                                    app.confirm_dialog.flag_installed(&installed, &infos);
+                                    app.install_options.needed = self.settings.always_needed;
+                                    Self::plan_install(&self.package_manager, app);
+                                } else if app.action_type == ActionType::Remove {
+                                    app.confirm_dialog.command =
+                                        Some(format!("pacman {}", app.remove_options.flag()));
+                                    app.confirm_dialog.flag_removal_risks(&infos, &self.settings.protected_packages);
                                 }
-
-                                self.theme_selector_active = false;
                             }
-                            (KeyCode::Esc, _) => {
-                                self.theme_selector_active = false;
+                            Action::None
+                        }
+                        // Cycle the repository filter (Install view), remembered between sessions
+                        AppAction::RepoFilterChanged(filter) => {
+                            self.settings.repo_filters.insert("install".to_string(), filter);
+                            if let Err(e) = config::save_settings(&self.settings) {
+                                app.alert.show(super::types::AlertType::Error, format!("Failed to save the filter: {}", e));
                             }
-                            _ => {}
+                            Action::None
                         }
-                        continue; // Don't process other keys when modal is active
-                    }
-
-                    // Handle view-specific events
-                    let mut action = Action::None;
-                    match &mut self.current_view {
-                        ViewState::Home(_) => {
-                            // Home view key handling
-                            action = match (key.code, key.modifiers) {
-                                // Switch tabs
-                                (KeyCode::Char('1'), _) => Action::SwitchView(ViewType::Home),
-                                (KeyCode::Char('2'), _) => Action::SwitchView(ViewType::Install),
-                                (KeyCode::Char('3'), _) => Action::SwitchView(ViewType::Remove),
-                                (KeyCode::Char('4'), _) => Action::SwitchView(ViewType::List),
-                                // Exit on ESC
-                                (KeyCode::Esc, _) => Action::Exit,
-                                // Refresh stats
-                                (KeyCode::Char('r'), KeyModifiers::CONTROL) => Action::RefreshHomeStats,
-                                _ => Action::None,
-                            };
+                        // The sort order is remembered per tab
+                        AppAction::SortChanged(mode) => {
+                            self.settings.sort_modes.insert(Self::sort_key(self.selected_tab).to_string(), mode);
+                            if let Err(e) = config::save_settings(&self.settings) {
+                                app.alert.show(super::types::AlertType::Error, format!("Failed to save the sort order: {}", e));
+                            }
+                            Action::None
                         }
-                        ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) => {
-                            // Package view key handling
-                            action = match (key.code, key.modifiers) {
-                                // Switch tabs
-                                (KeyCode::Char('1'), _) => Action::SwitchView(ViewType::Home),
-                                (KeyCode::Char('2'), _) => Action::SwitchView(ViewType::Install),
-                                (KeyCode::Char('3'), _) => Action::SwitchView(ViewType::Remove),
-                                (KeyCode::Char('4'), _) => Action::SwitchView(ViewType::List),
-                                // Go back to home on ESC (if not in search mode)
-                                (KeyCode::Esc, _) => {
-                                    if app.search_query.is_empty() {
-                                        Action::SwitchView(ViewType::Home)
-                                    } else {
-                                        app.search_query.clear();
-                                        app.filter_items();
-                                        Action::None
-                                    }
-                                }
-                                // Refresh current view data
-                                (KeyCode::Char('r'), KeyModifiers::CONTROL) => Action::RefreshView,
-                                // Enter to confirm selection
-                                (KeyCode::Enter, _) => {
-                                    let selected = app.get_selected_items();
-                                    if !selected.is_empty() {
-                                        app.confirm_dialog.show(app.action_type, selected);
+                        // Preview pane size, the same in every tab and remembered between sessions
+                        AppAction::PreviewResized(ratio) => {
+                            for parked in self.parked_views.values_mut() {
+                                parked.set_preview_ratio(ratio);
+                            }
+                            self.settings.preview_ratio = ratio;
+                            if let Err(e) = config::save_settings(&self.settings) {
+                                app.alert.show(super::types::AlertType::Error, format!("Failed to save the preview size: {}", e));
+                            }
+                            Action::None
+                        }
+                        // The preview pane is hidden or shown in every tab
+                        AppAction::PreviewToggled(enabled) => {
+                            for parked in self.parked_views.values_mut() {
+                                parked.set_preview_enabled(enabled);
+                            }
+                            self.settings.preview_enabled = enabled;
+                            if let Err(e) = config::save_settings(&self.settings) {
+                                app.alert.show(super::types::AlertType::Error, format!("Failed to save the preview setting: {}", e));
+                            }
+                            Action::None
+                        }
+                        AppAction::Copy => {
+                            app.yank(self.settings.copy_with_repository);
+                            Action::None
+                        }
+                        // Queue the selection instead of applying it (Install and Remove tabs)
+                        AppAction::Queue => {
+                            let selected = app.get_selected_items();
+                            let queueable = self.selected_tab == ViewType::Install as usize || self.selected_tab == ViewType::Remove as usize;
+                            if queueable && !selected.is_empty() {
+                                let mut skipped = Vec::new();
+                                let added = if app.action_type == ActionType::Install {
+                                    // AUR packages are built with the helper in the terminal, which a queue can't do
+                                    let (aur, official) = self.package_manager.separate_packages(&selected);
+                                    if !aur.is_empty() {
+                                        skipped.push(format!("AUR packages are installed on their own (Enter): {}", aur.join(", ")));
                                     }
-                                    Action::None
-                                }
-                                // Handle other navigation keys
-                                (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                                    app.next();
-                                    Action::None
-                                }
-                                (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                                    app.previous();
-                                    Action::None
-                                }
-                                (KeyCode::Tab, _) => {
-                                    app.toggle_select();
-                                    Action::None
-                                }
-                                (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
-                                    // Don't add if it's a tab switch key
-                                    if !matches!(c, '1' | '2' | '3' | '4') {
-                                        app.search_query.push(c);
-                                        app.filter_items();
+                                    self.queue.add(ActionType::Install, &official)
+                                } else {
+                                    // Protected packages need the typed confirmation of the Remove tab
+                                    let (protected, removable): (Vec<String>, Vec<String>) = selected
+                                        .into_iter()
+                                        .partition(|p| is_protected(p, &self.settings.protected_packages));
+                                    if !protected.is_empty() {
+                                        skipped.push(format!("Protected packages are removed on their own (Enter): {}", protected.join(", ")));
                                     }
-                                    Action::None
-                                }
-                                (KeyCode::Backspace, _) => {
-                                    app.search_query.pop();
-                                    app.filter_items();
-                                    Action::None
-                                }
-                                // Layout switching
-                                (KeyCode::Char('o'), KeyModifiers::ALT) => {
-                                    app.layout.toggle_to_horizontal();
-                                    Action::None
-                                }
-                                (KeyCode::Char('v'), KeyModifiers::ALT) => {
-                                    app.layout.toggle_to_vertical();
-                                    Action::None
+                                    self.queue.add(ActionType::Remove, &removable)
+                                };
+                                app.clear_selection();
+                                let mut message = format!("Queued {} package(s), Alt+Q to review and apply", added);
+                                for line in skipped {
+                                    message.push('\n');
+                                    message.push_str(&line);
                                 }
-                                _ => Action::None,
-                            };
+                                app.alert.show(super::types::AlertType::Info, message);
+                            }
+                            Action::None
                         }
-                    }
+                    },
+                };
 
-                    // Execute the action after match ends
-                    match action {
-                        Action::Exit => return Ok(()),
-                        Action::SwitchView(view_type) => self.switch_to_view(view_type)?,
-                        Action::RefreshView => self.refresh_current_view()?,
-                        Action::RefreshHomeStats => self.load_home_stats()?,
-                        Action::None => {}
+                // Execute the action after match ends
+                match action {
+                    Action::Exit => return Ok(()),
+                    Action::SwitchView(view_type) => self.switch_to_view(view_type)?,
+                    Action::RefreshView => {
+                        // Keep what was selected; items no longer listed are dropped once the view reloads
+                        let selected = match &mut self.current_view {
+                            ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) => {
+                                std::mem::take(&mut app.selected)
+                            }
+                            ViewState::Home(_) | ViewState::Settings(_) => HashSet::new(),
+                        };
+                        self.refresh_current_view()?;
+                        if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
+                            app.selected = selected;
+                        }
                     }
+                    Action::RefreshHomeStats => self.refresh_home_stats(),
+                    Action::SettingChanged(row) => self.apply_setting(row),
+                    Action::None => {}
                 }
             }
 
             // Check if confirmation dialog was confirmed and start operation
-            if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
+            if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
                 if app.confirm_dialog.is_confirmed() {
                     let packages = app.confirm_dialog.packages.clone();
-                    let action_type = app.action_type;
+                    let action_type = app.confirm_dialog.action_type;
+                    let install_options = app.install_options;
 
                     // Reset confirmation dialog first
                     app.confirm_dialog.cancel();
@@ -471,16 +722,19 @@ impl MainMenu {
 
                             // Handle official packages first (if any) using pkexec within TUI
                             if !official_packages.is_empty() {
-                                app.update_window.start_install_official(&official_packages);
+                                app.update_window.start_install_official(&official_packages, &install_options);
                             }
 
-                            // Handle AUR packages using handoff (exit TUI, run yay, return)
-                            if !aur_packages.is_empty() {
+                            // Handle AUR packages using handoff (exit TUI, run the AUR helper, return)
+                            if !aur_packages.is_empty() && !self.package_manager.backend().supports_aur() {
+                                app.alert.show_error(&PmgrError::BackendMissing("yay".to_string()));
+                            } else if !aur_packages.is_empty() {
                                 // Exit TUI for handoff
                                 disable_raw_mode()?;
                                 execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
 
-                                println!("\n📦 Installing {} AUR package(s) with yay...\n", aur_packages.len());
+                                let helper = self.package_manager.backend().command();
+                                println!("\n📦 Installing {} AUR package(s) with {}...\n", aur_packages.len(), helper);
 
                                 // Extract package names
                                 let pkg_names: Vec<String> = aur_packages
@@ -494,8 +748,8 @@ impl MainMenu {
                                     })
                                     .collect();
 
-                                // Run yay with full control (handoff)
-                                // Ignore SIGINT (Ctrl+C) temporarily so yay can handle it
+                                // Run the AUR helper with full control (handoff)
+                                // Ignore SIGINT (Ctrl+C) temporarily so the helper can handle it
                                 use signal_hook::consts::SIGINT;
                                 use signal_hook::flag;
                                 use std::sync::Arc;
@@ -504,9 +758,20 @@ impl MainMenu {
                                 let term = Arc::new(AtomicBool::new(false));
                                 let _guard = flag::register(SIGINT, Arc::clone(&term));
 
-                                let result = std::process::Command::new("yay")
+                                // The helper owns the terminal, so only the begin/end markers are logged
+                                let helper_args =
+                                    [install_options.args(), pkg_names.clone()].concat();
+                                let op_log = OperationLog::begin(
+                                    "Installing AUR Packages",
+                                    "install_aur",
+                                    &format!("{} -S {}", helper, helper_args.join(" ")),
+                                    self.settings.log_retention,
+                                )
+                                .ok();
+
+                                let result = runner::interactive(helper)
                                     .arg("-S")
-                                    .args(&pkg_names)
+                                    .args(&helper_args)
                                     .stdin(std::process::Stdio::inherit())
                                     .stdout(std::process::Stdio::inherit())
                                     .stderr(std::process::Stdio::inherit())
@@ -536,6 +801,10 @@ impl MainMenu {
                                     }
                                 };
 
+                                if let Some(op_log) = op_log {
+                                    let _ = op_log.end(was_successful);
+                                }
+
                                 println!("\n{}", "=".repeat(60));
 
                                 if was_successful {
@@ -563,17 +832,30 @@ impl MainMenu {
                                     let _ = io::stdin().read_line(&mut input);
                                 }
 
-                                // Re-enter TUI
+                                // Re-enter TUI (the helper's Ctrl+C was meant for it, not for pmgr)
+                                take_interrupt();
                                 enable_raw_mode()?;
                                 execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
                                 terminal.clear()?;
 
                                 // Clear cache and refresh FIRST
-                                self.cached_installed = None;
+                                self.invalidate_installed();
                                 self.refresh_current_view()?;
+                                if was_successful {
+                                    self.start_refresh(Dataset::Updates);
+                                }
+
+                                let (alert_type, message) = if was_successful {
+                                    (super::types::AlertType::Success, format!("✓ installed {} AUR pkgs", aur_packages.len()))
+                                } else if was_cancelled {
+                                    (super::types::AlertType::Info, "⚠ cancelled".to_string())
+                                } else {
+                                    (super::types::AlertType::Error, "✗ AUR install failed".to_string())
+                                };
+                                self.last_operation = Some(LastOperation { alert_type, message, at: Instant::now() });
 
                                 // Show result alert AFTER refresh (so it persists in the new App)
-                                if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
+                                if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
                                     if was_successful {
                                         app.alert.show(super::types::AlertType::Success,
                                             format!("✓ Successfully installed {} AUR package(s)", aur_packages.len()));
@@ -589,7 +871,24 @@ impl MainMenu {
                         }
                         ActionType::Remove => {
                             // For remove, use pkexec pacman directly (works for both AUR and official)
-                            app.update_window.start_remove(&packages);
+                            app.update_window.start_remove(&packages, &app.remove_options);
+                        }
+                        ActionType::Update => {
+                            // The news was read; run the update that was waiting for it
+                            app.start_system_update();
+                        }
+                        ActionType::Upgrade => {
+                            let held = self.settings.held_packages();
+                            if packages.len() == app.items.len() - app.locked.len() {
+                                app.update_window.start_update(&held);
+                            } else {
+                                // Hold back the upgrades that were not picked
+                                let names = Self::upgrade_names(&packages);
+                                let mut ignored = Self::upgrade_names(&app.items);
+                                ignored.retain(|name| !names.contains(name));
+                                ignored.extend(held);
+                                app.update_window.start_upgrade(&names, &ignored);
+                            }
                         }
                     }
                 }
@@ -597,13 +896,19 @@ impl MainMenu {
 
             // Always check for updates (even without key events)
             let mut need_view_refresh = false;
+            let mut databases_refreshed = false;
             let mut pending_alert: Option<(super::types::AlertType, String)> = None;
 
-            if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
+            if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
                 // Check for preview updates (so previews load automatically)
                 app.check_preview_updates();
+                app.check_provides_updates();
+                app.check_news_updates();
+                app.alert.expire(Instant::now());
+                app.apply_pending_filter();
 
-                // Check for update window updates
+                // Check for the confirm dialog's transaction size and update window progress
+                app.confirm_dialog.check_updates();
                 app.update_window.check_updates();
 
                 // Auto-close update window if completed successfully
@@ -615,11 +920,12 @@ impl MainMenu {
                 // Clear terminal if window was just closed to force full redraw
                 if app.update_window.just_closed {
                     terminal.clear()?;
+                    self.last_operation = Some(app.update_window.outcome());
 
                     // Prepare alert based on operation result (will show after refresh)
                     if app.update_window.cancelled_by_user {
                         pending_alert = Some((super::types::AlertType::Info, "⚠ Operation cancelled by user".to_string()));
-                    } else if app.update_window.was_successful {
+                    } else if app.update_window.summary.success {
                         // Show success message based on operation type
                         let message = if let Some(ref op_type) = app.update_window.operation_type {
                             if op_type.starts_with("remove_") {
@@ -628,41 +934,120 @@ impl MainMenu {
                             } else if op_type.starts_with("install_official_") {
                                 let count = op_type.strip_prefix("install_official_").unwrap_or("0");
                                 format!("✓ Successfully installed {} official package(s)", count)
+                            } else if let Some((removed, installed)) = op_type.strip_prefix("queue_").and_then(|counts| counts.split_once('_')) {
+                                format!("✓ Applied the queue: removed {} and installed {} package(s)", removed, installed)
+                            } else if let Some(count) = op_type.strip_prefix("upgrade_") {
+                                databases_refreshed = true;
+                                format!("✓ Successfully upgraded {} package(s)", count)
                             } else if op_type == "system_update" {
+                                // -Syu synced the databases, so the available list is outdated too
+                                databases_refreshed = true;
                                 "✓ System updated successfully".to_string()
+                            } else if op_type == "refresh_databases" {
+                                databases_refreshed = true;
+                                "✓ Package databases refreshed".to_string()
                             } else {
                                 "✓ Operation completed successfully".to_string()
                             }
                         } else {
                             "✓ Operation completed successfully".to_string()
                         };
+                        let message = format!("{}\n{}", message, app.update_window.summary.describe());
                         pending_alert = Some((super::types::AlertType::Success, message));
-                    } else if let Some(_) = app.update_window.operation_type {
+                    } else if app.update_window.operation_type.is_some() {
                         // Operation failed (not cancelled, not successful)
-                        pending_alert = Some((super::types::AlertType::Error, "✗ Operation failed".to_string()));
+                        pending_alert = Some((super::types::AlertType::Error, app.update_window.failure_message()));
                     }
 
                     app.update_window.clear_just_closed_flag();
                 }
             }
 
+            // The Install tab must not keep showing the list read from the old databases
+            if databases_refreshed {
+                self.invalidate_available();
+            }
+
             // Refresh view if needed (after window closes)
             if need_view_refresh {
-                self.cached_installed = None;
+                self.invalidate_installed();
                 self.refresh_current_view()?;
+                // Installed versions changed, so the pending update count is outdated
+                self.start_refresh(Dataset::Updates);
             }
 
             // Show pending alert AFTER refresh (so it persists in the new App)
             if let Some((alert_type, message)) = pending_alert {
-                if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
+                if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
                     app.alert.show(alert_type, message);
                 }
             }
         }
     }
 
-    /// Switch to a different view
+    /// Whether keys are being typed into a package view's search bar
+    fn search_focused(&self) -> bool {
+        match &self.current_view {
+            ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) => {
+                app.search_focused
+            }
+            ViewState::Home(_) | ViewState::Settings(_) => false,
+        }
+    }
+
+    /// Warnings for pinned packages whose version the install would change
+    fn pin_warnings(package_manager: &PackageManager, pins: &config::Pins, packages: &[String]) -> Vec<String> {
+        if pins.is_empty() {
+            return Vec::new();
+        }
+
+        let names: Vec<String> = packages
+            .iter()
+            .map(|p| p.rsplit('/').next().unwrap_or(p).to_string())
+            .collect();
+
+        // AUR packages can't be planned by pacman, so the check is best effort
+        package_manager
+            .plan_install(&names)
+            .map(|plan| config::pin_conflicts(pins, &plan))
+            .unwrap_or_default()
+    }
+
+    /// Switch to a different view, keeping the package view being left for when it is opened again
     fn switch_to_view(&mut self, view_type: ViewType) -> Result<()> {
+        let previous = std::mem::replace(&mut self.current_view, ViewState::Home(HomeState::new()));
+        match previous {
+            ViewState::Install(app) => self.park_view(ViewType::Install, app),
+            ViewState::Remove(app) => self.park_view(ViewType::Remove, app),
+            ViewState::List(app) => self.park_view(ViewType::List, app),
+            ViewState::Updates(app) => self.park_view(ViewType::Updates, app),
+            ViewState::Home(_) | ViewState::Settings(_) => {}
+        }
+
+        match self.parked_views.remove(&view_type) {
+            Some(app) => {
+                self.selected_tab = view_type as usize;
+                self.current_view = match view_type {
+                    ViewType::Install => ViewState::Install(app),
+                    ViewType::Remove => ViewState::Remove(app),
+                    ViewType::List => ViewState::List(app),
+                    _ => ViewState::Updates(app),
+                };
+                Ok(())
+            }
+            None => self.open_view(view_type),
+        }
+    }
+
+    /// Keep a package view for later; views still waiting for their list are built again instead
+    fn park_view(&mut self, view_type: ViewType, app: App) {
+        if !app.loading.is_active() {
+            self.parked_views.insert(view_type, app);
+        }
+    }
+
+    /// Open a freshly loaded view
+    fn open_view(&mut self, view_type: ViewType) -> Result<()> {
         self.selected_tab = view_type as usize;
 
         // Set loading state and pending load
@@ -673,81 +1058,203 @@ impl MainMenu {
                 self.pending_load = PendingLoad::Home;
             }
             ViewType::Install => {
-                self.loading_state.start("Loading available packages".to_string());
-                // Create empty app temporarily
-                self.current_view = ViewState::Install(App::new(
-                    vec![],
-                    true,
-                    Some("echo {} | xargs yay -Si".to_string()),
-                    ActionType::Install,
-                ));
+                // Empty until the packages are loaded (which may happen in the background)
+                self.current_view = ViewState::Install(self.package_app(true, false, ActionType::Install));
                 self.pending_load = PendingLoad::Install;
             }
             ViewType::Remove => {
-                self.loading_state.start("Loading installed packages".to_string());
-                self.current_view = ViewState::Remove(App::new(
-                    vec![],
-                    true,
-                    Some("echo {} | xargs yay -Qi".to_string()),
-                    ActionType::Remove,
-                ));
+                self.current_view = ViewState::Remove(self.package_app(true, true, ActionType::Remove));
                 self.pending_load = PendingLoad::Remove;
             }
             ViewType::List => {
-                self.loading_state.start("Loading installed packages".to_string());
-                self.current_view = ViewState::List(App::new(
-                    vec![],
-                    false,
-                    Some("echo {} | xargs yay -Qi".to_string()),
-                    ActionType::Install,
-                ));
+                self.current_view = ViewState::List(self.package_app(false, true, ActionType::Install));
                 self.pending_load = PendingLoad::List;
             }
+            ViewType::Updates => {
+                // Rows are `name  old → new`, so the configured preview command doesn't apply
+                let mut app = self.package_app(true, true, ActionType::Upgrade);
+                app.preview_source = Some(self.package_manager.backend().info_preview(true));
+                self.current_view = ViewState::Updates(app);
+                self.pending_load = PendingLoad::Updates;
+            }
+            ViewType::Settings => {
+                self.current_view = ViewState::Settings(SettingsState::new(config::settings_path().ok()));
+            }
         }
 
         Ok(())
     }
 
-    /// Load home view statistics
-    fn load_home_stats(&mut self) -> Result<()> {
-        self.loading_state.start("Refreshing system information".to_string());
+    /// An empty package view with the configured layout and preview command
+    fn package_app(&self, multi: bool, installed: bool, action_type: ActionType) -> App {
+        let preview = self
+            .settings
+            .preview_command
+            .as_deref()
+            .and_then(PreviewSource::custom)
+            .unwrap_or_else(|| self.package_manager.backend().info_preview(installed));
+        let mut app = App::new(vec![], multi, Some(preview), action_type);
+        app.layout = self.settings.layout;
+        app.set_preview_ratio(self.settings.preview_ratio);
+        app.preview_enabled = self.settings.preview_enabled;
+        app.preview_timeout = Duration::from_secs(self.settings.preview_timeout_secs);
+        app.update_window.auto_close_delay = self.settings.auto_close_delay.map(Duration::from_secs);
+        app
+    }
+
+    /// Apply the queue in the current package view: removals, then installs
+    fn execute_queue(&mut self) {
+        let (ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app)) = &mut self.current_view else {
+            return;
+        };
+        let (removals, installs) = self.queue.split();
+        let install_options = InstallOptions { needed: self.settings.always_needed, ..Default::default() };
+        app.update_window.start_queue(&removals, &installs, &self.settings.remove_behavior, &install_options);
+        self.queue.clear();
+    }
 
-        // Load stats data
-        let installed = self.package_manager.list_installed()?;
-        let available = self.package_manager.list_available()?;
+    /// Show where the packages to install come from and the commands that will run them
+    /// (official packages in the operation window, AUR ones with the helper)
+    fn plan_install(package_manager: &PackageManager, app: &mut App) {
+        let backend = package_manager.backend();
+        let (aur, official) = package_manager.separate_packages(&app.confirm_dialog.packages);
+        app.confirm_dialog.commands = SystemUpdateWindow::install_commands(&aur, &official, backend, &app.install_options);
+        app.confirm_dialog.split_sources(aur, official, "pacman", backend);
+    }
 
-        // TODO: Implement system update check
-        let updates_available = 0;
+    /// Backends that can be chosen in the Settings tab
+    fn installed_backends() -> Vec<Backend> {
+        [Backend::Pacman, Backend::Yay, Backend::Paru]
+            .into_iter()
+            .filter(|backend| PackageManager::command_exists(backend.command()))
+            .collect()
+    }
 
-        let stats = SystemStats {
-            installed_count: installed.len(),
-            available_count: available.len(),
-            updates_available,
-        };
+    /// Save a setting changed in the Settings tab and apply it right away
+    /// (package tabs kept from before are built again when the preview command changes)
+    fn apply_setting(&mut self, row: SettingsRow) {
+        let mut status = format!("{}: {}", row.label(), row.value(&self.settings));
 
-        // Apply to home state if currently in home view
-        if let ViewState::Home(home_state) = &mut self.current_view {
-            home_state.set_stats(stats);
+        match row {
+            SettingsRow::Theme => {
+                self.theme = self.settings.theme;
+                self.theme_selector_selected = self.theme as usize;
+            }
+            SettingsRow::Backend => match PackageManager::new() {
+                Ok(package_manager) => {
+                    self.package_manager = package_manager;
+                    // Kept tabs preview through the old backend
+                    self.parked_views.clear();
+                }
+                Err(e) => status = format!("{} (not applied: {})", status, e),
+            },
+            SettingsRow::PreviewCommand => self.parked_views.clear(),
+            SettingsRow::Layout => {
+                for app in self.parked_views.values_mut() {
+                    app.layout = self.settings.layout;
+                }
+            }
+            SettingsRow::NoConfirm => {}
         }
 
-        self.loading_state.stop();
-        Ok(())
+        if let Err(e) = config::save_settings(&self.settings) {
+            status = format!("Failed to save settings: {}", e);
+        }
+        if let ViewState::Settings(state) = &mut self.current_view {
+            state.status = Some(status);
+        }
     }
 
-    fn load_home_stats_into(&mut self, home_state: &mut HomeState) -> Result<()> {
-        let installed = self.package_manager.list_installed()?;
-        let available = self.package_manager.list_available()?;
+    /// Force a refresh of the Home view data (coalesces with refreshes already running)
+    fn refresh_home_stats(&mut self) {
+        for dataset in Dataset::ALL {
+            self.start_refresh(dataset);
+        }
+    }
 
-        // TODO: Implement system update check
-        let updates_available = 0;
+    /// Start the background refreshes that are due while the Home view is (or was recently) visible
+    fn run_scheduled_refreshes(&mut self) {
+        let now = Instant::now();
 
-        home_state.set_stats(SystemStats {
-            installed_count: installed.len(),
-            available_count: available.len(),
-            updates_available,
+        let refresh_status = || {
+            Dataset::ALL
+                .iter()
+                .map(|dataset| format!("{}: {}", dataset.label(), self.scheduler.status(*dataset, now)))
+                .collect()
+        };
+        match &mut self.current_view {
+            ViewState::Home(home_state) => {
+                self.home_last_visible = now;
+                home_state.refresh_status = refresh_status();
+            }
+            ViewState::Settings(state) => state.refresh_status = refresh_status(),
+            _ => {}
+        }
+        if now.duration_since(self.home_last_visible) > RECENTLY_VISIBLE {
+            return;
+        }
+
+        for dataset in Dataset::ALL {
+            if self.scheduler.is_due(dataset, now) {
+                self.start_refresh(dataset);
+            }
+        }
+        self.sync_home_stats();
+    }
+
+    /// Refresh a dataset on a background thread
+    fn start_refresh(&mut self, dataset: Dataset) {
+        if !self.scheduler.start(dataset) {
+            return;
+        }
+
+        let tx = self.refresh_tx.clone();
+        thread::spawn(move || {
+            let result = PackageManager::new().ok().and_then(|pm| match dataset {
+                Dataset::Stats => {
+                    let installed = pm.list_installed().ok()?.len();
+                    let available = pm.list_available().ok()?.len();
+                    Some(RefreshResult::Stats { installed, available })
+                }
+                Dataset::Updates => pm.count_upgradable().ok().map(RefreshResult::Updates),
+                Dataset::News => news::fetch_unread().ok().map(|items| RefreshResult::News(items.len())),
+            });
+
+            let _ = tx.send(result.unwrap_or(RefreshResult::Failed(dataset)));
         });
+    }
 
-        Ok(())
+    /// Apply results of finished background refreshes
+    fn check_refresh_results(&mut self) {
+        while let Ok(result) = self.refresh_rx.try_recv() {
+            let dataset = match result {
+                RefreshResult::Stats { installed, available } => {
+                    self.stats.installed_count = Some(installed);
+                    self.stats.available_count = Some(available);
+                    Dataset::Stats
+                }
+                RefreshResult::Updates(count) => {
+                    self.stats.updates_available = Some(count);
+                    Dataset::Updates
+                }
+                RefreshResult::News(count) => {
+                    self.stats.unread_news = Some(count);
+                    Dataset::News
+                }
+                // Failed refreshes are retried at the next interval
+                RefreshResult::Failed(dataset) => dataset,
+            };
+            self.scheduler.finish(dataset, Instant::now());
+            self.sync_home_stats();
+        }
+    }
+
+    /// Show the latest stats (and whether updates are being checked) on the Home view
+    fn sync_home_stats(&mut self) {
+        self.stats.checking_updates = self.scheduler.is_refreshing(Dataset::Updates);
+        if let ViewState::Home(home_state) = &mut self.current_view {
+            home_state.set_stats(self.stats.clone());
+        }
     }
 
     /// Get or load installed packages (with caching)
@@ -761,18 +1268,36 @@ impl MainMenu {
         Ok(packages)
     }
 
-    /// Get or load available packages (no caching for now as it's large)
-    fn get_or_load_available(&self) -> Result<Vec<crate::package::Package>> {
-        self.package_manager.list_available()
+    /// Drop the available package list so the next load reads the package databases again
+    fn invalidate_available(&mut self) {
+        self.cached_available = None;
+        self.refresh_available = true;
+        self.parked_views.remove(&ViewType::Install);
     }
 
-    /// Refresh the current view's data
+    /// Drop everything that depends on the installed packages after an install, removal or update
+    fn invalidate_installed(&mut self) {
+        self.cached_installed = None;
+        self.cached_upgradable = None;
+        self.parked_views.clear();
+    }
+
+    /// Refresh the current view's data, keeping the search and the highlighted package
     fn refresh_current_view(&mut self) -> Result<()> {
+        let kept = match &self.current_view {
+            ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) => {
+                Some((app.search_query.clone(), app.cursor().or_else(|| app.cursor_target.clone())))
+            }
+            ViewState::Home(_) | ViewState::Settings(_) => None,
+        };
+
         match self.selected_tab {
-            0 => self.load_home_stats()?,
+            0 => self.refresh_home_stats(),
             1 => {
+                // Re-read the package databases instead of the disk cache
+                self.refresh_available = true;
                 let view_type = ViewType::Install;
-                self.switch_to_view(view_type)?;
+                self.open_view(view_type)?;
             }
             2 | 3 => {
                 self.cached_installed = None;
@@ -781,77 +1306,947 @@ impl MainMenu {
                 } else {
                     ViewType::List
                 };
-                self.switch_to_view(view_type)?;
+                self.open_view(view_type)?;
+            }
+            4 => {
+                self.cached_upgradable = None;
+                self.open_view(ViewType::Updates)?;
             }
             _ => {}
         }
+
+        // Applied once the new list is filled in
+        if let (Some((query, cursor)), ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app)) =
+            (kept, &mut self.current_view)
+        {
+            app.search_query = query;
+            app.cursor_target = cursor;
+        }
         Ok(())
     }
 
     /// Perform the actual home data load
     fn perform_home_load(&mut self) -> Result<()> {
+        // Data comes from background refreshes; show whatever has been loaded so far
         if let ViewState::Home(home_state) = &mut self.current_view {
-            let installed = self.package_manager.list_installed()?;
-            let available = self.package_manager.list_available()?;
-            let updates_available = 0; // TODO: Implement
-
-            home_state.set_stats(SystemStats {
-                installed_count: installed.len(),
-                available_count: available.len(),
-                updates_available,
-            });
+            home_state.set_stats(self.stats.clone());
         }
         self.loading_state.stop();
         Ok(())
     }
 
     /// Perform the actual install view data load
+    /// Without a list in memory it is read on a background thread while the view stays usable
     fn perform_install_load(&mut self) -> Result<()> {
-        let packages = self.get_or_load_available()?;
-        let package_names: Vec<String> = packages
+        self.loading_state.stop();
+        let refresh = std::mem::replace(&mut self.refresh_available, false);
+
+        match self.cached_available.clone().filter(|_| !refresh) {
+            Some(available) => self.fill_install_view(&available),
+            None => {
+                if let ViewState::Install(app) = &mut self.current_view {
+                    app.loading.start("Loading available packages…".to_string());
+                }
+                if !self.loading_available {
+                    self.loading_available = true;
+                    let tx = self.load_tx.clone();
+                    thread::spawn(move || {
+                        let result = PackageManager::new().and_then(|pm| pm.list_available_cached(refresh));
+                        let _ = tx.send(PackageLoad::Available(result.map_err(|e| e.to_string())));
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Perform the actual remove view data load
+    fn perform_remove_load(&mut self) -> Result<()> {
+        self.load_installed_view();
+        Ok(())
+    }
+
+    /// Perform the actual list view data load
+    fn perform_list_load(&mut self) -> Result<()> {
+        self.load_installed_view();
+        Ok(())
+    }
+
+    /// Fill the Remove/List view with the installed packages, reading them in the background if needed
+    fn load_installed_view(&mut self) {
+        self.loading_state.stop();
+
+        if let Some(packages) = self.cached_installed.clone() {
+            self.fill_installed_view(packages);
+            return;
+        }
+
+        if let ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
+            app.loading.start("Loading installed packages…".to_string());
+        }
+        if !self.loading_installed {
+            // Details of the old list are reloaded with the new one
+            self.cached_installed_details = None;
+            self.loading_installed = true;
+            let tx = self.load_tx.clone();
+            thread::spawn(move || {
+                let result = PackageManager::new().and_then(|pm| pm.list_installed());
+                let _ = tx.send(PackageLoad::Installed(result.map_err(|e| e.to_string())));
+            });
+        }
+    }
+
+    /// Apply package lists that finished loading in the background
+    /// Lists are kept even when the user left the view meanwhile
+    fn check_package_loads(&mut self) {
+        while let Ok(load) = self.load_rx.try_recv() {
+            match load {
+                PackageLoad::Available(result) => {
+                    self.loading_available = false;
+                    match result {
+                        Ok(available) => {
+                            self.fill_install_view(&available);
+                            self.cached_available = Some(available);
+                        }
+                        Err(e) => {
+                            if let ViewState::Install(app) = &mut self.current_view {
+                                app.loading.stop();
+                                app.alert.show(super::types::AlertType::Error, format!("Failed to load available packages: {}", e));
+                            }
+                        }
+                    }
+                }
+                PackageLoad::Installed(result) => {
+                    self.loading_installed = false;
+                    match result {
+                        Ok(packages) => {
+                            self.cached_installed = Some(packages.clone());
+                            self.fill_installed_view(packages);
+                        }
+                        Err(e) => {
+                            if let ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
+                                app.loading.stop();
+                                app.alert.show(super::types::AlertType::Error, format!("Failed to load installed packages: {}", e));
+                            }
+                        }
+                    }
+                }
+                PackageLoad::Upgradable(result) => {
+                    self.loading_upgradable = false;
+                    match result {
+                        Ok(packages) => {
+                            self.cached_upgradable = Some(packages.clone());
+                            self.fill_updates_view(&packages);
+                        }
+                        Err(e) => {
+                            if let ViewState::Updates(app) = &mut self.current_view {
+                                app.loading.stop();
+                                app.alert.show(super::types::AlertType::Error, format!("Failed to check for updates: {}", e));
+                            }
+                        }
+                    }
+                }
+                // Only needed for sorting by size or date, so failures go unreported
+                PackageLoad::InstalledDetails(result) => {
+                    self.loading_details = false;
+                    if let Ok(details) = result {
+                        if let ViewState::Remove(app) | ViewState::List(app) = &mut self.current_view {
+                            app.set_installed_details(&details);
+                        }
+                        self.cached_installed_details = Some(details);
+                    }
+                }
+            }
+        }
+
+        if let ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) = &mut self.current_view {
+            app.loading.tick();
+        }
+    }
+
+    /// Show the available packages in the Install view (keeping any search typed while loading)
+    fn fill_install_view(&mut self, available: &CachedPackages) {
+        if !matches!(self.current_view, ViewState::Install(_)) {
+            return;
+        }
+        // Tagging installed packages is best effort, the list is usable without it
+        let installed = self.get_or_load_installed().unwrap_or_default();
+        let ViewState::Install(app) = &mut self.current_view else {
+            return;
+        };
+
+        let package_names: Vec<String> = available
+            .packages
             .iter()
             .map(|p| format!("{}/{}", p.repository, p.name))
             .collect();
 
-        let app = App::new(
-            package_names,
-            true,
-            Some("echo {} | xargs yay -Si".to_string()),
-            ActionType::Install,
-        );
+        app.set_package_details(&available.packages);
+        app.data_created_at = Some(available.created_at);
+        app.stale_after = Duration::from_secs(self.settings.stale_cache_hours * 3600);
+        app.repo_filter = Some(self.settings.repo_filter("install"));
+        app.repositories = self.package_manager.repositories();
+        app.sort_mode = self.settings.sort_mode("install");
+        app.pinned = self.settings.held_packages().into_iter().collect();
+        app.installed = installed.into_iter().collect();
+        app.loading.stop();
+        app.set_items(package_names);
+    }
+
+    /// Show the installed packages in the Remove or List view
+    fn fill_installed_view(&mut self, packages: Vec<String>) {
+        let (ViewState::Remove(app) | ViewState::List(app)) = &mut self.current_view else {
+            return;
+        };
+
+        app.pinned = self.settings.held_packages().into_iter().collect();
+        if app.action_type == ActionType::Remove {
+            app.remove_options = self.settings.remove_behavior;
+        }
+        app.sort_mode = self.settings.sort_mode(Self::sort_key(self.selected_tab));
+        // AUR packages get AUR details in their previews; when everything looks like AUR,
+        // pacman couldn't list the repositories and nothing is known
+        let (aur, _) = self.package_manager.separate_packages(&packages);
+        app.aur_items = if aur.len() < packages.len() { aur.into_iter().collect() } else { HashSet::new() };
+        app.loading.stop();
+        app.set_items(packages);
+
+        match &self.cached_installed_details {
+            Some(details) => app.set_installed_details(details),
+            None if !self.loading_details => {
+                self.loading_details = true;
+                let tx = self.load_tx.clone();
+                thread::spawn(move || {
+                    let result = PackageManager::new().and_then(|pm| pm.list_installed_detailed());
+                    let _ = tx.send(PackageLoad::InstalledDetails(result.map_err(|e| e.to_string())));
+                });
+            }
+            None => {}
+        }
+    }
 
-        self.current_view = ViewState::Install(app);
+    /// Check for pending upgrades on a background thread, unless they are already known
+    fn perform_updates_load(&mut self) -> Result<()> {
         self.loading_state.stop();
+
+        if let Some(packages) = self.cached_upgradable.clone() {
+            self.fill_updates_view(&packages);
+            return Ok(());
+        }
+
+        if let ViewState::Updates(app) = &mut self.current_view {
+            app.loading.start("Checking for updates…".to_string());
+        }
+        if !self.loading_upgradable {
+            self.loading_upgradable = true;
+            let tx = self.load_tx.clone();
+            thread::spawn(move || {
+                let result = PackageManager::new().and_then(|pm| pm.list_upgradable());
+                let _ = tx.send(PackageLoad::Upgradable(result.map_err(|e| e.to_string())));
+            });
+        }
         Ok(())
     }
 
-    /// Perform the actual remove view data load
-    fn perform_remove_load(&mut self) -> Result<()> {
-        let packages = self.get_or_load_installed()?;
-        let app = App::new(
-            packages,
-            true,
-            Some("echo {} | xargs yay -Qi".to_string()),
-            ActionType::Remove,
+    /// Show the pending upgrades in the Updates view, one `name  old → new` row each
+    fn fill_updates_view(&mut self, packages: &[UpgradablePackage]) {
+        let ViewState::Updates(app) = &mut self.current_view else {
+            return;
+        };
+
+        let rows: Vec<String> = packages
+            .iter()
+            .map(|pkg| {
+                let ignored = if pkg.ignored { " (ignored)" } else { "" };
+                format!("{}  {} → {}{}", pkg.name, pkg.current_version, pkg.new_version, ignored)
+            })
+            .collect();
+
+        // Held back upgrades stay listed for reference, but -Syu skips them so they can't be picked
+        app.locked = packages
+            .iter()
+            .zip(&rows)
+            .filter(|(pkg, _)| pkg.ignored)
+            .map(|(_, row)| row.clone())
+            .collect();
+        app.sort_mode = self.settings.sort_mode(Self::sort_key(self.selected_tab));
+        app.loading.stop();
+        app.set_items(rows);
+    }
+
+    /// Package names of Updates view rows
+    fn upgrade_names(rows: &[String]) -> Vec<String> {
+        rows.iter()
+            .filter_map(|row| row.split_whitespace().next())
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Ask before upgrading the picked rows; picking only some of them needs a second confirmation
+    fn confirm_upgrade(app: &mut App, mut selected: Vec<String>) {
+        selected.retain(|row| !app.locked.contains(row));
+        if selected.is_empty() {
+            return;
+        }
+
+        let held_back = app.items.len() - app.locked.len() - selected.len();
+        app.confirm_dialog.show(ActionType::Upgrade, selected);
+        if held_back == 0 {
+            app.confirm_dialog.command = Some("pacman -Syu".to_string());
+        } else {
+            app.confirm_dialog.command = Some("pacman -Syu --needed --ignore <upgrades not picked>".to_string());
+            app.confirm_dialog.warn_partial_upgrade(held_back);
+        }
+    }
+
+    /// Settings key of the sort order remembered for a package tab
+    fn sort_key(tab: usize) -> &'static str {
+        match tab {
+            2 => "remove",
+            3 => "list",
+            4 => "updates",
+            _ => "install",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::package::Package;
+    use crossterm::event::KeyEvent;
+    use ratatui::backend::TestBackend;
+    use crate::ui::events::ScriptedEvents;
+    use crate::ui::types::ConfirmButton;
+    use std::time::SystemTime;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn chars(text: &str) -> Vec<KeyEvent> {
+        text.chars().map(|c| key(KeyCode::Char(c))).collect()
+    }
+
+    /// Focus the search bar, type the query and give focus back to the list
+    fn search(query: &str) -> Vec<KeyEvent> {
+        let mut keys = vec![key(KeyCode::Char('/'))];
+        keys.extend(chars(query));
+        keys.push(key(KeyCode::Enter));
+        keys
+    }
+
+    fn package(repository: &str, name: &str) -> Package {
+        Package {
+            name: name.to_string(),
+            version: "1.0-1".to_string(),
+            description: String::new(),
+            repository: repository.to_string(),
+        }
+    }
+
+    /// A menu with fixed package data that never calls pacman
+    fn menu() -> MainMenu {
+        let mut menu = MainMenu::with_settings(PackageManager::offline(), config::Settings::default());
+        menu.cached_installed = Some(vec!["bash".to_string(), "git".to_string(), "vim".to_string()]);
+        menu.cached_available = Some(CachedPackages {
+            packages: vec![
+                package("core", "bash"),
+                package("core", "base"),
+                package("extra", "git"),
+                package("extra", "vim"),
+            ],
+            created_at: SystemTime::now(),
+        });
+        menu.cached_upgradable = Some(vec![upgradable("bash", "5.2-1", "5.2-2"), upgradable("linux", "6.9-1", "6.10-1")]);
+        menu
+    }
+
+    fn upgradable(name: &str, current_version: &str, new_version: &str) -> UpgradablePackage {
+        UpgradablePackage {
+            name: name.to_string(),
+            current_version: current_version.to_string(),
+            new_version: new_version.to_string(),
+            ignored: false,
+        }
+    }
+
+    /// Run the script; scripts that don't exit stop once their events run out
+    fn run(menu: &mut MainMenu, keys: &[KeyEvent]) -> (Terminal<TestBackend>, Result<()>) {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let result = menu.run_loop(&mut terminal, &mut ScriptedEvents::new(keys));
+        (terminal, result)
+    }
+
+    fn screen(terminal: &Terminal<TestBackend>) -> String {
+        let buffer = terminal.backend().buffer();
+        let width = buffer.area.width as usize;
+        buffer
+            .content
+            .chunks(width)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn current_app(menu: &MainMenu) -> &App {
+        match &menu.current_view {
+            ViewState::Install(app) | ViewState::Remove(app) | ViewState::List(app) | ViewState::Updates(app) => app,
+            ViewState::Home(_) | ViewState::Settings(_) => panic!("expected a package view"),
+        }
+    }
+
+    #[test]
+    fn select_and_confirm_in_install_view() {
+        let mut menu = menu();
+        let mut keys = vec![key(KeyCode::Char('2'))];
+        keys.extend(search("bas"));
+        keys.extend([key(KeyCode::Tab), key(KeyCode::Tab), key(KeyCode::Enter)]);
+
+        let (terminal, result) = run(&mut menu, &keys);
+        assert!(result.is_err(), "script should run out before the menu exits");
+
+        assert_eq!(menu.selected_tab, ViewType::Install as usize);
+        let app = current_app(&menu);
+        assert_eq!(app.search_query, "bas");
+        assert!(app.confirm_dialog.active);
+        assert_eq!(app.confirm_dialog.packages.len(), 2);
+        assert!(app.confirm_dialog.packages.iter().all(|p| p.starts_with("core/ba")));
+
+        let screen = screen(&terminal);
+        assert!(screen.contains("The following packages will be installed:"));
+        assert!(screen.contains("✓ core/bash"));
+    }
+
+    #[test]
+    fn install_confirmation_resolves_the_transaction_size() {
+        use crate::ui::types::TransactionSize;
+
+        let mut menu = menu();
+        let mut keys = vec![key(KeyCode::Char('2'))];
+        keys.extend(search("git"));
+        keys.extend([key(KeyCode::Tab), key(KeyCode::Enter)]);
+        let _ = run(&mut menu, &keys);
+        assert!(current_app(&menu).confirm_dialog.active);
+
+        // Keys that change nothing keep the loop going until the background lookup is in
+        let start = Instant::now();
+        while current_app(&menu).confirm_dialog.transaction == TransactionSize::Loading {
+            assert!(start.elapsed() < Duration::from_secs(5), "transaction size never resolved");
+            thread::sleep(Duration::from_millis(10));
+            let _ = run(&mut menu, &[key(KeyCode::Null)]);
+        }
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Null)]);
+        assert!(!screen(&terminal).contains("Calculating transaction size..."));
+    }
+
+    #[test]
+    fn keys_drive_the_list_until_the_search_is_focused() {
+        let mut menu = menu();
+        let _ = run(&mut menu, &[key(KeyCode::Char('2')), key(KeyCode::Char('G')), key(KeyCode::Char('x'))]);
+        let app = current_app(&menu);
+        assert_eq!(app.list_state.selected(), Some(3), "G jumps to the last package");
+        assert_eq!(app.search_query, "", "letters aren't typed while the list has focus");
+
+        let mut keys = vec![KeyEvent::new(KeyCode::Char('2'), KeyModifiers::ALT), key(KeyCode::Char('/'))];
+        keys.extend(chars("vi?3"));
+        keys.extend([key(KeyCode::Backspace), key(KeyCode::Backspace), key(KeyCode::Esc), key(KeyCode::Char('g'))]);
+        let (terminal, _) = run(&mut menu, &keys);
+        let app = current_app(&menu);
+        assert_eq!(menu.selected_tab, ViewType::Install as usize, "digits typed in the search don't switch tabs");
+        assert!(!app.help_visible, "'?' is typed, not the help key");
+        assert!(!app.search_focused);
+        assert_eq!(app.search_query, "vi", "Esc gives focus back without clearing the search");
+        assert_eq!(app.list_state.selected(), Some(0), "g jumps back to the first package");
+        assert!(screen(&terminal).contains("extra/vim"));
+    }
+
+    #[test]
+    fn digits_are_searched_and_alt_digits_switch_tabs() {
+        let mut menu = menu();
+        let alt = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT);
+        let mut keys = chars("2mp3");
+        keys.push(key(KeyCode::Enter));
+        let (terminal, _) = run(&mut menu, &keys);
+        let app = current_app(&menu);
+        assert_eq!(menu.selected_tab, ViewType::Install as usize);
+        assert_eq!(app.search_query, "3", "a digit starts typing a search");
+        assert!(!app.search_focused);
+        assert!(screen(&terminal).contains("[Alt+2] Install"));
+
+        let _ = run(&mut menu, &[key(KeyCode::Char('/')), key(KeyCode::Char('1')), key(KeyCode::Esc), alt('3')]);
+        assert_eq!(menu.selected_tab, ViewType::Remove as usize);
+    }
+
+    #[test]
+    fn package_tabs_keep_their_state_until_refreshed() {
+        let mut menu = menu();
+        let alt = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT);
+        let mut keys = vec![key(KeyCode::Char('2'))];
+        keys.extend(search("git"));
+        keys.extend([key(KeyCode::Tab), alt('1'), key(KeyCode::Char('3')), alt('2')]);
+        let _ = run(&mut menu, &keys);
+        let app = current_app(&menu);
+        assert_eq!(menu.selected_tab, ViewType::Install as usize);
+        assert_eq!(app.search_query, "git", "the search survives leaving the tab");
+        assert!(app.selected.contains("extra/git"), "so do the selections");
+        assert!(menu.parked_views.contains_key(&ViewType::Remove));
+
+        if let ViewState::Install(app) = &mut menu.current_view {
+            app.preview_cache.insert("extra/git".to_string(), "old preview".to_string());
+        }
+        let _ = run(&mut menu, &[KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL)]);
+        let app = current_app(&menu);
+        assert!(app.preview_cache.is_empty(), "Ctrl+R builds the tab again");
+        assert_eq!(app.search_query, "git", "keeping the search");
+        assert_eq!(app.cursor_target.as_ref().map(|(item, _)| item.as_str()), Some("extra/git"));
+        assert!(app.selected.contains("extra/git"), "and what was selected");
+
+        menu.invalidate_installed();
+        assert!(menu.parked_views.is_empty(), "an install or removal drops the kept tabs");
+    }
+
+    #[test]
+    fn install_view_tags_installed_packages() {
+        let mut menu = menu();
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Char('2'))]);
+
+        let screen = screen(&terminal);
+        assert!(screen.contains("core/bash  1.0-1 [installed]"));
+        assert!(!screen.contains("core/base  1.0-1 [installed]"));
+    }
+
+    #[test]
+    fn install_view_stays_usable_while_packages_load() {
+        let mut menu = menu();
+        let available = menu.cached_available.take().unwrap();
+        // Pretend a load is already running so the test doesn't start one
+        menu.loading_available = true;
+
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Char('2')), key(KeyCode::Char('/')), key(KeyCode::Char('b')), key(KeyCode::Char('a'))]);
+        let app = current_app(&menu);
+        assert!(app.loading.is_active());
+        assert!(app.items.is_empty());
+        assert_eq!(app.search_query, "ba");
+        assert!(screen(&terminal).contains("Loading available packages…"));
+
+        menu.load_tx.send(PackageLoad::Available(Ok(available))).unwrap();
+        let (terminal, _) = run(&mut menu, &[]);
+        let app = current_app(&menu);
+        assert!(!app.loading.is_active());
+        assert_eq!(app.items.len(), 4);
+        assert_eq!(app.search_query, "ba", "search typed while loading is kept");
+        assert!((0..app.filtered_items.len()).all(|i| app.filtered_item(i).unwrap().starts_with("core/ba")));
+        assert!(screen(&terminal).contains("core/bash"));
+        assert!(menu.cached_available.is_some());
+    }
+
+    #[test]
+    fn install_view_shows_truncated_descriptions() {
+        let mut menu = menu();
+        if let Some(available) = menu.cached_available.as_mut() {
+            available.packages[2].description = format!("the fast distributed version control system{}", " and more".repeat(20));
+        }
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Char('2'))]);
+
+        let screen = screen(&terminal);
+        let row = screen.lines().find(|line| line.contains("extra/git")).unwrap();
+        assert!(row.contains("extra/git  1.0-1 [installed]  the fast distributed"));
+        assert!(row.contains('…'), "description is cut at the pane edge: {}", row);
+    }
+
+    #[test]
+    fn cancel_and_return_home_then_exit() {
+        let mut menu = menu();
+        let mut keys = vec![key(KeyCode::Char('2'))];
+        keys.extend(search("bas"));
+        keys.extend([
+            key(KeyCode::Tab),
+            key(KeyCode::Tab),
+            key(KeyCode::Enter),
+            key(KeyCode::Esc), // cancel the confirmation
+            key(KeyCode::Esc), // clear the search
+            key(KeyCode::Esc), // back to Home
+            key(KeyCode::Esc), // exit
+        ]);
+
+        let (terminal, result) = run(&mut menu, &keys);
+        assert!(result.is_ok());
+        assert_eq!(menu.selected_tab, ViewType::Home as usize);
+        assert!(matches!(menu.current_view, ViewState::Home(_)));
+        assert!(screen(&terminal).contains("[Alt+1] Home"));
+    }
+
+    #[test]
+    fn remove_view_lists_installed_packages() {
+        let mut menu = menu();
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Char('3')), key(KeyCode::Down), key(KeyCode::Tab)]);
+
+        let app = current_app(&menu);
+        assert_eq!(app.action_type, ActionType::Remove);
+        assert_eq!(app.get_selected_items(), vec!["git"]);
+        assert!(screen(&terminal).contains("Select packages to remove"));
+    }
+
+    #[test]
+    fn mouse_clicks_select_and_wheel_scrolls_under_the_cursor() {
+        use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+
+        let mouse = |kind, column, row| Event::Mouse(MouseEvent { kind, column, row, modifiers: KeyModifiers::NONE });
+        // Tab bar and search bar take 6 rows, the list's first row is below its border
+        let click = |row| mouse(MouseEventKind::Down(MouseButton::Left), 5, row);
+        let mut menu = menu();
+        let mut events = ScriptedEvents::new(&[key(KeyCode::Char('3'))]);
+        events.0.extend([
+            click(8),
+            click(8),
+            // Toggling moves on to vim like Tab does
+            mouse(MouseEventKind::ScrollUp, 5, 10),
+            mouse(MouseEventKind::ScrollDown, 90, 10),
+            click(30),
+        ]);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let _ = menu.run_loop(&mut terminal, &mut events);
+
+        let app = current_app(&menu);
+        assert_eq!(app.get_selected_items(), vec!["git"], "second click on a row toggles it");
+        assert_eq!(app.list_state.selected(), Some(1), "the wheel over the list moves the selection");
+        assert_eq!(app.preview_scroll, 3, "the wheel over the preview scrolls it");
+    }
+
+    #[test]
+    fn confirmation_lists_items_checked_under_different_searches() {
+        let mut menu = menu();
+        let mut keys = vec![key(KeyCode::Char('2')), key(KeyCode::Tab)];
+        keys.extend(search("vim"));
+        keys.extend([key(KeyCode::Tab), key(KeyCode::Enter)]);
+
+        let (terminal, _) = run(&mut menu, &keys);
+        let app = current_app(&menu);
+        assert_eq!(app.confirm_dialog.packages, vec!["core/bash", "extra/vim"]);
+        let screen = screen(&terminal);
+        assert!(screen.contains("✓ extra/vim"));
+        assert!(screen.contains("2 selected"), "core/bash is still selected while the search hides it");
+    }
+
+    #[test]
+    fn theme_selector_previews_the_highlighted_theme() {
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        let uses_primary = |terminal: &Terminal<TestBackend>, theme: Theme| {
+            let primary = theme.palette().primary;
+            terminal.backend().buffer().content.iter().any(|cell| cell.fg == primary)
+        };
+        let previewed = Theme::all()[1];
+        assert_ne!(previewed.palette().primary, Theme::Default.palette().primary);
+
+        let mut previewing = menu();
+        let (terminal, _) = run(&mut previewing, &[ctrl_t, key(KeyCode::Down)]);
+        assert!(uses_primary(&terminal, previewed));
+        assert_eq!(previewing.theme, Theme::Default, "nothing is applied before Enter");
+
+        let mut cancelled = menu();
+        let (terminal, _) = run(&mut cancelled, &[ctrl_t, key(KeyCode::Down), key(KeyCode::Esc)]);
+        assert!(!uses_primary(&terminal, previewed), "Esc goes back to the current theme");
+        assert_eq!(cancelled.theme, Theme::Default);
+    }
+
+    #[test]
+    fn picking_some_upgrades_needs_a_second_confirmation() {
+        let mut partial = menu();
+        let (terminal, _) = run(&mut partial, &[key(KeyCode::Char('5')), key(KeyCode::Tab), key(KeyCode::Enter), key(KeyCode::Enter)]);
+
+        assert_eq!(partial.selected_tab, ViewType::Updates as usize);
+        let app = current_app(&partial);
+        assert!(app.items.contains(&"linux  6.9-1 → 6.10-1".to_string()));
+        assert!(app.confirm_dialog.active, "the first Enter only acknowledges the warning");
+        assert!(app.confirm_dialog.acknowledged);
+        assert!(!app.confirm_dialog.is_confirmed());
+        let screen = screen(&terminal);
+        assert!(screen.contains("Partial upgrades are unsupported"));
+        assert!(screen.contains("Press Y again to upgrade anyway"));
+
+        let mut everything = menu();
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let _ = run(&mut everything, &[key(KeyCode::Char('5')), ctrl_a, key(KeyCode::Enter)]);
+        let dialog = &current_app(&everything).confirm_dialog;
+        assert!(dialog.warnings.is_empty());
+        assert!(!dialog.needs_ack(), "a full upgrade is confirmed like any other action");
+        assert_eq!(dialog.command.as_deref(), Some("pacman -Syu"));
+        assert_eq!(MainMenu::upgrade_names(&dialog.packages), vec!["bash", "linux"]);
+    }
+
+    #[test]
+    fn held_upgrades_cannot_be_picked() {
+        let mut menu = menu();
+        menu.cached_upgradable = Some(vec![upgradable("bash", "5.2-1", "5.2-2"), UpgradablePackage { ignored: true, ..upgradable("linux", "6.9-1", "6.10-1") }]);
+        let ctrl_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::CONTROL);
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Char('5')), key(KeyCode::Down), key(KeyCode::Tab), ctrl_a, key(KeyCode::Enter)]);
+
+        let app = current_app(&menu);
+        assert!(app.items.contains(&"linux  6.9-1 → 6.10-1 (ignored)".to_string()), "held upgrades stay listed");
+        assert!(app.selected.iter().all(|row| !row.starts_with("linux")));
+        let dialog = &app.confirm_dialog;
+        assert_eq!(MainMenu::upgrade_names(&dialog.packages), vec!["bash"]);
+        assert!(dialog.warnings.is_empty(), "every upgrade that can be picked was picked");
+        assert_eq!(dialog.command.as_deref(), Some("pacman -Syu"));
+        assert!(!screen(&terminal).contains("Partial upgrades are unsupported"));
+    }
+
+    #[test]
+    fn settings_tab_lists_settings_and_the_config_file() {
+        let mut menu = menu();
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Char('6')), key(KeyCode::Down)]);
+
+        assert_eq!(menu.selected_tab, ViewType::Settings as usize);
+        let screen = screen(&terminal);
+        assert!(screen.contains("Theme"));
+        assert!(screen.contains("Preview layout"));
+        assert!(screen.contains(SettingsRow::Layout.description()), "the selected row is explained");
+        assert!(screen.contains("Config file:"));
+    }
+
+    #[test]
+    fn remove_confirmation_shows_configured_flags() {
+        let mut menu = menu();
+        menu.settings.remove_behavior.nosave = false;
+        let (terminal, _) = run(&mut menu, &[key(KeyCode::Char('3')), key(KeyCode::Tab), key(KeyCode::Enter)]);
+
+        let app = current_app(&menu);
+        assert!(app.confirm_dialog.active);
+        assert_eq!(app.confirm_dialog.command.as_deref(), Some("pacman -Rs"));
+        assert!(screen(&terminal).contains("Using: pacman -Rs"));
+    }
+
+    #[test]
+    fn enter_presses_the_focused_button() {
+        // Removals start on No, so Enter alone backs out
+        let mut cancelled = menu();
+        let _ = run(&mut cancelled, &[key(KeyCode::Char('3')), key(KeyCode::Tab), key(KeyCode::Enter), key(KeyCode::Enter)]);
+        let app = current_app(&cancelled);
+        assert!(!app.confirm_dialog.active && !app.confirm_dialog.is_confirmed());
+        assert!(!app.update_window.active);
+
+        // Moving to Yes and back leaves No focused
+        let mut focused = menu();
+        let keys = [key(KeyCode::Char('3')), key(KeyCode::Tab), key(KeyCode::Enter), key(KeyCode::Left), key(KeyCode::Tab)];
+        let _ = run(&mut focused, &keys);
+        let app = current_app(&focused);
+        assert!(app.confirm_dialog.active);
+        assert_eq!(app.confirm_dialog.focus, ConfirmButton::No);
+    }
+
+    #[test]
+    fn risky_removals_need_yes_typed() {
+        use crate::package::PackageInfo;
+        use crate::ui::types::ConfirmDialog;
+
+        let mut menu = menu();
+        menu.settings.protected_packages = vec!["bash".to_string()];
+        let mut keys = vec![key(KeyCode::Char('3')), key(KeyCode::Tab), key(KeyCode::Enter)];
+        keys.extend(chars("yn"));
+        let (terminal, _) = run(&mut menu, &keys);
+        let app = current_app(&menu);
+        assert!(app.confirm_dialog.active, "y and n are typed, not shortcuts");
+        assert_eq!(app.confirm_dialog.typed, "yn");
+        let screen = screen(&terminal);
+        assert!(screen.contains("bash is a protected system package"));
+        assert!(screen.contains("to remove: yn"));
+
+        let info = |name: &str, required_by: &[&str]| PackageInfo {
+            name: name.to_string(),
+            required_by: required_by.iter().map(|s| s.to_string()).collect(),
+            ..Default::default()
+        };
+        let infos: HashMap<String, PackageInfo> = [info("libfoo", &["foo", "bar"]), info("foo", &[]), info("htop", &[])]
+            .into_iter()
+            .map(|info| (info.name.clone(), info))
+            .collect();
+
+        let mut dialog = ConfirmDialog::new();
+        dialog.show(ActionType::Remove, vec!["htop".into()]);
+        dialog.flag_removal_risks(&infos, &[]);
+        assert!(!dialog.require_typed && dialog.warnings.is_empty());
+
+        // foo goes too, so only bar is left depending on libfoo
+        dialog.show(ActionType::Remove, vec!["libfoo".into(), "foo".into()]);
+        dialog.flag_removal_risks(&infos, &[]);
+        assert_eq!(dialog.warnings, ["libfoo is required by: bar"]);
+        dialog.focus = ConfirmButton::Yes;
+        dialog.confirm_or_ack();
+        assert!(!dialog.is_confirmed(), "Y alone doesn't confirm");
+        for c in "yes".chars() {
+            dialog.type_char(c);
+        }
+        assert_eq!(dialog.focus, ConfirmButton::Yes);
+        dialog.activate();
+        assert!(dialog.is_confirmed());
+    }
+
+    #[test]
+    fn installed_packages_are_reinstalls_or_upgrades() {
+        use crate::package::PackageInfo;
+        use crate::ui::types::ConfirmDialog;
+
+        let info = |name: &str, version: &str| PackageInfo { name: name.to_string(), version: version.to_string(), ..Default::default() };
+        let infos: HashMap<String, PackageInfo> =
+            [info("git", "2.46.0-1"), info("vim", "9.1.0-2")].into_iter().map(|info| (info.name.clone(), info)).collect();
+        let installed: HashMap<String, String> = [("git", "2.46.0-1"), ("vim", "9.1.0-1"), ("yay-bin", "12.3-1")]
+            .into_iter()
+            .map(|(name, version)| (name.to_string(), version.to_string()))
+            .collect();
+
+        let mut dialog = ConfirmDialog::new();
+        dialog.show(ActionType::Install, vec!["extra/git".into(), "extra/vim".into(), "aur/yay-bin".into(), "extra/fd".into()]);
+        dialog.flag_installed(&installed, &infos);
+        assert_eq!(dialog.reinstalls, ["extra/git", "aur/yay-bin"]);
+        assert_eq!(dialog.upgrades, ["extra/vim"]);
+        assert_eq!(dialog.packages_to_install(true), ["extra/vim", "extra/fd"]);
+        assert_eq!(dialog.packages_to_install(false).len(), 4);
+    }
+
+    #[test]
+    fn selections_are_queued_across_tabs() {
+        let alt = |c: char| KeyEvent::new(KeyCode::Char(c), KeyModifiers::ALT);
+        let mut menu = menu();
+        let mut keys = vec![key(KeyCode::Char('2'))];
+        keys.extend(search("vim"));
+        // Queue it (Esc closes the "Queued" alert), then a removal from the Remove tab
+        keys.extend([key(KeyCode::Tab), key(KeyCode::Char('a')), key(KeyCode::Esc)]);
+        keys.extend([alt('3'), key(KeyCode::Tab), key(KeyCode::Char('a')), key(KeyCode::Esc), alt('q')]);
+        let (terminal, _) = run(&mut menu, &keys);
+
+        assert_eq!(menu.queue.split(), (vec!["bash".to_string()], vec!["extra/vim".to_string()]));
+        assert!(menu.queue.reviewing);
+        assert!(current_app(&menu).selected.is_empty(), "queued packages are deselected");
+        let shown = screen(&terminal);
+        assert!(shown.contains("Queue: 1+ / 1−"));
+        assert!(shown.contains("− remove  bash"));
+        assert!(shown.contains("+ install extra/vim"));
+
+        // Discarding empties the queue and the indicator goes away
+        let (terminal, _) = run(&mut menu, &[KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT)]);
+        assert!(menu.queue.entries.is_empty() && !menu.queue.reviewing);
+        assert!(!screen(&terminal).contains("Queue:"));
+    }
+
+    #[test]
+    fn confirm_scroll_stops_at_the_last_package() {
+        use crate::ui::types::ConfirmDialog;
+
+        let mut dialog = ConfirmDialog::new();
+        dialog.show(ActionType::Install, (0..10).map(|i| format!("extra/pkg{}", i)).collect());
+        assert_eq!(dialog.focus, ConfirmButton::Yes);
+        dialog.fit_scroll(14, 6);
+        for _ in 0..20 {
+            dialog.scroll_down();
+        }
+        assert_eq!(dialog.scroll, 8);
+        // A taller window pulls the scroll back
+        dialog.fit_scroll(14, 10);
+        assert_eq!(dialog.scroll, 4);
+    }
+
+    #[test]
+    fn confirm_summary_lists_repositories() {
+        use crate::package::PackageInfo;
+        use crate::ui::types::ConfirmDialog;
+
+        let info = |name: &str, repository: Option<&str>, size: u64| PackageInfo {
+            name: name.to_string(),
+            repository: repository.map(str::to_string),
+            installed_size: Some(size),
+            download_size: Some(size / 2),
+            ..Default::default()
+        };
+        let infos: std::collections::HashMap<String, PackageInfo> = [info("git", Some("extra"), 2048), info("bash", Some("core"), 1024)]
+            .into_iter()
+            .map(|info| (info.name.clone(), info))
+            .collect();
+
+        let mut dialog = ConfirmDialog::new();
+        dialog.show(ActionType::Install, vec!["extra/git".into(), "bash".into(), "yay-bin".into()]);
+        dialog.summarize(&infos);
+        assert_eq!(dialog.summary, vec!["From: aur (1), core (1), extra (1)"]);
+
+        dialog.show(ActionType::Remove, vec!["git".into(), "unknown".into()]);
+        dialog.summarize(&infos);
+        assert!(dialog.summary.is_empty());
+    }
+
+    #[test]
+    fn confirm_dialog_resolves_the_transaction_in_the_background() {
+        use crate::package::{format_size, TransactionPreview};
+        use crate::ui::types::{ConfirmDialog, TransactionSize};
+
+        let wait = |dialog: &mut ConfirmDialog| {
+            let start = Instant::now();
+            while dialog.transaction == TransactionSize::Loading {
+                assert!(start.elapsed() < Duration::from_secs(5), "transaction never resolved");
+                dialog.check_updates();
+                thread::sleep(Duration::from_millis(5));
+            }
+        };
+
+        let mut dialog = ConfirmDialog::new();
+        dialog.show(ActionType::Install, vec!["extra/git".into()]);
+        let (release, released) = mpsc::channel::<()>();
+        dialog.resolve_transaction(move || {
+            let _ = released.recv();
+            Ok(TransactionPreview {
+                download_size: 8 << 20,
+                installed_size: 40 << 20,
+                dependencies: vec!["perl-error".into(), "perl-mailtools".into()],
+            })
+        });
+        // Still open and usable while pacman works it out
+        dialog.check_updates();
+        assert!(dialog.transaction_lines()[0].ends_with("Calculating transaction size..."));
+        release.send(()).unwrap();
+        wait(&mut dialog);
+        assert_eq!(
+            dialog.transaction_lines(),
+            [format!("Download: {}, Installed: {}, +2 new dependencies", format_size(8 << 20), format_size(40 << 20))]
         );
 
-        self.current_view = ViewState::Remove(app);
-        self.loading_state.stop();
-        Ok(())
+        dialog.show(ActionType::Remove, vec!["git".into()]);
+        dialog.resolve_transaction(|| Ok(TransactionPreview { installed_size: 1024, dependencies: vec!["perl-error".into()], ..Default::default() }));
+        wait(&mut dialog);
+        assert_eq!(dialog.transaction_lines(), [format!("Frees: {}", format_size(1024)), "Also removes 1 other package:".to_string(), "  - perl-error".to_string()]);
+
+        dialog.show(ActionType::Install, vec!["aur/yay".into()]);
+        dialog.resolve_transaction(|| Err(anyhow::anyhow!("AUR packages are built locally")));
+        wait(&mut dialog);
+        assert_eq!(dialog.transaction_lines(), ["Size unknown (AUR packages are built locally)"]);
     }
 
-    /// Perform the actual list view data load
-    fn perform_list_load(&mut self) -> Result<()> {
-        let packages = self.get_or_load_installed()?;
-        let app = App::new(
-            packages,
-            false,
-            Some("echo {} | xargs yay -Qi".to_string()),
-            ActionType::Install,
+    #[test]
+    fn install_confirmation_shows_sources_and_commands() {
+        use crate::package::{Backend, InstallOptions};
+        use crate::ui::types::ConfirmDialog;
+
+        let aur = vec!["aur/yay-bin".to_string()];
+        let official = vec!["extra/git".to_string(), "bash".to_string()];
+        let options = InstallOptions { needed: true, ..Default::default() };
+        assert_eq!(
+            SystemUpdateWindow::install_commands(&aur, &official, Backend::Paru, &options),
+            ["pkexec pacman -S --noconfirm --needed git bash", "paru -S --needed yay-bin"]
         );
 
-        self.current_view = ViewState::List(app);
-        self.loading_state.stop();
-        Ok(())
+        let mut dialog = ConfirmDialog::new();
+        dialog.show(ActionType::Install, [official.clone(), aur.clone()].concat());
+        dialog.split_sources(aur.clone(), official.clone(), "pacman", Backend::Yay);
+        assert_eq!(dialog.sources[0], ("Official (via pacman)".to_string(), official.clone()));
+        assert_eq!(dialog.sources[1], ("AUR (via yay)".to_string(), aur.clone()));
+        assert_eq!(dialog.blocked, None);
+        dialog.confirm_or_ack();
+        assert!(dialog.is_confirmed());
+
+        // Without an AUR helper the AUR packages can't be installed, so Yes does nothing
+        dialog.show(ActionType::Install, [official.clone(), aur.clone()].concat());
+        dialog.split_sources(aur, official, "pacman", Backend::Pacman);
+        assert_eq!(dialog.blocked.as_deref(), Some("yay is not installed, so AUR packages can't be installed"));
+        dialog.confirm_or_ack();
+        assert!(!dialog.is_confirmed() && dialog.active);
     }
 }
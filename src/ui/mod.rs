@@ -1,16 +1,28 @@
 // Module declarations
+mod ansi;
 mod app;
+mod events;
 mod help_window;
 mod home_state;
+mod links;
+mod log_viewer;
 mod main_menu;
+mod queue;
+mod refresh;
 mod render;
 mod selector;
+mod settings_state;
 mod spinner;
+mod terminal;
 mod theme;
 mod types;
 mod update_window;
 
 // Re-export public API
+pub use log_viewer::LogViewer;
 pub use main_menu::MainMenu;
+pub use refresh::{recorded_status, state_path as refresh_state_path};
 pub use selector::Selector;
+pub use terminal::install_panic_hook;
 pub use theme::Theme;
+pub use types::{PreviewLayout, RepoFilter, SortMode};
@@ -0,0 +1,161 @@
+use super::types::ActionType;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Installs and removals marked in the Install and Remove tabs (`a`), applied together from the review (Alt+Q)
+#[derive(Debug, Default)]
+pub struct OperationQueue {
+    /// Queued actions in the order they were added (`repository/name` for installs, `name` for removals)
+    pub entries: Vec<(ActionType, String)>,
+    /// The review overlay is open
+    pub reviewing: bool,
+    pub selected: usize,
+}
+
+/// What a key pressed in the review asks for
+#[derive(Debug, PartialEq, Eq)]
+pub enum QueueCommand {
+    None,
+    Close,
+    /// Run the queued removals, then the installs
+    Execute,
+}
+
+/// Package name without its repository
+fn name(package: &str) -> &str {
+    package.rsplit('/').next().unwrap_or(package)
+}
+
+impl OperationQueue {
+    /// Queue packages for an action; one already queued for the other action moves over
+    /// Returns how many weren't queued for this action before
+    pub fn add(&mut self, action: ActionType, packages: &[String]) -> usize {
+        let mut added = 0;
+        for package in packages {
+            match self.entries.iter_mut().find(|(_, queued)| name(queued) == name(package)) {
+                Some(entry) if entry.0 == action => {}
+                Some(entry) => {
+                    *entry = (action, package.clone());
+                    added += 1;
+                }
+                None => {
+                    self.entries.push((action, package.clone()));
+                    added += 1;
+                }
+            }
+        }
+        added
+    }
+
+    /// How many installs and removals are queued
+    pub fn counts(&self) -> (usize, usize) {
+        let installs = self.entries.iter().filter(|(action, _)| *action == ActionType::Install).count();
+        (installs, self.entries.len() - installs)
+    }
+
+    /// Tab bar indicator, e.g. "Queue: 3+ / 2−" (None when empty)
+    pub fn indicator(&self) -> Option<String> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let (installs, removals) = self.counts();
+        Some(format!("Queue: {}+ / {}−", installs, removals))
+    }
+
+    /// The queued packages in the order they run: (removals, installs)
+    pub fn split(&self) -> (Vec<String>, Vec<String>) {
+        let of = |kind: ActionType| {
+            self.entries
+                .iter()
+                .filter(|(action, _)| *action == kind)
+                .map(|(_, package)| package.clone())
+                .collect::<Vec<_>>()
+        };
+        (of(ActionType::Remove), of(ActionType::Install))
+    }
+
+    /// Entries as the review lists them: removals first, like they run
+    pub fn ordered(&self) -> Vec<&(ActionType, String)> {
+        let (removals, installs): (Vec<_>, Vec<_>) = self.entries.iter().partition(|(action, _)| *action == ActionType::Remove);
+        removals.into_iter().chain(installs).collect()
+    }
+
+    pub fn open(&mut self) {
+        self.reviewing = true;
+        self.selected = 0;
+    }
+
+    /// Drop everything queued (after running it, or when discarded)
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.reviewing = false;
+        self.selected = 0;
+    }
+
+    /// Handle a key in the review
+    pub fn handle_key(&mut self, key: KeyEvent) -> QueueCommand {
+        match (key.code, key.modifiers) {
+            (KeyCode::Esc, _) | (KeyCode::Char('q'), KeyModifiers::ALT) => {
+                self.reviewing = false;
+                return QueueCommand::Close;
+            }
+            (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => self.selected = self.selected.saturating_sub(1),
+            (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
+                self.selected = (self.selected + 1).min(self.entries.len().saturating_sub(1));
+            }
+            // Drop the selected entry
+            (KeyCode::Char('d'), KeyModifiers::NONE) | (KeyCode::Delete, _) | (KeyCode::Backspace, _) => {
+                if let Some((action, package)) = self.ordered().get(self.selected).map(|entry| (entry.0, entry.1.clone())) {
+                    self.entries.retain(|entry| *entry != (action, package.clone()));
+                    self.selected = self.selected.min(self.entries.len().saturating_sub(1));
+                }
+            }
+            // Discard the whole queue
+            (KeyCode::Char('D'), _) => {
+                self.clear();
+                return QueueCommand::Close;
+            }
+            (KeyCode::Enter, _) if !self.entries.is_empty() => {
+                self.reviewing = false;
+                return QueueCommand::Execute;
+            }
+            _ => {}
+        }
+        QueueCommand::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn packages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn queue_runs_removals_first_and_entries_can_be_dropped() {
+        let mut queue = OperationQueue::default();
+        assert_eq!(queue.indicator(), None);
+        assert_eq!(queue.add(ActionType::Install, &packages(&["extra/git", "extra/vim", "core/nano"])), 3);
+        assert_eq!(queue.add(ActionType::Remove, &packages(&["htop", "nano"])), 2, "nano moves over to the removals");
+        assert_eq!(queue.add(ActionType::Remove, &packages(&["htop"])), 0);
+        assert_eq!(queue.indicator().as_deref(), Some("Queue: 2+ / 2−"));
+        assert_eq!(queue.split(), (packages(&["nano", "htop"]), packages(&["extra/git", "extra/vim"])));
+
+        queue.open();
+        queue.handle_key(key(KeyCode::Down));
+        queue.handle_key(key(KeyCode::Char('d')));
+        assert_eq!(queue.split(), (packages(&["nano"]), packages(&["extra/git", "extra/vim"])));
+        assert_eq!(queue.handle_key(key(KeyCode::Enter)), QueueCommand::Execute);
+        assert!(!queue.reviewing);
+
+        queue.open();
+        assert_eq!(queue.handle_key(KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT)), QueueCommand::Close);
+        assert!(queue.entries.is_empty());
+        assert_eq!(queue.handle_key(key(KeyCode::Enter)), QueueCommand::None, "nothing to run");
+    }
+}
@@ -0,0 +1,233 @@
+use crate::config::RefreshIntervals;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Background datasets refreshed on a schedule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Dataset {
+    Stats,
+    Updates,
+    /// Arch Linux news published since the last upgrade
+    News,
+}
+
+impl Dataset {
+    pub const ALL: [Dataset; 3] = [Dataset::Stats, Dataset::Updates, Dataset::News];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Dataset::Stats => "Stats",
+            Dataset::Updates => "Updates",
+            Dataset::News => "News",
+        }
+    }
+
+    fn interval(&self, intervals: &RefreshIntervals) -> Duration {
+        let minutes = match self {
+            Dataset::Stats => intervals.stats,
+            Dataset::Updates => intervals.updates,
+            Dataset::News => intervals.news,
+        };
+        Duration::from_secs(minutes.max(1) * 60)
+    }
+}
+
+/// Tracks when each dataset was last refreshed and decides when the next refresh is due
+/// A refresh is due at most once no matter how many intervals elapsed (e.g. after a suspend)
+pub struct RefreshScheduler {
+    intervals: RefreshIntervals,
+    last_refresh: HashMap<Dataset, Instant>,
+    in_flight: HashSet<Dataset>,
+    state_file: Option<PathBuf>, // Where finished refreshes are recorded for `pmgr doctor`
+}
+
+impl RefreshScheduler {
+    pub fn new(intervals: RefreshIntervals) -> Self {
+        Self {
+            intervals,
+            last_refresh: HashMap::new(),
+            in_flight: HashSet::new(),
+            state_file: None,
+        }
+    }
+
+    /// Record finished refreshes in the state file, so `pmgr doctor` can report them
+    pub fn record_to(&mut self, path: PathBuf) {
+        self.state_file = Some(path);
+    }
+
+    /// Whether the dataset should be refreshed now
+    pub fn is_due(&self, dataset: Dataset, now: Instant) -> bool {
+        if self.in_flight.contains(&dataset) {
+            return false;
+        }
+
+        match self.last_refresh.get(&dataset) {
+            Some(last) => now.saturating_duration_since(*last) >= dataset.interval(&self.intervals),
+            None => true,
+        }
+    }
+
+    /// Mark a refresh as started; returns false if one is already running
+    pub fn start(&mut self, dataset: Dataset) -> bool {
+        self.in_flight.insert(dataset)
+    }
+
+    /// Whether a refresh of the dataset is running
+    pub fn is_refreshing(&self, dataset: Dataset) -> bool {
+        self.in_flight.contains(&dataset)
+    }
+
+    /// Mark a refresh as finished; the next one is due a full interval from now
+    pub fn finish(&mut self, dataset: Dataset, now: Instant) {
+        self.in_flight.remove(&dataset);
+        self.last_refresh.insert(dataset, now);
+        if let Some(path) = &self.state_file {
+            let _ = record(path, dataset, SystemTime::now());
+        }
+    }
+
+    /// Human-readable refresh status (e.g. "2m ago, next in 8m")
+    pub fn status(&self, dataset: Dataset, now: Instant) -> String {
+        if self.in_flight.contains(&dataset) {
+            return "refreshing...".to_string();
+        }
+
+        let age = self.last_refresh.get(&dataset).map(|last| now.saturating_duration_since(*last));
+        describe(age, dataset.interval(&self.intervals))
+    }
+}
+
+/// Refresh status from the time since the last refresh
+fn describe(age: Option<Duration>, interval: Duration) -> String {
+    match age {
+        Some(age) if age >= interval => format!("{} ago, due now", format_duration(age)),
+        Some(age) => format!("{} ago, next in {}", format_duration(age), format_duration(interval - age)),
+        None => "not refreshed yet".to_string(),
+    }
+}
+
+/// Refresh times recorded by the interactive mode (`~/.cache/pmgr/refresh.json`)
+pub fn state_path() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join("pmgr").join("refresh.json"))
+}
+
+/// When each dataset was last refreshed, in seconds since the epoch
+fn read_state(path: &Path) -> HashMap<String, u64> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn record(path: &Path, dataset: Dataset, at: SystemTime) -> anyhow::Result<()> {
+    let mut state = read_state(path);
+    state.insert(dataset.label().to_string(), at.duration_since(UNIX_EPOCH)?.as_secs());
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_string_pretty(&state)?)?;
+    Ok(())
+}
+
+/// Status of each dataset as recorded in the state file (the scheduler only runs while pmgr is open)
+pub fn recorded_status(path: &Path, intervals: &RefreshIntervals, now: SystemTime) -> Vec<(&'static str, String)> {
+    let state = read_state(path);
+    Dataset::ALL
+        .iter()
+        .map(|dataset| {
+            let last = state.get(dataset.label()).map(|secs| UNIX_EPOCH + Duration::from_secs(*secs));
+            let age = last.map(|last| now.duration_since(last).unwrap_or_default());
+            (dataset.label(), describe(age, dataset.interval(intervals)))
+        })
+        .collect()
+}
+
+/// Compact duration (e.g. "45s", "12m", "3h")
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs < 60 {
+        format!("{}s", secs)
+    } else if secs < 3_600 {
+        format!("{}m", secs / 60)
+    } else {
+        format!("{}h", secs / 3_600)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scheduler() -> RefreshScheduler {
+        RefreshScheduler::new(RefreshIntervals {
+            stats: 10,
+            updates: 60,
+            ..RefreshIntervals::default()
+        })
+    }
+
+    #[test]
+    fn due_until_first_refresh() {
+        let mut scheduler = scheduler();
+        let now = Instant::now();
+
+        assert!(scheduler.is_due(Dataset::Stats, now));
+        assert!(scheduler.start(Dataset::Stats));
+        assert!(scheduler.is_refreshing(Dataset::Stats));
+        assert!(!scheduler.is_due(Dataset::Stats, now));
+        assert!(!scheduler.start(Dataset::Stats));
+
+        scheduler.finish(Dataset::Stats, now);
+        assert!(!scheduler.is_refreshing(Dataset::Stats));
+        assert!(!scheduler.is_due(Dataset::Stats, now + Duration::from_secs(9 * 60)));
+        assert!(scheduler.is_due(Dataset::Stats, now + Duration::from_secs(10 * 60)));
+    }
+
+    #[test]
+    fn elapsed_intervals_coalesce() {
+        let mut scheduler = scheduler();
+        let start = Instant::now();
+        scheduler.finish(Dataset::Updates, start);
+
+        // Suspended for several intervals: a single refresh brings it back on schedule
+        let resumed = start + Duration::from_secs(5 * 60 * 60);
+        assert!(scheduler.is_due(Dataset::Updates, resumed));
+        scheduler.start(Dataset::Updates);
+        scheduler.finish(Dataset::Updates, resumed);
+        assert!(!scheduler.is_due(Dataset::Updates, resumed + Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn status_text() {
+        let mut scheduler = scheduler();
+        let now = Instant::now();
+
+        assert_eq!(scheduler.status(Dataset::Stats, now), "not refreshed yet");
+        scheduler.finish(Dataset::Stats, now);
+        assert_eq!(
+            scheduler.status(Dataset::Stats, now + Duration::from_secs(120)),
+            "2m ago, next in 8m"
+        );
+
+        // News follows its own (default) interval of 6h
+        scheduler.finish(Dataset::News, now);
+        assert_eq!(scheduler.status(Dataset::News, now + Duration::from_secs(60 * 60)), "1h ago, next in 5h");
+        assert_eq!(scheduler.status(Dataset::Stats, now + Duration::from_secs(11 * 60)), "11m ago, due now");
+    }
+
+    #[test]
+    fn finished_refreshes_are_recorded_for_doctor() {
+        let path = std::env::temp_dir().join(format!("pmgr-refresh-{}", std::process::id())).join("refresh.json");
+        let mut scheduler = scheduler();
+        scheduler.record_to(path.clone());
+        scheduler.finish(Dataset::Updates, Instant::now());
+
+        let status = recorded_status(&path, &RefreshIntervals::default(), SystemTime::now() + Duration::from_secs(150));
+        assert_eq!(status[0], ("Stats", "not refreshed yet".to_string()));
+        assert_eq!(status[1], ("Updates", "2m ago, next in 57m".to_string()));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -1,28 +1,159 @@
 use super::app::App;
+use super::help_window::{help_columns, HelpLine, Section};
+use super::links::find_urls;
+use super::log_viewer::LogViewer;
+use super::queue::OperationQueue;
 use super::spinner::LoadingState;
+use super::settings_state::{SettingsRow, SettingsState};
 use super::theme::ThemePalette;
-use super::types::{ActionType, AlertType, PreviewLayout};
+use super::types::{format_clock, format_duration, ActionType, AlertType, ConfirmButton, LastOperation, PreviewLayout, RepoFilter, SearchMode, SortMode, TransactionSize};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style, Stylize},
-    text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph, Wrap},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
+use crate::config::Settings;
+use crate::oplog;
+use crate::package::{aur, format_size, Backend, PackageInfo};
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Smallest terminal the views and dialogs are drawn in
+pub const MIN_WIDTH: u16 = 40;
+pub const MIN_HEIGHT: u16 = 10;
 
 pub fn ui(f: &mut Frame, app: &mut App, prompt: &str, palette: &ThemePalette) {
-    ui_in_area(f, app, prompt, f.area(), palette);
+    if render_too_small(f, palette) {
+        return;
+    }
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(f.area());
+    ui_in_area(f, app, prompt, chunks[0], palette);
+    let status = StatusBar { backend: None, app: Some(app), last_operation: None };
+    render_status_bar(f, chunks[1], &status, palette);
+}
+
+/// What the status bar at the bottom of the screen shows
+pub struct StatusBar<'a> {
+    pub backend: Option<Backend>,
+    /// The package view, for its selection, filters and sort order
+    pub app: Option<&'a App>,
+    pub last_operation: Option<&'a LastOperation>,
+}
+
+/// Status bar segments, left to right: backend, selection, filters and sort, last operation, help hint
+fn status_segments(status: &StatusBar, palette: &ThemePalette) -> Vec<Span<'static>> {
+    let mut segments = Vec::new();
+    if let Some(backend) = status.backend {
+        segments.push(Span::styled(
+            format!(" {} ", backend),
+            Style::default().fg(palette.background).bg(palette.primary).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(app) = status.app {
+        let color = if app.selected.is_empty() { palette.text_dim } else { palette.primary };
+        segments.push(Span::styled(format!("{} selected", app.selected.len()), Style::default().fg(color)));
+
+        let mut filters = Vec::new();
+        if let Some(repo_filter) = app.repo_filter.as_ref().filter(|f| **f != RepoFilter::All) {
+            filters.push(format!("repo: {}", repo_filter.label()));
+        }
+        if app.search_mode != SearchMode::Fuzzy {
+            filters.push(format!("{} search", app.search_mode.label()));
+        }
+        if app.search_descriptions {
+            filters.push("descriptions".to_string());
+        }
+        if app.selected_only {
+            filters.push("selected only".to_string());
+        }
+        filters.push(format!("sort: {}", app.sort_mode.label()));
+        segments.push(Span::styled(filters.join(" · "), Style::default().fg(palette.info)));
+    }
+    if let Some(last) = status.last_operation {
+        let color = match last.alert_type {
+            AlertType::Success => palette.success,
+            AlertType::Error => palette.error,
+            AlertType::Info => palette.warning,
+        };
+        segments.push(Span::styled(
+            format!("{} {} ago", last.message, format_age(last.at.elapsed())),
+            Style::default().fg(color),
+        ));
+    }
+    let hint = match status.app {
+        Some(app) if app.preview_source.is_some() && !app.preview_enabled => "Alt+P: show preview · ?: help",
+        _ => "?: help",
+    };
+    segments.push(Span::styled(hint, Style::default().fg(palette.text_dim)));
+    segments
+}
+
+/// Render the status bar, dropping segments from the right until it fits
+pub fn render_status_bar(f: &mut Frame, area: Rect, status: &StatusBar, palette: &ThemePalette) {
+    const SEPARATOR: &str = " │ ";
+    let mut segments = status_segments(status, palette);
+    // A leading space, then the segments between separators
+    let width = |segments: &[Span]| 1 + segments.iter().map(|s| s.width()).sum::<usize>() + SEPARATOR.width() * segments.len().saturating_sub(1);
+    while !segments.is_empty() && width(&segments) > area.width as usize {
+        segments.pop();
+    }
+
+    let mut spans = vec![Span::raw(" ")];
+    for (i, segment) in segments.into_iter().enumerate() {
+        if i > 0 {
+            spans.push(Span::styled(SEPARATOR, Style::default().fg(palette.border)));
+        }
+        spans.push(segment);
+    }
+    f.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// In a terminal smaller than MIN_WIDTH x MIN_HEIGHT, ask for a bigger one instead of drawing the UI
+/// Returns whether the terminal was too small
+pub fn render_too_small(f: &mut Frame, palette: &ThemePalette) -> bool {
+    let area = f.area();
+    if area.width >= MIN_WIDTH && area.height >= MIN_HEIGHT {
+        return false;
+    }
+
+    let message = Paragraph::new(format!("Terminal too small — resize to at least {}x{}", MIN_WIDTH, MIN_HEIGHT))
+        .alignment(Alignment::Center)
+        .wrap(Wrap { trim: true })
+        .style(Style::default().fg(palette.warning));
+    f.render_widget(message, area);
+    true
+}
+
+/// A `width` x `height` rectangle centered in `area`, shrunk to fit it
+fn centered(area: Rect, width: u16, height: u16) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + (area.height - height) / 2,
+        width,
+        height,
+    }
 }
 
 pub fn ui_in_area(f: &mut Frame, app: &mut App, prompt: &str, area: Rect, palette: &ThemePalette) {
+    // Without a preview pane the list takes the whole view
+    let ratio = if app.shows_preview() { app.preview_ratio } else { 0 };
+    let split = [Constraint::Percentage(100 - ratio), Constraint::Percentage(ratio)];
     let chunks = match app.layout {
         PreviewLayout::Vertical => Layout::default()
             .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints(split)
             .split(area),
         PreviewLayout::Horizontal => Layout::default()
             .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .constraints(split)
             .split(area),
     };
 
@@ -32,48 +163,152 @@ pub fn ui_in_area(f: &mut Frame, app: &mut App, prompt: &str, area: Rect, palett
         .constraints([
             Constraint::Length(3), // Search bar
             Constraint::Min(0),    // List
-            Constraint::Length(3), // Footer
         ])
         .split(chunks[0]);
 
-    // Search bar
-    let search_block = Block::default()
+    // Search bar, titled with the search mode; an unusable query turns it red with the reason
+    let mut search_block = Block::default()
         .borders(Borders::ALL)
-        .title(prompt)
+        .title(format!("[{}] {}", app.search_mode.label(), prompt))
         .style(Style::default().fg(palette.primary));
+    if let Some(error) = &app.search_error {
+        search_block = search_block
+            .border_style(Style::default().fg(palette.error))
+            .title_bottom(Line::from(format!(" {} ", error)).fg(palette.error));
+    } else if app.search_focused {
+        search_block = search_block.border_style(Style::default().fg(palette.border_focused));
+    }
 
-    let search_text = Paragraph::new(app.search_query.as_str())
-        .block(search_block)
-        .style(Style::default().fg(palette.secondary));
-
-    f.render_widget(search_text, list_chunks[0]);
+    let search_text = if app.search_query.is_empty() && !app.search_focused {
+        Paragraph::new("Press / to search").style(Style::default().fg(palette.text_dim))
+    } else {
+        Paragraph::new(app.search_query.as_str()).style(Style::default().fg(palette.secondary))
+    };
+    f.render_widget(search_text.block(search_block), list_chunks[0]);
+
+    // Typing cursor (hidden once it is past the end of the bar)
+    if app.search_focused {
+        let before: String = app.search_query.chars().take(app.search_cursor()).collect();
+        let typed = u16::try_from(Span::raw(before).width()).unwrap_or(u16::MAX);
+        let x = list_chunks[0].x.saturating_add(1).saturating_add(typed);
+        if x < list_chunks[0].right().saturating_sub(1) {
+            f.set_cursor_position((x, list_chunks[0].y + 1));
+        }
+    }
 
-    // List of items
-    let items: Vec<ListItem> = app
-        .filtered_items
+    // List of items (inside the borders and the highlight symbol)
+    // Only the rows that fit are built, the list can hold the whole package database
+    let row_width = usize::from(list_chunks[1].width.saturating_sub(5));
+    let visible = app.visible_range(usize::from(list_chunks[1].height.saturating_sub(2)));
+    let offset = visible.start;
+    let items: Vec<ListItem> = app.filtered_items[visible]
         .iter()
-        .enumerate()
-        .map(|(idx, (item, _))| {
-            let style = Style::default();
+        .map(|(index, _)| {
+            let item = &app.items[*index];
+            // Upgrades held back by a pin or IgnorePkg can't be picked
+            let style = if app.locked.contains(item) { Style::default().fg(palette.text_dim) } else { Style::default() };
 
             // Mark selected items with checkmark
-            let prefix = if app.selected_indices.contains(&idx) {
+            let prefix = if app.selected.contains(item) {
                 "✓ "
             } else {
                 "  "
             };
 
-            let content = format!("{}{}", prefix, item);
+            // Tint the repository so the list reads by source
+            let mut spans = match item.split_once('/') {
+                Some((repository, name)) if app.repo_filter.is_some() => vec![
+                    Span::raw(prefix),
+                    Span::styled(repository, Style::default().fg(palette.repo_color(repository))),
+                    Span::raw(format!("/{}", name)),
+                ],
+                _ => vec![Span::raw(format!("{}{}", prefix, item))],
+            };
 
-            ListItem::new(content).style(style)
+            let details = app.package_details.get(item);
+            if let Some(pkg) = details {
+                spans.push(Span::styled(format!("  {}", pkg.version), Style::default().fg(palette.text_secondary)));
+            }
+
+            // Pinned packages are held at their version
+            let name = item.rsplit('/').next().unwrap_or(item);
+            if app.pinned.contains(name) {
+                spans.push(Span::styled(" 📌", Style::default().fg(palette.warning)));
+            }
+
+            if app.installed.contains(name) {
+                spans.push(Span::styled(" [installed]", Style::default().fg(palette.warning)));
+            }
+
+            // Packages other installed packages depend on can't be removed on their own
+            if let Some(dependents) = app.required_by.get(item).filter(|d| !d.is_empty()) {
+                spans.push(Span::styled(
+                    format!("  required by {} package{}", dependents.len(), if dependents.len() == 1 { "" } else { "s" }),
+                    Style::default().fg(palette.error),
+                ));
+            }
+
+            // Label packages that only matched through the files database
+            if app.provides_matches.contains(item) {
+                spans.push(Span::styled(
+                    format!("  (provides {})", app.search_query),
+                    Style::default().fg(palette.info).add_modifier(Modifier::ITALIC),
+                ));
+            }
+
+            // Description in whatever room is left on the row
+            if let Some(pkg) = details.filter(|pkg| !pkg.description.is_empty()) {
+                let room = row_width.saturating_sub(Line::from(spans.clone()).width() + 2);
+                if room > 1 {
+                    spans.push(Span::styled(
+                        format!("  {}", truncate(&pkg.description, room)),
+                        Style::default().fg(palette.text_dim),
+                    ));
+                }
+            }
+
+            ListItem::new(Line::from(spans)).style(style)
         })
         .collect();
 
+    // Show how old cached data is, tinted once it's past the stale threshold
+    let mut count = match &app.filter_label {
+        Some(filter) => format!("{} items ({})", app.filtered_items.len(), filter),
+        None => format!("{} items", app.filtered_items.len()),
+    };
+    if let Some(repo_filter) = app.repo_filter.as_ref().filter(|f| **f != RepoFilter::All) {
+        count = format!("{} — repo: {}", count, repo_filter.label());
+    }
+    if app.search_descriptions {
+        count = format!("{} — searching descriptions", count);
+    }
+    if app.sort_mode != SortMode::Score {
+        count = format!("{} — sorted by {}", count, app.sort_mode.label());
+    }
+    let mut list_title = format!(" {} ", count);
+    let mut title_style = Style::default().fg(palette.border);
+    if app.loading.is_active() {
+        list_title = format!(" {} {} ", app.loading.spinner.current(), app.loading.message);
+        title_style = Style::default().fg(palette.primary);
+    } else if app.selected_only {
+        list_title = format!(" {} selected ", app.selected.len());
+        title_style = Style::default().fg(palette.primary);
+    } else if let Some(created_at) = app.data_created_at {
+        let age = created_at.elapsed().unwrap_or_default();
+        list_title = format!(" {} — data {} old ", count, format_age(age));
+        if age > app.stale_after {
+            title_style = Style::default().fg(palette.warning);
+        }
+    }
+    if let Some(filter_time) = app.filter_time {
+        list_title = format!("{}(filtered in {:.1}ms) ", list_title, filter_time.as_secs_f64() * 1000.0);
+    }
+
     let items_list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title(format!(" {} items ", app.filtered_items.len()))
+                .title(Span::styled(list_title, title_style))
                 .style(Style::default().fg(palette.border)),
         )
         .highlight_style(
@@ -83,27 +318,62 @@ pub fn ui_in_area(f: &mut Frame, app: &mut App, prompt: &str, area: Rect, palett
         )
         .highlight_symbol(">> ");
 
-    f.render_stateful_widget(items_list, list_chunks[1], &mut app.list_state);
-
-    // Footer with help hint
-    let footer_text = "Press '?' for help";
-
-    let footer = Paragraph::new(footer_text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(palette.primary));
-
-    f.render_widget(footer, list_chunks[2]);
+    // The rows start at the window, so the selection is relative to it
+    let mut window_state = ListState::default().with_selected(app.list_state.selected().map(|selected| selected - offset));
+    f.render_stateful_widget(items_list, list_chunks[1], &mut window_state);
+    app.list_area = list_chunks[1];
 
     // Right/Bottom panel (preview)
-    if app.preview_cmd.is_some() {
+    app.preview_area = if app.shows_preview() { chunks[1] } else { Rect::default() };
+    if app.shows_preview() && app.selection_preview {
+        render_selection_preview(f, app, chunks[1], palette);
+    } else if app.shows_preview() {
+        let title = if app.link_hints_active {
+            format!(" Preview - open link: {}_ (ESC to cancel) ", app.link_hint_input)
+        } else {
+            " Preview ".to_string()
+        };
+
         let preview_block = Block::default()
             .borders(Borders::ALL)
-            .title(" Preview ")
+            .title(title)
             .style(Style::default().fg(palette.preview_border));
 
-        let preview = Paragraph::new(app.preview_content.clone())
+        let mut content = if app.link_hints_active {
+            preview_with_link_hints(app, palette)
+        } else if let Some(info) = PackageInfo::from_pacman_output(&app.preview_content) {
+            let mut text = package_info_text(&info, &app.installed, palette);
+            if app.preview_content.trim_end().ends_with(aur::INFO_UNAVAILABLE) {
+                text.lines.push(Line::from(""));
+                text.lines.push(Line::from(Span::styled(aur::INFO_UNAVAILABLE, Style::default().fg(palette.text_dim))));
+            }
+            text
+        } else {
+            Text::from(app.preview_content.clone())
+        };
+
+        // List what blocks removal above the package details
+        let dependents = app
+            .current_preview_item
+            .as_ref()
+            .and_then(|item| app.required_by.get(item))
+            .filter(|d| !d.is_empty());
+        if let Some(dependents) = dependents {
+            let mut lines = vec![
+                Line::from(Span::styled(
+                    format!("Required by: {}", dependents.join(", ")),
+                    Style::default().fg(palette.error).add_modifier(Modifier::BOLD),
+                )),
+                Line::from(""),
+            ];
+            lines.append(&mut content.lines);
+            content = Text::from(lines);
+        }
+
+        let preview = Paragraph::new(content)
             .block(preview_block)
             .wrap(Wrap { trim: false })
+            .scroll((app.preview_scroll, 0))
             .style(Style::default().fg(palette.text_primary));
 
         f.render_widget(preview, chunks[1]);
@@ -130,20 +400,221 @@ pub fn ui_in_area(f: &mut Frame, app: &mut App, prompt: &str, area: Rect, palett
     }
 }
 
+/// Package details as aligned fields with colored labels
+/// Dependencies are green when installed and dim when not (uncolored if the installed packages aren't known);
+/// fields the parser doesn't know follow as pacman printed them
+fn package_info_text(info: &PackageInfo, installed: &HashSet<String>, palette: &ThemePalette) -> Text<'static> {
+    let mut fields: Vec<(String, Vec<String>)> =
+        info.fields().into_iter().map(|(key, values)| (key.to_string(), values)).collect();
+    fields.extend(info.extra.iter().cloned());
+    let key_width = fields.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    let key_style = Style::default().fg(palette.secondary).add_modifier(Modifier::BOLD);
+    let size_width = fields
+        .iter()
+        .filter(|(key, _)| key.ends_with(" Size"))
+        .flat_map(|(_, values)| values.iter().map(String::len))
+        .max()
+        .unwrap_or(0);
+
+    // Everything a local package depends on is installed; pacman wouldn't have installed it otherwise
+    let local = info.install_date.is_some();
+    let dependency_style = |dependency: &str| {
+        let name = dependency.split(['<', '>', '=']).next().unwrap_or(dependency);
+        if local || installed.contains(name) {
+            Style::default().fg(palette.success)
+        } else if installed.is_empty() {
+            Style::default().fg(palette.text_primary)
+        } else {
+            Style::default().fg(palette.text_dim)
+        }
+    };
+
+    let mut lines = Vec::new();
+    if let Some(since) = info.flagged_out_of_date() {
+        lines.push(Line::from(Span::styled(
+            format!("FLAGGED OUT OF DATE (since {})", since),
+            Style::default().fg(palette.error).add_modifier(Modifier::BOLD),
+        )));
+        lines.push(Line::from(""));
+    }
+    for (key, values) in fields {
+        let mut label = Span::styled(format!("{:<width$} ", key, width = key_width), key_style);
+        if values.is_empty() {
+            lines.push(Line::from(vec![label, Span::styled("None", Style::default().fg(palette.text_dim))]));
+            continue;
+        }
+
+        // Dependencies wrap as one list, each colored by whether it is installed
+        if key == "Depends On" {
+            let mut spans = vec![label];
+            for (i, dependency) in info.depends.iter().enumerate() {
+                if i > 0 {
+                    spans.push(Span::raw("  "));
+                }
+                spans.push(Span::styled(dependency.clone(), dependency_style(dependency)));
+            }
+            lines.push(Line::from(spans));
+            continue;
+        }
+
+        for value in values {
+            let (value, value_style) = match key.as_str() {
+                "Name" => (value, Style::default().fg(palette.text_primary).add_modifier(Modifier::BOLD)),
+                "Version" => (value, Style::default().fg(palette.success)),
+                "URL" => (value, Style::default().fg(palette.info).add_modifier(Modifier::UNDERLINED)),
+                "Optional Deps" if value.ends_with("[installed]") => (value, Style::default().fg(palette.success)),
+                "Optional Deps" => (value, Style::default().fg(palette.text_dim)),
+                "Download Size" | "Installed Size" => {
+                    (format!("{:>width$}", value, width = size_width), Style::default().fg(palette.text_primary))
+                }
+                _ => (value, Style::default().fg(palette.text_primary)),
+            };
+            lines.push(Line::from(vec![label, Span::styled(value, value_style)]));
+            // Further entries (e.g. optional dependencies) line up under the first
+            label = Span::raw(" ".repeat(key_width + 1));
+        }
+    }
+
+    Text::from(lines)
+}
+
+/// Preview text with each link's number shown in front of it
+fn preview_with_link_hints<'a>(app: &'a App, palette: &ThemePalette) -> Text<'a> {
+    let hint_style = Style::default()
+        .fg(palette.warning)
+        .add_modifier(Modifier::BOLD | Modifier::REVERSED);
+    let link_style = Style::default()
+        .fg(palette.info)
+        .add_modifier(Modifier::UNDERLINED);
+
+    let lines: Vec<Line> = app
+        .preview_content
+        .lines()
+        .map(|line| {
+            let mut spans = Vec::new();
+            let mut last = 0;
+
+            for m in find_urls(line) {
+                let url = &line[m.start..m.end];
+                let number = app.links.iter().position(|l| l == url).map_or(0, |i| i + 1);
+
+                spans.push(Span::raw(&line[last..m.start]));
+                spans.push(Span::styled(format!("[{}]", number), hint_style));
+                spans.push(Span::styled(url, link_style));
+                last = m.end;
+            }
+
+            spans.push(Span::raw(&line[last..]));
+            Line::from(spans)
+        })
+        .collect();
+
+    Text::from(lines)
+}
+
+/// Combined summary of all selected packages, built from the preview cache
+fn render_selection_preview(f: &mut Frame, app: &App, area: Rect, palette: &ThemePalette) {
+    let selected = app.get_selected_items();
+    let mut lines = Vec::new();
+    let mut total_size = 0u64;
+    let mut loading = 0;
+
+    for item in &selected {
+        let Some(info) = app.preview_cache.get(item) else {
+            loading += 1;
+            lines.push(Line::from(Span::styled(item.clone(), Style::default().fg(palette.primary).add_modifier(Modifier::BOLD))));
+            lines.push(Line::from(Span::styled("  Loading...", Style::default().fg(palette.text_secondary))));
+            lines.push(Line::from(""));
+            continue;
+        };
+
+        let info = PackageInfo::from_pacman_output(info).unwrap_or_default();
+        total_size += info.installed_size.unwrap_or(0);
+        let or_dash = |value: String| if value.is_empty() { "—".to_string() } else { value };
+
+        lines.push(Line::from(vec![
+            Span::styled(item.clone(), Style::default().fg(palette.primary).add_modifier(Modifier::BOLD)),
+            Span::raw(" "),
+            Span::styled(or_dash(info.version), Style::default().fg(palette.success)),
+            Span::styled(
+                format!("  {}", info.installed_size.map_or("—".to_string(), format_size)),
+                Style::default().fg(palette.text_secondary),
+            ),
+        ]));
+        lines.push(Line::from(format!("  {}", or_dash(info.description))));
+        lines.push(Line::from(""));
+    }
+
+    if selected.is_empty() {
+        lines.push(Line::from(Span::styled("No packages selected (TAB to select)", Style::default().fg(palette.text_secondary))));
+    } else {
+        lines.push(Line::from(Span::styled(
+            format!("Total: {} package(s), {} installed size", selected.len(), format_size(total_size)),
+            Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD),
+        )));
+        if loading > 0 {
+            lines.push(Line::from(Span::styled(
+                format!("{} still loading", loading),
+                Style::default().fg(palette.text_secondary),
+            )));
+        }
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Selection ({}) - Alt+S for item preview ", selected.len()))
+        .style(Style::default().fg(palette.preview_border));
+
+    let preview = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false })
+        .scroll((app.selection_scroll, 0))
+        .style(Style::default().fg(palette.text_primary));
+
+    f.render_widget(preview, area);
+}
+
+/// Cut text to `width` terminal columns, ending with "…" when something was cut
+/// Wide characters (CJK, emoji) take two columns and are never split; combining marks stay with their character
+fn truncate(text: &str, width: usize) -> String {
+    if text.width() <= width {
+        return text.to_string();
+    }
+    if width == 0 {
+        return String::new();
+    }
+
+    let room = width - 1;
+    let mut used = 0;
+    let mut cut: String = text
+        .chars()
+        .take_while(|c| {
+            used += c.width().unwrap_or(0);
+            used <= room
+        })
+        .collect();
+    cut.push('…');
+    cut
+}
+
+/// Format a duration as a short age ("<1m", "45m", "3h", "2d")
+fn format_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    if secs < 60 {
+        "<1m".to_string()
+    } else if secs < 3_600 {
+        format!("{}m", secs / 60)
+    } else if secs < 86_400 {
+        format!("{}h", secs / 3_600)
+    } else {
+        format!("{}d", secs / 86_400)
+    }
+}
+
 fn render_update_window(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
     // Create a centered overlay area (80% width, 80% height)
     let area = f.area();
-    let overlay_width = (area.width as f32 * 0.8) as u16;
-    let overlay_height = (area.height as f32 * 0.8) as u16;
-    let overlay_x = (area.width - overlay_width) / 2;
-    let overlay_y = (area.height - overlay_height) / 2;
-
-    let overlay_area = Rect {
-        x: overlay_x,
-        y: overlay_y,
-        width: overlay_width,
-        height: overlay_height,
-    };
+    let overlay_area = centered(area, (area.width as f32 * 0.8) as u16, (area.height as f32 * 0.8) as u16);
 
     // Clear the background to create a dimmed effect
     f.render_widget(Clear, overlay_area);
@@ -155,24 +626,49 @@ fn render_update_window(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
         &app.update_window.title
     };
 
-    let title = if app.update_window.completed {
-        if app.update_window.has_error {
-            format!(" {} - FAILED ", base_title)
+    // The clock ticks on every redraw while running, then shows how long the operation took
+    let elapsed = app.update_window.elapsed();
+    let title = if app.update_window.is_waiting_for_lock() {
+        format!(" {} - Waiting for database lock ", base_title)
+    } else if app.update_window.completed {
+        let took = format_duration(elapsed);
+        if app.update_window.is_cancelled() {
+            format!(" {} - CANCELLED after {} ", base_title, took)
+        } else if app.update_window.has_error {
+            format!(" {} - FAILED after {} ", base_title, took)
+        } else if let Some(left) = app.update_window.auto_close_in() {
+            format!(" {} - COMPLETED in {} - closing in {}s ", base_title, took, left.as_secs_f64().ceil())
         } else {
-            format!(" {} - COMPLETED ", base_title)
+            format!(" {} - COMPLETED in {} ", base_title, took)
         }
+    } else if app.update_window.is_cancelled() {
+        format!(" {} - Cancelling... {} ", base_title, format_clock(elapsed))
+    } else if let Some(step) = app.update_window.current_step().filter(|_| app.update_window.steps.len() > 1) {
+        format!(" {} - Step {}/{} - Running... {} ", base_title, step + 1, app.update_window.steps.len(), format_clock(elapsed))
     } else {
-        format!(" {} - Running... ", base_title)
+        format!(" {} - Running... {} ", base_title, format_clock(elapsed))
     };
 
     // Footer with keybinding - visible and prominent
-    let footer = if app.update_window.completed || app.update_window.has_error {
-        " Press Alt+X to close "
+    let footer = if app.update_window.is_waiting_for_lock() {
+        " Press R to check again | Esc to cancel "
+    } else if app.update_window.completed && app.update_window.is_cancelled() {
+        " Press R to run it again | Alt+X to close "
+    } else if app.update_window.completed && app.update_window.has_error {
+        " Press R to retry the failed step | Alt+X to close "
+    } else if app.update_window.auto_close_in().is_some() {
+        " Any key keeps it open | Enter or Alt+X to close now "
+    } else if app.update_window.completed || app.update_window.has_error {
+        " Press Enter or Alt+X to close "
+    } else if app.update_window.is_cancelled() {
+        " Ctrl+C again to terminate "
     } else {
-        " Running... Alt+X available after completion "
+        " Running... Ctrl+C to cancel | Alt+X available after completion "
     };
 
-    let border_color = if app.update_window.completed {
+    let border_color = if app.update_window.is_cancelled() {
+        palette.info
+    } else if app.update_window.completed {
         if app.update_window.has_error {
             palette.error
         } else {
@@ -182,67 +678,43 @@ fn render_update_window(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
         palette.warning
     };
 
+    // Pacman's progress takes the last two rows (a status line and a gauge) while the step runs
+    let progress = app.update_window.progress.as_ref().filter(|_| !app.update_window.completed);
+    let progress_rows = if progress.is_some() { 2 } else { 0 };
+
+    // Calculate how many lines we can show (subtract 2 for borders)
+    let content_height = overlay_area.height.saturating_sub(2 + progress_rows) as usize;
+    let content_width = overlay_area.width.saturating_sub(4) as usize; // Subtract borders and padding
+    app.update_window.page_height = content_height;
+
+    // Whether new output scrolls into view, once there is more than fits
+    let scroll_indicator = if app.update_window.output.len() <= content_height {
+        Line::default()
+    } else if app.update_window.is_following() {
+        Line::from(" ▼ following ").right_aligned()
+    } else {
+        Line::from(" ⤓ scrolled (End to follow) ").right_aligned()
+    };
+
     let update_block = Block::default()
         .borders(Borders::ALL)
         .title(title)
+        .title(scroll_indicator)
         .title_bottom(footer)
         .title_style(Style::default().fg(border_color).bold())
         .border_style(Style::default().fg(border_color));
 
-    // Calculate how many lines we can show (subtract 2 for borders)
-    let content_height = overlay_height.saturating_sub(2) as usize;
-    let content_width = overlay_width.saturating_sub(4) as usize; // Subtract borders and padding
-
-    // Helper function to remove ANSI escape codes
-    fn strip_ansi_codes(s: &str) -> String {
-        let mut result = String::new();
-        let mut chars = s.chars();
-        while let Some(c) = chars.next() {
-            if c == '\x1b' {
-                // Skip escape sequence
-                if chars.next() == Some('[') {
-                    // Skip until we find a letter (end of escape sequence)
-                    while let Some(next_c) = chars.next() {
-                        if next_c.is_alphabetic() {
-                            break;
-                        }
-                    }
-                }
-            } else {
-                result.push(c);
-            }
-        }
-        result
-    }
-
-    // Process output: strip ANSI codes and truncate long lines
-    let processed_output: Vec<String> = app.update_window.output
+    // The page of output scrolled to (the newest while following); long lines are truncated
+    // (escape codes were stripped as the output arrived)
+    let output_text = app
+        .update_window
+        .output
         .iter()
-        .map(|line| {
-            let stripped = strip_ansi_codes(line);
-            if stripped.len() > content_width {
-                // Truncate and add ellipsis
-                format!("{}...", &stripped[..content_width.saturating_sub(3)])
-            } else {
-                stripped
-            }
-        })
-        .collect();
-
-    // Get the last N lines that fit in the window
-    let start_idx = if processed_output.len() > content_height {
-        processed_output.len() - content_height
-    } else {
-        0
-    };
-
-    let visible_output: Vec<String> = processed_output
-        .iter()
-        .skip(start_idx)
-        .cloned()
-        .collect();
-
-    let output_text = visible_output.join("\n");
+        .skip(app.update_window.visible_start())
+        .take(content_height)
+        .map(|line| truncate(line, content_width))
+        .collect::<Vec<_>>()
+        .join("\n");
 
     let update_content = Paragraph::new(output_text)
         .block(update_block)
@@ -250,6 +722,24 @@ fn render_update_window(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
         .style(Style::default().fg(palette.text_primary));
 
     f.render_widget(update_content, overlay_area);
+
+    if let Some(progress) = progress {
+        let inner = Block::default().borders(Borders::ALL).inner(overlay_area);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1), Constraint::Length(1)])
+            .split(inner);
+
+        let status = Paragraph::new(truncate(&progress.label(), content_width)).style(Style::default().fg(palette.info).bold());
+        f.render_widget(status, rows[1]);
+
+        let ratio = progress.ratio();
+        let gauge = Gauge::default()
+            .gauge_style(Style::default().fg(palette.info).bg(palette.background))
+            .ratio(ratio)
+            .label(format!("{:.0}%", ratio * 100.0));
+        f.render_widget(gauge, rows[2]);
+    }
 }
 
 fn render_help_window(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
@@ -259,20 +749,12 @@ fn render_help_window(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
     // Calculate responsive dimensions (min 80 cols for two columns)
     let min_width = 80u16;
     let max_width_percent = 0.90;
-    let overlay_width = ((area.width as f32 * max_width_percent) as u16).max(min_width).min(area.width - 4);
+    let overlay_width = ((area.width as f32 * max_width_percent) as u16).max(min_width).min(area.width.saturating_sub(4));
 
     // Height: 90% of screen or max available
-    let overlay_height = ((area.height as f32 * 0.90) as u16).min(area.height - 4);
+    let overlay_height = ((area.height as f32 * 0.90) as u16).min(area.height.saturating_sub(4));
 
-    let overlay_x = (area.width.saturating_sub(overlay_width)) / 2;
-    let overlay_y = (area.height.saturating_sub(overlay_height)) / 2;
-
-    let overlay_area = Rect {
-        x: overlay_x,
-        y: overlay_y,
-        width: overlay_width,
-        height: overlay_height,
-    };
+    let overlay_area = centered(area, overlay_width, overlay_height);
 
     // Clear the background
     f.render_widget(Clear, overlay_area);
@@ -314,130 +796,36 @@ fn render_help_window(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
 
     f.render_widget(title_widget, main_chunks[0]);
 
-    // Determine number of columns based on width
-    let use_two_columns = overlay_width >= 80;
-
-    if use_two_columns {
-        // Two column layout
-        let columns = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(50),
-                Constraint::Percentage(50),
-            ])
-            .split(main_chunks[1]);
-
-        // Left column content
-        let left_content = vec![
-            Line::from(vec![
-                Span::styled("NAVIGATION", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  ↑ / k        Move up in list"),
-            Line::from("  ↓ / j        Move down in list"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("SELECTION & ACTIONS", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  TAB          Toggle selection"),
-            Line::from("  ENTER        Confirm selection"),
-            Line::from("  ESC          Cancel and exit"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("SEARCH", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  Type         Filter packages"),
-            Line::from("  Backspace    Delete character"),
-            Line::from(""),
-        ];
-
-        // Right column content
-        let right_content = vec![
-            Line::from(vec![
-                Span::styled("LAYOUT", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  Alt+O        Horizontal layout"),
-            Line::from("  Alt+V        Vertical layout"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("SYSTEM", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  Ctrl+U       Update system"),
-            Line::from("  Ctrl+T       Change theme"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("HELP", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  ?            Show/hide help"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("TIPS", Style::default().fg(palette.success).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("• Fuzzy search available"),
-            Line::from("• Multi-select with TAB"),
-            Line::from("• Updates auto-close"),
-            Line::from("• Alt+X closes errors"),
-        ];
-
-        let left_para = Paragraph::new(left_content)
-            .scroll((app.help_scroll, 0))
-            .style(Style::default().fg(palette.text_primary));
-
-        let right_para = Paragraph::new(right_content)
-            .scroll((app.help_scroll, 0))
-            .style(Style::default().fg(palette.text_primary));
-
-        f.render_widget(left_para, columns[0]);
-        f.render_widget(right_para, columns[1]);
-    } else {
-        // Single column layout for narrow screens
-        let content = vec![
-            Line::from(vec![
-                Span::styled("NAVIGATION", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  ↑ / k        Move up in list"),
-            Line::from("  ↓ / j        Move down in list"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("SELECTION", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  TAB          Toggle selection"),
-            Line::from("  ENTER        Confirm"),
-            Line::from("  ESC          Cancel"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("SEARCH", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  Type         Filter"),
-            Line::from("  Backspace    Delete"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("LAYOUT", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  Alt+O        Horizontal"),
-            Line::from("  Alt+V        Vertical"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("SYSTEM", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  Ctrl+U       Update"),
-            Line::from("  Ctrl+T       Theme"),
-            Line::from(""),
-            Line::from(vec![
-                Span::styled("HELP", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-            ]),
-            Line::from("  ?            Toggle help"),
-            Line::from(""),
-        ];
-
-        let para = Paragraph::new(content)
+    // Two columns once there is room for them, generated from the one list of bindings
+    let column_count = if overlay_width >= 80 { 2 } else { 1 };
+    let columns = help_columns(column_count);
+    let longest = columns.iter().map(Vec::len).max().unwrap_or(0);
+    app.help_scroll = app.help_scroll.min(longest.saturating_sub(main_chunks[1].height as usize) as u16);
+
+    let areas = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Ratio(1, column_count as u32); column_count])
+        .split(main_chunks[1]);
+    for (lines, area) in columns.into_iter().zip(areas.iter()) {
+        let lines: Vec<Line> = lines
+            .into_iter()
+            .map(|line| match line {
+                HelpLine::Heading(section) => {
+                    let color = if section == Section::Tips { palette.success } else { palette.help_section };
+                    Line::from(Span::styled(section.title(), Style::default().fg(color).add_modifier(Modifier::BOLD)))
+                }
+                HelpLine::Binding(keys, description) => Line::from(format!("{}{}", keys, description)),
+                HelpLine::Blank => Line::from(""),
+            })
+            .collect();
+        let paragraph = Paragraph::new(lines)
             .scroll((app.help_scroll, 0))
             .style(Style::default().fg(palette.text_primary));
-
-        f.render_widget(para, main_chunks[1]);
+        f.render_widget(paragraph, *area);
     }
 }
 
-fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
+fn render_confirm_dialog(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
     // Create a responsive centered dialog
     let area = f.area();
 
@@ -465,31 +853,52 @@ fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
 
     // Calculate height based on content
     let max_visible_packages = 6u16;
-    let package_count = (app.confirm_dialog.packages.len() as u16).min(max_visible_packages);
+    let badge_count: usize = app.confirm_dialog.badges.values().map(|b| b.len()).sum();
+    // Official/AUR section headers take a line each
+    let listed_lines = app.confirm_dialog.packages.len()
+        + badge_count
+        + app.confirm_dialog.sources.len()
+        + usize::from(!app.confirm_dialog.reinstalls.is_empty());
+    let package_count = (listed_lines as u16).min(max_visible_packages);
 
     // Height breakdown:
     // - Title border: 2 lines
     // - message + empty line: 2 lines
-    // - Packages: package_count lines
+    // - Packages: package_count lines (badges take a line each)
     // - Empty line: 1 line
     // - Separator + empty line: 2 lines
     // - Question + empty line: 2 lines
     // - Buttons: 3 lines
     // - ESC text: 1 line
+    // - Warnings (wrapped) + empty line, if any
     // - Bottom border included in calculation
-    let content_height = 2 + 2 + package_count + 1 + 2 + 2 + 3 + 1;
+    let warning_width = dialog_width.saturating_sub(4).max(1) as usize;
+    let warning_height: u16 = if app.confirm_dialog.warnings.is_empty() {
+        0
+    } else {
+        app.confirm_dialog
+            .warnings
+            .iter()
+            .map(|w| (w.width() + 2).div_ceil(warning_width) as u16)
+            .sum::<u16>()
+            + 1
+    };
+    // Command and summary lines under the message
+    let transaction_lines = app.confirm_dialog.transaction_lines();
+    let details_height =
+        u16::from(app.confirm_dialog.command.is_some()) + (app.confirm_dialog.summary.len() + transaction_lines.len()) as u16;
+    // Exact command lines at the bottom (wrapped)
+    let command_height: u16 = app
+        .confirm_dialog
+        .commands
+        .iter()
+        .map(|c| (c.width() + 2).div_ceil(warning_width) as u16)
+        .sum();
+    let content_height = 2 + 2 + details_height + package_count + 1 + 2 + 2 + 3 + 1 + warning_height + command_height;
     let max_height = (area.height as f32 * 0.7) as u16;
     let dialog_height = content_height.min(max_height).max(16).min(area.height.saturating_sub(4));
 
-    let dialog_x = (area.width.saturating_sub(dialog_width)) / 2;
-    let dialog_y = (area.height.saturating_sub(dialog_height)) / 2;
-
-    let dialog_area = Rect {
-        x: dialog_x,
-        y: dialog_y,
-        width: dialog_width,
-        height: dialog_height,
-    };
+    let dialog_area = centered(area, dialog_width, dialog_height);
 
     // Clear the background
     f.render_widget(Clear, dialog_area);
@@ -504,10 +913,18 @@ fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
             " Confirm Removal ",
             palette.error,
         ),
+        ActionType::Update => (
+            " Arch Linux News ",
+            palette.warning,
+        ),
+        ActionType::Upgrade => (
+            " Confirm Upgrade ",
+            palette.warning,
+        ),
     };
 
     // Add scroll hint to title if there are many packages
-    let title = if app.confirm_dialog.packages.len() > max_visible_packages as usize {
+    let title = if listed_lines > max_visible_packages as usize {
         format!("{} - ↑/↓ to scroll ", title_text)
     } else {
         title_text.to_string()
@@ -524,16 +941,42 @@ fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
     f.render_widget(dialog_block, dialog_area);
 
     // Split inner area: package list area + buttons area
-    // Package area height: 2 (header) + package_count + 1 (bottom padding)
-    let package_area_height = 2 + package_count + 1;
+    // Package area height: 2 (header) + command and summary + package_count + 1 (bottom padding)
+    let package_area_height = 2 + details_height + package_count + 1;
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(warning_height), // Warnings (if any)
+            Constraint::Min(0),                 // Packages + buttons
+        ])
+        .split(inner_area);
+
+    if warning_height > 0 {
+        let warning_lines: Vec<Line> = app
+            .confirm_dialog
+            .warnings
+            .iter()
+            .map(|w| {
+                Line::from(Span::styled(
+                    format!("⚠ {}", w),
+                    Style::default().fg(palette.error).add_modifier(Modifier::BOLD),
+                ))
+            })
+            .collect();
+
+        let warnings = Paragraph::new(warning_lines).wrap(Wrap { trim: false });
+        f.render_widget(warnings, sections[0]);
+    }
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
             Constraint::Length(package_area_height), // Package list (scrollable)
             Constraint::Min(9),                      // Buttons (fixed)
+            Constraint::Length(command_height),      // Commands that will run
         ])
-        .split(inner_area);
+        .split(sections[1]);
 
     // Create package list content
     let mut package_lines = vec![];
@@ -542,31 +985,93 @@ fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
     let action_msg = match app.confirm_dialog.action_type {
         ActionType::Install => "The following packages will be installed:",
         ActionType::Remove => "The following packages will be removed:",
+        ActionType::Update => "News since your last upgrade (may need manual steps):",
+        ActionType::Upgrade => "The following packages will be upgraded:",
     };
     package_lines.push(Line::from(vec![
         Span::styled(action_msg, Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
     ]));
+    // Exact command, so the removal flags are never a surprise
+    if let Some(command) = &app.confirm_dialog.command {
+        package_lines.push(Line::from(vec![
+            Span::styled("Using: ", Style::default().fg(palette.text_secondary)),
+            Span::styled(command.clone(), Style::default().fg(palette.warning).add_modifier(Modifier::BOLD)),
+        ]));
+    }
+    for line in &app.confirm_dialog.summary {
+        package_lines.push(Line::from(Span::styled(line.clone(), Style::default().fg(palette.text_secondary))));
+    }
+    // Sizes of the whole transaction, dim until (or unless) they are known
+    let transaction_style = match app.confirm_dialog.transaction {
+        TransactionSize::Ready(_) => Style::default().fg(palette.text_secondary),
+        _ => Style::default().fg(palette.text_dim).add_modifier(Modifier::ITALIC),
+    };
+    for line in transaction_lines {
+        package_lines.push(Line::from(Span::styled(line, transaction_style)));
+    }
     package_lines.push(Line::from(""));
 
-    // All packages (no limit, scroll handles overflow)
-    for pkg in &app.confirm_dialog.packages {
+    // All packages (no limit, scroll handles overflow), under their source when split
+    let push_package = |package_lines: &mut Vec<Line>, pkg: &String| {
         // Truncate package name if too long
         let max_pkg_width = (dialog_width.saturating_sub(8)) as usize;
-        let pkg_display = if pkg.len() > max_pkg_width {
-            format!("{}...", &pkg[..max_pkg_width.saturating_sub(3)])
+        let pkg_display = truncate(pkg, max_pkg_width);
+
+        let mut spans = vec![Span::raw("  • ")];
+        if app.confirm_dialog.reinstalls.contains(pkg) {
+            spans.push(Span::styled(format!("{} (reinstall)", pkg_display), Style::default().fg(palette.text_dim)));
         } else {
-            pkg.clone()
+            spans.push(Span::styled(pkg_display, Style::default().fg(palette.primary)));
+        }
+        if app.confirm_dialog.upgrades.contains(pkg) {
+            spans.push(Span::styled(" (upgrade)", Style::default().fg(palette.info)));
+        }
+        package_lines.push(Line::from(spans));
+
+        // AUR maintenance warnings under the affected package
+        for badge in app.confirm_dialog.badges.get(pkg).into_iter().flatten() {
+            package_lines.push(Line::from(vec![
+                Span::raw("      "),
+                Span::styled(format!("⚠ {}", badge), Style::default().fg(palette.warning)),
+            ]));
+        }
+    };
+    // Packages already up to date are listed on their own, after the ones that change
+    let changes = |pkg: &&String| !app.confirm_dialog.reinstalls.contains(pkg);
+    if app.confirm_dialog.sources.is_empty() {
+        for pkg in app.confirm_dialog.packages.iter().filter(changes) {
+            push_package(&mut package_lines, pkg);
+        }
+    } else {
+        for (source, packages) in &app.confirm_dialog.sources {
+            if !packages.iter().any(|pkg| changes(&pkg)) {
+                continue;
+            }
+            package_lines.push(Line::from(Span::styled(
+                format!("{}:", source),
+                Style::default().fg(palette.text_secondary).add_modifier(Modifier::BOLD),
+            )));
+            for pkg in packages.iter().filter(changes) {
+                push_package(&mut package_lines, pkg);
+            }
+        }
+    }
+    if !app.confirm_dialog.reinstalls.is_empty() {
+        let header = if app.install_options.needed {
+            "Already up to date, skipped (R to reinstall):"
+        } else {
+            "Already up to date, reinstalled (R to skip):"
         };
-
-        package_lines.push(Line::from(vec![
-            Span::raw("  • "),
-            Span::styled(pkg_display, Style::default().fg(palette.primary))
-        ]));
+        package_lines.push(Line::from(Span::styled(header, Style::default().fg(palette.text_dim).add_modifier(Modifier::BOLD))));
+        for pkg in &app.confirm_dialog.reinstalls {
+            push_package(&mut package_lines, pkg);
+        }
     }
 
     package_lines.push(Line::from(""));
 
-    // Package list with scroll
+    // Package list with scroll, kept within its lines
+    app.confirm_dialog.fit_scroll(package_lines.len(), chunks[0].height);
     let package_list = Paragraph::new(package_lines)
         .scroll((app.confirm_dialog.scroll, 0))
         .alignment(Alignment::Left)
@@ -585,32 +1090,71 @@ fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
     button_lines.push(Line::from(""));
 
     // Confirmation prompt with icon
-    button_lines.push(Line::from(vec![
-        Span::styled("", Style::default().fg(palette.warning)), // Question icon
-        Span::raw(" "),
-        Span::styled("Do you want to continue?", Style::default().fg(palette.text_primary).add_modifier(Modifier::BOLD))
-    ]));
+    if let Some(reason) = &app.confirm_dialog.blocked {
+        button_lines.push(Line::from(vec![
+            Span::styled("✗ ", Style::default().fg(palette.error)),
+            Span::styled(reason.clone(), Style::default().fg(palette.error).add_modifier(Modifier::BOLD))
+        ]));
+    } else if app.confirm_dialog.require_typed {
+        // Risky removal: "yes" has to be typed out
+        let typed_color = if app.confirm_dialog.needs_typing() { palette.error } else { palette.success };
+        button_lines.push(Line::from(vec![
+            Span::styled("Type ", Style::default().fg(palette.error).add_modifier(Modifier::BOLD)),
+            Span::styled("yes", Style::default().fg(palette.error).add_modifier(Modifier::BOLD | Modifier::UNDERLINED)),
+            Span::styled(" to remove: ", Style::default().fg(palette.error).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("{}█", app.confirm_dialog.typed), Style::default().fg(typed_color)),
+        ]));
+    } else if app.confirm_dialog.acknowledged {
+        let again = if app.confirm_dialog.action_type == ActionType::Upgrade {
+            "Press Y again to upgrade anyway"
+        } else {
+            "Press Y again to install anyway"
+        };
+        button_lines.push(Line::from(vec![
+            Span::styled("⚠ ", Style::default().fg(palette.warning)),
+            Span::styled(again, Style::default().fg(palette.warning).add_modifier(Modifier::BOLD))
+        ]));
+    } else {
+        button_lines.push(Line::from(vec![
+            Span::styled("", Style::default().fg(palette.warning)), // Question icon
+            Span::raw(" "),
+            Span::styled("Do you want to continue?", Style::default().fg(palette.text_primary).add_modifier(Modifier::BOLD))
+        ]));
+    }
     button_lines.push(Line::from(""));
 
-    // Buttons with box drawing and icons
+    // Buttons with box drawing and icons (Yes is greyed out while confirming is blocked or "yes" isn't typed)
+    let yes_color = if app.confirm_dialog.blocked.is_some() || app.confirm_dialog.needs_typing() { palette.text_dim } else { palette.success };
     button_lines.push(Line::from(vec![
-        Span::styled("┌───────────┐", Style::default().fg(palette.success)),
+        Span::styled("┌───────────┐", Style::default().fg(yes_color)),
         Span::raw("  "),
         Span::styled("┌────────────┐", Style::default().fg(palette.error)),
     ]));
+    // The focused button (pressed by Enter) is drawn in inverted colors
+    let inside = |color, button| {
+        if app.confirm_dialog.focus == button {
+            Style::default().fg(palette.background).bg(color).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color)
+        }
+    };
+    let yes_inside = inside(yes_color, ConfirmButton::Yes);
+    let no_inside = inside(palette.error, ConfirmButton::No);
     button_lines.push(Line::from(vec![
-        Span::styled("│ ", Style::default().fg(palette.success)),
-        Span::styled("✓ ", Style::default().fg(palette.success).add_modifier(Modifier::BOLD)), // Checkmark icon
-        Span::styled("Y", Style::default().fg(palette.success).add_modifier(Modifier::BOLD)),
-        Span::styled(" - Yes │", Style::default().fg(palette.success)),
+        Span::styled("│", Style::default().fg(yes_color)),
+        Span::styled(" ✓ ", yes_inside.add_modifier(Modifier::BOLD)), // Checkmark icon
+        Span::styled("Y", yes_inside.add_modifier(Modifier::BOLD)),
+        Span::styled(" - Yes ", yes_inside),
+        Span::styled("│", Style::default().fg(yes_color)),
         Span::raw("  "),
-        Span::styled("│ ", Style::default().fg(palette.error)),
-        Span::styled("✗ ", Style::default().fg(palette.error).add_modifier(Modifier::BOLD)), // X icon
-        Span::styled("N", Style::default().fg(palette.error).add_modifier(Modifier::BOLD)),
-        Span::styled(" - No   │", Style::default().fg(palette.error)),
+        Span::styled("│", Style::default().fg(palette.error)),
+        Span::styled(" ✗ ", no_inside.add_modifier(Modifier::BOLD)), // X icon
+        Span::styled("N", no_inside.add_modifier(Modifier::BOLD)),
+        Span::styled(" - No   ", no_inside),
+        Span::styled("│", Style::default().fg(palette.error)),
     ]));
     button_lines.push(Line::from(vec![
-        Span::styled("└───────────┘", Style::default().fg(palette.success)),
+        Span::styled("└───────────┘", Style::default().fg(yes_color)),
         Span::raw("  "),
         Span::styled("└────────────┘", Style::default().fg(palette.error)),
     ]));
@@ -618,7 +1162,9 @@ fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
         Span::styled(" ", Style::default().fg(palette.text_secondary)), // Keyboard icon
         Span::raw(" Press "),
         Span::styled("ESC", Style::default().fg(palette.error).add_modifier(Modifier::BOLD)),
-        Span::raw(" to cancel"),
+        Span::raw(" to cancel, "),
+        Span::styled("←/→", Style::default().fg(palette.text_secondary).add_modifier(Modifier::BOLD)),
+        Span::raw(" to choose"),
     ]));
 
     let buttons = Paragraph::new(button_lines)
@@ -626,17 +1172,28 @@ fn render_confirm_dialog(f: &mut Frame, app: &App, palette: &ThemePalette) {
         .style(Style::default().fg(palette.text_primary));
 
     f.render_widget(buttons, chunks[1]);
+
+    // The exact commands, so it's clear what runs (and whether the AUR helper is involved)
+    let command_lines: Vec<Line> = app
+        .confirm_dialog
+        .commands
+        .iter()
+        .map(|c| Line::from(Span::styled(format!("$ {}", c), Style::default().fg(palette.text_dim))))
+        .collect();
+    f.render_widget(Paragraph::new(command_lines).wrap(Wrap { trim: false }), chunks[2]);
 }
 
 /// Render tab bar at the top of the screen
-pub fn render_tab_bar(f: &mut Frame, area: Rect, selected_tab: usize, palette: &ThemePalette) {
+pub fn render_tab_bar(f: &mut Frame, area: Rect, selected_tab: usize, queue: Option<&str>, palette: &ThemePalette) {
     use super::types::ViewType;
 
-    let tabs = vec![
-        ("[1] Home", ViewType::Home as usize),
-        ("[2] Install", ViewType::Install as usize),
-        ("[3] Remove", ViewType::Remove as usize),
-        ("[4] List", ViewType::List as usize),
+    let tabs = [
+        ("[Alt+1] Home", ViewType::Home as usize),
+        ("[Alt+2] Install", ViewType::Install as usize),
+        ("[Alt+3] Remove", ViewType::Remove as usize),
+        ("[Alt+4] List", ViewType::List as usize),
+        ("[Alt+5] Updates", ViewType::Updates as usize),
+        ("[Alt+6] Settings", ViewType::Settings as usize),
     ];
 
     let mut tab_spans = vec![];
@@ -659,12 +1216,87 @@ pub fn render_tab_bar(f: &mut Frame, area: Rect, selected_tab: usize, palette: &
     }
 
     let tabs_line = Line::from(tab_spans);
-    let tabs_paragraph = Paragraph::new(tabs_line)
-        .block(Block::default().borders(Borders::BOTTOM));
+    let mut block = Block::default().borders(Borders::BOTTOM);
+    // Queued installs and removals, on the border until they are applied or discarded
+    if let Some(queue) = queue {
+        block = block.title_bottom(
+            Line::styled(format!(" {} (Alt+Q) ", queue), Style::default().fg(palette.warning).add_modifier(Modifier::BOLD)).right_aligned(),
+        );
+    }
+    let tabs_paragraph = Paragraph::new(tabs_line).block(block);
 
     f.render_widget(tabs_paragraph, area);
 }
 
+/// Render the Settings tab: one row per setting, what the selected one does, and the config file
+pub fn render_settings_view(f: &mut Frame, area: Rect, state: &SettingsState, settings: &Settings, palette: &ThemePalette) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Settings - ↑/↓ select | Enter change | ESC back ")
+        .style(Style::default().fg(palette.primary));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(SettingsRow::ALL.len() as u16 + 1), // Rows
+            Constraint::Min(0),                                    // Description or input
+            Constraint::Length(state.refresh_status.len() as u16 + 1), // Background refreshes
+            Constraint::Length(2),                                 // Status and config file
+        ])
+        .split(inner);
+
+    let label_width = SettingsRow::ALL.iter().map(|row| row.label().len()).max().unwrap_or(0);
+    let rows: Vec<Line> = SettingsRow::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let selected = i == state.selected;
+            let marker = if selected { ">> " } else { "   " };
+            let label_style = if selected {
+                Style::default().fg(palette.primary).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(palette.text_primary)
+            };
+            Line::from(vec![
+                Span::styled(format!("{}{:<width$}  ", marker, row.label(), width = label_width), label_style),
+                Span::styled(row.value(settings), Style::default().fg(palette.secondary)),
+            ])
+        })
+        .collect();
+    f.render_widget(Paragraph::new(rows), chunks[0]);
+
+    let row = state.selected_row();
+    let details = match &state.input {
+        Some(input) => vec![
+            Line::from(Span::styled(format!("{}:", row.label()), Style::default().fg(palette.text_secondary))),
+            Line::from(Span::styled(format!("{}_", input), Style::default().fg(palette.text_primary).add_modifier(Modifier::BOLD))),
+            Line::from(Span::styled("Enter to save, ESC to cancel", Style::default().fg(palette.text_dim))),
+        ],
+        None => vec![Line::from(Span::styled(row.description(), Style::default().fg(palette.text_secondary)))],
+    };
+    f.render_widget(Paragraph::new(details).wrap(Wrap { trim: false }), chunks[1]);
+
+    let refreshes: Vec<Line> = state
+        .refresh_status
+        .iter()
+        .map(|status| Line::from(status.clone().fg(palette.text_dim).italic()))
+        .collect();
+    f.render_widget(Paragraph::new(refreshes), chunks[2]);
+
+    let path = state
+        .config_path
+        .as_ref()
+        .map(|path| path.display().to_string())
+        .unwrap_or_else(|| "unavailable".to_string());
+    let footer = vec![
+        Line::from(Span::styled(state.status.clone().unwrap_or_default(), Style::default().fg(palette.success))),
+        Line::from(Span::styled(format!("Config file: {}", path), Style::default().fg(palette.text_dim))),
+    ];
+    f.render_widget(Paragraph::new(footer), chunks[3]);
+}
+
 /// Render the home view
 pub fn render_home_view(f: &mut Frame, area: Rect, home_state: &super::home_state::HomeState, palette: &ThemePalette) {
     // Create centered content area
@@ -687,51 +1319,52 @@ pub fn render_home_view(f: &mut Frame, area: Rect, home_state: &super::home_stat
         .split(inner_area);
 
     // Render title section (ASCII art + subtitle)
-    let mut title_lines = vec![];
-    title_lines.push(Line::from(""));
-    title_lines.push(Line::from(Span::styled(
-        "  ______   _____    ___________",
-        Style::default().fg(palette.ascii_art_1),
-    )));
-    title_lines.push(Line::from(Span::styled(
-        "   \\____ \\ /     \\  / ___\\_  __ \\",
-        Style::default().fg(palette.ascii_art_2),
-    )));
-    title_lines.push(Line::from(Span::styled(
-        "   |  |_> >  Y Y  \\/ /_/  >  | \\/",
-        Style::default().fg(palette.ascii_art_3),
-    )));
-    title_lines.push(Line::from(Span::styled(
-        " |   __/|__|_|  /\\___  /|__| ",
-        Style::default().fg(palette.ascii_art_4),
-    )));
-    title_lines.push(Line::from(Span::styled(
-        "|__|         \\//_____/    ",
-        Style::default().fg(palette.ascii_art_5),
-    )));
-    title_lines.push(Line::from(""));
-    title_lines.push(Line::from(vec![
-        "Modern package manager ".fg(palette.primary),
-        "for Arch Linux".fg(palette.secondary).italic(),
-    ]));
-    title_lines.push(Line::from(
-        ratatui::symbols::line::HORIZONTAL
-            .repeat(50)
-            .fg(palette.text_dim),
-    ));
-    title_lines.push(Line::from(
-    env!("CARGO_PKG_REPOSITORY")
-        .italic()
-        .fg(palette.text_secondary),
-    ));
-    title_lines.push(Line::from(vec![
-        "[".fg(palette.text_dim),
-        "with ".into(),
-        "♥".fg(palette.primary),
-        " by ".into(),
-        "@DavidOlmos03".fg(palette.primary),
-        "]".fg(palette.text_dim),
-    ]));
+    let title_lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "  ______   _____    ___________",
+            Style::default().fg(palette.ascii_art_1),
+        )),
+        Line::from(Span::styled(
+            "   \\____ \\ /     \\  / ___\\_  __ \\",
+            Style::default().fg(palette.ascii_art_2),
+        )),
+        Line::from(Span::styled(
+            "   |  |_> >  Y Y  \\/ /_/  >  | \\/",
+            Style::default().fg(palette.ascii_art_3),
+        )),
+        Line::from(Span::styled(
+            " |   __/|__|_|  /\\___  /|__| ",
+            Style::default().fg(palette.ascii_art_4),
+        )),
+        Line::from(Span::styled(
+            "|__|         \\//_____/    ",
+            Style::default().fg(palette.ascii_art_5),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            "Modern package manager ".fg(palette.primary),
+            "for Arch Linux".fg(palette.secondary).italic(),
+        ]),
+        Line::from(
+            ratatui::symbols::line::HORIZONTAL
+                .repeat(50)
+                .fg(palette.text_dim),
+        ),
+        Line::from(
+            env!("CARGO_PKG_REPOSITORY")
+                .italic()
+                .fg(palette.text_secondary),
+        ),
+        Line::from(vec![
+            "[".fg(palette.text_dim),
+            "with ".into(),
+            "♥".fg(palette.primary),
+            " by ".into(),
+            "@DavidOlmos03".fg(palette.primary),
+            "]".fg(palette.text_dim),
+        ]),
+    ];
 
     let title_widget = Paragraph::new(title_lines)
         .alignment(Alignment::Center)
@@ -761,66 +1394,91 @@ pub fn render_home_view(f: &mut Frame, area: Rect, home_state: &super::home_stat
     ));
     sys_info_lines.push(Line::from(""));
 
-    if let Some(ref stats) = home_state.stats {
-        sys_info_lines.push(Line::from(vec![
-            "Installed".fg(palette.primary),
-            Span::raw(": ").fg(palette.text_dim),
-            Span::styled(
-                stats.installed_count.to_string(),
-                Style::default().fg(palette.success)
-            )
-        ]));
-        sys_info_lines.push(Line::from(vec![
-            "Available".fg(palette.primary),
-            Span::raw(": ").fg(palette.text_dim),
-            Span::styled(
-                stats.available_count.to_string(),
-                Style::default().fg(palette.info)
-            )
-        ]));
+    let stats = &home_state.stats;
+    let count = |value: Option<usize>| value.map_or("…".to_string(), |v| v.to_string());
+    sys_info_lines.push(Line::from(vec![
+        "Installed".fg(palette.primary),
+        Span::raw(": ").fg(palette.text_dim),
+        Span::styled(
+            count(stats.installed_count),
+            Style::default().fg(palette.success)
+        )
+    ]));
+    sys_info_lines.push(Line::from(vec![
+        "Available".fg(palette.primary),
+        Span::raw(": ").fg(palette.text_dim),
+        Span::styled(
+            count(stats.available_count),
+            Style::default().fg(palette.info)
+        )
+    ]));
+    let checking = Span::styled("checking…", Style::default().fg(palette.text_dim).add_modifier(Modifier::ITALIC));
+    let mut update_spans = vec!["Updates".fg(palette.primary), Span::raw(": ").fg(palette.text_dim)];
+    match stats.updates_available {
+        None if stats.checking_updates => update_spans.push(checking),
+        updates => {
+            update_spans.push(Span::styled(
+                count(updates),
+                Style::default().fg(if updates.unwrap_or(0) > 0 { palette.error } else { palette.success })
+            ));
+            // A recheck keeps showing the previous count
+            if stats.checking_updates {
+                update_spans.push(Span::raw(" "));
+                update_spans.push(checking);
+            }
+        }
+    }
+    sys_info_lines.push(Line::from(update_spans));
+    if let Some(news) = stats.unread_news {
         sys_info_lines.push(Line::from(vec![
-            "Updates".fg(palette.primary),
+            "News".fg(palette.primary),
             Span::raw(": ").fg(palette.text_dim),
             Span::styled(
-                format!("{}", stats.updates_available),
-                Style::default().fg(if stats.updates_available > 0 { palette.error } else { palette.success })
+                format!("{} since last upgrade", news),
+                Style::default().fg(if news > 0 { palette.warning } else { palette.text_secondary })
             )
         ]));
-    } else {
-        sys_info_lines.push(Line::from("Loading...".italic()));
+    }
+
+    if !home_state.refresh_status.is_empty() {
+        sys_info_lines.push(Line::from(""));
+        for status in &home_state.refresh_status {
+            sys_info_lines.push(Line::from(status.clone().fg(palette.text_dim).italic()));
+        }
     }
 
     // Create Quick Actions section
-    let mut quick_actions_lines = vec![];
-    quick_actions_lines.push(Line::from(vec![
-        Span::styled("Quick Actions", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
-    ]));
-    quick_actions_lines.push(Line::from(
-        ratatui::symbols::line::HORIZONTAL
-            .repeat(13)
-            .fg(palette.text_dim),
-    ));
-    quick_actions_lines.push(Line::from(""));
-    quick_actions_lines.push(Line::from(vec![
-        "[1]".fg(palette.primary),
-        Span::raw(": ").fg(palette.text_dim),
-        "Install packages".into(),
-    ]));
-    quick_actions_lines.push(Line::from(vec![
-        "[2]".fg(palette.primary),
-        Span::raw(": ").fg(palette.text_dim),
-        "Remove packages".into(),
-    ]));
-    quick_actions_lines.push(Line::from(vec![
-        "[3]".fg(palette.primary),
-        Span::raw(": ").fg(palette.text_dim),
-        "List packages".into(),
-    ]));
-    quick_actions_lines.push(Line::from(vec![
-        "[Ctrl+U]".fg(palette.warning),
-        Span::raw(": ").fg(palette.text_dim),
-        "System update".into(),
-    ]));
+    let quick_actions_lines = vec![
+        Line::from(vec![
+            Span::styled("Quick Actions", Style::default().fg(palette.help_section).add_modifier(Modifier::BOLD))
+        ]),
+        Line::from(
+            ratatui::symbols::line::HORIZONTAL
+                .repeat(13)
+                .fg(palette.text_dim),
+        ),
+        Line::from(""),
+        Line::from(vec![
+            "[1]".fg(palette.primary),
+            Span::raw(": ").fg(palette.text_dim),
+            "Install packages".into(),
+        ]),
+        Line::from(vec![
+            "[2]".fg(palette.primary),
+            Span::raw(": ").fg(palette.text_dim),
+            "Remove packages".into(),
+        ]),
+        Line::from(vec![
+            "[3]".fg(palette.primary),
+            Span::raw(": ").fg(palette.text_dim),
+            "List packages".into(),
+        ]),
+        Line::from(vec![
+            "[Ctrl+U]".fg(palette.warning),
+            Span::raw(": ").fg(palette.text_dim),
+            "System update".into(),
+        ]),
+    ];
 
     // Create Keyboard Shortcuts section
     let mut shortcuts_lines = vec![];
@@ -834,7 +1492,7 @@ pub fn render_home_view(f: &mut Frame, area: Rect, home_state: &super::home_stat
     ));
     shortcuts_lines.push(Line::from(""));
     shortcuts_lines.push(Line::from(vec![
-        "1-4".fg(palette.primary),
+        "Alt+1-6".fg(palette.primary),
         Span::raw(": ").fg(palette.text_dim),
         "Switch tabs".into(),
     ]));
@@ -925,7 +1583,6 @@ pub fn render_home_view(f: &mut Frame, area: Rect, home_state: &super::home_stat
 
         let single_column = Paragraph::new(all_lines)
             .alignment(Alignment::Center)
-            .scroll((home_state.scroll_position, 0))
             .style(Style::default().fg(palette.text_primary));
 
         f.render_widget(single_column, main_chunks[1]);
@@ -936,17 +1593,12 @@ fn render_alert(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
     // Create a centered overlay area for alert (60% width, auto height)
     let area = f.area();
     let overlay_width = (area.width as f32 * 0.6).min(80.0) as u16;
-    let overlay_height = 7; // Fixed height for alert
-
-    let overlay_x = (area.width.saturating_sub(overlay_width)) / 2;
-    let overlay_y = (area.height.saturating_sub(overlay_height)) / 2;
-
-    let overlay_area = Rect {
-        x: overlay_x,
-        y: overlay_y,
-        width: overlay_width,
-        height: overlay_height,
-    };
+    // Tall enough for each line of the message once wrapped
+    let inner_width = overlay_width.saturating_sub(2).max(1) as usize;
+    let message_rows: usize = app.alert.message.lines().map(|line| line.width().max(1).div_ceil(inner_width)).sum();
+    // Alerts that close by themselves get a countdown bar
+    let remaining = app.alert.remaining(Instant::now());
+    let overlay_area = centered(area, overlay_width, 6 + message_rows.max(1) as u16 + remaining.is_some() as u16);
 
     // Clear the area
     f.render_widget(Clear, overlay_area);
@@ -958,11 +1610,15 @@ fn render_alert(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
         AlertType::Info => (palette.info, Style::default().fg(palette.info).add_modifier(Modifier::BOLD)),
     };
 
-    let title = match app.alert.alert_type {
+    let mut title = match app.alert.alert_type {
         AlertType::Success => "Success",
         AlertType::Error => "Error",
         AlertType::Info => "Info",
-    };
+    }
+    .to_string();
+    if !app.alert.queue.is_empty() {
+        title.push_str(&format!(" · {} more", app.alert.queue.len()));
+    }
 
     // Create the alert block
     let block = Block::default()
@@ -975,15 +1631,24 @@ fn render_alert(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
         .style(Style::default());
 
     // Create message paragraph
-    let message_lines = vec![
-        Line::from(""),
-        Line::from(Span::styled(&app.alert.message, Style::default().fg(palette.text_primary))),
+    let mut message_lines = vec![Line::from("")];
+    message_lines.extend(
+        app.alert
+            .message
+            .lines()
+            .map(|line| Line::from(Span::styled(line, Style::default().fg(palette.text_primary)))),
+    );
+    message_lines.extend([
         Line::from(""),
         Line::from(Span::styled(
             "Press any key to close",
             Style::default().fg(palette.text_dim).add_modifier(Modifier::ITALIC),
         )),
-    ];
+    ]);
+    if let Some(remaining) = remaining {
+        let width = (inner_width as f64 * remaining).ceil() as usize;
+        message_lines.push(Line::from(Span::styled("━".repeat(width), Style::default().fg(border_color))));
+    }
 
     let paragraph = Paragraph::new(message_lines)
         .block(block)
@@ -994,23 +1659,63 @@ fn render_alert(f: &mut Frame, app: &mut App, palette: &ThemePalette) {
 }
 
 /// Render theme selector modal
+/// Render the review of the queued installs and removals (Alt+Q)
+pub fn render_queue_review(f: &mut Frame, queue: &OperationQueue, palette: &ThemePalette) {
+    let area = f.area();
+    let entries = queue.ordered();
+    let width = ((area.width as f32 * 0.6) as u16).clamp(44, 80);
+    let height = (entries.len() as u16 + 4).clamp(8, area.height.saturating_sub(4).max(8));
+    let modal_area = centered(area, width, height);
+    f.render_widget(Clear, modal_area);
+
+    let (installs, removals) = queue.counts();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" Queue: {} install(s), {} removal(s) ", installs, removals))
+        .title_bottom(" Enter: Apply  |  d: Drop  |  D: Discard all  |  ESC: Close ")
+        .title_style(Style::default().fg(palette.primary).bold())
+        .border_style(Style::default().fg(palette.border_focused));
+
+    if entries.is_empty() {
+        let paragraph = Paragraph::new("Nothing queued: press a in the Install or Remove tab to queue the selection")
+            .block(block)
+            .wrap(Wrap { trim: true })
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(palette.text_secondary));
+        f.render_widget(paragraph, modal_area);
+        return;
+    }
+
+    // Removals run first, so they are listed first
+    let items: Vec<ListItem> = entries
+        .iter()
+        .map(|(action, package)| {
+            let (sign, color) = match action {
+                ActionType::Remove => ("− remove  ", palette.error),
+                _ => ("+ install ", palette.success),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(sign, Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                Span::styled(package.clone(), Style::default().fg(palette.text_primary)),
+            ]))
+        })
+        .collect();
+
+    let mut state = ListState::default().with_selected(Some(queue.selected));
+    let list = List::new(items)
+        .block(block)
+        .highlight_style(Style::default().fg(palette.highlight).add_modifier(Modifier::BOLD))
+        .highlight_symbol("► ");
+    f.render_stateful_widget(list, modal_area, &mut state);
+}
+
 pub fn render_theme_selector(f: &mut Frame, palette: &ThemePalette, selected_idx: usize) {
     use super::theme::Theme;
 
     // Create centered overlay (50% width, fixed height)
     let area = f.area();
     let modal_width = ((area.width as f32 * 0.5).min(60.0) as u16).max(40);
-    let modal_height = 15;
-
-    let modal_x = (area.width.saturating_sub(modal_width)) / 2;
-    let modal_y = (area.height.saturating_sub(modal_height)) / 2;
-
-    let modal_area = Rect {
-        x: modal_x,
-        y: modal_y,
-        width: modal_width,
-        height: modal_height,
-    };
+    let modal_area = centered(area, modal_width, 15);
 
     // Clear background
     f.render_widget(Clear, modal_area);
@@ -1071,22 +1776,92 @@ pub fn render_theme_selector(f: &mut Frame, palette: &ThemePalette, selected_idx
     f.render_widget(footer, chunks[2]);
 }
 
+/// Render the operation log browser over the whole screen
+pub fn render_log_viewer(f: &mut Frame, viewer: &mut LogViewer, palette: &ThemePalette) {
+    let area = f.area();
+    f.render_widget(Clear, area);
+
+    if let Some(open) = &mut viewer.open {
+        let name = open.path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let content_height = area.height.saturating_sub(2) as usize;
+        let content_width = area.width.saturating_sub(2) as usize;
+        open.set_page_height(content_height);
+
+        let last = (open.scroll + content_height).min(open.lines.len());
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", name))
+            .title(Line::from(format!(" {}-{} of {} ", open.scroll + 1, last, open.lines.len())).right_aligned())
+            .title_bottom(" ↑/↓ PgUp/PgDn Home/End: Scroll  |  ESC: Back to the logs ")
+            .title_style(Style::default().fg(palette.primary).bold())
+            .border_style(Style::default().fg(palette.border_focused));
+
+        let lines: Vec<Line> = open
+            .lines
+            .iter()
+            .skip(open.scroll)
+            .take(content_height)
+            .map(|line| {
+                let lower = line.to_lowercase();
+                let color = if line.starts_with(oplog::BEGIN_MARKER) || line.starts_with(oplog::END_MARKER) {
+                    palette.info
+                } else if lower.contains("error") || lower.contains("failed") {
+                    palette.error
+                } else if lower.contains("warning") {
+                    palette.warning
+                } else {
+                    palette.text_primary
+                };
+                Line::styled(truncate(line, content_width), Style::default().fg(color))
+            })
+            .collect();
+
+        f.render_widget(Paragraph::new(lines).block(block), area);
+        return;
+    }
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(" Operation Logs (Ctrl+L) ")
+        .title_bottom(" ↑/↓: Navigate  |  Enter: Open  |  ESC: Close ")
+        .title_style(Style::default().fg(palette.primary).bold())
+        .border_style(Style::default().fg(palette.border_focused));
+
+    let message = match &viewer.error {
+        Some(error) => Some(error.clone()),
+        None if viewer.logs.is_empty() => Some("No operations logged yet".to_string()),
+        None => None,
+    };
+    if let Some(message) = message {
+        let paragraph = Paragraph::new(message)
+            .block(block)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(palette.text_secondary));
+        f.render_widget(paragraph, area);
+        return;
+    }
+
+    let items: Vec<ListItem> = viewer
+        .logs
+        .iter()
+        .map(|path| ListItem::new(path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default()))
+        .collect();
+    let list = List::new(items)
+        .block(block)
+        .style(Style::default().fg(palette.text_primary))
+        .highlight_style(Style::default().fg(palette.highlight).add_modifier(Modifier::BOLD))
+        .highlight_symbol("► ");
+
+    let mut state = ListState::default().with_selected(Some(viewer.selected));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
 /// Render loading spinner overlay
 pub fn render_loading_spinner(f: &mut Frame, loading_state: &LoadingState, palette: &ThemePalette) {
     // Create centered overlay (50% width, 10 lines height)
     let area = f.area();
     let overlay_width = (area.width as f32 * 0.5).min(60.0) as u16;
-    let overlay_height = 10;
-
-    let overlay_x = (area.width.saturating_sub(overlay_width)) / 2;
-    let overlay_y = (area.height.saturating_sub(overlay_height)) / 2;
-
-    let overlay_area = Rect {
-        x: overlay_x,
-        y: overlay_y,
-        width: overlay_width,
-        height: overlay_height,
-    };
+    let overlay_area = centered(area, overlay_width, 10);
 
     // Clear background
     f.render_widget(Clear, overlay_area);
@@ -1131,3 +1906,251 @@ pub fn render_loading_spinner(f: &mut Frame, loading_state: &LoadingState, palet
 
     f.render_widget(paragraph, inner);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::home_state::{HomeState, SystemStats};
+    use crate::ui::Theme;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    const SYNC: &str = "\
+Repository      : extra
+Name            : ripgrep
+Version         : 14.1.0-1
+Description     : A search tool
+Architecture    : x86_64
+URL             : https://github.com/BurntSushi/ripgrep
+Licenses        : MIT  Unlicense
+Depends On      : gcc-libs  glibc>=2.38  pcre2
+Optional Deps   : bash-completion: completions [installed]
+                  zsh: completions
+Download Size   : 1.60 MiB
+Installed Size  : 14.50 MiB
+Packager        : Orhun
+Build Date      : Tue 09 Jan 2024
+";
+
+    /// An app with every overlay open at once
+    fn app_with_overlays() -> App {
+        let mut app = App::new(vec!["extra/ripgrep".to_string(), "core/bash".to_string()], true, None, ActionType::Install);
+        app.update_window.active = true;
+        app.update_window.output = vec!["resolving dependencies...".to_string(); 20];
+        app.help_visible = true;
+        app.confirm_dialog.show(ActionType::Install, vec!["extra/ripgrep".to_string()]);
+        app.confirm_dialog.warnings = vec!["ripgrep is pinned to 13.0.0-1".to_string()];
+        app.alert.show(AlertType::Info, "Installed".to_string());
+        app
+    }
+
+    fn draw(width: u16, height: u16, app: &mut App) -> String {
+        let palette = Theme::Default.palette();
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal.draw(|f| ui(f, app, "Select: ", &palette)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer.content.iter().map(|cell| cell.symbol()).collect()
+    }
+
+    /// Compare the screen with its snapshot in src/ui/snapshots (trailing spaces trimmed)
+    /// After an intended change to a screen, run the tests with UPDATE_SNAPSHOTS=1 to store the new one
+    fn assert_snapshot(name: &str, terminal: &Terminal<TestBackend>) {
+        let buffer = terminal.backend().buffer();
+        let screen: String = buffer
+            .content
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>().trim_end().to_string() + "\n")
+            .collect();
+        let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/ui/snapshots").join(format!("{}.txt", name));
+        if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, &screen).unwrap();
+            return;
+        }
+        let stored = std::fs::read_to_string(&path).unwrap_or_else(|_| panic!("no snapshot at {} (UPDATE_SNAPSHOTS=1 stores it)", path.display()));
+        assert!(stored == screen, "{} changed (UPDATE_SNAPSHOTS=1 stores it if intended):\n{}", name, screen);
+    }
+
+    /// A package list with a search typed and a package selected
+    fn list_app() -> App {
+        let items = ["extra/ripgrep", "core/bash", "extra/git", "extra/vim", "aur/yay-bin"];
+        let mut app = App::new(items.iter().map(|s| s.to_string()).collect(), true, None, ActionType::Install);
+        app.selected.insert("extra/git".to_string());
+        app
+    }
+
+    /// Terminal sizes the screens are checked at, down to the smallest one pmgr draws in
+    const SNAPSHOT_SIZES: [(u16, u16); 3] = [(80, 24), (120, 40), (MIN_WIDTH, MIN_HEIGHT)];
+
+    /// Draw a package view at each size and compare it with its `<scene>_<size>` snapshots
+    fn assert_view_snapshots(scene: &str, app: impl Fn() -> App) {
+        let palette = Theme::Default.palette();
+        for (width, height) in SNAPSHOT_SIZES {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal.draw(|f| ui(f, &mut app(), "Select packages: ", &palette)).unwrap();
+            assert_snapshot(&format!("{}_{}x{}", scene, width, height), &terminal);
+        }
+    }
+
+    #[test]
+    fn list_matches_its_snapshots() {
+        assert_view_snapshots("list", list_app);
+    }
+
+    #[test]
+    fn confirm_dialog_matches_its_snapshots() {
+        assert_view_snapshots("confirm", || {
+            let mut app = list_app();
+            app.confirm_dialog.show(ActionType::Install, vec!["extra/git".to_string(), "aur/yay-bin".to_string()]);
+            app.confirm_dialog.split_sources(vec!["aur/yay-bin".to_string()], vec!["extra/git".to_string()], "pacman", Backend::Yay);
+            app.confirm_dialog.warnings = vec!["git is pinned to 2.44.0-1".to_string()];
+            app
+        });
+    }
+
+    #[test]
+    fn help_window_matches_its_snapshots() {
+        assert_view_snapshots("help", || {
+            let mut app = list_app();
+            app.help_visible = true;
+            app
+        });
+    }
+
+    #[test]
+    fn update_window_matches_its_snapshots() {
+        assert_view_snapshots("update", || {
+            let mut app = list_app();
+            app.update_window.active = true;
+            app.update_window.title = "System Update".to_string();
+            app.update_window.output = (1..=30).map(|i| format!("({}/30) upgrading package-{}", i, i)).collect();
+            app
+        });
+    }
+
+    #[test]
+    fn home_view_matches_its_snapshots() {
+        let palette = Theme::Default.palette();
+        let mut home = HomeState::new();
+        home.set_stats(SystemStats { installed_count: Some(1024), available_count: Some(14000), updates_available: Some(3), checking_updates: false, unread_news: Some(2) });
+        home.refresh_status = vec!["Updates: checked 5m ago".to_string()];
+        for (width, height) in SNAPSHOT_SIZES {
+            let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+            terminal.draw(|f| render_home_view(f, f.area(), &home, &palette)).unwrap();
+            assert_snapshot(&format!("home_{}x{}", width, height), &terminal);
+        }
+    }
+
+    #[test]
+    fn too_small_terminal_matches_its_snapshot() {
+        let palette = Theme::Default.palette();
+        let mut terminal = Terminal::new(TestBackend::new(30, 10)).unwrap();
+        terminal.draw(|f| ui(f, &mut list_app(), "Select packages: ", &palette)).unwrap();
+        assert_snapshot("too_small_30x10", &terminal);
+    }
+
+    #[test]
+    fn tiny_terminals_ask_to_be_resized() {
+        for (width, height) in [(20, 5), (10, 3), (MIN_WIDTH - 1, MIN_HEIGHT)] {
+            let screen = draw(width, height, &mut app_with_overlays());
+            assert!(screen.contains("Terminal"), "{}x{}: {:?}", width, height, screen);
+            assert!(!screen.contains("ripgrep"));
+        }
+        assert_eq!(draw(1, 1, &mut app_with_overlays()), "T");
+    }
+
+    #[test]
+    fn overlays_fit_the_smallest_supported_terminal() {
+        for (width, height) in [(MIN_WIDTH, MIN_HEIGHT), (45, 12), (120, 40)] {
+            let screen = draw(width, height, &mut app_with_overlays());
+            assert!(!screen.contains("Terminal too small"));
+        }
+
+        let area = Rect::new(2, 1, 30, 8);
+        assert_eq!(centered(area, 10, 4), Rect::new(12, 3, 10, 4));
+        assert_eq!(centered(area, 80, 20), area, "overlays larger than the screen are shrunk to it");
+    }
+
+    #[test]
+    fn status_bar_drops_segments_from_the_right() {
+        let mut app = App::new(vec!["extra/ripgrep".to_string(), "core/bash".to_string()], true, None, ActionType::Install);
+        app.selected.insert("extra/ripgrep".to_string());
+        app.repo_filter = Some(RepoFilter::Repository("extra".to_string()));
+        let last = LastOperation { alert_type: AlertType::Success, message: "✓ installed 3 pkgs".to_string(), at: Instant::now() };
+        let status = StatusBar { backend: Some(Backend::Paru), app: Some(&app), last_operation: Some(&last) };
+        let palette = Theme::Default.palette();
+        let bar = |width: u16| {
+            let mut terminal = Terminal::new(TestBackend::new(width, 1)).unwrap();
+            terminal.draw(|f| render_status_bar(f, f.area(), &status, &palette)).unwrap();
+            terminal.backend().buffer().content.iter().map(|cell| cell.symbol()).collect::<String>().trim_end().to_string()
+        };
+
+        assert_eq!(bar(100), "  paru  │ 1 selected │ repo: extra · sort: best match │ ✓ installed 3 pkgs <1m ago │ ?: help");
+        assert_eq!(bar(60), "  paru  │ 1 selected │ repo: extra · sort: best match");
+        assert_eq!(bar(10), "  paru");
+    }
+
+    #[test]
+    fn truncation_counts_columns_and_keeps_characters_whole() {
+        assert_eq!(truncate("ripgrep", 7), "ripgrep");
+        assert_eq!(truncate("ripgrep", 4), "rip…");
+        assert_eq!(truncate("ripgrep", 0), "");
+
+        // Two columns each: a wide character that doesn't fit is left out whole
+        assert_eq!(truncate("日本語のパッケージ", 6), "日本…");
+        assert_eq!(truncate("日本語のパッケージ", 7), "日本語…");
+        assert_eq!(truncate("📦📦📦 box", 4), "📦…");
+
+        // Combining accents take no column and stay on their letter
+        let cut = truncate("Jose\u{301} Olmos, mantenedor", 5);
+        assert_eq!(cut, "Jose\u{301}…");
+        assert_eq!(cut.width(), 5);
+
+        for line in ["│ ├─ Descargando paquetes ███▌  ", "Maintainer : Jesús Ñúñez", "🎉🎉🎉🎉"] {
+            for width in 0..line.width() + 2 {
+                assert!(truncate(line, width).width() <= width, "{:?} cut to {}", line, width);
+            }
+        }
+    }
+
+    #[test]
+    fn package_info_renders_as_styled_fields() {
+        let palette = Theme::Default.palette();
+        let info = PackageInfo::from_pacman_output(SYNC).unwrap();
+        let installed: HashSet<String> = ["gcc-libs", "glibc"].iter().map(|s| s.to_string()).collect();
+        let text = package_info_text(&info, &installed, &palette);
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 14)).unwrap();
+        terminal.draw(|f| f.render_widget(Paragraph::new(text), f.area())).unwrap();
+        let buffer = terminal.backend().buffer();
+        let rows: Vec<String> = (0..buffer.area.height)
+            .map(|y| (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect::<String>().trim_end().to_string())
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                "Name           ripgrep",
+                "Version        14.1.0-1",
+                "Repository     extra",
+                "Description    A search tool",
+                "URL            https://github.com/BurntSushi/ripgrep",
+                "Licenses       MIT  Unlicense",
+                "Depends On     gcc-libs  glibc>=2.38  pcre2",
+                "Optional Deps  bash-completion: completions [installed]",
+                "               zsh: completions",
+                "Download Size   1.6 MiB",
+                "Installed Size 14.5 MiB",
+                "Packager       Orhun",
+                "Build Date     Tue 09 Jan 2024",
+                "Architecture   x86_64",
+            ]
+        );
+
+        let fg = |x: u16, y: u16| buffer[(x, y)].fg;
+        assert_eq!(fg(0, 0), palette.secondary, "field names");
+        assert_eq!(fg(15, 1), palette.success, "version");
+        assert!(buffer[(15, 4)].modifier.contains(Modifier::UNDERLINED), "URL");
+        assert_eq!(fg(25, 6), palette.success, "installed dependency (glibc>=2.38)");
+        assert_eq!(fg(40, 6), palette.text_dim, "missing dependency (pcre2)");
+        assert_eq!(fg(15, 8), palette.text_dim, "optional dependency that isn't installed");
+    }
+}
@@ -1,26 +1,66 @@
-use super::app::App;
-use super::render::ui;
-use super::theme::Theme;
+use super::app::{App, AppAction};
+use super::events::{CrosstermEvents, EventSource};
+use super::log_viewer::LogViewer;
+use super::render::{render_log_viewer, ui};
+use super::terminal::{take_interrupt, TerminalGuard};
 use super::types::ActionType;
+use crate::config;
+use crate::package::{runner, Backend, Package, PackageManager, PreviewSource, RemoveOptions};
 use anyhow::Result;
 use crossterm::{
-    event::{self, poll, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
+    event::{DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
+use std::collections::HashMap;
 use std::io;
-use std::process::Command;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Show where the packages to install come from and the command that will run them:
+/// `pmgr install` hands everything to the backend, leaving out up to date packages unless they are included
+fn plan_install(pm: &PackageManager, app: &mut App) {
+    let backend = pm.backend();
+    let names: Vec<String> = app
+        .confirm_dialog
+        .packages_to_install(app.install_options.needed)
+        .iter()
+        .map(|p| p.rsplit('/').next().unwrap_or(p).to_string())
+        .collect();
+    app.confirm_dialog.commands = vec![format!("{} -S {}", backend, names.join(" "))];
+    let (aur, official) = pm.separate_packages(&app.confirm_dialog.packages);
+    app.confirm_dialog.split_sources(aur, official, backend.command(), backend);
+}
 
 fn run_app<B: ratatui::backend::Backend>(
     terminal: &mut Terminal<B>,
+    events: &mut impl EventSource,
     mut app: App,
     prompt: &str,
+    mut settings: config::Settings,
 ) -> Result<Vec<String>> {
+    app.layout = settings.layout;
+    app.set_preview_ratio(settings.preview_ratio);
+    app.preview_enabled = settings.preview_enabled;
+    app.preview_timeout = Duration::from_secs(settings.preview_timeout_secs);
+    app.update_window.auto_close_delay = settings.auto_close_delay.map(Duration::from_secs);
+    if let (Some(_), Some(source)) = (&app.preview_source, settings.preview_command.as_deref().and_then(PreviewSource::custom)) {
+        app.set_preview_source(source);
+    }
+    app.request_preview();
+    // Operation log browser (Ctrl+L), drawn over everything
+    let mut log_viewer: Option<LogViewer> = None;
+
     loop {
         // Check for preview updates from background threads
         app.check_preview_updates();
+        app.check_provides_updates();
+        app.apply_pending_filter();
+
+        // Check for news fetched before a system update
+        app.check_news_updates();
+        app.alert.expire(Instant::now());
+        app.confirm_dialog.check_updates();
 
         // Check for system update progress
         app.update_window.check_updates();
@@ -36,93 +76,88 @@ fn run_app<B: ratatui::backend::Backend>(
             app.update_window.clear_just_closed_flag();
         }
 
-        // Use Default theme for standalone selector
-        let palette = Theme::Default.palette();
-        terminal.draw(|f| ui(f, &mut app, prompt, &palette))?;
+        // Same theme as the interactive menu
+        let palette = settings.theme.palette();
+        terminal.draw(|f| {
+            ui(f, &mut app, prompt, &palette);
+            if let Some(viewer) = &mut log_viewer {
+                render_log_viewer(f, viewer, &palette);
+            }
+        })?;
+
+        // A SIGINT from outside (raw mode reads Ctrl+C as a key) cancels like Esc
+        if take_interrupt() {
+            return Ok(Vec::new());
+        }
 
         // Use poll with timeout to allow periodic UI updates
-        if poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                // If update window is active, only allow Alt+X to close it (if has error)
-                if app.update_window.active {
-                    match (key.code, key.modifiers) {
-                        (KeyCode::Char('x'), KeyModifiers::ALT) => {
-                            if app.update_window.has_error || app.update_window.completed {
-                                app.update_window.close(true); // Cancelled by user
-                            }
-                        }
-                        _ => {} // Ignore other keys while update window is active
-                    }
-                    continue;
+        if let Some(event) = events.next_event(app.poll_timeout(Duration::from_millis(100)))? {
+            if let Event::Mouse(mouse) = event {
+                if log_viewer.is_none() {
+                    app.handle_mouse(mouse);
                 }
-
-                // If confirmation dialog is active, handle separately
-                if app.confirm_dialog.active {
-                    match (key.code, key.modifiers) {
-                        // Confirm with Y or Enter
-                        (KeyCode::Char('y'), KeyModifiers::NONE | KeyModifiers::SHIFT)
-                        | (KeyCode::Enter, _) => {
-                            app.confirm_dialog.confirm();
-                            return Ok(app.confirm_dialog.packages.clone());
-                        }
-                        // Cancel with N or ESC
-                        (KeyCode::Char('n'), KeyModifiers::NONE | KeyModifiers::SHIFT)
-                        | (KeyCode::Esc, _) => {
-                            app.confirm_dialog.cancel();
-                        }
-                        // Scroll down
-                        (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                            app.confirm_dialog.scroll_down();
-                        }
-                        // Scroll up
-                        (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                            app.confirm_dialog.scroll_up();
-                        }
-                        _ => {} // Ignore other keys while dialog is active
+            }
+            if let Event::Key(key) = event {
+                // The log browser takes every key until it is closed
+                if let Some(viewer) = &mut log_viewer {
+                    if !viewer.handle_key(key) {
+                        log_viewer = None;
                     }
                     continue;
                 }
-
-                // If help screen is visible, handle separately
-                if app.help_visible {
-                    match (key.code, key.modifiers) {
-                        (KeyCode::Char('?'), KeyModifiers::NONE | KeyModifiers::SHIFT)
-                        | (KeyCode::Esc, _) => {
-                            app.help_visible = false;
-                            app.help_scroll = 0; // Reset scroll when closing
-                        }
-                        // Scroll down
-                        (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                            app.help_scroll = app.help_scroll.saturating_add(1);
-                        }
-                        // Scroll up
-                        (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                            app.help_scroll = app.help_scroll.saturating_sub(1);
-                        }
-                        _ => {} // Ignore other keys while help is visible
-                    }
+                if let (KeyCode::Char('l'), KeyModifiers::CONTROL) = (key.code, key.modifiers) {
+                    log_viewer = Some(LogViewer::load());
                     continue;
                 }
 
-                match (key.code, key.modifiers) {
-                    // Show help on '?'
-                    (KeyCode::Char('?'), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
-                        app.help_visible = true;
-                        app.help_scroll = 0; // Reset scroll when opening
+                match app.handle_key(key) {
+                    AppAction::Cancel => return Ok(Vec::new()),
+                    AppAction::Replan => {
+                        if let Ok(pm) = PackageManager::new() {
+                            plan_install(&pm, &mut app);
+                        }
                     }
-                    // Exit on ESC
-                    (KeyCode::Esc, _) => {
-                        return Ok(Vec::new());
+                    // Confirming the news starts the update that was waiting for it
+                    AppAction::Confirmed if app.confirm_dialog.action_type == ActionType::Update => {
+                        app.confirm_dialog.cancel();
+                        app.start_system_update();
                     }
-                    // Confirm on Enter - show confirmation dialog
-                    (KeyCode::Enter, _) => {
-                        let selected = app.get_selected_items();
-                        if !selected.is_empty() {
-                            app.confirm_dialog.show(app.action_type, selected);
+                    AppAction::Confirmed => return Ok(app.confirm_dialog.packages_to_install(app.install_options.needed)),
+                    // Show the confirmation dialog
+                    AppAction::Confirm(selected) if !selected.is_empty() => {
+                        let badges = if app.action_type == ActionType::Install {
+                            app.aur_badges(&selected, settings.aur_stale_days)
+                        } else {
+                            HashMap::new()
+                        };
+                        // Repositories of the packages to install, dependents of the ones to remove
+                        // (sizes are resolved in the background)
+                        let infos = match app.action_type {
+                            ActionType::Install | ActionType::Remove => PackageManager::new()
+                                .and_then(|pm| pm.get_info_batch(&selected, app.action_type == ActionType::Remove))
+                                .unwrap_or_default(),
+                            ActionType::Update | ActionType::Upgrade => HashMap::new(),
+                        };
+                        app.confirm_dialog.show(app.action_type, selected);
+                        app.confirm_dialog.summarize(&infos);
+                        app.resolve_transaction();
+                        app.confirm_dialog.badges = badges;
+                        app.confirm_dialog.require_ack = settings.confirm_aur_warnings;
+                        if app.action_type == ActionType::Install {
+                            app.confirm_dialog.flag_ignored(&settings.ignored_packages);
+                            if let Ok(pm) = PackageManager::new() {
+                                let installed = pm.installed_versions(&app.confirm_dialog.packages).unwrap_or_default();
+                                app.confirm_dialog.flag_installed(&installed, &infos);
+                                app.install_options.needed = settings.always_needed;
+                                plan_install(&pm, &mut app);
+                            }
+                        } else if app.action_type == ActionType::Remove {
+                            app.confirm_dialog.command =
+                                Some(format!("pacman {}", app.remove_options.flag()));
+                            app.confirm_dialog.flag_removal_risks(&infos, &settings.protected_packages);
                         }
                     }
-                    // Start system update with Ctrl+U
-                    (KeyCode::Char('u'), KeyModifiers::CONTROL) => {
+                    AppAction::StartUpdate => {
                         // Exit raw mode temporarily to ask for sudo password
                         disable_raw_mode()?;
                         execute!(
@@ -133,11 +168,12 @@ fn run_app<B: ratatui::backend::Backend>(
 
                         // Ask for sudo password
                         println!("System update requires sudo access. Please enter your password:");
-                        let sudo_result = Command::new("sudo")
+                        let sudo_result = runner::interactive("sudo")
                             .arg("-v")
                             .status();
 
-                        // Re-enter raw mode
+                        // Re-enter raw mode (a Ctrl+C at the password prompt doesn't close the selector)
+                        take_interrupt();
                         enable_raw_mode()?;
                         execute!(
                             io::stdout(),
@@ -148,41 +184,38 @@ fn run_app<B: ratatui::backend::Backend>(
                         // Start update if sudo was successful
                         match sudo_result {
                             Ok(status) if status.success() => {
-                                app.update_window.start_update();
+                                app.request_system_update(settings.held_packages());
                             }
                             _ => {
                                 // Could show error message, but for now just ignore
                             }
                         }
                     }
-                    // Navigation
-                    (KeyCode::Down, _) | (KeyCode::Char('j'), KeyModifiers::NONE) => {
-                        app.next();
-                    }
-                    (KeyCode::Up, _) | (KeyCode::Char('k'), KeyModifiers::NONE) => {
-                        app.previous();
+                    // The repository filter, sort order and preview pane are remembered for the next run
+                    // (not remembering them is harmless)
+                    AppAction::RepoFilterChanged(filter) => {
+                        settings.repo_filters.insert("select".to_string(), filter);
+                        let _ = config::save_settings(&settings);
                     }
-                    // Multi-select with Tab
-                    (KeyCode::Tab, _) => {
-                        app.toggle_select();
+                    AppAction::SortChanged(mode) => {
+                        settings.sort_modes.insert("select".to_string(), mode);
+                        let _ = config::save_settings(&settings);
                     }
-                    // Layout switching
-                    (KeyCode::Char('o'), KeyModifiers::ALT) => {
-                        app.layout.toggle_to_horizontal();
+                    AppAction::PreviewResized(ratio) => {
+                        settings.preview_ratio = ratio;
+                        let _ = config::save_settings(&settings);
                     }
-                    (KeyCode::Char('v'), KeyModifiers::ALT) => {
-                        app.layout.toggle_to_vertical();
+                    AppAction::PreviewToggled(enabled) => {
+                        settings.preview_enabled = enabled;
+                        let _ = config::save_settings(&settings);
                     }
-                    // Search input
-                    (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
-                        app.search_query.push(c);
-                        app.filter_items();
-                    }
-                    (KeyCode::Backspace, _) => {
-                        app.search_query.pop();
-                        app.filter_items();
-                    }
-                    _ => {}
+                    AppAction::Copy => app.yank(settings.copy_with_repository),
+                    // Tabs, reloading and the queue belong to the interactive menu
+                    AppAction::None
+                    | AppAction::Confirm(_)
+                    | AppAction::SwitchTab(_)
+                    | AppAction::Refresh
+                    | AppAction::Queue => {}
                 }
             }
         }
@@ -197,64 +230,115 @@ impl Selector {
         items: Vec<String>,
         prompt: &str,
         multi: bool,
-        preview_cmd: Option<String>,
+        preview_source: Option<PreviewSource>,
         action_type: ActionType,
     ) -> Result<Vec<String>> {
-        // Setup terminal
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-
-        // Create app and run
-        let app = App::new(items, multi, preview_cmd, action_type);
-        let result = run_app(&mut terminal, app, prompt);
-
-        // Restore terminal
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
-        terminal.show_cursor()?;
-
-        result
+        Self::run(App::new(items, multi, preview_source, action_type), prompt)
     }
 
-    /// Select from installed packages
-    pub fn select_installed(packages: Vec<String>) -> Result<Vec<String>> {
-        Self::select_packages(
+    /// Run the selector for a prepared app
+    fn run(app: App, prompt: &str) -> Result<Vec<String>> {
+        // The terminal is restored when the guard drops, whatever way the selector ends
+        let _guard = TerminalGuard::enter(true)?;
+        let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+
+        run_app(&mut terminal, &mut CrosstermEvents, app, prompt, config::load_settings())
+    }
+
+    /// Select installed packages to remove; the confirm dialog shows the removal flags
+    pub fn select_installed(packages: Vec<String>, options: RemoveOptions, backend: Backend) -> Result<Vec<String>> {
+        let mut app = App::new(
             packages,
-            "Select packages to remove (TAB: multi-select, ENTER: confirm): ",
             true,
-            Some("echo {} | xargs yay -Qi".to_string()),
+            Some(backend.info_preview(true)),
             ActionType::Remove,
-        )
+        );
+        app.remove_options = options;
+
+        Self::run(app, "Select packages to remove (TAB: multi-select, ENTER: confirm): ")
     }
 
     /// Select from available packages
-    pub fn select_available(packages: Vec<String>) -> Result<Vec<String>> {
-        Self::select_packages(
-            packages,
-            "Select packages to install (TAB: multi-select, ENTER: confirm): ",
-            true,
-            Some("echo {} | xargs yay -Si".to_string()),
-            ActionType::Install,
-        )
+    pub fn select_available(packages: &[Package], pm: &PackageManager) -> Result<Vec<String>> {
+        let items = packages.iter().map(|p| format!("{}/{}", p.repository, p.name)).collect();
+        let mut app = App::new(items, true, Some(pm.backend().info_preview(false)), ActionType::Install);
+        app.set_package_details(packages);
+        let settings = config::load_settings();
+        app.repo_filter = Some(settings.repo_filter("select"));
+        app.repositories = pm.repositories();
+        app.sort_mode = settings.sort_mode("select");
+        app.filter_items();
+
+        Self::run(app, "Select packages to install (TAB: multi-select, ENTER: confirm): ")
+    }
+
+    /// Pick a single version of a package
+    pub fn select_version(package: &str, versions: Vec<String>) -> Result<Option<String>> {
+        let prompt = format!("Select the version of {} to install (ENTER: confirm): ", package);
+        let result = Self::select_packages(versions, &prompt, false, None, ActionType::Install)?;
+
+        Ok(result.first().cloned())
     }
 
-    /// Browse installed packages (view only)
-    pub fn browse_installed(packages: Vec<String>) -> Result<Option<String>> {
-        let result = Self::select_packages(
+    /// Browse installed packages (view only), labelled with the filter that produced them
+    pub fn browse_installed(packages: Vec<String>, filter: Option<&str>, backend: Backend) -> Result<Option<String>> {
+        let mut app = App::new(
             packages,
-            "Browse installed packages (ESC to exit): ",
             false,
-            Some("echo {} | xargs yay -Qi".to_string()),
+            Some(backend.info_preview(true)),
             ActionType::Install, // Default to Install for browse mode
-        )?;
+        );
+        app.filter_label = filter.map(|f| f.to_string());
+
+        let result = Self::run(app, "Browse installed packages (ESC to exit): ")?;
 
         Ok(result.first().cloned())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::events::ScriptedEvents;
+    use crossterm::event::KeyEvent;
+    use ratatui::backend::TestBackend;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// Run the selector over a few packages with the default settings
+    fn select(keys: &[KeyEvent]) -> (String, Result<Vec<String>>) {
+        let app = App::new(vec!["extra/ripgrep".to_string(), "core/bash".to_string()], true, None, ActionType::Install);
+        let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+        let result = run_app(&mut terminal, &mut ScriptedEvents::new(keys), app, "Select: ", config::Settings::default());
+        let screen = terminal.backend().buffer().content.iter().map(|cell| cell.symbol()).collect();
+        (screen, result)
+    }
+
+    #[test]
+    fn selector_confirms_through_the_dialog() {
+        let (screen, result) = select(&[key(KeyCode::Tab), key(KeyCode::Enter)]);
+        assert!(result.is_err(), "still waiting for an answer");
+        assert!(screen.contains("extra/ripgrep") && screen.contains("Yes"), "the confirm dialog is shown");
+
+        // No goes back to the list, Yes hands the selection to the command
+        let (_, result) = select(&[key(KeyCode::Tab), key(KeyCode::Enter), key(KeyCode::Char('n')), key(KeyCode::Esc)]);
+        assert_eq!(result.unwrap(), Vec::<String>::new());
+        let (_, result) = select(&[key(KeyCode::Tab), key(KeyCode::Enter), key(KeyCode::Char('y'))]);
+        assert_eq!(result.unwrap(), vec!["extra/ripgrep".to_string()]);
+    }
+
+    #[test]
+    fn selector_shows_help() {
+        let (screen, _) = select(&[key(KeyCode::Char('?'))]);
+        assert!(screen.contains("Keyboard Shortcuts"));
+
+        // Esc closes the help first, then leaves the selector
+        let (screen, result) = select(&[key(KeyCode::Char('?')), key(KeyCode::Esc)]);
+        assert!(!screen.contains("Keyboard Shortcuts"));
+        assert!(result.is_err());
+        let (_, result) = select(&[key(KeyCode::Char('?')), key(KeyCode::Esc), key(KeyCode::Esc)]);
+        assert_eq!(result.unwrap(), Vec::<String>::new());
+    }
+}
@@ -0,0 +1,180 @@
+use super::theme::Theme;
+use super::types::PreviewLayout;
+use crate::config::Settings;
+use crate::package::Backend;
+use std::path::PathBuf;
+
+/// A setting that can be changed in the Settings tab
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsRow {
+    Theme,
+    Layout,
+    Backend,
+    NoConfirm,
+    PreviewCommand,
+}
+
+impl SettingsRow {
+    pub const ALL: [SettingsRow; 5] = [
+        SettingsRow::Theme,
+        SettingsRow::Layout,
+        SettingsRow::Backend,
+        SettingsRow::NoConfirm,
+        SettingsRow::PreviewCommand,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsRow::Theme => "Theme",
+            SettingsRow::Layout => "Preview layout",
+            SettingsRow::Backend => "Backend",
+            SettingsRow::NoConfirm => "Skip pacman prompts (CLI)",
+            SettingsRow::PreviewCommand => "Preview command",
+        }
+    }
+
+    /// What the setting does, shown below the list
+    pub fn description(self) -> &'static str {
+        match self {
+            SettingsRow::Theme => "Colors of the whole interface",
+            SettingsRow::Layout => "Where the preview pane goes in the package tabs",
+            SettingsRow::Backend => "Program used for queries and transactions (only installed ones are offered)",
+            SettingsRow::NoConfirm => "Pass --noconfirm to `pmgr install` and `pmgr remove`",
+            SettingsRow::PreviewCommand => "Command for the preview pane (no shell), {} is the package (empty: package info)",
+        }
+    }
+
+    /// The current value, as shown in the list
+    pub fn value(self, settings: &Settings) -> String {
+        match self {
+            SettingsRow::Theme => settings.theme.name().to_string(),
+            SettingsRow::Layout => match settings.layout {
+                PreviewLayout::Vertical => "preview on the right".to_string(),
+                PreviewLayout::Horizontal => "preview below".to_string(),
+            },
+            SettingsRow::Backend => match settings.backend {
+                Some(backend) => backend.to_string(),
+                None => "auto-detect".to_string(),
+            },
+            SettingsRow::NoConfirm => if settings.noconfirm { "yes" } else { "no" }.to_string(),
+            SettingsRow::PreviewCommand => settings.preview_command.clone().unwrap_or_else(|| "package info".to_string()),
+        }
+    }
+}
+
+/// State of the Settings tab
+#[derive(Debug, Clone)]
+pub struct SettingsState {
+    pub selected: usize,
+    pub input: Option<String>, // Text being typed for the selected setting (None: not editing)
+    pub config_path: Option<PathBuf>, // Shown so the file can still be edited by hand
+    pub status: Option<String>, // Result of the last change (saved, or why not)
+    pub refresh_status: Vec<String>, // When each background dataset was/will be refreshed
+}
+
+impl SettingsState {
+    pub fn new(config_path: Option<PathBuf>) -> Self {
+        Self {
+            selected: 0,
+            input: None,
+            config_path,
+            status: None,
+            refresh_status: Vec::new(),
+        }
+    }
+
+    pub fn selected_row(&self) -> SettingsRow {
+        SettingsRow::ALL[self.selected]
+    }
+
+    pub fn next(&mut self) {
+        self.selected = (self.selected + 1) % SettingsRow::ALL.len();
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = self.selected.checked_sub(1).unwrap_or(SettingsRow::ALL.len() - 1);
+    }
+
+    /// Enter on the selected row: switch to the next value, or start typing a text value
+    /// Returns the row whose value changed; `backends` are the installed ones
+    pub fn activate(&mut self, settings: &mut Settings, backends: &[Backend]) -> Option<SettingsRow> {
+        let row = self.selected_row();
+        match row {
+            SettingsRow::Theme => {
+                let themes = Theme::all();
+                let current = themes.iter().position(|theme| *theme == settings.theme).unwrap_or(0);
+                settings.theme = themes[(current + 1) % themes.len()];
+            }
+            SettingsRow::Layout => {
+                settings.layout = match settings.layout {
+                    PreviewLayout::Vertical => PreviewLayout::Horizontal,
+                    PreviewLayout::Horizontal => PreviewLayout::Vertical,
+                };
+            }
+            SettingsRow::Backend => {
+                // Auto-detect, then each installed backend
+                let choices: Vec<Option<Backend>> = std::iter::once(None).chain(backends.iter().copied().map(Some)).collect();
+                let current = choices.iter().position(|choice| *choice == settings.backend).unwrap_or(0);
+                settings.backend = choices[(current + 1) % choices.len()];
+            }
+            SettingsRow::NoConfirm => settings.noconfirm = !settings.noconfirm,
+            SettingsRow::PreviewCommand => {
+                self.input = Some(settings.preview_command.clone().unwrap_or_default());
+                return None;
+            }
+        }
+        Some(row)
+    }
+
+    /// Store the typed text in the selected setting (empty text goes back to the default)
+    pub fn commit_input(&mut self, settings: &mut Settings) -> Option<SettingsRow> {
+        let text = self.input.take()?;
+        let text = text.trim();
+        match self.selected_row() {
+            SettingsRow::PreviewCommand => {
+                settings.preview_command = (!text.is_empty()).then(|| text.to_string());
+                Some(SettingsRow::PreviewCommand)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(state: &mut SettingsState, row: SettingsRow) {
+        state.selected = SettingsRow::ALL.iter().position(|r| *r == row).unwrap();
+    }
+
+    #[test]
+    fn enter_cycles_values_and_edits_text() {
+        let mut settings = Settings::default();
+        let mut state = SettingsState::new(None);
+
+        select(&mut state, SettingsRow::Backend);
+        let installed = [Backend::Pacman, Backend::Paru];
+        assert_eq!(state.activate(&mut settings, &installed), Some(SettingsRow::Backend));
+        assert_eq!(settings.backend, Some(Backend::Pacman));
+        state.activate(&mut settings, &installed);
+        state.activate(&mut settings, &installed);
+        assert_eq!(settings.backend, None, "back to auto-detection after the installed backends");
+
+        select(&mut state, SettingsRow::Layout);
+        state.activate(&mut settings, &installed);
+        assert_eq!(settings.layout, PreviewLayout::Horizontal);
+
+        select(&mut state, SettingsRow::PreviewCommand);
+        assert_eq!(state.activate(&mut settings, &installed), None);
+        state.input.as_mut().unwrap().push_str("pacman -Qil {}");
+        assert_eq!(state.commit_input(&mut settings), Some(SettingsRow::PreviewCommand));
+        assert_eq!(settings.preview_command.as_deref(), Some("pacman -Qil {}"));
+
+        state.activate(&mut settings, &installed);
+        state.input = Some("  ".to_string());
+        state.commit_input(&mut settings);
+        assert_eq!(settings.preview_command, None);
+        assert_eq!(SettingsRow::PreviewCommand.value(&settings), "package info");
+    }
+}
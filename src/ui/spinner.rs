@@ -20,46 +20,6 @@ impl Spinner {
         }
     }
 
-    /// Create a dots spinner
-    pub fn dots() -> Self {
-        Self {
-            frames: vec!["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"],
-            current_frame: 0,
-            last_update: Instant::now(),
-            interval: Duration::from_millis(80),
-        }
-    }
-
-    /// Create a simple dots spinner
-    pub fn simple_dots() -> Self {
-        Self {
-            frames: vec!["   ", ".  ", ".. ", "..."],
-            current_frame: 0,
-            last_update: Instant::now(),
-            interval: Duration::from_millis(200),
-        }
-    }
-
-    /// Create a line spinner
-    pub fn line() -> Self {
-        Self {
-            frames: vec!["-", "\\", "|", "/"],
-            current_frame: 0,
-            last_update: Instant::now(),
-            interval: Duration::from_millis(100),
-        }
-    }
-
-    /// Create a box spinner
-    pub fn boxed() -> Self {
-        Self {
-            frames: vec!["◰", "◳", "◲", "◱"],
-            current_frame: 0,
-            last_update: Instant::now(),
-            interval: Duration::from_millis(100),
-        }
-    }
-
     /// Update the spinner animation
     pub fn tick(&mut self) {
         if self.last_update.elapsed() >= self.interval {
@@ -0,0 +1,111 @@
+use anyhow::Result;
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use signal_hook::{consts::SIGINT, SigId};
+use std::io::{self, Stdout, Write};
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Once, OnceLock};
+
+/// A full-screen view is on screen (the panic hook only touches the terminal then)
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Set by a SIGINT that arrives as a signal while a view is on screen
+/// (raw mode turns Ctrl+C into a key, but `kill -INT` and Ctrl+C during a handoff still send one)
+fn interrupt_flag() -> &'static Arc<AtomicBool> {
+    static FLAG: OnceLock<Arc<AtomicBool>> = OnceLock::new();
+    FLAG.get_or_init(Default::default)
+}
+
+/// Whether a SIGINT arrived since the last call; the event loops close their view when it did
+pub fn take_interrupt() -> bool {
+    interrupt_flag().swap(false, Ordering::Relaxed)
+}
+
+/// Raw mode, the alternate screen and (optionally) mouse capture for a full-screen view
+/// Dropping the guard gives the user their terminal back, also on `?` and while a panic unwinds
+pub struct TerminalGuard<W: Write = Stdout> {
+    out: W,
+    mouse: bool,
+    /// The SIGINT handler, removed again so the command line can be interrupted as usual
+    signal: Option<SigId>,
+}
+
+impl TerminalGuard {
+    /// Switch the terminal to full-screen mode until the guard is dropped
+    pub fn enter(mouse: bool) -> Result<Self> {
+        install_panic_hook();
+        enable_raw_mode()?;
+        ACTIVE.store(true, Ordering::Relaxed);
+        take_interrupt();
+        let mut guard = Self {
+            out: io::stdout(),
+            mouse,
+            signal: signal_hook::flag::register(SIGINT, Arc::clone(interrupt_flag())).ok(),
+        };
+        execute!(guard.out, EnterAlternateScreen)?;
+        if mouse {
+            execute!(guard.out, EnableMouseCapture)?;
+        }
+        Ok(guard)
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        if let Some(id) = self.signal.take() {
+            signal_hook::low_level::unregister(id);
+        }
+        ACTIVE.store(false, Ordering::Relaxed);
+        let _ = restore(&mut self.out, self.mouse);
+    }
+}
+
+/// Leave full-screen mode (harmless when it is already off)
+fn restore(out: &mut impl Write, mouse: bool) -> io::Result<()> {
+    let _ = disable_raw_mode();
+    if mouse {
+        execute!(out, DisableMouseCapture)?;
+    }
+    execute!(out, LeaveAlternateScreen, Show)
+}
+
+/// Restore the terminal before a panic message is printed, so it isn't lost in the alternate screen
+pub fn install_panic_hook() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            if ACTIVE.swap(false, Ordering::Relaxed) {
+                let _ = restore(&mut io::stdout(), true);
+            }
+            previous(info);
+        }));
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dropping_the_guard_restores_the_terminal() {
+        let mut out = Vec::new();
+        ACTIVE.store(true, Ordering::Relaxed);
+        drop(TerminalGuard { out: &mut out, mouse: true, signal: None });
+
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.contains("\x1b[?1049l"), "leaves the alternate screen: {:?}", written);
+        assert!(written.contains("\x1b[?1000l"), "stops capturing the mouse: {:?}", written);
+        assert!(written.ends_with("\x1b[?25h"), "shows the cursor: {:?}", written);
+        assert!(!ACTIVE.load(Ordering::Relaxed));
+
+        let mut out = Vec::new();
+        drop(TerminalGuard { out: &mut out, mouse: false, signal: None });
+        assert!(!String::from_utf8(out).unwrap().contains("\x1b[?1000l"));
+    }
+}
@@ -37,9 +37,23 @@ pub struct ThemePalette {
     pub ascii_art_5: Color,
 }
 
+impl ThemePalette {
+    /// Color of a repository name in `repository/name` items
+    pub fn repo_color(&self, repository: &str) -> Color {
+        match repository {
+            "core" => self.primary,
+            "extra" => self.secondary,
+            "multilib" => self.info,
+            "aur" => self.warning,
+            _ => self.text_dim,
+        }
+    }
+}
+
 /// Available themes
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub enum Theme {
+    #[default]
     Default,
     Nord,
     Dracula,
@@ -246,8 +260,3 @@ impl Theme {
     }
 }
 
-impl Default for Theme {
-    fn default() -> Self {
-        Theme::Default
-    }
-}
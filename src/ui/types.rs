@@ -1,7 +1,21 @@
-use std::sync::mpsc::Receiver;
+use super::spinner::Spinner;
+use crate::package::progress::Progress;
+use crate::package::deps::is_protected;
+use crate::package::version::vercmp;
+use crate::package::{format_size, Backend, DbLock, PackageInfo, PmgrError, TransactionPreview};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PreviewLayout {
+    #[default]
     Vertical,   // Preview on the right
     Horizontal, // Preview below
 }
@@ -19,7 +33,39 @@ impl PreviewLayout {
 #[derive(Debug)]
 pub enum UpdateMessage {
     Output(String),
-    Completed(bool), // true if successful, false if error
+    ErrorOutput(String),       // A stderr line (also shown in the output)
+    StepStarted(usize),        // Index of the step that started running
+    StepFinished(usize, bool), // Step index and whether it succeeded
+    Completed(bool),           // true if successful, false if error
+}
+
+/// A single command run inside the update window
+#[derive(Debug, Clone)]
+pub struct CommandStep {
+    pub title: String,
+    pub command: String,
+    pub args: Vec<String>,
+    pub success_message: String,
+}
+
+impl CommandStep {
+    pub fn new(title: &str, command: &str, args: Vec<String>, success_message: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            command: command.to_string(),
+            args,
+            success_message: success_message.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StepStatus {
+    Pending,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
 }
 
 pub struct SystemUpdateWindow {
@@ -32,22 +78,150 @@ pub struct SystemUpdateWindow {
     pub title: String, // Window title
     pub cancelled_by_user: bool, // True if user manually closed with Alt+X
     pub operation_type: Option<String>, // Type of operation (for showing correct alert)
-    pub was_successful: bool, // True if operation completed successfully
+    pub summary: OperationSummary, // What the operation did; kept after closing for the alert
+    pub steps: Vec<CommandStep>, // Commands run sequentially in this window
+    pub step_status: Vec<StepStatus>, // Status of each step
+    pub abort_on_failure: bool, // Stop the chain at the first failed step
+    pub lock_wait: Option<LockWait>, // Set while waiting for another package manager to finish
+    pub lock_file: Option<PathBuf>, // pacman's db.lck, read from pacman.conf when unset
+    pub failure: Option<PmgrError>, // Why the operation failed, read from its output when closed
+    pub error_output: Vec<String>, // stderr of the current run, to explain failures
+    pub scroll_top: Option<usize>, // First output line shown when scrolled; None follows new output
+    pub page_height: usize, // Output lines that fit in the window (set when drawn)
+    pub running_pid: Arc<Mutex<Option<u32>>>, // Process group of the running step (its command leads it)
+    pub cancel_flag: Arc<AtomicBool>, // Tells the worker to start no more steps
+    pub cancel_requested: Option<Instant>, // When Ctrl+C interrupted the operation
+    pub terminated: bool, // SIGTERM followed the SIGINT
+    pub progress: Option<Progress>, // Last progress line pacman printed in the running step
+    pub log_path: Option<PathBuf>, // This run's own log file (mentioned when it fails)
+    pub started_at: Option<Instant>, // When the first command started (not counting a lock wait)
+    pub finished_at: Option<Instant>, // When the chain completed, stopping the clock
+    pub auto_close_delay: Option<Duration>, // How long a successful operation stays open; None until closed by hand
+    pub kept_open: bool, // A key was pressed during the auto-close countdown
+}
+
+/// What an operation did, for its completion message and the alert once it closes
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OperationSummary {
+    pub success: bool,
+    pub upgraded: usize,
+    pub installed: usize,
+    pub removed: usize,
+    pub duration: Duration,
+}
+
+impl OperationSummary {
+    /// Count a package from pacman's `(n/total) verb name` lines
+    pub fn count(&mut self, progress: &Progress) {
+        let verb = progress.action.split_whitespace().next().unwrap_or_default();
+        match verb {
+            "upgrading" | "downgrading" => self.upgraded += 1,
+            "installing" | "reinstalling" => self.installed += 1,
+            "removing" => self.removed += 1,
+            _ => {}
+        }
+    }
+
+    /// e.g. "Upgraded 14 packages, installed 2 packages in 2m31s"
+    pub fn describe(&self) -> String {
+        let counts = [("Upgraded", self.upgraded), ("Installed", self.installed), ("Removed", self.removed)];
+        let done: Vec<String> = counts
+            .iter()
+            .filter(|(_, count)| *count > 0)
+            .enumerate()
+            .map(|(i, (verb, count))| {
+                let verb = if i == 0 { verb.to_string() } else { verb.to_lowercase() };
+                format!("{} {} package{}", verb, count, if *count == 1 { "" } else { "s" })
+            })
+            .collect();
+
+        if done.is_empty() {
+            format!("Finished in {}", format_duration(self.duration))
+        } else {
+            format!("{} in {}", done.join(", "), format_duration(self.duration))
+        }
+    }
+}
+
+/// A running timer as `mm:ss` (minutes keep counting past the hour)
+pub fn format_clock(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+/// A total time, e.g. "45s", "2m31s" or "1h05m"
+pub fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    match secs {
+        0..=59 => format!("{}s", secs),
+        60..=3599 => format!("{}m{:02}s", secs / 60, secs % 60),
+        _ => format!("{}h{:02}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+/// Steps waiting for the pacman database lock to be released
+#[derive(Debug, Clone)]
+pub struct LockWait {
+    pub lock_file: PathBuf,
+    pub state: DbLock,
+    pub since: Instant,
+    pub last_check: Instant,
+    pub timeout: Duration, // Stop polling after this long (R checks again)
+    pub timed_out: bool,
+    pub indices: Vec<usize>, // Steps to run once the lock is free
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ActionType {
     Install,
     Remove,
+    Update, // System update, confirmed when there is Arch news to read first
+    Upgrade, // Upgrade of the packages picked in the Updates tab
 }
 
-#[derive(Debug, Clone)]
+/// Size of the whole transaction, resolved on a background thread
+#[derive(Debug, Clone, PartialEq)]
+pub enum TransactionSize {
+    NotRequested,
+    Loading,
+    Ready(TransactionPreview),
+    /// Resolving failed (e.g. AUR packages, or pacman couldn't plan it); the reason
+    Unknown(String),
+}
+
+/// Button of the confirmation dialog that Enter activates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmButton {
+    Yes,
+    No,
+}
+
+#[derive(Debug)]
 pub struct ConfirmDialog {
     pub active: bool,
     pub action_type: ActionType,
     pub packages: Vec<String>,
     pub confirmed: bool,
     pub scroll: u16,
+    pub max_scroll: u16, // Last scroll position that still shows packages (set when drawn)
+    pub focus: ConfirmButton, // Button Enter activates (Left/Right/Tab move it)
+    pub warnings: Vec<String>, // Shown prominently above the confirmation prompt
+    pub badges: HashMap<String, Vec<String>>, // Per-package warnings shown next to the name
+    pub require_ack: bool, // Badges need an extra confirmation keystroke
+    pub acknowledged: bool,
+    pub partial_upgrade: bool, // Only some upgrades were picked, which always needs the extra keystroke
+    pub command: Option<String>, // Command that will run, shown under the message (e.g. "pacman -Rns")
+    pub summary: Vec<String>, // Repositories the packages come from
+    pub transaction: TransactionSize, // Sizes and dependencies of the whole transaction
+    transaction_rx: Option<Receiver<Result<TransactionPreview, String>>>,
+    pub spinner: Spinner, // Shown while the transaction is resolved
+    pub sources: Vec<(String, Vec<String>)>, // Packages to install grouped by the program that installs them
+    pub commands: Vec<String>, // Exact command lines that will run, shown dim at the bottom
+    pub blocked: Option<String>, // Why confirming is disabled (e.g. no AUR helper for AUR packages)
+    pub require_typed: bool, // Risky removals are confirmed by typing "yes" rather than a keypress
+    pub typed: String, // What has been typed so far
+    pub reinstalls: Vec<String>, // Selected packages already installed and up to date
+    pub upgrades: Vec<String>, // Selected packages installed in an older version
 }
 
 impl ConfirmDialog {
@@ -58,6 +232,25 @@ impl ConfirmDialog {
             packages: Vec::new(),
             confirmed: false,
             scroll: 0,
+            max_scroll: u16::MAX,
+            focus: ConfirmButton::Yes,
+            warnings: Vec::new(),
+            badges: HashMap::new(),
+            require_ack: false,
+            acknowledged: false,
+            partial_upgrade: false,
+            command: None,
+            summary: Vec::new(),
+            transaction: TransactionSize::NotRequested,
+            transaction_rx: None,
+            spinner: Spinner::new(),
+            sources: Vec::new(),
+            commands: Vec::new(),
+            blocked: None,
+            require_typed: false,
+            typed: String::new(),
+            reinstalls: Vec::new(),
+            upgrades: Vec::new(),
         }
     }
 
@@ -67,6 +260,260 @@ impl ConfirmDialog {
         self.packages = packages;
         self.confirmed = false;
         self.scroll = 0;
+        self.max_scroll = u16::MAX;
+        // Removals start on No, so a stray Enter doesn't remove anything
+        self.focus = if action_type == ActionType::Remove { ConfirmButton::No } else { ConfirmButton::Yes };
+        self.warnings.clear();
+        self.badges.clear();
+        self.require_ack = false;
+        self.acknowledged = false;
+        self.partial_upgrade = false;
+        self.command = None;
+        self.summary.clear();
+        self.transaction = TransactionSize::NotRequested;
+        self.transaction_rx = None;
+        self.sources.clear();
+        self.commands.clear();
+        self.blocked = None;
+        self.require_typed = false;
+        self.typed.clear();
+        self.reinstalls.clear();
+        self.upgrades.clear();
+    }
+
+    /// Group the packages to install into "Official (via ...)" and "AUR (via ...)" sections
+    /// AUR packages need an AUR helper: with the pacman backend confirming is disabled
+    pub fn split_sources(&mut self, aur: Vec<String>, official: Vec<String>, official_via: &str, backend: Backend) {
+        self.sources.clear();
+        if !official.is_empty() {
+            self.sources.push((format!("Official (via {})", official_via), official));
+        }
+        if !aur.is_empty() {
+            let helper = if backend.supports_aur() { backend.command() } else { "yay" };
+            self.sources.push((format!("AUR (via {})", helper), aur));
+            if !backend.supports_aur() {
+                self.blocked = Some(format!("{} is not installed, so AUR packages can't be installed", helper));
+                self.focus = ConfirmButton::No;
+            }
+        }
+    }
+
+    /// Work out the transaction's size on another thread, so the dialog stays responsive
+    pub fn resolve_transaction(&mut self, resolve: impl FnOnce() -> anyhow::Result<TransactionPreview> + Send + 'static) {
+        let (tx, rx) = mpsc::channel();
+        self.transaction = TransactionSize::Loading;
+        self.transaction_rx = Some(rx);
+        self.spinner.reset();
+        thread::spawn(move || {
+            let _ = tx.send(resolve().map_err(|e| e.to_string()));
+        });
+    }
+
+    /// Apply the resolved transaction, if it arrived (called from the event loop)
+    pub fn check_updates(&mut self) {
+        if self.transaction != TransactionSize::Loading {
+            return;
+        }
+        self.spinner.tick();
+        let Some(rx) = &self.transaction_rx else {
+            return;
+        };
+        match rx.try_recv() {
+            Ok(Ok(preview)) => self.transaction = TransactionSize::Ready(preview),
+            Ok(Err(reason)) => self.transaction = TransactionSize::Unknown(reason.lines().next().unwrap_or_default().to_string()),
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => self.transaction = TransactionSize::Unknown("resolving stopped".to_string()),
+        }
+        self.transaction_rx = None;
+    }
+
+    /// Lines describing the whole transaction, e.g. "Download: 182 MiB, Installed: 640 MiB, +3 new dependencies"
+    pub fn transaction_lines(&self) -> Vec<String> {
+        match &self.transaction {
+            TransactionSize::NotRequested => Vec::new(),
+            TransactionSize::Loading => vec![format!("{} Calculating transaction size...", self.spinner.current())],
+            TransactionSize::Unknown(reason) => vec![format!("Size unknown ({})", reason)],
+            TransactionSize::Ready(preview) if self.action_type == ActionType::Install => {
+                let mut line = format!(
+                    "Download: {}, Installed: {}",
+                    format_size(preview.download_size),
+                    format_size(preview.installed_size)
+                );
+                match preview.dependencies.len() {
+                    0 => {}
+                    1 => line.push_str(", +1 new dependency"),
+                    n => line.push_str(&format!(", +{} new dependencies", n)),
+                }
+                vec![line]
+            }
+            TransactionSize::Ready(preview) => {
+                let mut lines = vec![format!("Frees: {}", format_size(preview.installed_size))];
+                if !preview.dependencies.is_empty() {
+                    let count = preview.dependencies.len();
+                    lines.push(format!("Also removes {} other package{}:", count, if count == 1 { "" } else { "s" }));
+                    lines.extend(preview.dependencies.iter().map(|name| format!("  - {}", name)));
+                }
+                lines
+            }
+        }
+    }
+
+    /// Summarize the repositories of the packages to install from their details (keyed by package name)
+    /// Sizes come from the whole transaction instead (see `resolve_transaction`)
+    pub fn summarize(&mut self, infos: &HashMap<String, PackageInfo>) {
+        self.summary.clear();
+        match self.action_type {
+            ActionType::Install => {
+                let mut repositories: Vec<(String, usize)> = Vec::new();
+                for pkg in &self.packages {
+                    let name = pkg.rsplit('/').next().unwrap_or(pkg);
+                    let repository = match pkg.split_once('/') {
+                        Some((repository, _)) => repository.to_string(),
+                        None => infos
+                            .get(name)
+                            .and_then(|info| info.repository.clone())
+                            .unwrap_or_else(|| "aur".to_string()),
+                    };
+                    match repositories.iter_mut().find(|(r, _)| *r == repository) {
+                        Some((_, count)) => *count += 1,
+                        None => repositories.push((repository, 1)),
+                    }
+                }
+                repositories.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+                let repositories: Vec<String> = repositories
+                    .iter()
+                    .map(|(repository, count)| format!("{} ({})", repository, count))
+                    .collect();
+                self.summary.push(format!("From: {}", repositories.join(", ")));
+            }
+            ActionType::Remove | ActionType::Update | ActionType::Upgrade => {}
+        }
+    }
+
+    /// Mark the packages to install that are already installed (versions keyed by name), comparing with
+    /// their sync details; without a sync version (AUR) an installed package counts as up to date
+    pub fn flag_installed(&mut self, installed: &HashMap<String, String>, infos: &HashMap<String, PackageInfo>) {
+        self.reinstalls.clear();
+        self.upgrades.clear();
+        for pkg in &self.packages {
+            let name = pkg.rsplit('/').next().unwrap_or(pkg);
+            let Some(current) = installed.get(name) else {
+                continue;
+            };
+            match infos.get(name) {
+                Some(info) if vercmp(current, &info.version).is_lt() => self.upgrades.push(pkg.clone()),
+                _ => self.reinstalls.push(pkg.clone()),
+            }
+        }
+    }
+
+    /// Packages the install actually changes: up to date ones are left out when `needed`
+    pub fn packages_to_install(&self, needed: bool) -> Vec<String> {
+        self.packages
+            .iter()
+            .filter(|pkg| !needed || !self.reinstalls.contains(pkg))
+            .cloned()
+            .collect()
+    }
+
+    /// Flag packages on pmgr's ignore list; installing them needs the extra keystroke
+    pub fn flag_ignored(&mut self, ignored: &[String]) {
+        for pkg in &self.packages {
+            let name = pkg.rsplit('/').next().unwrap_or(pkg);
+            if ignored.iter().any(|i| i == name) {
+                self.badges
+                    .entry(pkg.clone())
+                    .or_default()
+                    .push("ignored by pmgr (held back from updates)".to_string());
+                self.require_ack = true;
+            }
+        }
+    }
+
+    /// Warn that the upgrades not picked are held back; confirming then needs the extra keystroke
+    pub fn warn_partial_upgrade(&mut self, held_back: usize) {
+        self.warnings.push(format!(
+            "Partial upgrades are unsupported on Arch Linux: {} other upgrade(s) will be held back, \
+             which can break packages that depend on them",
+            held_back
+        ));
+        self.partial_upgrade = true;
+    }
+
+    /// Warn about removing protected packages or ones that other installed packages need (from their `-Qi`
+    /// details, keyed by name); confirming then takes typing "yes"
+    pub fn flag_removal_risks(&mut self, infos: &HashMap<String, PackageInfo>, protected: &[String]) {
+        let names: Vec<&str> = self.packages.iter().map(|p| p.rsplit('/').next().unwrap_or(p)).collect();
+        for name in &names {
+            if is_protected(name, protected) {
+                self.warnings.push(format!("{} is a protected system package", name));
+                self.require_typed = true;
+            }
+            // Dependents removed along with it don't count
+            let dependents: Vec<&str> = infos
+                .get(*name)
+                .map(|info| info.required_by.iter().map(String::as_str).filter(|d| !names.contains(d)).collect())
+                .unwrap_or_default();
+            if !dependents.is_empty() {
+                let shown = dependents.iter().take(10).copied().collect::<Vec<_>>().join(", ");
+                let more = dependents.len().saturating_sub(10);
+                let more = if more > 0 { format!(" (+{} more)", more) } else { String::new() };
+                self.warnings.push(format!("{} is required by: {}{}", name, shown, more));
+                self.require_typed = true;
+            }
+        }
+        if self.require_typed {
+            self.focus = ConfirmButton::No;
+        }
+    }
+
+    /// Type into the confirmation input; Yes gets the focus once it reads "yes"
+    pub fn type_char(&mut self, c: char) {
+        self.typed.push(c);
+        self.focus = if self.needs_typing() { ConfirmButton::No } else { ConfirmButton::Yes };
+    }
+
+    pub fn delete_char(&mut self) {
+        self.typed.pop();
+        self.focus = if self.needs_typing() { ConfirmButton::No } else { ConfirmButton::Yes };
+    }
+
+    /// Whether the typed confirmation is still missing
+    pub fn needs_typing(&self) -> bool {
+        self.require_typed && self.typed.trim() != "yes"
+    }
+
+    /// Whether confirming still needs the extra keystroke for badged packages or a partial upgrade
+    pub fn needs_ack(&self) -> bool {
+        (self.partial_upgrade || (self.require_ack && !self.badges.is_empty())) && !self.acknowledged
+    }
+
+    /// Confirm, or take the first of two keystrokes when badges need acknowledging (nothing when blocked)
+    pub fn confirm_or_ack(&mut self) {
+        if self.blocked.is_some() || self.needs_typing() {
+            return;
+        }
+        if self.needs_ack() {
+            self.acknowledged = true;
+        } else {
+            self.confirm();
+        }
+    }
+
+    /// Move the focus to the other button
+    pub fn toggle_focus(&mut self) {
+        self.focus = match self.focus {
+            ConfirmButton::Yes => ConfirmButton::No,
+            ConfirmButton::No => ConfirmButton::Yes,
+        };
+    }
+
+    /// Enter: press the focused button
+    pub fn activate(&mut self) {
+        match self.focus {
+            ConfirmButton::Yes => self.confirm_or_ack(),
+            ConfirmButton::No => self.cancel(),
+        }
     }
 
     pub fn confirm(&mut self) {
@@ -86,7 +533,13 @@ impl ConfirmDialog {
     }
 
     pub fn scroll_down(&mut self) {
-        self.scroll = self.scroll.saturating_add(1);
+        self.scroll = self.scroll.saturating_add(1).min(self.max_scroll);
+    }
+
+    /// Record how many lines the package list has and how many fit, keeping the scroll within it
+    pub fn fit_scroll(&mut self, lines: usize, height: u16) {
+        self.max_scroll = u16::try_from(lines).unwrap_or(u16::MAX).saturating_sub(height);
+        self.scroll = self.scroll.min(self.max_scroll);
     }
 
     pub fn scroll_up(&mut self) {
@@ -94,12 +547,136 @@ impl ConfirmDialog {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+/// Repository the Install list is narrowed to (items are `repository/name`)
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum RepoFilter {
+    #[default]
+    All,
+    Repository(String), // As in the item prefix
+}
+
+impl RepoFilter {
+    /// The filter after this one among `repositories`, wrapping back to All
+    pub fn next(&self, repositories: &[String]) -> Self {
+        let position = match self {
+            RepoFilter::All => 0,
+            RepoFilter::Repository(name) => repositories.iter().position(|r| r == name).map_or(0, |i| i + 1),
+        };
+        repositories.get(position).map_or(RepoFilter::All, |r| RepoFilter::Repository(r.clone()))
+    }
+
+    /// Repository name, as in the item prefix
+    pub fn label(&self) -> &str {
+        match self {
+            RepoFilter::All => "all",
+            RepoFilter::Repository(name) => name,
+        }
+    }
+
+    pub fn matches(&self, item: &str) -> bool {
+        *self == RepoFilter::All || item.split_once('/').is_some_and(|(repository, _)| repository == self.label())
+    }
+}
+
+impl From<String> for RepoFilter {
+    fn from(name: String) -> Self {
+        if name == "all" { RepoFilter::All } else { RepoFilter::Repository(name) }
+    }
+}
+
+impl From<RepoFilter> for String {
+    fn from(filter: RepoFilter) -> Self {
+        filter.label().to_string()
+    }
+}
+
+/// How the search query matches items
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SearchMode {
+    #[default]
+    Fuzzy,
+    Exact, // Every term is a substring; `^`/`$` anchor a term to the package name
+    Regex, // Case-insensitive, against the package name or the whole item
+}
+
+impl SearchMode {
+    pub fn next(self) -> Self {
+        match self {
+            SearchMode::Fuzzy => SearchMode::Exact,
+            SearchMode::Exact => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Fuzzy,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SearchMode::Fuzzy => "fuzzy",
+            SearchMode::Exact => "exact",
+            SearchMode::Regex => "regex",
+        }
+    }
+}
+
+/// Order of a package list; equal keys keep their fuzzy-score order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortMode {
+    #[default]
+    Score,
+    Name,
+    Repository,
+    /// Largest installed size first (installed packages only)
+    Size,
+    /// Most recently installed first (installed packages only)
+    Date,
+}
+
+impl SortMode {
+    /// The mode after this one, skipping modes the list has no data for
+    pub fn next(self, has_repositories: bool, has_details: bool) -> Self {
+        let mut mode = self;
+        loop {
+            mode = match mode {
+                SortMode::Score => SortMode::Name,
+                SortMode::Name => SortMode::Repository,
+                SortMode::Repository => SortMode::Size,
+                SortMode::Size => SortMode::Date,
+                SortMode::Date => SortMode::Score,
+            };
+            if mode.is_available(has_repositories, has_details) {
+                return mode;
+            }
+        }
+    }
+
+    fn is_available(self, has_repositories: bool, has_details: bool) -> bool {
+        match self {
+            SortMode::Score | SortMode::Name => true,
+            SortMode::Repository => has_repositories,
+            SortMode::Size | SortMode::Date => has_details,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SortMode::Score => "best match",
+            SortMode::Name => "name",
+            SortMode::Repository => "repository",
+            SortMode::Size => "size",
+            SortMode::Date => "install date",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ViewType {
     Home = 0,
     Install = 1,
     Remove = 2,
     List = 3,
+    Updates = 4,
+    Settings = 5,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -109,11 +686,21 @@ pub enum AlertType {
     Info,
 }
 
+/// How long success and info alerts stay up; errors stay until a key is pressed
+pub const ALERT_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// Alerts waiting behind the shown one; the oldest are dropped past this
+const ALERT_QUEUE_LIMIT: usize = 5;
+
 #[derive(Debug, Clone)]
 pub struct Alert {
     pub active: bool,
     pub alert_type: AlertType,
     pub message: String,
+    /// When the shown alert closes by itself (None for errors)
+    pub expires_at: Option<Instant>,
+    /// Alerts shown after this one closes, oldest first
+    pub queue: VecDeque<(AlertType, String)>,
 }
 
 impl Alert {
@@ -122,17 +709,146 @@ impl Alert {
             active: false,
             alert_type: AlertType::Info,
             message: String::new(),
+            expires_at: None,
+            queue: VecDeque::new(),
         }
     }
 
+    /// Show an alert, or queue it behind the one already shown
     pub fn show(&mut self, alert_type: AlertType, message: String) {
+        if !self.active {
+            self.display(alert_type, message);
+            return;
+        }
+        // The same alert twice in a row shows once
+        let last = self.queue.back().map(|(_, queued)| queued).unwrap_or(&self.message);
+        if *last != message {
+            if self.queue.len() == ALERT_QUEUE_LIMIT {
+                self.queue.pop_front();
+            }
+            self.queue.push_back((alert_type, message));
+        }
+    }
+
+    fn display(&mut self, alert_type: AlertType, message: String) {
         self.active = true;
+        self.expires_at = (alert_type != AlertType::Error).then(|| Instant::now() + ALERT_TIMEOUT);
         self.alert_type = alert_type;
         self.message = message;
     }
 
+    /// Close the shown alert; the next queued one takes its place
     pub fn close(&mut self) {
         self.active = false;
         self.message.clear();
+        self.expires_at = None;
+        if let Some((alert_type, message)) = self.queue.pop_front() {
+            self.display(alert_type, message);
+        }
+    }
+
+    /// Take back an alert that no longer applies, whether shown or still queued
+    pub fn dismiss(&mut self, message: &str) {
+        if self.active && self.message == message {
+            self.close();
+        } else {
+            self.queue.retain(|(_, queued)| queued != message);
+        }
+    }
+
+    /// Close the shown alert once its time is up
+    pub fn expire(&mut self, now: Instant) {
+        if self.active && self.expires_at.is_some_and(|at| now >= at) {
+            self.close();
+        }
+    }
+
+    /// Share of its time the shown alert has left, from 1 to 0 (None if it stays)
+    pub fn remaining(&self, now: Instant) -> Option<f64> {
+        let left = self.expires_at?.saturating_duration_since(now);
+        Some(left.as_secs_f64() / ALERT_TIMEOUT.as_secs_f64())
+    }
+
+    pub fn show_error(&mut self, error: &PmgrError) {
+        self.show(AlertType::Error, error_message(error));
+    }
+}
+
+/// How the last operation ended, for the status bar
+#[derive(Debug, Clone)]
+pub struct LastOperation {
+    pub alert_type: AlertType,
+    /// A few words, e.g. "✓ installed 3 pkgs"
+    pub message: String,
+    pub at: Instant,
+}
+
+/// Alert text for a failed operation, with advice for the causes that have a known fix
+pub fn error_message(error: &PmgrError) -> String {
+    match error {
+        PmgrError::BackendMissing(helper) if helper == "yay" || helper == "paru" => format!(
+            "✗ AUR packages need an AUR helper — install {0} (git clone https://aur.archlinux.org/{0}.git, then makepkg -si)",
+            helper
+        ),
+        PmgrError::BackendMissing(program) => {
+            format!("✗ {} is not installed — install it or pick another backend in the settings", program)
+        }
+        PmgrError::PackageNotFound(package) => format!(
+            "✗ Package not found: {} — the package databases may be out of date, try a system update",
+            package
+        ),
+        PmgrError::DatabaseLocked { stale: false } => {
+            "✗ Another package manager is running — try again once it has finished".to_string()
+        }
+        PmgrError::DatabaseLocked { stale: true } => {
+            "✗ The pacman database is locked but nothing is running — remove the stale db.lck".to_string()
+        }
+        PmgrError::PermissionDenied => "✗ Authentication failed or was cancelled".to_string(),
+        PmgrError::Network(_) => format!("✗ {} — check your connection or mirrors", error),
+        // Only the first line fits in the alert
+        PmgrError::CommandFailed { cmd, stderr, .. } if !stderr.is_empty() => {
+            format!("✗ {} failed: {}", cmd, stderr.lines().next().unwrap_or_default())
+        }
+        PmgrError::CommandFailed { .. } | PmgrError::PacmanNotFound => format!("✗ {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_queue_up_and_expire() {
+        let mut alert = Alert::new();
+        alert.show(AlertType::Success, "Installed vim".to_string());
+        alert.show(AlertType::Error, "✗ Preview failed".to_string());
+        alert.show(AlertType::Error, "✗ Preview failed".to_string());
+        assert_eq!(alert.message, "Installed vim", "a later alert waits its turn");
+        assert_eq!(alert.queue.len(), 1, "repeats show once");
+
+        // Success closes by itself, then the error stays until dismissed
+        let shown = Instant::now();
+        assert!(alert.remaining(shown).is_some_and(|left| left > 0.9));
+        alert.expire(shown + ALERT_TIMEOUT);
+        assert_eq!(alert.message, "✗ Preview failed");
+        assert_eq!(alert.remaining(shown), None);
+        alert.expire(shown + ALERT_TIMEOUT * 10);
+        assert!(alert.active);
+        alert.close();
+        assert!(!alert.active);
+
+        alert.show(AlertType::Info, "first".to_string());
+        for i in 0..10 {
+            alert.show(AlertType::Info, format!("queued {}", i));
+        }
+        assert_eq!(alert.queue.len(), ALERT_QUEUE_LIMIT);
+        assert_eq!(alert.queue.front().map(|(_, message)| message.as_str()), Some("queued 5"), "the oldest are dropped");
+    }
+
+    #[test]
+    fn repo_filters_are_saved_as_repository_names() {
+        let filters: Vec<RepoFilter> = serde_json::from_str(r#"["all", "core", "custom"]"#).unwrap();
+        assert_eq!(filters, vec![RepoFilter::All, RepoFilter::Repository("core".to_string()), RepoFilter::Repository("custom".to_string())]);
+        assert_eq!(serde_json::to_string(&filters).unwrap(), r#"["all","core","custom"]"#);
     }
 }
@@ -1,8 +1,19 @@
-use super::types::{SystemUpdateWindow, UpdateMessage};
-use std::io::{BufRead, BufReader};
-use std::process::{Command, Stdio};
-use std::sync::mpsc;
+use super::ansi;
+use super::types::{error_message, AlertType, CommandStep, LastOperation, LockWait, OperationSummary, StepStatus, SystemUpdateWindow, UpdateMessage};
+use crate::config;
+use crate::oplog::OperationLog;
+use crate::package::progress::Progress;
+use crate::package::{lock, runner, Backend, DbLock, InstallOptions, PacmanConfig, PmgrError, RemoveOptions};
+use std::io::{self, BufRead, BufReader};
+use std::os::unix::process::CommandExt;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long an interrupted command gets to stop before it is sent SIGTERM
+const CANCEL_GRACE: Duration = Duration::from_secs(5);
 
 impl SystemUpdateWindow {
     pub fn new() -> Self {
@@ -16,168 +27,338 @@ impl SystemUpdateWindow {
             title: String::new(),
             cancelled_by_user: false,
             operation_type: None,
-            was_successful: false,
+            summary: OperationSummary::default(),
+            steps: Vec::new(),
+            step_status: Vec::new(),
+            abort_on_failure: true,
+            lock_wait: None,
+            lock_file: None,
+            failure: None,
+            error_output: Vec::new(),
+            scroll_top: None,
+            page_height: 0,
+            running_pid: Arc::new(Mutex::new(None)),
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+            cancel_requested: None,
+            terminated: false,
+            progress: None,
+            log_path: None,
+            started_at: None,
+            finished_at: None,
+            auto_close_delay: Some(Duration::from_secs(3)),
+            kept_open: false,
         }
     }
 
-    /// Generic method to execute a command with arguments
-    fn start_command(&mut self, command: String, args: Vec<String>, initial_message: &str, success_message: &str, title: &str) {
+    /// Run a chain of commands sequentially in the window
+    /// With `abort_on_failure`, steps after the first failure are skipped
+    pub fn start_chain(&mut self, steps: Vec<CommandStep>, abort_on_failure: bool, initial_message: &str, title: &str) {
         self.active = true;
         self.output.clear();
         self.output.push(initial_message.to_string());
+        self.scroll_top = None;
+        self.summary = OperationSummary::default();
+        self.started_at = None;
+        self.title = title.to_string();
+        self.step_status = vec![StepStatus::Pending; steps.len()];
+        self.steps = steps;
+        self.abort_on_failure = abort_on_failure;
+
+        let indices = (0..self.steps.len()).collect();
+        self.run_when_unlocked(indices);
+    }
+
+    /// Generic method to execute a single command with arguments
+    fn start_command(&mut self, command: String, args: Vec<String>, initial_message: &str, success_message: &str, title: &str) {
+        let step = CommandStep::new(title, &command, args, success_message);
+        self.start_chain(vec![step], true, initial_message, title);
+    }
+
+    /// Re-run the failed step (and any steps skipped after it), or check a held lock again
+    /// Returns false if there is nothing to retry
+    pub fn retry_failed(&mut self) -> bool {
+        if let Some(wait) = self.lock_wait.as_mut() {
+            wait.since = Instant::now();
+            wait.timed_out = false;
+            self.output.push("Checking the database lock again...".to_string());
+            self.check_lock(true);
+            return true;
+        }
+
+        if !self.completed || !self.has_error {
+            return false;
+        }
+
+        let indices: Vec<usize> = self
+            .step_status
+            .iter()
+            .enumerate()
+            .filter(|(_, status)| **status != StepStatus::Succeeded)
+            .map(|(i, _)| i)
+            .collect();
+
+        if indices.is_empty() {
+            return false;
+        }
+
+        self.output.push(String::new());
+        self.output.push("Retrying failed step...".to_string());
+        self.run_when_unlocked(indices);
+        true
+    }
+
+    /// Whether the steps are waiting for the pacman database lock
+    pub fn is_waiting_for_lock(&self) -> bool {
+        self.lock_wait.is_some()
+    }
+
+    /// Run the steps right away if the pacman database is free, otherwise wait for the lock
+    fn run_when_unlocked(&mut self, indices: Vec<usize>) {
+        let lock_file = self
+            .lock_file
+            .clone()
+            .unwrap_or_else(|| lock::lock_path(&PacmanConfig::load().db_path));
+        let now = Instant::now();
+
+        self.lock_wait = Some(LockWait {
+            lock_file,
+            state: DbLock::Free,
+            since: now,
+            last_check: now,
+            timeout: Duration::from_secs(config::load_settings().lock_wait_secs),
+            timed_out: false,
+            indices,
+        });
+        self.check_lock(false);
+    }
+
+    /// Check the lock of the waiting steps, starting them once it is free
+    /// Explains the state when it changes (or always, when `report` is set)
+    fn check_lock(&mut self, report: bool) {
+        let Some(wait) = self.lock_wait.as_mut() else {
+            return;
+        };
+        let previous = wait.state;
+        wait.state = lock::check(&wait.lock_file, lock::pacman_running);
+        wait.last_check = Instant::now();
+        let state = wait.state;
+        let lock_file = wait.lock_file.clone();
+
+        if state == DbLock::Free {
+            let indices = std::mem::take(&mut wait.indices);
+            self.lock_wait = None;
+            if previous != DbLock::Free {
+                self.output.push("✓ Database lock released, continuing...".to_string());
+            }
+            self.run_steps(indices);
+            return;
+        }
+
+        if state == previous && !report {
+            return;
+        }
+        match state {
+            DbLock::Held => {
+                self.output.push(String::new());
+                self.output.push("⚠ Another package manager is running — waiting for it to finish".to_string());
+                self.output.push("  Press R to check again, Esc to cancel".to_string());
+            }
+            DbLock::Stale => {
+                self.output.push(String::new());
+                self.output.push(format!("⚠ The pacman database is locked ({})", lock_file.display()));
+                self.output.push(format!("  {}", lock::stale_hint(&lock_file)));
+                self.output.push("  Press R to check again, Esc to cancel".to_string());
+            }
+            DbLock::Free => {}
+        }
+    }
+
+    /// Poll a held lock until it is released or the wait times out
+    fn poll_lock(&mut self) {
+        let Some(wait) = self.lock_wait.as_mut() else {
+            return;
+        };
+        if wait.state != DbLock::Held || wait.timed_out || wait.last_check.elapsed() < lock::POLL_INTERVAL {
+            return;
+        }
+
+        if wait.since.elapsed() >= wait.timeout {
+            wait.timed_out = true;
+            let message = format!(
+                "Still locked after {}s. Press R to check again, Esc to cancel",
+                wait.timeout.as_secs()
+            );
+            self.output.push(message);
+            return;
+        }
+        self.check_lock(false);
+    }
+
+    /// Index of the step currently running, if any
+    pub fn current_step(&self) -> Option<usize> {
+        self.step_status.iter().position(|status| *status == StepStatus::Running)
+    }
+
+    /// Spawn the worker thread running the given steps in order
+    fn run_steps(&mut self, indices: Vec<usize>) {
         self.completed = false;
         self.has_error = false;
-        self.title = title.to_string();
+        self.error_output.clear();
+        self.cancel_requested = None;
+        self.terminated = false;
+        self.progress = None;
+        // A retry keeps the clock running from the first attempt
+        self.started_at.get_or_insert_with(Instant::now);
+        self.finished_at = None;
+        self.kept_open = false;
+        self.cancel_flag = Arc::new(AtomicBool::new(false));
+        let cancel = Arc::clone(&self.cancel_flag);
+        let running = Arc::clone(&self.running_pid);
 
         let (tx, rx) = mpsc::channel();
         self.rx = Some(rx);
 
-        let success_message = success_message.to_string();
+        let steps: Vec<(usize, CommandStep)> = indices
+            .into_iter()
+            .map(|i| (i, self.steps[i].clone()))
+            .collect();
+        let total = self.steps.len();
+        let abort_on_failure = self.abort_on_failure;
+
+        // Mirror output to the operations log (for `pmgr log --follow`) and to a file of its own
+        let command_line = steps
+            .iter()
+            .map(|(_, step)| format!("{} {}", step.command, step.args.join(" ")))
+            .collect::<Vec<_>>()
+            .join(" && ");
+        let operation = self.operation_type.clone().unwrap_or_else(|| self.title.clone());
+        let log = OperationLog::begin(&self.title, &operation, &command_line, config::load_settings().log_retention).ok();
+        self.log_path = log.as_ref().map(|log| log.path().to_path_buf());
 
         thread::spawn(move || {
-            // Log the command being executed for debugging
-            let _ = tx.send(UpdateMessage::Output(format!("Executing: {} {}", command, args.join(" "))));
-            let _ = tx.send(UpdateMessage::Output(String::new())); // Empty line for readability
-
-            let mut child = match Command::new(&command)
-                .args(&args)
-                .stdin(Stdio::null()) // Polkit will handle authentication via GUI
-                .stdout(Stdio::piped())
-                .stderr(Stdio::piped())
-                .spawn()
-            {
-                Ok(child) => child,
-                Err(e) => {
-                    let _ = tx.send(UpdateMessage::Output(format!("Error: Failed to start command: {}", e)));
-                    let _ = tx.send(UpdateMessage::Completed(false));
-                    return;
-                }
-            };
-
-            // Read stdout in separate thread
-            let stdout = child.stdout.take();
-            let tx_stdout = tx.clone();
-            let stdout_handle = thread::spawn(move || {
-                if let Some(stdout) = stdout {
-                    let reader = BufReader::new(stdout);
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            let _ = tx_stdout.send(UpdateMessage::Output(line));
-                        }
-                    }
+            let log = Arc::new(Mutex::new(log));
+
+            let mut all_succeeded = true;
+            for (index, step) in steps {
+                // Cancelled between steps: the rest stay pending
+                if cancel.load(Ordering::Relaxed) {
+                    all_succeeded = false;
+                    break;
                 }
-            });
-
-            // Read stderr in separate thread
-            let stderr = child.stderr.take();
-            let tx_stderr = tx.clone();
-            let stderr_handle = thread::spawn(move || {
-                if let Some(stderr) = stderr {
-                    let reader = BufReader::new(stderr);
-                    for line in reader.lines() {
-                        if let Ok(line) = line {
-                            let _ = tx_stderr.send(UpdateMessage::Output(line));
-                        }
-                    }
+
+                // Per-step header when running more than one command
+                if total > 1 {
+                    let header = format!("━━ Step {}/{}: {} ━━", index + 1, total, step.title);
+                    write_log_line(&log, &header);
+                    let _ = tx.send(UpdateMessage::Output(header));
                 }
-            });
-
-            // Wait for both reading threads to complete
-            let _ = stdout_handle.join();
-            let _ = stderr_handle.join();
-
-            // Wait for process to complete
-            match child.wait() {
-                Ok(status) => {
-                    let success = status.success();
-                    if success {
-                        let _ = tx.send(UpdateMessage::Output(format!("\n{}", success_message)));
-                    } else {
-                        let _ = tx.send(UpdateMessage::Output(format!("\n✗ Operation failed with code: {:?}", status.code())));
+
+                let _ = tx.send(UpdateMessage::StepStarted(index));
+                let success = run_step(&step, &tx, &log, &running, &cancel);
+                let _ = tx.send(UpdateMessage::StepFinished(index, success));
+
+                if !success {
+                    all_succeeded = false;
+                    if abort_on_failure {
+                        break;
                     }
-                    let _ = tx.send(UpdateMessage::Completed(success));
-                }
-                Err(e) => {
-                    let _ = tx.send(UpdateMessage::Output(format!("\nError waiting for process: {}", e)));
-                    let _ = tx.send(UpdateMessage::Completed(false));
                 }
             }
+
+            finish_log(&log, all_succeeded);
+            let _ = tx.send(UpdateMessage::Completed(all_succeeded));
         });
     }
 
-    pub fn start_update(&mut self) {
+    /// Start a full system update, skipping the `ignored` (pinned) packages
+    pub fn start_update(&mut self, ignored: &[String]) {
         self.operation_type = Some("system_update".to_string());
+
+        let mut args = vec!["pacman".to_string(), "-Syu".to_string(), "--noconfirm".to_string()];
+        if !ignored.is_empty() {
+            args.push("--ignore".to_string());
+            args.push(ignored.join(","));
+        }
+
         self.start_command(
             "pkexec".to_string(),
-            vec!["pacman".to_string(), "-Syu".to_string(), "--noconfirm".to_string()],
+            args,
             "Starting system update...",
             "✓ System update completed successfully!",
             "System Update"
         );
     }
 
-    pub fn start_install_official(&mut self, packages: &[String]) {
-        self.operation_type = Some(format!("install_official_{}", packages.len()));
+    /// Upgrade only the given packages, holding back `ignored` (the upgrades that were not picked)
+    pub fn start_upgrade(&mut self, packages: &[String], ignored: &[String]) {
+        self.operation_type = Some(format!("upgrade_{}", packages.len()));
 
-        // Extract package names from "repository/package" format
-        let package_names: Vec<String> = packages
-            .iter()
-            .map(|p| {
-                if let Some(idx) = p.rfind('/') {
-                    p[idx + 1..].to_string()
-                } else {
-                    p.clone()
-                }
-            })
-            .collect();
-
-        let mut args = vec!["pacman".to_string(), "-S".to_string(), "--noconfirm".to_string()];
-        args.extend(package_names);
+        let mut args = vec!["pacman".to_string(), "-Syu".to_string(), "--needed".to_string(), "--noconfirm".to_string()];
+        if !ignored.is_empty() {
+            args.push("--ignore".to_string());
+            args.push(ignored.join(","));
+        }
+        args.extend(packages.iter().cloned());
 
         self.start_command(
             "pkexec".to_string(),
             args,
-            &format!("Installing {} official package(s)...", packages.len()),
-            "✓ Installation completed successfully!",
-            "Installing Official Packages"
+            &format!("Upgrading {} package(s)...", packages.len()),
+            "✓ Upgrade completed successfully!",
+            "Upgrading Packages"
         );
     }
 
-    pub fn start_install(&mut self, packages: &[String]) {
-        // Extract package names from "repository/package" format
-        let package_names: Vec<String> = packages
-            .iter()
-            .map(|p| {
-                // If package is in "repo/name" format, extract just the name
-                if let Some(idx) = p.rfind('/') {
-                    p[idx + 1..].to_string()
-                } else {
-                    p.clone()
-                }
-            })
-            .collect();
+    /// Download fresh sync databases (`pacman -Sy`) without upgrading anything
+    pub fn start_refresh(&mut self) {
+        self.operation_type = Some("refresh_databases".to_string());
 
-        let mut args = vec![
-            "-S".to_string(),
-            "--noconfirm".to_string(),
-            "--answerdiff".to_string(), "None".to_string(),
-            "--answerclean".to_string(), "None".to_string(),
-            "--answeredit".to_string(), "None".to_string(),
-            "--answerupgrade".to_string(), "None".to_string(),
-            "--removemake".to_string(),
-        ];
-        args.extend(package_names);
+        self.start_command(
+            "pkexec".to_string(),
+            vec!["pacman".to_string(), "-Sy".to_string()],
+            "Refreshing package databases...",
+            "✓ Package databases refreshed",
+            "Refresh Databases"
+        );
+    }
+
+    /// Arguments of `pkexec` that install official packages (given as `name` or `repository/name`)
+    pub fn install_official_args(packages: &[String], options: &InstallOptions) -> Vec<String> {
+        let mut args = vec!["pacman".to_string(), "-S".to_string(), "--noconfirm".to_string()];
+        args.extend(options.args());
+        args.extend(packages.iter().map(|p| p.rsplit('/').next().unwrap_or(p).to_string()));
+        args
+    }
+
+    /// Command lines an install from the main menu runs: official packages in this window,
+    /// then AUR packages with the helper (yay when the backend has none)
+    pub fn install_commands(aur: &[String], official: &[String], backend: Backend, options: &InstallOptions) -> Vec<String> {
+        let mut commands = Vec::new();
+        if !official.is_empty() {
+            commands.push(format!("pkexec {}", Self::install_official_args(official, options).join(" ")));
+        }
+        if !aur.is_empty() {
+            let helper = if backend.supports_aur() { backend.command() } else { "yay" };
+            let names = aur.iter().map(|p| p.rsplit('/').next().unwrap_or(p).to_string());
+            let args: Vec<String> = options.args().into_iter().chain(names).collect();
+            commands.push(format!("{} -S {}", helper, args.join(" ")));
+        }
+        commands
+    }
+
+    pub fn start_install_official(&mut self, packages: &[String], options: &InstallOptions) {
+        self.operation_type = Some(format!("install_official_{}", packages.len()));
 
         self.start_command(
-            "yay".to_string(),
-            args,
-            &format!("Installing {} package(s)...", packages.len()),
+            "pkexec".to_string(),
+            Self::install_official_args(packages, options),
+            &format!("Installing {} official package(s)...", packages.len()),
             "✓ Installation completed successfully!",
-            "Installing Packages"
+            "Installing Official Packages"
         );
     }
 
-    pub fn start_remove(&mut self, packages: &[String]) {
+    pub fn start_remove(&mut self, packages: &[String], options: &RemoveOptions) {
         self.operation_type = Some(format!("remove_{}", packages.len()));
 
         // Extract package names from "repository/package" format
@@ -192,7 +373,7 @@ impl SystemUpdateWindow {
             })
             .collect();
 
-        let mut args = vec!["pacman".to_string(), "-Rns".to_string(), "--noconfirm".to_string()];
+        let mut args = vec!["pacman".to_string(), options.flag(), "--noconfirm".to_string()];
         args.extend(package_names);
 
         self.start_command(
@@ -204,44 +385,703 @@ impl SystemUpdateWindow {
         );
     }
 
+    /// Run a queue built across the tabs: the removals, then the installs (skipped if the removals fail)
+    pub fn start_queue(&mut self, removals: &[String], installs: &[String], remove_options: &RemoveOptions, install_options: &InstallOptions) {
+        self.operation_type = Some(format!("queue_{}_{}", removals.len(), installs.len()));
+
+        let mut steps = Vec::new();
+        if !removals.is_empty() {
+            let mut args = vec!["pacman".to_string(), remove_options.flag(), "--noconfirm".to_string()];
+            args.extend(removals.iter().map(|p| p.rsplit('/').next().unwrap_or(p).to_string()));
+            steps.push(CommandStep::new(
+                &format!("Remove {} package(s)", removals.len()),
+                "pkexec",
+                args,
+                "✓ Removal completed successfully!",
+            ));
+        }
+        if !installs.is_empty() {
+            steps.push(CommandStep::new(
+                &format!("Install {} package(s)", installs.len()),
+                "pkexec",
+                Self::install_official_args(installs, install_options),
+                "✓ Installation completed successfully!",
+            ));
+        }
+
+        self.start_chain(
+            steps,
+            true,
+            &format!("Applying the queue: {} removal(s), then {} install(s)...", removals.len(), installs.len()),
+            "Applying Queue",
+        );
+    }
+
     pub fn check_updates(&mut self) {
+        self.poll_lock();
+        self.poll_cancel();
+
         if let Some(ref rx) = self.rx {
             while let Ok(msg) = rx.try_recv() {
                 match msg {
+                    // Kept as the terminal would show it (no colors, progress bars at their last state)
+                    // Pacman's progress lines also drive the gauge
                     UpdateMessage::Output(line) => {
+                        let line = ansi::strip(&line);
+                        if let Some(progress) = Progress::parse(&line) {
+                            // A package is counted once, however often its line is redrawn
+                            if self.progress.as_ref().map(Progress::label) != Some(progress.label()) {
+                                self.summary.count(&progress);
+                            }
+                            self.progress = Some(progress);
+                        }
+                        self.output.push(line);
+                    }
+                    UpdateMessage::ErrorOutput(line) => {
+                        let line = ansi::strip(&line);
+                        self.error_output.push(line.clone());
                         self.output.push(line);
                     }
+                    UpdateMessage::StepStarted(index) => {
+                        self.progress = None;
+                        if let Some(status) = self.step_status.get_mut(index) {
+                            *status = StepStatus::Running;
+                        }
+                    }
+                    UpdateMessage::StepFinished(index, success) => {
+                        if let Some(status) = self.step_status.get_mut(index) {
+                            *status = match (success, self.cancel_requested) {
+                                (true, _) => StepStatus::Succeeded,
+                                (false, Some(_)) => StepStatus::Cancelled,
+                                (false, None) => StepStatus::Failed,
+                            };
+                        }
+                    }
                     UpdateMessage::Completed(success) => {
                         self.completed = true;
                         self.has_error = !success;
+                        self.finished_at = Some(Instant::now());
+                        self.summary.duration = self.elapsed();
+                        if success {
+                            self.output.push(format!("✓ {}", self.summary.describe()));
+                        }
                     }
                 }
             }
         }
     }
 
+    /// Time since the operation started, up to when it completed
+    pub fn elapsed(&self) -> Duration {
+        match self.started_at {
+            Some(started) => self.finished_at.unwrap_or_else(Instant::now).saturating_duration_since(started),
+            None => Duration::ZERO,
+        }
+    }
+
+    /// Whether Ctrl+C interrupted the operation
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_requested.is_some()
+    }
+
+    /// Interrupt the running operation (Ctrl+C): SIGINT to the running command's process group,
+    /// SIGTERM if it is still running after CANCEL_GRACE or on a second Ctrl+C; no further steps start
+    /// Returns false if nothing is running
+    pub fn cancel(&mut self) -> bool {
+        if !self.active || self.completed || self.rx.is_none() {
+            return false;
+        }
+
+        self.cancel_flag.store(true, Ordering::Relaxed);
+        if self.cancel_requested.is_none() {
+            self.cancel_requested = Some(Instant::now());
+            self.output.push(String::new());
+            self.output.push("⚠ Cancelling... (Ctrl+C again to terminate)".to_string());
+            self.signal_running(libc::SIGINT);
+        } else if !self.terminated {
+            self.terminate();
+        }
+        true
+    }
+
+    /// Send SIGTERM to a cancelled command that hasn't stopped within the grace period
+    fn poll_cancel(&mut self) {
+        let Some(since) = self.cancel_requested else {
+            return;
+        };
+        if !self.completed && !self.terminated && since.elapsed() >= CANCEL_GRACE {
+            self.terminate();
+        }
+    }
+
+    fn terminate(&mut self) {
+        self.terminated = true;
+        self.output.push("⚠ Terminating...".to_string());
+        self.signal_running(libc::SIGTERM);
+    }
+
+    /// Signal the process group of the running step, explaining when that isn't allowed
+    fn signal_running(&mut self, signal: i32) {
+        let pid = self.running_pid.lock().ok().and_then(|pid| *pid);
+        if let Some(pid) = pid {
+            if let Err(e) = signal_group(pid, signal) {
+                self.output.push(format!("⚠ Couldn't stop the command ({}), it may be running as root", e));
+            }
+        }
+    }
+
+    /// First line of the last page of output
+    fn last_page(&self) -> usize {
+        self.output.len().saturating_sub(self.page_height)
+    }
+
+    /// First output line to show: the last page while following, else where it was scrolled to
+    pub fn visible_start(&self) -> usize {
+        self.scroll_top.map_or(self.last_page(), |top| top.min(self.last_page()))
+    }
+
+    /// Whether the window shows the newest output as it arrives
+    pub fn is_following(&self) -> bool {
+        self.scroll_top.is_none()
+    }
+
+    /// Scroll back through earlier output; new output no longer moves the view
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll_top = Some(self.visible_start().saturating_sub(lines));
+    }
+
+    /// Scroll towards the newest output, which is followed again once reached
+    pub fn scroll_down(&mut self, lines: usize) {
+        if let Some(top) = self.scroll_top {
+            let top = top + lines;
+            self.scroll_top = (top < self.last_page()).then_some(top);
+        }
+    }
+
+    /// Lines moved by PageUp/PageDown
+    pub fn page(&self) -> usize {
+        self.page_height.saturating_sub(1).max(1)
+    }
+
+    /// Jump to the first line of output
+    pub fn scroll_to_top(&mut self) {
+        self.scroll_top = Some(0);
+    }
+
+    /// Jump to the newest output and follow it again
+    pub fn follow(&mut self) {
+        self.scroll_top = None;
+    }
+
+    /// Time left before a successful operation's window closes itself, if it will
+    pub fn auto_close_in(&self) -> Option<Duration> {
+        if !self.completed || self.has_error || self.kept_open {
+            return None;
+        }
+        let finished = self.finished_at?;
+        self.auto_close_delay.map(|delay| delay.saturating_sub(finished.elapsed()))
+    }
+
     pub fn should_auto_close(&self) -> bool {
-        self.completed && !self.has_error
+        self.auto_close_in().is_some_and(|left| left.is_zero())
+    }
+
+    /// Stop the auto-close countdown, leaving the output on screen until the window is closed
+    pub fn keep_open(&mut self) {
+        if self.auto_close_in().is_some() {
+            self.kept_open = true;
+        }
     }
 
     pub fn close(&mut self, cancelled_by_user: bool) {
         // Capture success state before clearing
-        self.was_successful = self.completed && !self.has_error;
+        self.summary.success = self.completed && !self.has_error;
+        self.summary.duration = self.elapsed();
+        // A cancelled operation didn't fail
+        self.failure = (self.completed && self.has_error && !self.is_cancelled()).then(|| self.classify_failure());
 
         self.active = false;
         self.output.clear();
+        self.error_output.clear();
+        self.scroll_top = None;
+        self.progress = None;
         self.completed = false;
         self.has_error = false;
         self.rx = None;
+        self.lock_wait = None;
         self.just_closed = true;
-        self.cancelled_by_user = cancelled_by_user;
-        // Keep operation_type and was_successful for showing alert
+        self.cancelled_by_user = cancelled_by_user || self.is_cancelled();
+        self.cancel_requested = None;
+        self.terminated = false;
+        // Keep operation_type and summary for showing alert
     }
 
     pub fn clear_just_closed_flag(&mut self) {
         self.just_closed = false;
         self.cancelled_by_user = false;
         self.operation_type = None;
-        self.was_successful = false;
+        self.summary = OperationSummary::default();
+        self.failure = None;
+    }
+
+    /// Alert text for a failed operation, tailored when the cause is recognised
+    /// followed by where the full output was logged
+    pub fn failure_message(&self) -> String {
+        let message = match &self.failure {
+            Some(PmgrError::CommandFailed { stderr, .. }) if !stderr.is_empty() => {
+                format!("✗ Operation failed: {}", stderr.lines().next().unwrap_or_default())
+            }
+            Some(PmgrError::CommandFailed { .. }) | None => "✗ Operation failed".to_string(),
+            Some(error) => error_message(error),
+        };
+        match &self.log_path {
+            Some(path) => format!("{}\nFull output: {} (Ctrl+L to browse logs)", message, path.display()),
+            None => message,
+        }
+    }
+
+    /// How the closed operation ended, in a few words for the status bar
+    pub fn outcome(&self) -> LastOperation {
+        let what = match self.operation_type.as_deref().unwrap_or_default() {
+            op if op.starts_with("remove_") => format!("removed {} pkgs", &op["remove_".len()..]),
+            op if op.starts_with("install_official_") => format!("installed {} pkgs", &op["install_official_".len()..]),
+            op if op.starts_with("upgrade_") => format!("upgraded {} pkgs", &op["upgrade_".len()..]),
+            op if op.starts_with("queue_") => "applied the queue".to_string(),
+            "system_update" => "system updated".to_string(),
+            "refresh_databases" => "databases refreshed".to_string(),
+            _ => "operation done".to_string(),
+        };
+        let (alert_type, message) = if self.cancelled_by_user {
+            (AlertType::Info, "⚠ cancelled".to_string())
+        } else if self.summary.success {
+            (AlertType::Success, format!("✓ {}", what))
+        } else {
+            (AlertType::Error, "✗ operation failed".to_string())
+        };
+        LastOperation { alert_type, message, at: Instant::now() }
+    }
+
+    /// Why the failed step failed, from what it printed on stderr
+    fn classify_failure(&self) -> PmgrError {
+        let command = self
+            .step_status
+            .iter()
+            .position(|status| *status == StepStatus::Failed)
+            .and_then(|index| self.steps.get(index))
+            .map(|step| format!("{} {}", step.command, step.args.join(" ")))
+            .unwrap_or_default();
+        PmgrError::from_failure(&command, None, &self.error_output.join("\n"))
+    }
+}
+
+/// Run one step, streaming its output; returns whether it succeeded
+/// The command leads its own process group (recorded in `running`), so cancelling stops everything it started
+fn run_step(
+    step: &CommandStep,
+    tx: &mpsc::Sender<UpdateMessage>,
+    log: &Arc<Mutex<Option<OperationLog>>>,
+    running: &Mutex<Option<u32>>,
+    cancel: &AtomicBool,
+) -> bool {
+    // Log the command being executed for debugging
+    let command_line = format!("{} {}", step.command, step.args.join(" "));
+    let _ = tx.send(UpdateMessage::Output(format!("Executing: {}", command_line)));
+    let _ = tx.send(UpdateMessage::Output(String::new())); // Empty line for readability
+
+    let mut child = match runner::interactive(&step.command)
+        .args(&step.args)
+        .stdin(Stdio::null()) // Polkit will handle authentication via GUI
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .process_group(0)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            let message = format!("Error: Failed to start command: {}", e);
+            write_log_line(log, &message);
+            let _ = tx.send(UpdateMessage::Output(message));
+            return false;
+        }
+    };
+
+    if let Ok(mut pid) = running.lock() {
+        *pid = Some(child.id());
+    }
+    // Cancelled while it was starting
+    if cancel.load(Ordering::Relaxed) {
+        let _ = signal_group(child.id(), libc::SIGINT);
+    }
+
+    // Read stdout in separate thread
+    let stdout = child.stdout.take();
+    let tx_stdout = tx.clone();
+    let log_stdout = Arc::clone(log);
+    let stdout_handle = thread::spawn(move || {
+        if let Some(stdout) = stdout {
+            let reader = BufReader::new(stdout);
+            for line in reader.lines().map_while(Result::ok) {
+                write_log_line(&log_stdout, &line);
+                let _ = tx_stdout.send(UpdateMessage::Output(line));
+            }
+        }
+    });
+
+    // Read stderr in separate thread
+    let stderr = child.stderr.take();
+    let tx_stderr = tx.clone();
+    let log_stderr = Arc::clone(log);
+    let stderr_handle = thread::spawn(move || {
+        if let Some(stderr) = stderr {
+            let reader = BufReader::new(stderr);
+            for line in reader.lines().map_while(Result::ok) {
+                write_log_line(&log_stderr, &line);
+                let _ = tx_stderr.send(UpdateMessage::ErrorOutput(line));
+            }
+        }
+    });
+
+    // Wait for both reading threads to complete
+    let _ = stdout_handle.join();
+    let _ = stderr_handle.join();
+
+    // Wait for process to complete
+    let status = child.wait();
+    if let Ok(mut pid) = running.lock() {
+        *pid = None;
+    }
+    let (success, message) = match status {
+        Ok(status) if status.success() => (true, step.success_message.clone()),
+        Ok(_) if cancel.load(Ordering::Relaxed) => (false, "✗ Cancelled".to_string()),
+        Ok(status) => (false, format!("✗ Operation failed with code: {:?}", status.code())),
+        Err(e) => (false, format!("Error waiting for process: {}", e)),
+    };
+
+    write_log_line(log, &message);
+    let _ = tx.send(UpdateMessage::Output(format!("\n{}", message)));
+    success
+}
+
+/// Send a signal to every process in a process group
+fn signal_group(pgid: u32, signal: i32) -> io::Result<()> {
+    let pgid = libc::pid_t::try_from(pgid).map_err(|_| io::Error::from(io::ErrorKind::InvalidInput))?;
+    // SAFETY: kill has no memory-safety preconditions; a negative pid addresses the group
+    if unsafe { libc::kill(-pgid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
+
+/// Append a line to the shared operation log, if it could be opened
+fn write_log_line(log: &Mutex<Option<OperationLog>>, line: &str) {
+    if let Ok(mut guard) = log.lock() {
+        if let Some(log) = guard.as_mut() {
+            let _ = log.write_line(line);
+        }
+    }
+}
+
+/// Write the end marker to the shared operation log
+fn finish_log(log: &Mutex<Option<OperationLog>>, success: bool) {
+    if let Ok(mut guard) = log.lock() {
+        if let Some(log) = guard.take() {
+            let _ = log.end(success);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn sh(title: &str, script: &str) -> CommandStep {
+        CommandStep::new(title, "sh", vec!["-c".to_string(), script.to_string()], "done")
+    }
+
+    /// Poll the window until the chain completes
+    fn wait_for_completion(window: &mut SystemUpdateWindow) {
+        let start = Instant::now();
+        while !window.completed {
+            assert!(start.elapsed() < Duration::from_secs(10), "chain did not complete");
+            window.check_updates();
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    fn start(steps: Vec<CommandStep>, abort_on_failure: bool) -> SystemUpdateWindow {
+        // Keep the operations log out of the user's data directory
+        std::env::set_var("XDG_DATA_HOME", std::env::temp_dir().join("pmgr-test-data"));
+
+        let mut window = SystemUpdateWindow::new();
+        window.lock_file = Some(std::env::temp_dir().join("pmgr-test-no-lock").join("db.lck"));
+        window.start_chain(steps, abort_on_failure, "Starting...", "Test");
+        wait_for_completion(&mut window);
+        window
+    }
+
+    #[test]
+    fn chain_succeeds_when_all_steps_succeed() {
+        let window = start(vec![sh("one", "exit 0"), sh("two", "echo hi")], true);
+
+        assert!(!window.has_error);
+        assert_eq!(window.step_status, vec![StepStatus::Succeeded, StepStatus::Succeeded]);
+        assert!(window.output.iter().any(|line| line == "hi"));
+        assert!(window.output.iter().any(|line| line.contains("Step 2/2: two")));
+    }
+
+    #[test]
+    fn chain_aborts_on_first_failure() {
+        let window = start(vec![sh("one", "exit 0"), sh("two", "exit 1"), sh("three", "exit 0")], true);
+
+        assert!(window.has_error);
+        assert_eq!(
+            window.step_status,
+            vec![StepStatus::Succeeded, StepStatus::Failed, StepStatus::Pending]
+        );
+    }
+
+    #[test]
+    fn chain_continues_after_failure_when_configured() {
+        let window = start(vec![sh("one", "exit 1"), sh("two", "exit 0")], false);
+
+        assert!(window.has_error);
+        assert_eq!(window.step_status, vec![StepStatus::Failed, StepStatus::Succeeded]);
+    }
+
+    #[test]
+    fn retry_reruns_only_the_failed_step() {
+        let dir = std::env::temp_dir().join(format!("pmgr-retry-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let counter = dir.join("first-runs");
+        let marker = dir.join("flaky-ran");
+
+        // First step counts its runs; second fails on its first run only
+        let steps = vec![
+            sh("count", &format!("echo x >> {}", counter.display())),
+            sh("flaky", &format!("test -f {0} || {{ touch {0}; exit 1; }}", marker.display())),
+        ];
+        let mut window = start(steps, true);
+        assert_eq!(window.step_status[1], StepStatus::Failed);
+
+        assert!(window.retry_failed());
+        wait_for_completion(&mut window);
+
+        assert!(!window.has_error);
+        assert_eq!(window.step_status, vec![StepStatus::Succeeded, StepStatus::Succeeded]);
+        assert_eq!(std::fs::read_to_string(&counter).unwrap().lines().count(), 1);
+        assert!(!window.retry_failed());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn steps_wait_for_the_database_lock() {
+        std::env::set_var("XDG_DATA_HOME", std::env::temp_dir().join("pmgr-test-data"));
+        let dir = std::env::temp_dir().join(format!("pmgr-window-lock-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let lock_file = lock::lock_path(&dir);
+        std::fs::write(&lock_file, "").unwrap();
+
+        let mut window = SystemUpdateWindow::new();
+        window.lock_file = Some(lock_file.clone());
+        window.start_chain(vec![sh("one", "echo ran")], true, "Starting...", "Test");
+
+        // Held or stale depending on whether pacman runs here; either way nothing starts
+        assert!(window.is_waiting_for_lock());
+        window.check_updates();
+        assert_eq!(window.step_status, vec![StepStatus::Pending]);
+        assert!(window.output.iter().any(|line| line.contains("Esc to cancel")));
+
+        std::fs::remove_file(&lock_file).unwrap();
+        assert!(window.retry_failed());
+        assert!(!window.is_waiting_for_lock());
+        wait_for_completion(&mut window);
+
+        assert!(!window.has_error);
+        assert!(window.output.iter().any(|line| line == "ran"));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn refresh_syncs_databases_without_upgrading() {
+        let dir = std::env::temp_dir().join(format!("pmgr-window-refresh-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let lock_file = lock::lock_path(&dir);
+        // Locked, so pkexec never runs here
+        std::fs::write(&lock_file, "").unwrap();
+
+        let mut window = SystemUpdateWindow::new();
+        window.lock_file = Some(lock_file);
+        window.start_refresh();
+
+        assert!(window.is_waiting_for_lock());
+        assert_eq!(window.operation_type.as_deref(), Some("refresh_databases"));
+        assert_eq!(window.steps.len(), 1);
+        assert_eq!(window.steps[0].command, "pkexec");
+        assert_eq!(window.steps[0].args, vec!["pacman", "-Sy"]);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn cancelling_stops_the_command_and_the_chain() {
+        std::env::set_var("XDG_DATA_HOME", std::env::temp_dir().join("pmgr-test-data"));
+        let mut window = SystemUpdateWindow::new();
+        window.lock_file = Some(std::env::temp_dir().join("pmgr-test-no-lock").join("db.lck"));
+        // Ignores SIGINT (and so does its sleep), like a command that cleans up before exiting
+        let steps = vec![sh("stubborn", "trap '' INT; sleep 10; sleep 10"), sh("next", "echo ran")];
+        window.start_chain(steps, true, "Starting...", "Test");
+        while window.running_pid.lock().unwrap().is_none() {
+            thread::sleep(Duration::from_millis(10));
+        }
+
+        let started = Instant::now();
+        assert!(window.cancel());
+        // Still running until the second Ctrl+C terminates it
+        thread::sleep(Duration::from_millis(200));
+        window.check_updates();
+        assert!(!window.completed);
+        assert!(window.cancel());
+        wait_for_completion(&mut window);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        assert!(window.is_cancelled());
+        assert_eq!(window.step_status, vec![StepStatus::Cancelled, StepStatus::Pending]);
+        assert!(!window.output.iter().any(|line| line == "ran"));
+        assert!(!window.cancel(), "nothing left to cancel");
+
+        window.close(false);
+        assert!(window.cancelled_by_user);
+        assert_eq!(window.failure, None, "a cancelled operation didn't fail");
+    }
+
+    #[test]
+    fn scrolled_output_stays_put_until_the_bottom_is_reached() {
+        let mut window = SystemUpdateWindow::new();
+        window.output = (0..20).map(|i| i.to_string()).collect();
+        window.page_height = 5;
+        assert!(window.is_following());
+        assert_eq!(window.visible_start(), 15);
+
+        window.scroll_up(3);
+        assert_eq!(window.visible_start(), 12);
+        window.output.extend((20..30).map(|i| i.to_string()));
+        assert_eq!(window.visible_start(), 12, "new output doesn't move a scrolled view");
+
+        window.scroll_down(window.page());
+        assert_eq!(window.visible_start(), 16);
+        window.scroll_down(20);
+        assert!(window.is_following(), "reaching the bottom follows again");
+        assert_eq!(window.visible_start(), 25);
+
+        window.scroll_to_top();
+        window.scroll_up(1);
+        assert_eq!(window.visible_start(), 0);
+        window.follow();
+        assert_eq!(window.visible_start(), 25);
+    }
+
+    #[test]
+    fn progress_lines_drive_the_gauge_of_their_step() {
+        let window = start(vec![sh("upgrade", r"printf '\033[1m(1/2) upgrading foo\033[0m [###---]  50%%\n'")], true);
+        let progress = window.progress.as_ref().expect("progress line parsed");
+        assert_eq!(progress.label(), "(1/2) upgrading foo");
+        assert_eq!(progress.percent, Some(50));
+
+        // The next step starts without a gauge until it reports progress itself
+        let window = start(vec![sh("upgrade", "echo '(1/1) upgrading foo'"), sh("clean", "echo cleaned")], true);
+        assert_eq!(window.progress, None);
+    }
+
+    #[test]
+    fn completion_summarizes_packages_and_time() {
+        let script = "echo '(1/3) upgrading linux'; echo '(1/3) upgrading linux'; echo '(2/3) upgrading vim'; \
+                      echo '(3/3) installing vim-runtime'; echo '(1/1) checking keys in keyring'";
+        let mut window = start(vec![sh("upgrade", script)], true);
+
+        let summary = &window.summary;
+        assert_eq!((summary.upgraded, summary.installed, summary.removed), (2, 1, 0));
+        assert_eq!(window.output.last().unwrap(), "✓ Upgraded 2 packages, installed 1 package in 0s");
+
+        // The clock stops at completion and the summary survives closing for the alert
+        let elapsed = window.elapsed();
+        thread::sleep(Duration::from_millis(20));
+        assert_eq!(window.elapsed(), elapsed);
+        window.close(false);
+        assert!(window.summary.success);
+        assert_eq!(window.summary.describe(), "Upgraded 2 packages, installed 1 package in 0s");
+
+        let summary = OperationSummary { removed: 1, duration: Duration::from_secs(151), ..Default::default() };
+        assert_eq!(summary.describe(), "Removed 1 package in 2m31s");
+        assert_eq!(OperationSummary { duration: Duration::from_secs(3720), ..Default::default() }.describe(), "Finished in 1h02m");
+        assert_eq!(crate::ui::types::format_clock(Duration::from_secs(3725)), "62:05");
+    }
+
+    #[test]
+    fn successful_windows_close_after_a_countdown_unless_kept_open() {
+        let mut window = start(vec![sh("install", "exit 0")], true);
+        window.auto_close_delay = Some(Duration::from_millis(400));
+        assert!(!window.should_auto_close());
+        assert!(window.auto_close_in().is_some());
+        thread::sleep(Duration::from_millis(450));
+        assert!(window.should_auto_close());
+
+        // A key pressed during the countdown keeps the window until it is closed by hand
+        window.keep_open();
+        assert!(!window.should_auto_close());
+        assert_eq!(window.auto_close_in(), None);
+
+        let mut window = start(vec![sh("install", "exit 0")], true);
+        window.auto_close_delay = None;
+        assert!(!window.should_auto_close());
+
+        let mut window = start(vec![sh("install", "exit 1")], true);
+        window.auto_close_delay = Some(Duration::ZERO);
+        assert!(!window.should_auto_close(), "failures stay open");
+    }
+
+    #[test]
+    fn failure_is_classified_from_the_output() {
+        let mut window = start(vec![sh("install", "echo 'error: target not found: nope' >&2; exit 1")], true);
+        window.close(false);
+
+        assert_eq!(window.failure, Some(PmgrError::PackageNotFound("nope".to_string())));
+        assert!(window.failure_message().contains("Package not found: nope"));
+
+        window.clear_just_closed_flag();
+        assert_eq!(window.failure_message().lines().next(), Some("✗ Operation failed"));
+    }
+
+    #[test]
+    fn missing_aur_helper_suggests_installing_one() {
+        let message = error_message(&PmgrError::BackendMissing("yay".to_string()));
+        assert!(message.contains("aur.archlinux.org/yay.git"));
+    }
+
+    #[test]
+    fn failure_alert_shows_the_first_stderr_line() {
+        let mut window = start(vec![sh("sync", "echo progress; echo 'mirror down' >&2; echo 'retry later' >&2; exit 1")], true);
+        window.close(false);
+
+        let message = window.failure_message();
+        assert_eq!(message.lines().next(), Some("✗ Operation failed: mirror down"));
+
+        // The whole output is kept in the operation's log, which the alert points to
+        let log = window.log_path.clone().expect("operation logged");
+        assert!(message.contains(&log.display().to_string()));
+        let logged = std::fs::read_to_string(&log).unwrap();
+        assert!(logged.contains("progress") && logged.contains("retry later"));
+        assert_eq!(
+            error_message(&PmgrError::CommandFailed {
+                cmd: "pacman -Sl".to_string(),
+                code: Some(1),
+                stderr: "first\nsecond".to_string(),
+            }),
+            "✗ pacman -Sl failed: first"
+        );
     }
 }
@@ -0,0 +1,92 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+const ESC: u8 = 0x1b;
+
+/// Data directory holding an operations log with colored lines (errors, warnings, markers)
+fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("pmgr-plain-{}", name));
+    fs::create_dir_all(dir.join("pmgr")).unwrap();
+    fs::write(
+        dir.join("pmgr").join("operations.log"),
+        "=== pmgr: begin [2024-01-01 10:00:00] System Update ===\n\
+         $ pacman -Syu\n\
+         warning: something\n\
+         error: failed to commit transaction\n\
+         === pmgr: end [2024-01-01 10:01:00] failed ===\n",
+    )
+    .unwrap();
+    dir
+}
+
+/// pmgr with colors forced on, as if writing to a color terminal
+fn pmgr(name: &str, args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_pmgr"));
+    command
+        .args(args)
+        .env("XDG_DATA_HOME", data_dir(name))
+        .env("CLICOLOR_FORCE", "1")
+        .env("LANG", "en_US.UTF-8")
+        .env_remove("NO_COLOR")
+        .env_remove("TERM");
+    command
+}
+
+fn run(name: &str, args: &[&str]) -> Output {
+    pmgr(name, args).output().unwrap()
+}
+
+#[test]
+fn colors_are_used_without_plain() {
+    let output = run("colored", &["log"]);
+
+    assert!(output.status.success());
+    assert!(output.stdout.contains(&ESC));
+}
+
+#[test]
+fn plain_flag_removes_escape_sequences() {
+    let output = run("plain", &["--plain", "log"]);
+
+    assert!(output.status.success());
+    assert!(!output.stdout.is_empty());
+    assert!(!output.stdout.contains(&ESC));
+    assert!(String::from_utf8(output.stdout).unwrap().is_ascii());
+}
+
+#[test]
+fn plain_flag_applies_to_errors() {
+    let output = run("plain-error", &["--plain", "import", "/nonexistent/pmgr-packages.txt"]);
+
+    assert!(!output.status.success());
+    assert!(!output.stderr.is_empty());
+    assert!(!output.stderr.contains(&ESC));
+}
+
+#[test]
+fn no_color_flag_removes_escape_sequences() {
+    let output = run("no-color", &["--no-color", "log"]);
+
+    assert!(output.status.success());
+    assert!(!output.stdout.is_empty());
+    assert!(!output.stdout.contains(&ESC));
+}
+
+#[test]
+fn no_color_env_removes_escape_sequences() {
+    let output = pmgr("no-color-env", &["log"]).env("NO_COLOR", "1").output().unwrap();
+
+    assert!(output.status.success());
+    assert!(!output.stdout.contains(&ESC));
+}
+
+#[test]
+fn piped_output_is_not_colored() {
+    // Output is captured through a pipe, so colors are off unless forced
+    let output = pmgr("piped", &["log"]).env_remove("CLICOLOR_FORCE").output().unwrap();
+
+    assert!(output.status.success());
+    assert!(!output.stdout.is_empty());
+    assert!(!output.stdout.contains(&ESC));
+}